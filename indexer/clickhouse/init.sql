@@ -2,6 +2,9 @@
 -- Pre-creates rindexer raw event tables + normalized trades table + materialized views.
 -- rindexer's CREATE TABLE IF NOT EXISTS will safely skip tables that already exist.
 --
+-- This file only runs on a fresh volume (docker-entrypoint-initdb.d). Schema
+-- changes for existing databases live in migrations/ (`make migrate`).
+--
 -- IMPORTANT: uint256 fields MUST use UInt256 (not String) to match rindexer's
 -- generated schema. Unquoted large integer literals inserted into String columns
 -- go through Float64, losing precision (e.g. 76-digit token IDs → scientific notation).
@@ -239,10 +242,12 @@ WHERE taker_asset_id = 0;
 -- =============================================================================
 
 CREATE TABLE IF NOT EXISTS poly_dearboard.resolved_prices (
-    asset_id       String,
-    resolved_price String,
-    condition_id   String,
-    block_number   UInt64
+    asset_id        String,
+    resolved_price  String,
+    condition_id    String,
+    block_number    UInt64,
+    -- 'normal', or 'invalid' when every outcome pays the same (UMA 50/50)
+    resolution_kind LowCardinality(String) DEFAULT 'normal'
 ) ENGINE = ReplacingMergeTree
 ORDER BY (asset_id);
 
@@ -265,6 +270,7 @@ CREATE TABLE IF NOT EXISTS poly_dearboard.market_metadata (
     active          UInt8           DEFAULT 1,
     all_token_ids   Array(String)   DEFAULT [],
     outcomes        Array(String)   DEFAULT [],
+    end_date        String          DEFAULT '',
     updated_at      DateTime('UTC') DEFAULT now()
 ) ENGINE = ReplacingMergeTree(updated_at)
 ORDER BY (asset_id);
@@ -353,7 +359,7 @@ WHERE trader NOT IN (
 )
 GROUP BY key;
 
--- ── Daily PnL snapshots (for pnl_chart beyond 3-day window) ─────────────────
+-- ── Daily PnL snapshots (for pnl_chart + 7d/30d leaderboard beyond 3-day window)
 
 CREATE TABLE IF NOT EXISTS poly_dearboard.pnl_daily (
     trader           FixedString(42),
@@ -363,6 +369,8 @@ CREATE TABLE IF NOT EXISTS poly_dearboard.pnl_daily (
     sell_amount      SimpleAggregateFunction(sum, Float64),
     buy_usdc         SimpleAggregateFunction(sum, Float64),
     sell_usdc        SimpleAggregateFunction(sum, Float64),
+    trade_count      SimpleAggregateFunction(sum, UInt64),
+    fee              SimpleAggregateFunction(sum, Float64),
     last_price_state AggregateFunction(argMax, Float64, UInt64)
 ) ENGINE = AggregatingMergeTree
 ORDER BY (trader, day, asset_id);
@@ -377,11 +385,47 @@ SELECT
     sumIf(toFloat64(amount), side = 'sell') AS sell_amount,
     sumIf(toFloat64(usdc_amount), side = 'buy') AS buy_usdc,
     sumIf(toFloat64(usdc_amount), side = 'sell') AS sell_usdc,
+    toUInt64(count()) AS trade_count,
+    sum(toFloat64(fee)) AS fee,
     argMaxState(toFloat64(price), block_number * 1000000 + log_index) AS last_price_state
 FROM poly_dearboard.trades
 WHERE block_timestamp > toDateTime('1970-01-01 00:00:00')
 GROUP BY trader, day, asset_id;
 
+-- First day each late-added pnl_daily column is complete from. Only written by
+-- migrations/ on deployments whose pnl_daily predates the column; fresh
+-- databases have no rows, i.e. full coverage.
+CREATE TABLE IF NOT EXISTS poly_dearboard.pnl_daily_coverage (
+    metric        LowCardinality(String),
+    complete_from Date
+) ENGINE = ReplacingMergeTree
+ORDER BY (metric);
+
+-- ── Hourly leaderboard rank snapshots (for rank_change_24h) ─────────────────
+-- Written by the API server (top 1000 by all-time PnL), not by a materialized view.
+
+CREATE TABLE IF NOT EXISTS poly_dearboard.leaderboard_snapshots (
+    snapshot_at DateTime('UTC'),
+    trader      String,
+    rank        UInt32
+) ENGINE = MergeTree
+ORDER BY (snapshot_at, trader)
+TTL snapshot_at + INTERVAL 7 DAY;
+
+-- ── Alert history (for /api/alerts/history) ─────────────────────────────────
+-- Written by the API server from alert_tx. `payload` is the serialized alert.
+-- ReplacingMergeTree collapses failover duplicates the in-memory dedup misses.
+
+CREATE TABLE IF NOT EXISTS poly_dearboard.alerts_history (
+    kind         LowCardinality(String),
+    timestamp    DateTime('UTC'),
+    tx_hash      String,
+    block_number UInt64,
+    log_index    UInt64,
+    payload      String
+) ENGINE = ReplacingMergeTree
+ORDER BY (kind, tx_hash, block_number, log_index);
+
 -- ── Daily asset stats (for hot_markets beyond 3-day window) ─────────────────
 
 CREATE TABLE IF NOT EXISTS poly_dearboard.asset_stats_daily (
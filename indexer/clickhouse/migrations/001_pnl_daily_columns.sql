@@ -0,0 +1,86 @@
+-- Columns added to pnl_daily after its first release, for deployments whose
+-- pnl_daily predates them. init.sql already creates the current schema, so on
+-- fresh databases every statement here is a no-op.
+--
+-- Raw `trades` only keeps a few days, so trade counts and fees can only be
+-- rebuilt for days it still holds. Older days stay at 0; `pnl_daily_coverage` records the first
+-- complete day so the API can flag windows that reach further back.
+
+SET allow_experimental_alter_materialized_view_structure = 1;
+
+CREATE TABLE IF NOT EXISTS poly_dearboard.pnl_daily_coverage (
+    metric        LowCardinality(String),
+    complete_from Date
+) ENGINE = ReplacingMergeTree
+ORDER BY (metric);
+
+-- Recorded once per column, only when it is about to be added to existing rows.
+-- The oldest day left in `trades` may be partly expired, so it isn't counted.
+INSERT INTO poly_dearboard.pnl_daily_coverage (metric, complete_from)
+SELECT
+    metric,
+    (SELECT ifNull(min(toDate(block_timestamp)) + 1, today())
+     FROM poly_dearboard.trades
+     WHERE block_timestamp > toDateTime('1970-01-01 00:00:00'))
+FROM (SELECT arrayJoin(['trade_count', 'fee']) AS metric)
+WHERE metric NOT IN (
+        SELECT name FROM system.columns
+        WHERE database = 'poly_dearboard' AND table = 'pnl_daily'
+    )
+  AND metric NOT IN (SELECT metric FROM poly_dearboard.pnl_daily_coverage)
+  AND (SELECT count() FROM poly_dearboard.pnl_daily) > 0;
+
+ALTER TABLE poly_dearboard.pnl_daily
+    ADD COLUMN IF NOT EXISTS trade_count SimpleAggregateFunction(sum, UInt64) AFTER sell_usdc,
+    ADD COLUMN IF NOT EXISTS fee SimpleAggregateFunction(sum, Float64) AFTER trade_count;
+
+-- Altered in place: dropping and recreating the view would lose inserts in between
+ALTER TABLE poly_dearboard.mv_pnl_daily MODIFY QUERY
+SELECT
+    trader,
+    toDate(block_timestamp) AS day,
+    asset_id,
+    sumIf(toFloat64(amount), side = 'buy') AS buy_amount,
+    sumIf(toFloat64(amount), side = 'sell') AS sell_amount,
+    sumIf(toFloat64(usdc_amount), side = 'buy') AS buy_usdc,
+    sumIf(toFloat64(usdc_amount), side = 'sell') AS sell_usdc,
+    toUInt64(count()) AS trade_count,
+    sum(toFloat64(fee)) AS fee,
+    argMaxState(toFloat64(price), block_number * 1000000 + log_index) AS last_price_state
+FROM poly_dearboard.trades
+WHERE block_timestamp > toDateTime('1970-01-01 00:00:00')
+GROUP BY trader, day, asset_id;
+
+-- Tops each key up to the count `trades` still holds. Only the shortfall is
+-- inserted, so keys the altered view already counts aren't double-counted and
+-- re-running this is a no-op.
+INSERT INTO poly_dearboard.pnl_daily (trader, day, asset_id, trade_count)
+SELECT t.trader, t.day, t.asset_id, t.n - d.n
+FROM (
+    SELECT trader, toDate(block_timestamp) AS day, asset_id, toUInt64(count()) AS n
+    FROM poly_dearboard.trades
+    WHERE block_timestamp > toDateTime('1970-01-01 00:00:00')
+    GROUP BY trader, day, asset_id
+) AS t
+INNER JOIN (
+    SELECT trader, day, asset_id, sum(trade_count) AS n
+    FROM poly_dearboard.pnl_daily
+    GROUP BY trader, day, asset_id
+) AS d ON t.trader = d.trader AND t.day = d.day AND t.asset_id = d.asset_id
+WHERE t.n > d.n;
+
+-- Same for fee. Fees are never negative, so a shortfall means missing days.
+INSERT INTO poly_dearboard.pnl_daily (trader, day, asset_id, fee)
+SELECT t.trader, t.day, t.asset_id, t.fee - d.fee
+FROM (
+    SELECT trader, toDate(block_timestamp) AS day, asset_id, sum(toFloat64(fee)) AS fee
+    FROM poly_dearboard.trades
+    WHERE block_timestamp > toDateTime('1970-01-01 00:00:00')
+    GROUP BY trader, day, asset_id
+) AS t
+INNER JOIN (
+    SELECT trader, day, asset_id, sum(fee) AS fee
+    FROM poly_dearboard.pnl_daily
+    GROUP BY trader, day, asset_id
+) AS d ON t.trader = d.trader AND t.day = d.day AND t.asset_id = d.asset_id
+WHERE t.fee - d.fee > 0.000001;
@@ -0,0 +1,10 @@
+-- Hourly leaderboard rank snapshots (the rank_change_24h baseline), for
+-- deployments created before the table. Matches init.sql.
+
+CREATE TABLE IF NOT EXISTS poly_dearboard.leaderboard_snapshots (
+    snapshot_at DateTime('UTC'),
+    trader      String,
+    rank        UInt32
+) ENGINE = MergeTree
+ORDER BY (snapshot_at, trader)
+TTL snapshot_at + INTERVAL 7 DAY;
@@ -0,0 +1,6 @@
+-- market_metadata.end_date, for deployments whose table predates it. The API
+-- writes it on every metadata insert, so those inserts fail until it exists.
+-- Existing rows read '' until the next cache refresh rewrites them.
+
+ALTER TABLE poly_dearboard.market_metadata
+    ADD COLUMN IF NOT EXISTS end_date String DEFAULT '' AFTER outcomes;
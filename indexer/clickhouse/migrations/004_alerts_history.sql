@@ -0,0 +1,12 @@
+-- Persisted alert history (/api/alerts/history), for deployments created
+-- before the table. Matches init.sql.
+
+CREATE TABLE IF NOT EXISTS poly_dearboard.alerts_history (
+    kind         LowCardinality(String),
+    timestamp    DateTime('UTC'),
+    tx_hash      String,
+    block_number UInt64,
+    log_index    UInt64,
+    payload      String
+) ENGINE = ReplacingMergeTree
+ORDER BY (kind, tx_hash, block_number, log_index);
@@ -0,0 +1,24 @@
+-- resolved_prices.resolution_kind, for deployments whose table predates it.
+-- Settlement queries read it and the API writes it on every insert.
+--
+-- Existing rows default to 'normal' and are never rewritten (populate_resolved_prices
+-- skips assets it already has), so conditions whose outcomes all pay the same
+-- are re-inserted as 'invalid' here. ReplacingMergeTree keeps the newer row.
+
+ALTER TABLE poly_dearboard.resolved_prices
+    ADD COLUMN IF NOT EXISTS resolution_kind LowCardinality(String) DEFAULT 'normal';
+
+INSERT INTO poly_dearboard.resolved_prices
+    (asset_id, resolved_price, condition_id, block_number, resolution_kind)
+SELECT asset_id, resolved_price, condition_id, block_number, 'invalid'
+FROM poly_dearboard.resolved_prices FINAL
+WHERE resolution_kind = 'normal'
+  AND condition_id IN (
+        SELECT condition_id
+        FROM poly_dearboard.resolved_prices FINAL
+        WHERE condition_id != ''
+        GROUP BY condition_id
+        HAVING count() > 1
+           AND min(toFloat64(resolved_price)) > 0
+           AND min(resolved_price) = max(resolved_price)
+    );
@@ -45,6 +45,11 @@ else
     echo "Could not fetch latest block from ClickHouse, keeping existing start_block"
 fi
 
+# Existing databases never re-run init.sql, so schema changes go through migrations
+echo "Applying ClickHouse migrations..."
+CLICKHOUSE_URL="$CH_URL" CLICKHOUSE_USER="$CH_USER" CLICKHOUSE_PASSWORD="$CH_PASS" \
+    "$ROOT/scripts/migrate.sh"
+
 echo "Pulling latest images and restarting..."
 $SSH_CMD "cd $REMOTE_DIR/deployments/polyderboard-prod && \
     docker compose -f docker-compose.prod.yml pull && \
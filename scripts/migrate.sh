@@ -0,0 +1,27 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+# Applies indexer/clickhouse/migrations/*.sql (in name order) to an existing
+# ClickHouse. init.sql only runs on a fresh volume; every migration is
+# idempotent, so re-running this is safe.
+
+ROOT="$(cd "$(dirname "$0")/.." && pwd)"
+CH_URL="${CLICKHOUSE_URL:-http://localhost:8123}"
+CH_USER="${CLICKHOUSE_USER:-default}"
+CH_PASS="${CLICKHOUSE_PASSWORD:-}"
+# One HTTP session per run so SET statements carry over to later statements
+SESSION="migrate-$$"
+
+for file in "$ROOT"/indexer/clickhouse/migrations/*.sql; do
+    echo "Applying $(basename "$file")..."
+    # Drop comment lines, then send one statement per request (split on ';')
+    grep -v '^[[:space:]]*--' "$file" \
+        | awk 'BEGIN { RS = ";" } { gsub(/^[[:space:]]+|[[:space:]]+$/, ""); if (length($0)) printf "%s%c", $0, 0 }' \
+        | while IFS= read -r -d '' stmt; do
+            curl -sS --fail-with-body \
+                "${CH_URL}/?user=${CH_USER}&password=${CH_PASS}&session_id=${SESSION}" \
+                --data-binary "$stmt" >/dev/null
+        done
+done
+
+echo "Migrations applied."
@@ -1,5 +1,6 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 use axum::{
@@ -9,13 +10,19 @@ use axum::{
         ws::{Message, WebSocket},
     },
     http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    response::{
+        IntoResponse,
+        sse::{Event, KeepAlive, Sse},
+    },
 };
 use serde::{Deserialize, Serialize};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 
-use super::types::CopyTradeUpdate;
-use super::{markets, server::AppState};
+use super::types::{AlertHistoryRow, CopyTradeUpdate, RecentTradeRow, WebhookStats};
+use super::{
+    markets,
+    server::{AppState, ConvergenceHistory},
+};
 
 // ---------------------------------------------------------------------------
 // Alert types
@@ -34,8 +41,14 @@ pub enum Alert {
         token_amount: String,
         tx_hash: String,
         block_number: u64,
+        /// Log position within the tx; part of the history dedup key
+        #[serde(skip)]
+        log_index: u64,
         question: Option<String>,
         outcome: Option<String>,
+        /// `usdc_amount` in raw 6-decimal units, for per-connection thresholds
+        #[serde(skip)]
+        usdc_raw: u128,
     },
     MarketResolution {
         timestamp: String,
@@ -45,9 +58,18 @@ pub enum Alert {
         payout_numerators: Vec<String>,
         tx_hash: String,
         block_number: u64,
+        /// Log position within the tx; part of the history dedup key
+        #[serde(skip)]
+        log_index: u64,
         question: Option<String>,
+        /// Null for invalid (50/50) resolutions
         winning_outcome: Option<String>,
+        winning_token_id: Option<String>,
         outcomes: Vec<String>,
+        /// Numerator / sum per outcome, parallel to `outcomes`
+        resolved_prices: Vec<String>,
+        /// All payout numerators equal: the market resolved 50/50
+        invalid: bool,
         token_id: Option<String>,
     },
     FailedSettlement {
@@ -59,6 +81,66 @@ pub enum Alert {
         function_name: String,
         gas_used: String,
     },
+    /// Traded price moved more than the configured delta within the window.
+    PriceSwing {
+        timestamp: String,
+        asset_id: String,
+        question: String,
+        outcome: String,
+        /// Window low (`up`) or high (`down`) the move is measured from
+        old_price: String,
+        new_price: String,
+        /// `up` or `down`
+        direction: String,
+        window_seconds: u64,
+        tx_hash: String,
+        block_number: u64,
+        /// Log position within the tx; part of the history dedup key
+        #[serde(skip)]
+        log_index: u64,
+    },
+    /// Top leaderboard trader bought into a market they had never traded.
+    SmartMoneyEntry {
+        timestamp: String,
+        trader: String,
+        /// 1-based leaderboard rank at the last refresh
+        rank: u32,
+        asset_id: String,
+        question: String,
+        outcome: String,
+        usdc_amount: String,
+        price: String,
+        tx_hash: String,
+        block_number: u64,
+        /// Log position within the tx; part of the history dedup key
+        #[serde(skip)]
+        log_index: u64,
+    },
+    /// Shared top-trader convergence from `convergence_loop`; not persisted.
+    Convergence(ConvergenceAlert),
+    /// Live trade by a followed trader; only delivered to `owners` on /ws/alerts.
+    FollowedTraderTrade {
+        #[serde(skip)]
+        owners: HashSet<String>,
+        #[serde(flatten)]
+        trade: LiveTrade,
+    },
+}
+
+/// Out-of-band frame on /ws/alerts and /ws/trades, tagged like alerts.
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+enum SystemMessage {
+    /// The client fell behind the broadcast channel; `dropped` items were skipped
+    Lag { dropped: u64 },
+}
+
+/// Tell the client it missed `dropped` items. `false` once the socket is gone.
+async fn send_lag(socket: &mut WebSocket, dropped: u64) -> bool {
+    let Ok(json) = serde_json::to_string(&SystemMessage::Lag { dropped }) else {
+        return true;
+    };
+    socket.send(Message::Text(json.into())).await.is_ok()
 }
 
 // ---------------------------------------------------------------------------
@@ -79,6 +161,9 @@ pub struct LiveTrade {
     pub outcome: String,
     pub category: String,
     pub block_number: u64,
+    pub log_index: u64,
+    /// Replayed via `eth_getLogs` after a WS subscriber reconnect
+    pub recovered: bool,
     #[serde(skip)]
     pub cache_key: String,
 }
@@ -88,7 +173,7 @@ pub struct LiveTrade {
 // ---------------------------------------------------------------------------
 
 #[derive(Deserialize)]
-pub(crate) struct WebhookPayload {
+pub struct WebhookPayload {
     event_name: String,
     event_data: Vec<serde_json::Value>,
     #[allow(dead_code)]
@@ -96,13 +181,15 @@ pub(crate) struct WebhookPayload {
 }
 
 #[derive(Deserialize)]
-struct TxInfo {
+pub(crate) struct TxInfo {
+    #[serde(default)]
+    pub(crate) transaction_hash: String,
     #[serde(default)]
-    transaction_hash: String,
+    pub(crate) block_number: u64,
     #[serde(default)]
-    block_number: u64,
+    pub(crate) block_timestamp: String,
     #[serde(default)]
-    block_timestamp: String,
+    pub(crate) log_index: u64,
 }
 
 // ---------------------------------------------------------------------------
@@ -126,17 +213,80 @@ pub async fn webhook_handler(
         }
     }
 
+    state
+        .webhook_counters
+        .received
+        .fetch_add(1, Ordering::Relaxed);
+    // Processing happens in `webhook_worker`; a full queue makes rindexer retry
+    match state.webhook_tx.try_send(payload) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(mpsc::error::TrySendError::Full(_)) => {
+            state
+                .webhook_counters
+                .dropped
+                .fetch_add(1, Ordering::Relaxed);
+            Err((StatusCode::TOO_MANY_REQUESTS, "Webhook queue full".into()))
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Webhook worker stopped".into(),
+        )),
+    }
+}
+
+/// Payloads buffered between `webhook_handler` and `webhook_worker`
+pub const WEBHOOK_QUEUE_CAPACITY: usize = 256;
+
+/// Webhook pipeline counters, reported by `/api/health`.
+#[derive(Default)]
+pub struct WebhookCounters {
+    received: AtomicU64,
+    processed: AtomicU64,
+    dropped: AtomicU64,
+    enrichment_misses: AtomicU64,
+}
+
+impl WebhookCounters {
+    pub fn stats(&self, queued: usize) -> WebhookStats {
+        WebhookStats {
+            received: self.received.load(Ordering::Relaxed),
+            processed: self.processed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            enrichment_misses: self.enrichment_misses.load(Ordering::Relaxed),
+            queued,
+            queue_capacity: WEBHOOK_QUEUE_CAPACITY,
+        }
+    }
+}
+
+/// Drains queued webhook payloads: cache lookups, Gamma enrichment, broadcasts.
+pub async fn webhook_worker(state: AppState, mut rx: mpsc::Receiver<WebhookPayload>) {
+    while let Some(payload) = rx.recv().await {
+        process_webhook_payload(&state, payload).await;
+        state
+            .webhook_counters
+            .processed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+async fn process_webhook_payload(state: &AppState, payload: WebhookPayload) {
     for event in &payload.event_data {
         let is_live = is_event_live(event);
+        let is_trade = matches!(payload.event_name.as_str(), "OrderFilled" | "OrdersMatched");
+        // A taker fill arrives as both a taker-summary OrderFilled and an
+        // OrdersMatched with the same order hash — only the first one counts
+        let duplicate =
+            is_trade && taker_order_hash(event).is_some_and(|h| !state.taker_orders.first_seen(h));
 
         let mut alert = {
             let cache = state.market_cache.read().await;
 
             // Broadcast trades + queue metadata persistence.
             // Webhook is the primary source for live feed and whale alerts.
-            if payload.event_name == "OrderFilled" && is_live {
+            if is_trade && is_live && !duplicate {
                 if let Some(live_trade) = build_live_trade(event, &cache) {
-                    if let Some(info) = cache.get(&live_trade.cache_key) {
+                    if let Some(info) = cache.get(&live_trade.asset_id) {
                         let _ = state
                             .metadata_tx
                             .try_send((live_trade.asset_id.clone(), info.clone()));
@@ -146,71 +296,105 @@ pub async fn webhook_handler(
             }
 
             match payload.event_name.as_str() {
-                "OrderFilled" => parse_order_filled(event, &cache),
+                "OrderFilled" | "OrdersMatched" if !duplicate => {
+                    parse_order_filled(event, &cache, usdc_to_raw(state.whale_alerts.floor_usdc))
+                }
                 "ConditionResolution" => parse_condition_resolution(event, &cache),
                 _ => None,
             }
         };
 
-        // Enrich resolution alerts on cache miss — query Gamma API by condition_id.
-        // Drop resolutions we can't identify (old V1 markets, unknown conditions).
-        if let Some(Alert::MarketResolution {
-            ref condition_id,
-            ref mut question,
-            ref mut outcomes,
-            ref mut winning_outcome,
-            ref mut token_id,
-            ref payout_numerators,
-            ..
-        }) = alert
-        {
-            if question.is_some() {
-                tracing::info!(
-                    "ConditionResolution enriched from cache: condition_id={condition_id}"
-                );
-            } else {
-                tracing::warn!(
-                    "ConditionResolution cache miss: condition_id={condition_id}, trying Gamma API"
-                );
-                if let Some((q, outs, tid)) =
-                    fetch_resolution_context(&state.http, condition_id).await
-                {
-                    tracing::info!(
-                        "ConditionResolution enriched from Gamma: condition_id={condition_id}"
-                    );
-                    let winner = payout_numerators
-                        .iter()
-                        .enumerate()
-                        .find(|(_, n)| n.parse::<u64>().unwrap_or(0) > 0)
-                        .and_then(|(i, _)| outs.get(i).cloned());
-
-                    *question = Some(q);
-                    *outcomes = outs;
-                    *winning_outcome = winner;
-                    if !tid.is_empty() {
-                        *token_id = Some(tid);
-                    }
-                } else {
-                    tracing::warn!(
-                        "ConditionResolution Gamma miss: condition_id={condition_id} (broadcasting with raw data)"
-                    );
-                }
+        // Enrich resolution alerts on cache miss — query Gamma API by condition_id
+        if let Some(alert) = alert.as_mut() {
+            if !enrich_resolution(&state.http, &state.gamma, alert).await {
+                state
+                    .webhook_counters
+                    .enrichment_misses
+                    .fetch_add(1, Ordering::Relaxed);
             }
         }
 
         if let Some(alert) = alert {
-            if is_live {
-                let _ = state.alert_tx.send(alert);
-            } else {
+            if !is_live {
                 tracing::debug!("Backfill guard: suppressed alert for stale event");
+            } else if matches!(&alert, Alert::MarketResolution { condition_id, .. }
+                if !state.resolutions.first_seen(condition_id.trim_start_matches("0x")))
+            {
+                // Already broadcast by the WS subscriber
+                tracing::debug!("ConditionResolution duplicate suppressed");
+            } else {
+                let _ = state.alert_tx.send(alert);
             }
         }
     }
-
-    Ok(StatusCode::OK)
 }
 
 /// Common fields extracted from an OrderFilled event.
+/// `true` if `addr` is one of the exchange contracts (not a real trader).
+fn is_exchange_address(addr: &str) -> bool {
+    super::routes::EXCHANGE_CONTRACTS
+        .iter()
+        .any(|c| c.eq_ignore_ascii_case(addr))
+}
+
+/// Order hash of a taker fill: `takerOrderHash` of an OrdersMatched, or the
+/// `orderHash` of a taker-summary OrderFilled (an exchange contract on either
+/// side). `None` for maker fills.
+fn taker_order_hash(event: &serde_json::Value) -> Option<&str> {
+    if let Some(hash) = event.get("takerOrderHash") {
+        return hash.as_str();
+    }
+    let is_summary = ["maker", "taker"].iter().any(|field| {
+        event
+            .get(*field)
+            .and_then(|v| v.as_str())
+            .is_some_and(is_exchange_address)
+    });
+    if is_summary {
+        event.get("orderHash")?.as_str()
+    } else {
+        None
+    }
+}
+
+pub const TAKER_ORDER_DEDUP_TTL: Duration = Duration::from_secs(600);
+/// Resolutions arrive from both the webhook and the WS subscriber
+pub const RESOLUTION_DEDUP_TTL: Duration = Duration::from_secs(3600);
+
+/// Keys (order hashes, condition ids) already acted on within a TTL, shared
+/// across ingestion paths.
+#[derive(Clone)]
+pub struct RecentKeys {
+    seen: std::sync::Arc<std::sync::Mutex<HashMap<String, Instant>>>,
+    ttl: Duration,
+}
+
+impl RecentKeys {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            seen: Default::default(),
+            ttl,
+        }
+    }
+
+    /// `true` the first time `key` (case-insensitive) is seen within the TTL.
+    pub fn first_seen(&self, key: &str) -> bool {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|p| p.into_inner());
+        if seen.len() > 10_000 {
+            seen.retain(|_, ts| now.duration_since(*ts) < self.ttl);
+        }
+        let key = key.to_lowercase();
+        match seen.get(&key) {
+            Some(ts) if now.duration_since(*ts) < self.ttl => false,
+            _ => {
+                seen.insert(key, now);
+                true
+            }
+        }
+    }
+}
+
 struct TradeData<'a> {
     tx_info: TxInfo,
     side: &'static str,
@@ -225,7 +409,7 @@ struct TradeData<'a> {
 
 fn parse_trade_data<'a>(
     event: &'a serde_json::Value,
-    cache: &'a std::collections::HashMap<String, markets::MarketInfo>,
+    cache: &'a markets::MarketMap,
 ) -> Option<TradeData<'a>> {
     let tx_info: TxInfo =
         serde_json::from_value(event.get("transaction_information")?.clone()).ok()?;
@@ -234,7 +418,16 @@ fn parse_trade_data<'a>(
     let taker_asset_id = event.get("takerAssetId")?.as_str()?;
     let maker_amount = event.get("makerAmountFilled")?.as_str()?;
     let taker_amount = event.get("takerAmountFilled")?.as_str()?;
-    let maker = event.get("maker")?.as_str()?;
+    // OrdersMatched: the taker order's maker is the real taker.
+    // OrderFilled: the order's maker; an exchange contract there marks a taker
+    // summary, which OrdersMatched attributes instead.
+    let maker = match event.get("takerOrderMaker") {
+        Some(taker) => taker.as_str()?,
+        None => event
+            .get("maker")?
+            .as_str()
+            .filter(|m| !is_exchange_address(m))?,
+    };
 
     let (side, asset_id, usdc_raw, token_raw) = if maker_asset_id == "0" {
         ("buy", taker_asset_id, maker_amount, taker_amount)
@@ -255,7 +448,7 @@ fn parse_trade_data<'a>(
     };
 
     let key = markets::cache_key(asset_id);
-    let info = cache.get(&key);
+    let info = cache.get(asset_id);
 
     Some(TradeData {
         tx_info,
@@ -270,15 +463,49 @@ fn parse_trade_data<'a>(
     })
 }
 
+/// Whale alert thresholds in USDC. Every fill of at least `floor_usdc` becomes
+/// a candidate whale alert; `/ws/alerts` clients only see `default_usdc` and up
+/// unless they pass their own `min_usdc` (clamped to the floor).
+#[derive(Clone, Copy)]
+pub struct WhaleAlertConfig {
+    pub floor_usdc: f64,
+    pub default_usdc: f64,
+}
+
+impl WhaleAlertConfig {
+    /// `WHALE_ALERT_FLOOR_USDC` (default 5000) and `WHALE_ALERT_DEFAULT_USDC`
+    /// (default 25000, never below the floor).
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: f64| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(default)
+        };
+        let floor_usdc = read("WHALE_ALERT_FLOOR_USDC", 5_000.0);
+        let default_usdc = read("WHALE_ALERT_DEFAULT_USDC", 25_000.0).max(floor_usdc);
+        Self {
+            floor_usdc,
+            default_usdc,
+        }
+    }
+}
+
+/// USDC → raw 6-decimal units
+fn usdc_to_raw(usdc: f64) -> u128 {
+    (usdc * 1_000_000.0) as u128
+}
+
 fn parse_order_filled(
     event: &serde_json::Value,
-    cache: &std::collections::HashMap<String, markets::MarketInfo>,
+    cache: &markets::MarketMap,
+    floor_raw: u128,
 ) -> Option<Alert> {
     let td = parse_trade_data(event, cache)?;
 
-    // Whale threshold: $25k USDC = 25_000_000_000 raw (6 decimals)
     let usdc_raw_n: u128 = td.usdc_raw.parse().unwrap_or(0);
-    if usdc_raw_n < 25_000_000_000 {
+    if usdc_raw_n < floor_raw {
         return None;
     }
 
@@ -292,15 +519,14 @@ fn parse_order_filled(
         token_amount: format_usdc(td.token_raw),
         tx_hash: td.tx_info.transaction_hash,
         block_number: td.tx_info.block_number,
+        log_index: td.tx_info.log_index,
         question: td.info.map(|i| i.question.clone()),
         outcome: td.info.map(|i| i.outcome.clone()),
+        usdc_raw: usdc_raw_n,
     })
 }
 
-fn build_live_trade(
-    event: &serde_json::Value,
-    cache: &std::collections::HashMap<String, markets::MarketInfo>,
-) -> Option<LiveTrade> {
+fn build_live_trade(event: &serde_json::Value, cache: &markets::MarketMap) -> Option<LiveTrade> {
     let td = parse_trade_data(event, cache)?;
 
     let usdc_n: f64 = td.usdc_raw.parse().unwrap_or(0.0);
@@ -323,13 +549,52 @@ fn build_live_trade(
         outcome: td.info.map(|i| i.outcome.clone()).unwrap_or_default(),
         category: td.info.map(|i| i.category.clone()).unwrap_or_default(),
         block_number: td.tx_info.block_number,
+        log_index: td.tx_info.log_index,
+        recovered: false,
         cache_key: td.key,
     })
 }
 
+/// Resolution prices derived from a condition's `payoutNumerators`.
+struct ResolutionPayout {
+    resolved_prices: Vec<String>,
+    winning_index: Option<usize>,
+    invalid: bool,
+}
+
+impl ResolutionPayout {
+    fn from_numerators(numerators: &[String]) -> Self {
+        let values: Vec<u128> = numerators.iter().map(|n| n.parse().unwrap_or(0)).collect();
+        let total: u128 = values.iter().sum();
+        if total == 0 {
+            return Self {
+                resolved_prices: Vec::new(),
+                winning_index: None,
+                invalid: false,
+            };
+        }
+        let resolved_prices = values
+            .iter()
+            .map(|&n| format!("{:.6}", n as f64 / total as f64))
+            .collect();
+        // Equal payouts to every outcome: no winner
+        let invalid = values.len() > 1 && values.iter().all(|&n| n == values[0]);
+        let winning_index = if invalid {
+            None
+        } else {
+            values.iter().position(|&n| n > 0)
+        };
+        Self {
+            resolved_prices,
+            winning_index,
+            invalid,
+        }
+    }
+}
+
 fn parse_condition_resolution(
     event: &serde_json::Value,
-    cache: &std::collections::HashMap<String, markets::MarketInfo>,
+    cache: &markets::MarketMap,
 ) -> Option<Alert> {
     let tx_info: TxInfo =
         serde_json::from_value(event.get("transaction_information")?.clone()).ok()?;
@@ -342,6 +607,26 @@ fn parse_condition_resolution(
         .and_then(|v| serde_json::from_value(v.clone()).ok())
         .unwrap_or_default();
 
+    Some(resolution_alert(
+        condition_id,
+        oracle,
+        question_id,
+        numerators,
+        tx_info,
+        cache,
+    ))
+}
+
+/// `Alert::MarketResolution` for a ConditionResolution event, enriched from the
+/// market cache where possible (see `enrich_resolution` for cache misses).
+pub(crate) fn resolution_alert(
+    condition_id: &str,
+    oracle: &str,
+    question_id: &str,
+    numerators: Vec<String>,
+    tx_info: TxInfo,
+    cache: &markets::MarketMap,
+) -> Alert {
     // Collect all cache entries matching this condition_id, sorted by outcome_index.
     // Compare without 0x prefix since on-chain events omit it but Gamma includes it.
     let bare_cid = condition_id.strip_prefix("0x").unwrap_or(condition_id);
@@ -359,14 +644,13 @@ fn parse_condition_resolution(
     let outcomes: Vec<String> = matched.iter().map(|info| info.outcome.clone()).collect();
     let token_id = matched.first().map(|info| info.gamma_token_id.clone());
 
-    // Determine winning outcome: index where payout_numerator > 0
-    let winning_outcome = numerators
-        .iter()
-        .enumerate()
-        .find(|(_, n)| n.parse::<u64>().unwrap_or(0) > 0)
-        .and_then(|(i, _)| outcomes.get(i).cloned());
+    let payout = ResolutionPayout::from_numerators(&numerators);
+    let winning_outcome = payout.winning_index.and_then(|i| outcomes.get(i).cloned());
+    let winning_token_id = payout
+        .winning_index
+        .and_then(|i| matched.first()?.all_token_ids.get(i).cloned());
 
-    Some(Alert::MarketResolution {
+    Alert::MarketResolution {
         timestamp: tx_info.block_timestamp,
         condition_id: condition_id.into(),
         oracle: oracle.into(),
@@ -374,22 +658,74 @@ fn parse_condition_resolution(
         payout_numerators: numerators,
         tx_hash: tx_info.transaction_hash,
         block_number: tx_info.block_number,
+        log_index: tx_info.log_index,
         question,
         winning_outcome,
+        winning_token_id,
         outcomes,
+        resolved_prices: payout.resolved_prices,
+        invalid: payout.invalid,
         token_id,
-    })
+    }
+}
+
+/// Fill a resolution alert's market context from the Gamma API when the market
+/// cache had no match. Returns `false` if the market is still unidentified (the
+/// alert is broadcast with raw data). Other alert kinds are left untouched.
+pub(crate) async fn enrich_resolution(
+    http: &reqwest::Client,
+    gamma: &markets::GammaBackoff,
+    alert: &mut Alert,
+) -> bool {
+    let Alert::MarketResolution {
+        condition_id,
+        question,
+        outcomes,
+        winning_outcome,
+        winning_token_id,
+        token_id,
+        payout_numerators,
+        ..
+    } = alert
+    else {
+        return true;
+    };
+
+    if question.is_some() {
+        tracing::info!("ConditionResolution enriched from cache: condition_id={condition_id}");
+        return true;
+    }
+    tracing::warn!("ConditionResolution cache miss: condition_id={condition_id}, trying Gamma API");
+    let Some((q, outs, token_ids)) = fetch_resolution_context(http, gamma, condition_id).await
+    else {
+        tracing::warn!(
+            "ConditionResolution Gamma miss: condition_id={condition_id} (broadcasting with raw data)"
+        );
+        return false;
+    };
+    tracing::info!("ConditionResolution enriched from Gamma: condition_id={condition_id}");
+    let winning_index = ResolutionPayout::from_numerators(payout_numerators).winning_index;
+
+    *question = Some(q);
+    *winning_outcome = winning_index.and_then(|i| outs.get(i).cloned());
+    *winning_token_id = winning_index.and_then(|i| token_ids.get(i).cloned());
+    *outcomes = outs;
+    if let Some(tid) = token_ids.into_iter().next().filter(|t| !t.is_empty()) {
+        *token_id = Some(tid);
+    }
+    true
 }
 
 /// Fallback: query Gamma API by condition_id when market cache misses.
-/// Returns (question, outcomes, first_token_id).
+/// Returns (question, outcomes, token_ids), the last two parallel.
 ///
 /// Note: Gamma API silently ignores unknown filter params and returns default
 /// paginated results, so we MUST verify the returned conditionId matches.
 async fn fetch_resolution_context(
     http: &reqwest::Client,
+    gamma: &markets::GammaBackoff,
     condition_id: &str,
-) -> Option<(String, Vec<String>, String)> {
+) -> Option<(String, Vec<String>, Vec<String>)> {
     let cid_hex = if condition_id.starts_with("0x") {
         condition_id.to_string()
     } else {
@@ -399,14 +735,10 @@ async fn fetch_resolution_context(
         "https://gamma-api.polymarket.com/markets?condition_ids={}",
         cid_hex
     );
-    let resp = http
-        .get(&url)
-        .timeout(std::time::Duration::from_secs(5))
-        .send()
-        .await
-        .ok()?;
-
-    let body: Vec<serde_json::Value> = resp.json().await.ok()?;
+    let body: Vec<serde_json::Value> =
+        markets::gamma_get(http, gamma, &url, Duration::from_secs(5))
+            .await
+            .ok()?;
 
     // Find the market whose conditionId actually matches — Gamma may return
     // unrelated results if the filter param is silently ignored.
@@ -436,9 +768,7 @@ async fn fetch_resolution_context(
         .and_then(|s| serde_json::from_str(s).ok())
         .unwrap_or_default();
 
-    let token_id = token_ids.into_iter().next().unwrap_or_default();
-
-    Some((question, outcomes, token_id))
+    Some((question, outcomes, token_ids))
 }
 
 fn format_usdc(raw: &str) -> String {
@@ -469,11 +799,7 @@ fn is_event_live(event: &serde_json::Value) -> bool {
         return true; // fail-open: broadcast if timestamp unavailable
     }
     let ts_str = ts_str.unwrap();
-    let Ok(ts) = (if let Some(hex) = ts_str.strip_prefix("0x") {
-        i64::from_str_radix(hex, 16)
-    } else {
-        ts_str.parse::<i64>()
-    }) else {
+    let Some(ts) = parse_block_timestamp(ts_str) else {
         tracing::warn!("is_event_live: failed to parse block_timestamp: {ts_str}");
         return true; // fail-open
     };
@@ -485,143 +811,1168 @@ fn is_event_live(event: &serde_json::Value) -> bool {
     delta < 300
 }
 
+/// Unix seconds from a block timestamp string, decimal or `0x`-prefixed hex.
+fn parse_block_timestamp(raw: &str) -> Option<i64> {
+    if let Some(hex) = raw.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16).ok()
+    } else {
+        raw.parse::<i64>().ok()
+    }
+}
+
 // ---------------------------------------------------------------------------
-// GET /ws/alerts — WebSocket upgrade
+// Alert history (alert_tx → ClickHouse alerts_history)
 // ---------------------------------------------------------------------------
 
-pub async fn ws_handler(State(state): State<AppState>, ws: WebSocketUpgrade) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_ws(socket, state.alert_tx.subscribe()))
+const ALERT_HISTORY_BATCH: usize = 100;
+const ALERT_HISTORY_FLUSH: Duration = Duration::from_secs(5);
+/// Unflushed rows kept while ClickHouse is unavailable
+const ALERT_HISTORY_MAX_PENDING: usize = 10_000;
+/// How long a dedup key is remembered; failover duplicates arrive within seconds
+const ALERT_HISTORY_DEDUP_TTL: Duration = Duration::from_secs(3600);
+
+/// Every serialized `kind` tag. The first `HISTORY_KIND_COUNT` are tied to a
+/// single tx and kept in `alerts_history`.
+const ALERT_KINDS: &[&str] = &[
+    "WhaleTrade",
+    "MarketResolution",
+    "FailedSettlement",
+    "PriceSwing",
+    "SmartMoneyEntry",
+    "Convergence",
+    "FollowedTraderTrade",
+];
+const HISTORY_KIND_COUNT: usize = 5;
+
+/// Kinds persisted to `alerts_history` (and filterable on `/api/alerts/history`)
+pub(crate) const HISTORY_ALERT_KINDS: &[&str] = ALERT_KINDS.split_at(HISTORY_KIND_COUNT).0;
+
+impl Alert {
+    /// Serialized `kind` tag
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Alert::WhaleTrade { .. } => "WhaleTrade",
+            Alert::MarketResolution { .. } => "MarketResolution",
+            Alert::FailedSettlement { .. } => "FailedSettlement",
+            Alert::PriceSwing { .. } => "PriceSwing",
+            Alert::SmartMoneyEntry { .. } => "SmartMoneyEntry",
+            Alert::Convergence(_) => "Convergence",
+            Alert::FollowedTraderTrade { .. } => "FollowedTraderTrade",
+        }
+    }
+
+    /// `(kind, tx_hash, block_number, log_index, timestamp)` of alerts whose kind
+    /// is in `HISTORY_ALERT_KINDS`. Failed settlements are whole txs and use log
+    /// index 0.
+    fn history_key(&self) -> Option<(&'static str, &str, u64, u64, &str)> {
+        if !HISTORY_ALERT_KINDS.contains(&self.kind()) {
+            return None;
+        }
+        match self {
+            Alert::WhaleTrade {
+                tx_hash,
+                block_number,
+                log_index,
+                timestamp,
+                ..
+            }
+            | Alert::MarketResolution {
+                tx_hash,
+                block_number,
+                log_index,
+                timestamp,
+                ..
+            }
+            | Alert::PriceSwing {
+                tx_hash,
+                block_number,
+                log_index,
+                timestamp,
+                ..
+            }
+            | Alert::SmartMoneyEntry {
+                tx_hash,
+                block_number,
+                log_index,
+                timestamp,
+                ..
+            } => Some((self.kind(), tx_hash, *block_number, *log_index, timestamp)),
+            Alert::FailedSettlement {
+                tx_hash,
+                block_number,
+                timestamp,
+                ..
+            } => Some((self.kind(), tx_hash, *block_number, 0, timestamp)),
+            Alert::Convergence(_) | Alert::FollowedTraderTrade { .. } => None,
+        }
+    }
 }
 
-async fn handle_ws(mut socket: WebSocket, mut rx: broadcast::Receiver<Alert>) {
+/// Batch-inserts broadcast alerts into `alerts_history`. The webhook and the WS
+/// subscriber can both emit the same whale alert during failover, so alerts are
+/// deduplicated on (kind, tx_hash, block_number, log_index) before insert.
+pub async fn alert_history_loop(db: clickhouse::Client, mut rx: broadcast::Receiver<Alert>) {
+    let mut seen: HashMap<String, Instant> = HashMap::new();
+    let mut batch: Vec<AlertHistoryRow> = Vec::new();
+    let mut flush = tokio::time::interval(ALERT_HISTORY_FLUSH);
+
     loop {
         tokio::select! {
             result = rx.recv() => {
                 match result {
                     Ok(alert) => {
-                        let json = match serde_json::to_string(&alert) {
-                            Ok(j) => j,
-                            Err(_) => continue,
+                        let Some((kind, tx_hash, block_number, log_index, timestamp)) =
+                            alert.history_key()
+                        else {
+                            continue;
                         };
-                        if socket.send(Message::Text(json.into())).await.is_err() {
-                            break; // Client disconnected
+                        let key = format!("{kind}|{tx_hash}|{block_number}|{log_index}");
+                        if seen.contains_key(&key) {
+                            continue;
+                        }
+                        let Ok(payload) = serde_json::to_string(&alert) else {
+                            continue;
+                        };
+                        batch.push(AlertHistoryRow {
+                            kind: kind.to_string(),
+                            timestamp: parse_block_timestamp(timestamp)
+                                .unwrap_or_else(|| chrono::Utc::now().timestamp())
+                                as u32,
+                            tx_hash: tx_hash.to_string(),
+                            block_number,
+                            log_index,
+                            payload,
+                        });
+                        seen.insert(key, Instant::now());
+                        if batch.len() >= ALERT_HISTORY_BATCH {
+                            flush_alert_history(&db, &mut batch).await;
                         }
                     }
                     Err(broadcast::error::RecvError::Lagged(n)) => {
-                        tracing::warn!("WebSocket client lagged, skipped {n} alerts");
+                        tracing::warn!("Alert history lagged, {n} alerts not persisted");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        flush_alert_history(&db, &mut batch).await;
+                        break;
                     }
-                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
-            // Handle incoming messages (ping/pong/close)
-            msg = socket.recv() => {
-                match msg {
-                    Some(Ok(Message::Close(_))) | None => break,
-                    Some(Err(_)) => break,
-                    _ => {} // Ignore text/binary from client
-                }
+            _ = flush.tick() => {
+                flush_alert_history(&db, &mut batch).await;
+                seen.retain(|_, at| at.elapsed() < ALERT_HISTORY_DEDUP_TTL);
             }
         }
     }
 }
 
+/// Writes `batch` to `alerts_history`. On failure the rows are kept for the next
+/// flush, dropping the oldest beyond `ALERT_HISTORY_MAX_PENDING`.
+async fn flush_alert_history(db: &clickhouse::Client, batch: &mut Vec<AlertHistoryRow>) {
+    if batch.is_empty() {
+        return;
+    }
+    if let Err(e) = insert_alert_history(db, batch).await {
+        tracing::warn!("Failed to persist alerts_history: {e}");
+        if batch.len() > ALERT_HISTORY_MAX_PENDING {
+            let dropped = batch.len() - ALERT_HISTORY_MAX_PENDING;
+            batch.drain(..dropped);
+            tracing::warn!("Alert history backlog full, dropped {dropped} oldest alerts");
+        }
+        return;
+    }
+    tracing::debug!("Persisted {} alerts to history", batch.len());
+    batch.clear();
+}
+
+async fn insert_alert_history(
+    db: &clickhouse::Client,
+    rows: &[AlertHistoryRow],
+) -> Result<(), clickhouse::error::Error> {
+    let mut inserter = db.insert("poly_dearboard.alerts_history")?;
+    for row in rows {
+        inserter.write(row).await?;
+    }
+    inserter.end().await
+}
+
 // ---------------------------------------------------------------------------
-// GET /ws/trades — WebSocket upgrade (market-filtered trade stream)
+// GET /ws/alerts — WebSocket upgrade
 // ---------------------------------------------------------------------------
 
 #[derive(Deserialize)]
-pub struct TradesWsParams {
-    token_ids: String,
-    /// Optional comma-separated trader addresses for server-side filtering.
-    /// When set, only trades from these addresses are forwarded.
-    traders: Option<String>,
+pub struct AlertsWsParams {
+    /// Optional JWT (browsers can't set headers on WebSocket upgrades).
+    /// When valid, followed-trader alerts for this user are delivered too.
+    token: Option<String>,
+    /// Comma-separated alert kinds to receive (default: all)
+    kinds: Option<String>,
+    /// Minimum `WhaleTrade` size in USDC (default `WHALE_ALERT_DEFAULT_USDC`,
+    /// clamped to `WHALE_ALERT_FLOOR_USDC`); other kinds are unaffected
+    min_usdc: Option<f64>,
+    /// Comma-separated market categories (case-insensitive); alerts without a
+    /// known market are dropped when set
+    categories: Option<String>,
 }
 
-pub async fn trades_ws_handler(
+/// Per-connection `/ws/alerts` filter
+struct AlertsWsFilter {
+    /// Empty means every kind
+    kinds: HashSet<&'static str>,
+    /// Whale threshold in raw 6-decimal units
+    min_usdc_raw: u128,
+    /// Lowercased; empty means every category
+    categories: HashSet<String>,
+}
+
+impl AlertsWsFilter {
+    async fn matches(&self, alert: &Alert, cache: &markets::MarketCache) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(alert.kind()) {
+            return false;
+        }
+        if let Alert::WhaleTrade { usdc_raw, .. } = alert {
+            if *usdc_raw < self.min_usdc_raw {
+                return false;
+            }
+        }
+        if self.categories.is_empty() {
+            return true;
+        }
+        let category = match alert {
+            Alert::FollowedTraderTrade { trade, .. } => Some(trade.category.clone()),
+            Alert::Convergence(ConvergenceAlert { asset_id, .. })
+            | Alert::WhaleTrade { asset_id, .. }
+            | Alert::PriceSwing { asset_id, .. }
+            | Alert::SmartMoneyEntry { asset_id, .. }
+            | Alert::MarketResolution {
+                token_id: Some(asset_id),
+                ..
+            } => cache.read().await.get(asset_id).map(|i| i.category.clone()),
+            _ => None,
+        };
+        category.is_some_and(|c| self.categories.contains(&c.to_lowercase()))
+    }
+}
+
+pub async fn ws_handler(
     State(state): State<AppState>,
-    Query(params): Query<TradesWsParams>,
+    Query(params): Query<AlertsWsParams>,
     ws: WebSocketUpgrade,
-) -> impl IntoResponse {
-    let prefixes: HashSet<String> = params
-        .token_ids
-        .split(',')
-        .map(|s| markets::cache_key(s.trim()))
-        .collect();
-    let trader_filter: HashSet<String> = params
-        .traders
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let owner = match params.token.as_deref().filter(|t| !t.is_empty()) {
+        Some(token) => Some(
+            super::auth::validate_jwt(token, &state.jwt_secret, &state.revocations)
+                .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid token".to_string()))?,
+        ),
+        None => None,
+    };
+
+    let mut kinds = HashSet::new();
+    for kind in params
+        .kinds
         .as_deref()
         .unwrap_or("")
         .split(',')
-        .map(|s| s.trim().to_lowercase())
-        .filter(|s| !s.is_empty())
-        .collect();
-    ws.on_upgrade(move |socket| {
-        handle_trades_ws(socket, state.trade_tx.subscribe(), prefixes, trader_filter)
-    })
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+    {
+        let Some(known) = ALERT_KINDS.iter().find(|k| **k == kind) else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid kind `{kind}`. Allowed: {ALERT_KINDS:?}"),
+            ));
+        };
+        kinds.insert(*known);
+    }
+    if params.min_usdc.is_some_and(|m| !m.is_finite() || m < 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid min_usdc: must be a non-negative number".into(),
+        ));
+    }
+    let whales = state.whale_alerts;
+    let filter = AlertsWsFilter {
+        kinds,
+        min_usdc_raw: usdc_to_raw(
+            params
+                .min_usdc
+                .unwrap_or(whales.default_usdc)
+                .max(whales.floor_usdc),
+        ),
+        categories: params
+            .categories
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty())
+            .collect(),
+    };
+
+    Ok(ws.on_upgrade(move |socket| {
+        handle_ws(
+            socket,
+            state.alert_tx.subscribe(),
+            state.convergence_tx.subscribe(),
+            owner,
+            filter,
+            state.market_cache.clone(),
+        )
+    }))
 }
 
-async fn handle_trades_ws(
+async fn handle_ws(
     mut socket: WebSocket,
-    mut rx: broadcast::Receiver<LiveTrade>,
-    prefixes: HashSet<String>,
-    trader_filter: HashSet<String>,
+    mut rx: broadcast::Receiver<Alert>,
+    mut convergence_rx: broadcast::Receiver<ConvergenceAlert>,
+    owner: Option<String>,
+    filter: AlertsWsFilter,
+    cache: markets::MarketCache,
 ) {
     loop {
-        tokio::select! {
+        let alert = tokio::select! {
             result = rx.recv() => {
                 match result {
-                    Ok(trade) => {
-                        if !prefixes.contains(&trade.cache_key) {
-                            continue;
-                        }
-                        if !trader_filter.is_empty()
-                            && !trader_filter.contains(&trade.trader.to_lowercase())
-                        {
-                            continue;
-                        }
-                        let json = match serde_json::to_string(&trade) {
-                            Ok(j) => j,
-                            Err(_) => continue,
-                        };
-                        if socket.send(Message::Text(json.into())).await.is_err() {
+                    Ok(alert) => alert,
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("WebSocket client lagged, skipped {n} alerts");
+                        if !send_lag(&mut socket, n).await {
                             break;
                         }
+                        continue;
                     }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            result = convergence_rx.recv() => {
+                match result {
+                    Ok(alert) => Alert::Convergence(alert),
                     Err(broadcast::error::RecvError::Lagged(n)) => {
-                        tracing::debug!("Trades WS client lagged, skipped {n} trades");
+                        tracing::warn!("WebSocket client lagged, skipped {n} convergence alerts");
+                        if !send_lag(&mut socket, n).await {
+                            break;
+                        }
+                        continue;
                     }
                     Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
+            // Handle incoming messages (ping/pong/close)
             msg = socket.recv() => {
                 match msg {
                     Some(Ok(Message::Close(_))) | None => break,
                     Some(Err(_)) => break,
-                    _ => {}
+                    _ => continue, // Ignore text/binary from client
                 }
             }
+        };
+
+        if let Alert::FollowedTraderTrade { ref owners, .. } = alert {
+            if !owner.as_ref().is_some_and(|o| owners.contains(o)) {
+                continue;
+            }
+        }
+        if !filter.matches(&alert, &cache).await {
+            continue;
+        }
+        let json = match serde_json::to_string(&alert) {
+            Ok(j) => j,
+            Err(_) => continue,
+        };
+        if socket.send(Message::Text(json.into())).await.is_err() {
+            break; // Client disconnected
         }
     }
 }
 
 // ---------------------------------------------------------------------------
-// GET /ws/signals — Trader-filtered signal feed with convergence detection
+// Followed-trader alerts (trade_tx → alert_tx)
 // ---------------------------------------------------------------------------
-// JWT is passed via query param since WebSocket upgrade can't send headers.
-// Accepted tradeoff: token appears in logs. Data sensitivity is low (public
-// trader addresses). See spec 09-polylab-evolution.md for design rationale.
 
-#[derive(Clone, Serialize)]
-#[serde(tag = "kind")]
-pub enum SignalMessage {
-    Trade(LiveTrade),
-    Convergence(ConvergenceAlert),
-    Lag { dropped: u64 },
+/// Re-emits live trades by followed traders as `Alert::FollowedTraderTrade`,
+/// tagged with the owners following that address.
+pub async fn follow_alerts_loop(
+    mut trade_rx: broadcast::Receiver<LiveTrade>,
+    follows_rx: tokio::sync::watch::Receiver<HashMap<String, HashSet<String>>>,
+    alert_tx: broadcast::Sender<Alert>,
+) {
+    loop {
+        match trade_rx.recv().await {
+            Ok(trade) => {
+                let owners = match follows_rx.borrow().get(&trade.trader.to_lowercase()) {
+                    Some(owners) if !owners.is_empty() => owners.clone(),
+                    _ => continue,
+                };
+                let _ = alert_tx.send(Alert::FollowedTraderTrade { owners, trade });
+            }
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                tracing::warn!("Follow alerts lagged, skipped {n} trades");
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
 }
 
-#[derive(Clone, Serialize)]
-pub struct ConvergenceAlert {
-    pub question: String,
+// ---------------------------------------------------------------------------
+// Price swing alerts (trade_tx → alert_tx)
+// ---------------------------------------------------------------------------
+
+const PRICE_SWING_WINDOW: Duration = Duration::from_secs(600);
+const PRICE_SWING_DEDUP: Duration = Duration::from_secs(900);
+
+struct PriceSwingDetector {
+    // cache_key → [(timestamp, price)] within the window
+    prices: HashMap<String, VecDeque<(Instant, f64)>>,
+    delta: f64,
+    last_alert: HashMap<String, Instant>,
+    max_assets: usize,
+}
+
+impl PriceSwingDetector {
+    fn new(delta: f64) -> Self {
+        Self {
+            prices: HashMap::new(),
+            delta,
+            last_alert: HashMap::new(),
+            max_assets: 5000,
+        }
+    }
+
+    fn record_trade(&mut self, trade: &LiveTrade) -> Option<Alert> {
+        let price: f64 = trade.price.parse().ok()?;
+        if !(price > 0.0 && price <= 1.0) {
+            return None;
+        }
+        let now = Instant::now();
+        let key = &trade.cache_key;
+
+        let entries = self.prices.entry(key.clone()).or_default();
+        while entries
+            .front()
+            .is_some_and(|(ts, _)| now.duration_since(*ts) >= PRICE_SWING_WINDOW)
+        {
+            entries.pop_front();
+        }
+        let (low, high) = entries
+            .iter()
+            .fold((price, price), |(lo, hi), (_, p)| (lo.min(*p), hi.max(*p)));
+        entries.push_back((now, price));
+
+        // Epsilon so an exact `delta` move still fires despite float rounding
+        let (old_price, direction) = if price - low >= self.delta - 1e-9 {
+            (low, "up")
+        } else if high - price >= self.delta - 1e-9 {
+            (high, "down")
+        } else {
+            return None;
+        };
+
+        if self
+            .last_alert
+            .get(key)
+            .is_some_and(|last| now.duration_since(*last) < PRICE_SWING_DEDUP)
+        {
+            return None;
+        }
+        self.last_alert.insert(key.clone(), now);
+
+        Some(Alert::PriceSwing {
+            timestamp: trade.block_timestamp.clone(),
+            asset_id: trade.asset_id.clone(),
+            question: trade.question.clone(),
+            outcome: trade.outcome.clone(),
+            old_price: format!("{old_price:.6}"),
+            new_price: format!("{price:.6}"),
+            direction: direction.into(),
+            window_seconds: PRICE_SWING_WINDOW.as_secs(),
+            tx_hash: trade.tx_hash.clone(),
+            block_number: trade.block_number,
+            log_index: trade.log_index,
+        })
+    }
+
+    /// Periodic cleanup: drop expired prices and dedup entries.
+    fn sweep(&mut self) {
+        let now = Instant::now();
+        self.prices.retain(|_, entries| {
+            entries.retain(|(ts, _)| now.duration_since(*ts) < PRICE_SWING_WINDOW);
+            !entries.is_empty()
+        });
+        self.last_alert
+            .retain(|_, ts| now.duration_since(*ts) < PRICE_SWING_DEDUP);
+
+        // Hard cap on tracked assets — drop the least recently traded
+        while self.prices.len() > self.max_assets {
+            let Some(oldest_key) = self
+                .prices
+                .iter()
+                .min_by_key(|(_, entries)| entries.back().map_or(now, |(ts, _)| *ts))
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            self.prices.remove(&oldest_key);
+        }
+    }
+}
+
+/// Emits `Alert::PriceSwing` when an asset's traded price moves more than
+/// `PRICE_SWING_ALERT_DELTA` (default 0.10) within 10 minutes.
+pub async fn price_swing_loop(
+    mut trade_rx: broadcast::Receiver<LiveTrade>,
+    alert_tx: broadcast::Sender<Alert>,
+) {
+    let delta = env::var("PRICE_SWING_ALERT_DELTA")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|d| d.is_finite() && *d > 0.0)
+        .unwrap_or(0.10);
+    let mut detector = PriceSwingDetector::new(delta);
+    let mut sweep_interval = tokio::time::interval(Duration::from_secs(60));
+    sweep_interval.tick().await; // skip immediate tick
+
+    loop {
+        tokio::select! {
+            result = trade_rx.recv() => {
+                match result {
+                    Ok(trade) => {
+                        if let Some(alert) = detector.record_trade(&trade) {
+                            let _ = alert_tx.send(alert);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("Price swing alerts lagged, skipped {n} trades");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            _ = sweep_interval.tick() => {
+                detector.sweep();
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Smart money entry alerts (trade_tx → alert_tx)
+// ---------------------------------------------------------------------------
+
+const SMART_MONEY_REFRESH: Duration = Duration::from_secs(600);
+
+/// Top traders (address → rank) and the assets (cache keys) each has traded.
+#[derive(Default)]
+struct SmartMoneyTracker {
+    ranks: HashMap<String, u32>,
+    traded: HashMap<String, HashSet<String>>,
+}
+
+impl SmartMoneyTracker {
+    async fn load(db: &clickhouse::Client, top_n: u32) -> Result<Self, clickhouse::error::Error> {
+        let traders = fetch_top_traders(db, top_n).await?;
+        if traders.is_empty() {
+            return Ok(Self::default());
+        }
+        let trader_list = traders
+            .iter()
+            .map(|t| format!("'{}'", t.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        #[derive(clickhouse::Row, serde::Deserialize)]
+        struct PositionRow {
+            trader: String,
+            asset_id: String,
+        }
+
+        let rows = db
+            .query(&format!(
+                "SELECT DISTINCT lower(toString(trader)) AS trader, asset_id
+                FROM poly_dearboard.trader_positions
+                WHERE lower(toString(trader)) IN ({trader_list})"
+            ))
+            .fetch_all::<PositionRow>()
+            .await?;
+
+        let mut traded: HashMap<String, HashSet<String>> = HashMap::new();
+        for r in rows {
+            traded
+                .entry(r.trader)
+                .or_default()
+                .insert(markets::cache_key(&r.asset_id));
+        }
+        let ranks = traders
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| (t.to_lowercase(), i as u32 + 1))
+            .collect();
+        Ok(Self { ranks, traded })
+    }
+
+    fn record_trade(&mut self, trade: &LiveTrade, min_usdc: f64) -> Option<Alert> {
+        let trader = trade.trader.to_lowercase();
+        let rank = *self.ranks.get(&trader)?;
+        let is_new = self
+            .traded
+            .entry(trader)
+            .or_default()
+            .insert(trade.cache_key.clone());
+        let usdc: f64 = trade.usdc_amount.parse().unwrap_or(0.0);
+        if !is_new || trade.side != "buy" || usdc < min_usdc {
+            return None;
+        }
+        Some(Alert::SmartMoneyEntry {
+            timestamp: trade.block_timestamp.clone(),
+            trader: trade.trader.clone(),
+            rank,
+            asset_id: trade.asset_id.clone(),
+            question: trade.question.clone(),
+            outcome: trade.outcome.clone(),
+            usdc_amount: trade.usdc_amount.clone(),
+            price: trade.price.clone(),
+            tx_hash: trade.tx_hash.clone(),
+            block_number: trade.block_number,
+            log_index: trade.log_index,
+        })
+    }
+}
+
+/// Emits `Alert::SmartMoneyEntry` when one of the top `SMART_MONEY_TOP_N`
+/// (default 20) leaderboard traders buys at least `SMART_MONEY_MIN_USDC`
+/// (default 5000) of an asset they had never traded. The trader set and their
+/// traded assets are reloaded every 10 minutes.
+pub async fn smart_money_loop(
+    db: clickhouse::Client,
+    mut trade_rx: broadcast::Receiver<LiveTrade>,
+    alert_tx: broadcast::Sender<Alert>,
+) {
+    let top_n = env::var("SMART_MONEY_TOP_N")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(20)
+        .clamp(1, 200);
+    let min_usdc = env::var("SMART_MONEY_MIN_USDC")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .unwrap_or(5_000.0);
+    let mut tracker = SmartMoneyTracker::default();
+    let mut refresh_interval = tokio::time::interval(SMART_MONEY_REFRESH);
+
+    loop {
+        tokio::select! {
+            result = trade_rx.recv() => {
+                match result {
+                    Ok(trade) => {
+                        if let Some(alert) = tracker.record_trade(&trade, min_usdc) {
+                            let _ = alert_tx.send(alert);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("Smart money alerts lagged, skipped {n} trades");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            _ = refresh_interval.tick() => {
+                match SmartMoneyTracker::load(&db, top_n).await {
+                    Ok(t) => tracker = t,
+                    Err(e) => tracing::warn!("Smart money refresh failed: {e}"),
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shared convergence feed (trade_tx → convergence_tx)
+// ---------------------------------------------------------------------------
+
+/// Leaderboard size watched by the shared convergence detector
+const SHARED_CONVERGENCE_TOP_N: u32 = 20;
+const SHARED_CONVERGENCE_REFRESH: Duration = Duration::from_secs(600);
+/// Alerts kept for `GET /api/signals/convergence/recent`
+pub const CONVERGENCE_HISTORY_LEN: usize = 100;
+
+/// Runs one `ConvergenceDetector` over the global top-20 traders for every
+/// `/ws/signals` and `/ws/alerts` client, keeping the latest alerts in `history`.
+pub async fn convergence_loop(
+    db: clickhouse::Client,
+    mut trade_rx: broadcast::Receiver<LiveTrade>,
+    convergence_tx: broadcast::Sender<ConvergenceAlert>,
+    history: ConvergenceHistory,
+) {
+    let mut detector = ConvergenceDetector::new(ConvergenceConfig::default());
+    let mut trader_set: HashSet<String> = HashSet::new();
+    let mut refresh_interval = tokio::time::interval(SHARED_CONVERGENCE_REFRESH);
+    let mut sweep_interval = tokio::time::interval(Duration::from_secs(60));
+    sweep_interval.tick().await; // skip immediate tick
+
+    loop {
+        tokio::select! {
+            result = trade_rx.recv() => {
+                match result {
+                    Ok(trade) => {
+                        if !trader_set.contains(&trade.trader.to_lowercase()) {
+                            continue;
+                        }
+                        if let Some(alert) = detector.record_trade(&trade) {
+                            {
+                                let mut history = history.write().await;
+                                if history.len() == CONVERGENCE_HISTORY_LEN {
+                                    history.pop_back();
+                                }
+                                history.push_front(alert.clone());
+                            }
+                            let _ = convergence_tx.send(alert);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("Shared convergence lagged, skipped {n} trades");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            _ = refresh_interval.tick() => {
+                match fetch_top_traders(&db, SHARED_CONVERGENCE_TOP_N).await {
+                    Ok(traders) => {
+                        trader_set = traders.into_iter().map(|t| t.to_lowercase()).collect();
+                    }
+                    Err(e) => tracing::warn!("Shared convergence refresh failed: {e}"),
+                }
+            }
+            _ = sweep_interval.tick() => {
+                detector.sweep();
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GET /ws/trades — WebSocket upgrade (market-filtered trade stream)
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+pub struct TradesWsParams {
+    /// Initial subscription; change it live with `subscribe`/`unsubscribe` messages
+    #[serde(default)]
+    token_ids: String,
+    /// Optional comma-separated trader addresses for server-side filtering.
+    /// When set, only trades from these addresses are forwarded.
+    traders: Option<String>,
+    /// Optional comma-separated market categories (case-insensitive). A trade
+    /// is forwarded when it matches `token_ids` OR `categories`.
+    categories: Option<String>,
+    /// Recent trades sent on connect before the live stream (0 disables)
+    snapshot: Option<u32>,
+}
+
+const TRADES_WS_SNAPSHOT_DEFAULT: u32 = 25;
+const TRADES_WS_SNAPSHOT_MAX: u32 = 200;
+
+pub async fn trades_ws_handler(
+    State(state): State<AppState>,
+    Query(params): Query<TradesWsParams>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let prefixes: HashSet<String> = params
+        .token_ids
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(markets::cache_key)
+        .collect();
+    let trader_filter: HashSet<String> = params
+        .traders
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let categories: HashSet<String> = params
+        .categories
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let snapshot = params
+        .snapshot
+        .unwrap_or(TRADES_WS_SNAPSHOT_DEFAULT)
+        .min(TRADES_WS_SNAPSHOT_MAX);
+    ws.on_upgrade(move |socket| {
+        // Subscribe before the snapshot query so no trade falls in between
+        let rx = state.trade_tx.subscribe();
+        handle_trades_ws(
+            socket,
+            state,
+            rx,
+            prefixes,
+            categories,
+            trader_filter,
+            snapshot,
+        )
+    })
+}
+
+/// Snapshot frame: a `LiveTrade` tagged `"snapshot": true`
+#[derive(Serialize)]
+struct SnapshotTrade<'a> {
+    #[serde(flatten)]
+    trade: &'a LiveTrade,
+    snapshot: bool,
+}
+
+/// Most recent `limit` trades for the subscribed token prefixes, oldest first.
+async fn fetch_trades_snapshot(
+    state: &AppState,
+    prefixes: &HashSet<String>,
+    trader_filter: &HashSet<String>,
+    limit: u32,
+) -> Result<Vec<LiveTrade>, clickhouse::error::Error> {
+    if prefixes.is_empty() || limit == 0 {
+        return Ok(Vec::new());
+    }
+    let quote = |s: &String| format!("'{}'", s.replace('\'', "''"));
+    let prefix_list = prefixes.iter().map(quote).collect::<Vec<_>>().join(",");
+    let mut conditions = vec![
+        format!("trader NOT IN ({})", super::routes::exclude_clause()),
+        format!("left(asset_id, {}) IN ({prefix_list})", markets::PREFIX_LEN),
+    ];
+    if !trader_filter.is_empty() {
+        let trader_list = trader_filter
+            .iter()
+            .map(quote)
+            .collect::<Vec<_>>()
+            .join(",");
+        conditions.push(format!("lower(trader) IN ({trader_list})"));
+    }
+    let where_clause = conditions.join(" AND ");
+
+    let mut rows = state
+        .db
+        .query(&format!(
+            "SELECT
+                toString(tx_hash) AS tx_hash,
+                ifNull(toString(block_timestamp), '') AS block_timestamp,
+                toString(trader) AS trader,
+                side,
+                asset_id,
+                toString(amount) AS amount,
+                toString(price) AS price,
+                toString(usdc_amount) AS usdc_amount,
+                block_number,
+                log_index
+            FROM poly_dearboard.trades
+            WHERE {where_clause}
+            ORDER BY block_number DESC, log_index DESC
+            LIMIT ?"
+        ))
+        .bind(limit)
+        .fetch_all::<RecentTradeRow>()
+        .await?;
+    rows.reverse();
+
+    let ids: Vec<String> = rows
+        .iter()
+        .map(|r| r.asset_id.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &ids,
+    )
+    .await;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let info = market_info.get(&r.asset_id);
+            LiveTrade {
+                cache_key: markets::cache_key(&r.asset_id),
+                asset_id: info
+                    .map(|i| i.gamma_token_id.clone())
+                    .unwrap_or_else(|| markets::to_integer_id(&r.asset_id)),
+                question: info.map(|i| i.question.clone()).unwrap_or_default(),
+                outcome: info.map(|i| i.outcome.clone()).unwrap_or_default(),
+                category: info.map(|i| i.category.clone()).unwrap_or_default(),
+                tx_hash: r.tx_hash,
+                block_timestamp: r.block_timestamp,
+                trader: r.trader,
+                side: r.side,
+                amount: r.amount,
+                price: r.price,
+                usdc_amount: r.usdc_amount,
+                block_number: r.block_number,
+                log_index: r.log_index,
+                recovered: false,
+            }
+        })
+        .collect())
+}
+
+/// Client → server message on /ws/trades:
+/// `{"op":"subscribe"|"unsubscribe","token_ids":[...]}`
+#[derive(Deserialize)]
+struct TradesWsCommand {
+    op: String,
+    #[serde(default)]
+    token_ids: Vec<String>,
+}
+
+/// Apply a client message to the subscription set and build the reply frame.
+/// An empty set receives nothing.
+fn apply_trades_ws_command(prefixes: &mut HashSet<String>, text: &str) -> serde_json::Value {
+    let cmd = match serde_json::from_str::<TradesWsCommand>(text) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            return serde_json::json!({ "op": "error", "message": format!("Invalid message: {e}") });
+        }
+    };
+    let keys = cmd
+        .token_ids
+        .iter()
+        .map(|id| id.trim())
+        .filter(|id| !id.is_empty())
+        .map(markets::cache_key);
+    match cmd.op.as_str() {
+        "subscribe" => prefixes.extend(keys),
+        "unsubscribe" => {
+            for key in keys {
+                prefixes.remove(&key);
+            }
+        }
+        op => {
+            return serde_json::json!({ "op": "error", "message": format!("Unknown op `{op}`") });
+        }
+    }
+    serde_json::json!({ "op": "subscribed", "count": prefixes.len() })
+}
+
+async fn handle_trades_ws(
+    mut socket: WebSocket,
+    state: AppState,
+    mut rx: broadcast::Receiver<LiveTrade>,
+    mut prefixes: HashSet<String>,
+    categories: HashSet<String>,
+    trader_filter: HashSet<String>,
+    snapshot: u32,
+) {
+    let hello = serde_json::json!({
+        "op": "hello",
+        "count": prefixes.len(),
+        "categories": categories,
+        "traders": trader_filter.len(),
+        "match": "token_ids OR categories, then traders",
+    });
+    if socket
+        .send(Message::Text(hello.to_string().into()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    // Category subscriptions snapshot every cached market in those categories
+    let mut snapshot_prefixes = prefixes.clone();
+    if !categories.is_empty() {
+        let cache = state.market_cache.read().await;
+        snapshot_prefixes.extend(
+            cache
+                .iter()
+                .filter(|(_, info)| categories.contains(&info.category.to_lowercase()))
+                .map(|(key, _)| key.clone()),
+        );
+    }
+    let snapshot = fetch_trades_snapshot(&state, &snapshot_prefixes, &trader_filter, snapshot)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Trades WS snapshot query failed: {e}");
+            Vec::new()
+        });
+    // Live trades already delivered in the snapshot are dropped
+    let mut seen: HashSet<(u64, u64)> = HashSet::with_capacity(snapshot.len());
+    for trade in &snapshot {
+        seen.insert((trade.block_number, trade.log_index));
+        let frame = SnapshotTrade {
+            trade,
+            snapshot: true,
+        };
+        let Ok(json) = serde_json::to_string(&frame) else {
+            continue;
+        };
+        if socket.send(Message::Text(json.into())).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            result = rx.recv() => {
+                match result {
+                    Ok(trade) => {
+                        if !prefixes.contains(&trade.cache_key)
+                            && !categories.contains(&trade.category.to_lowercase())
+                        {
+                            continue;
+                        }
+                        if !seen.is_empty() && seen.remove(&(trade.block_number, trade.log_index)) {
+                            continue;
+                        }
+                        if !trader_filter.is_empty()
+                            && !trader_filter.contains(&trade.trader.to_lowercase())
+                        {
+                            continue;
+                        }
+                        let json = match serde_json::to_string(&trade) {
+                            Ok(j) => j,
+                            Err(_) => continue,
+                        };
+                        if socket.send(Message::Text(json.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::debug!("Trades WS client lagged, skipped {n} trades");
+                        if !send_lag(&mut socket, n).await {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            msg = socket.recv() => {
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        let reply = apply_trades_ws_command(&mut prefixes, text.as_str());
+                        if socket.send(Message::Text(reply.to_string().into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(_)) => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GET /api/trades/stream — SSE trade stream (for clients without WebSockets)
+// ---------------------------------------------------------------------------
+
+const TRADES_STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize)]
+pub struct TradesStreamParams {
+    /// Optional comma-separated token IDs, matched by cache key like /ws/trades
+    token_ids: Option<String>,
+    /// Minimum trade size in USDC
+    min_usdc: Option<f64>,
+}
+
+struct TradesStreamFilter {
+    /// Empty means every market
+    prefixes: HashSet<String>,
+    min_usdc: Option<f64>,
+}
+
+impl TradesStreamFilter {
+    fn matches(&self, trade: &LiveTrade) -> bool {
+        (self.prefixes.is_empty() || self.prefixes.contains(&trade.cache_key))
+            && self.min_usdc.is_none_or(|min| {
+                trade
+                    .usdc_amount
+                    .parse::<f64>()
+                    .is_ok_and(|usdc| usdc >= min)
+            })
+    }
+}
+
+/// Each matching trade is an `event: trade` frame; a lagging client gets an
+/// `event: lag` frame with the number of dropped trades. The stream (and its
+/// broadcast receiver) is dropped when the client disconnects.
+pub async fn trades_stream_handler(
+    State(state): State<AppState>,
+    Query(params): Query<TradesStreamParams>,
+) -> Result<
+    Sse<impl futures_util::Stream<Item = Result<Event, std::convert::Infallible>>>,
+    (StatusCode, String),
+> {
+    if params.min_usdc.is_some_and(|m| !m.is_finite() || m < 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid min_usdc: must be a non-negative number".into(),
+        ));
+    }
+    let filter = TradesStreamFilter {
+        prefixes: params
+            .token_ids
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(markets::cache_key)
+            .collect(),
+        min_usdc: params.min_usdc,
+    };
+
+    let rx = state.trade_tx.subscribe();
+    let stream = futures_util::stream::unfold((rx, filter), |(mut rx, filter)| async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(trade) => {
+                    if !filter.matches(&trade) {
+                        continue;
+                    }
+                    match Event::default().event("trade").json_data(&trade) {
+                        Ok(event) => event,
+                        Err(_) => continue,
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::debug!("Trades SSE client lagged, skipped {n} trades");
+                    Event::default()
+                        .event("lag")
+                        .data(serde_json::json!({ "dropped": n }).to_string())
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            return Some((Ok(event), (rx, filter)));
+        }
+    });
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(TRADES_STREAM_KEEP_ALIVE)
+            .text("keep-alive"),
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// GET /ws/signals — Trader-filtered signal feed with convergence detection
+// ---------------------------------------------------------------------------
+// JWT is passed via query param since WebSocket upgrade can't send headers.
+// Accepted tradeoff: token appears in logs. Data sensitivity is low (public
+// trader addresses). See spec 09-polylab-evolution.md for design rationale.
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind")]
+pub enum SignalMessage {
+    Trade(LiveTrade),
+    Convergence(ConvergenceAlert),
+    Lag { dropped: u64 },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ConvergenceAlert {
+    pub question: String,
     pub asset_id: String,
     pub outcome: String,
     pub traders: Vec<String>,
@@ -636,6 +1987,12 @@ pub struct SignalWsParams {
     list_id: Option<String>,
     top_n: Option<u32>,
     token: String,
+    /// Convergence window, 30–3600 seconds (default 300)
+    window_seconds: Option<u64>,
+    /// Distinct same-side traders needed, 2–20 (default 2)
+    min_traders: Option<u32>,
+    /// Combined USDC of the converging trades (default 0)
+    min_total_usdc: Option<f64>,
 }
 
 pub async fn signals_ws_handler(
@@ -644,9 +2001,35 @@ pub async fn signals_ws_handler(
     ws: WebSocketUpgrade,
 ) -> Result<impl IntoResponse, (axum::http::StatusCode, String)> {
     // Validate JWT from query param before upgrading
-    let owner = super::auth::validate_jwt(&params.token, &state.jwt_secret)
+    let owner = super::auth::validate_jwt(&params.token, &state.jwt_secret, &state.revocations)
         .map_err(|_| (axum::http::StatusCode::UNAUTHORIZED, "Invalid token".into()))?;
 
+    if params
+        .min_total_usdc
+        .is_some_and(|m| !m.is_finite() || m < 0.0)
+    {
+        return Err((
+            axum::http::StatusCode::BAD_REQUEST,
+            "Invalid min_total_usdc: must be a non-negative number".into(),
+        ));
+    }
+    // Default top-20 with default tuning is served by the shared detector
+    let shared = params.list_id.is_none()
+        && params.top_n.unwrap_or(SHARED_CONVERGENCE_TOP_N) == SHARED_CONVERGENCE_TOP_N
+        && params.window_seconds.is_none()
+        && params.min_traders.is_none()
+        && params.min_total_usdc.is_none();
+    let defaults = ConvergenceConfig::default();
+    let config = ConvergenceConfig {
+        window: params
+            .window_seconds
+            .map_or(defaults.window, |s| Duration::from_secs(s.clamp(30, 3600))),
+        min_traders: params
+            .min_traders
+            .map_or(defaults.min_traders, |n| n.clamp(2, 20) as usize),
+        min_total_usdc: params.min_total_usdc.unwrap_or(defaults.min_total_usdc),
+    };
+
     // Mutual exclusion: exactly one of list_id or top_n
     if params.list_id.is_some() && params.top_n.is_some() {
         return Err((
@@ -664,34 +2047,11 @@ pub async fn signals_ws_handler(
     } else {
         // Top N from ClickHouse leaderboard (default 20)
         let top_n = params.top_n.unwrap_or(20).clamp(1, 50);
-        let exclude = super::routes::exclude_clause();
-        let query = format!(
-            "WITH resolved AS (
-                SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-                FROM poly_dearboard.resolved_prices FINAL
-            )
-            SELECT toString(p.trader) AS address
-            FROM poly_dearboard.trader_positions p
-            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-            LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-            WHERE p.trader NOT IN ({exclude})
-            GROUP BY p.trader
-            ORDER BY sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) DESC
-            LIMIT {top_n}"
-        );
-
-        #[derive(clickhouse::Row, serde::Deserialize)]
-        struct Addr {
-            address: String,
-        }
-
-        let rows: Vec<Addr> = state
-            .db
-            .query(&query)
-            .fetch_all::<Addr>()
+        fetch_top_traders(&state.db, top_n)
             .await
-            .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-        rows.into_iter().map(|r| r.address).collect()
+            .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .into_iter()
+            .collect()
     };
 
     if trader_set.is_empty() {
@@ -701,32 +2061,93 @@ pub async fn signals_ws_handler(
         ));
     }
 
-    Ok(ws
-        .on_upgrade(move |socket| handle_signal_ws(socket, state.trade_tx.subscribe(), trader_set)))
+    Ok(ws.on_upgrade(move |socket| {
+        let convergence = if shared {
+            ConvergenceSource::Shared(state.convergence_tx.subscribe())
+        } else {
+            ConvergenceSource::Local(ConvergenceDetector::new(config))
+        };
+        handle_signal_ws(socket, state.trade_tx.subscribe(), trader_set, convergence)
+    }))
+}
+
+/// Top `top_n` leaderboard traders by all-time PnL, best first.
+async fn fetch_top_traders(
+    db: &clickhouse::Client,
+    top_n: u32,
+) -> Result<Vec<String>, clickhouse::error::Error> {
+    let exclude = super::routes::exclude_clause();
+    let query = format!(
+        "WITH resolved AS (
+            SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+            FROM poly_dearboard.resolved_prices FINAL
+        )
+        SELECT toString(p.trader) AS address
+        FROM poly_dearboard.trader_positions p
+        LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+        LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+        WHERE p.trader NOT IN ({exclude})
+        GROUP BY p.trader
+        ORDER BY sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) DESC
+        LIMIT {top_n}"
+    );
+
+    #[derive(clickhouse::Row, serde::Deserialize)]
+    struct Addr {
+        address: String,
+    }
+
+    let rows = db.query(&query).fetch_all::<Addr>().await?;
+    Ok(rows.into_iter().map(|r| r.address).collect())
 }
 
+/// Convergence tuning from `/ws/signals` query params.
+struct ConvergenceConfig {
+    window: Duration,
+    min_traders: usize,
+    min_total_usdc: f64,
+}
+
+impl Default for ConvergenceConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(300), // 5 minutes
+            min_traders: 2,
+            min_total_usdc: 0.0,
+        }
+    }
+}
+
+/// Don't re-fire for the same asset within this long
+const CONVERGENCE_DEDUP: Duration = Duration::from_secs(60);
+
 struct ConvergenceDetector {
     // asset_id → [(trader, timestamp, side, usdc_amount)]
     recent_trades: HashMap<String, Vec<(String, Instant, String, f64)>>,
     window: Duration,
     threshold: usize,
+    min_total_usdc: f64,
     last_alert: HashMap<String, Instant>,
     max_assets: usize,
 }
 
 impl ConvergenceDetector {
-    fn new() -> Self {
+    fn new(config: ConvergenceConfig) -> Self {
         Self {
             recent_trades: HashMap::new(),
-            window: Duration::from_secs(300), // 5 minutes
-            threshold: 2,
+            window: config.window,
+            threshold: config.min_traders,
+            min_total_usdc: config.min_total_usdc,
             last_alert: HashMap::new(),
             max_assets: 500,
         }
     }
 
     fn record_trade(&mut self, trade: &LiveTrade) -> Option<ConvergenceAlert> {
-        let now = Instant::now();
+        self.record_trade_at(trade, Instant::now())
+    }
+
+    fn record_trade_at(&mut self, trade: &LiveTrade, now: Instant) -> Option<ConvergenceAlert> {
         let asset_id = &trade.asset_id;
         let usdc: f64 = trade.usdc_amount.parse().unwrap_or(0.0);
 
@@ -737,46 +2158,38 @@ impl ConvergenceDetector {
         // Evict old entries for this asset
         entries.retain(|(_, ts, _, _)| now.duration_since(*ts) < self.window);
 
-        // Count distinct traders
+        // Only trades on the same side as this one converge with it
+        let same_side: Vec<&(String, Instant, String, f64)> = entries
+            .iter()
+            .filter(|(_, _, s, _)| *s == trade.side)
+            .collect();
         let distinct_traders: HashSet<&str> =
-            entries.iter().map(|(t, _, _, _)| t.as_str()).collect();
+            same_side.iter().map(|(t, _, _, _)| t.as_str()).collect();
         let count = distinct_traders.len();
+        let total_usdc: f64 = same_side.iter().map(|(_, _, _, u)| u).sum();
 
-        if count >= self.threshold {
-            // Dedup: don't re-fire for same asset within 60s
-            if let Some(last) = self.last_alert.get(asset_id) {
-                if now.duration_since(*last) < Duration::from_secs(60) {
-                    return None;
-                }
-            }
-            self.last_alert.insert(asset_id.clone(), now);
-
-            // Build alert
-            let traders: Vec<String> = distinct_traders.into_iter().map(String::from).collect();
-            let total_usdc: f64 = entries.iter().map(|(_, _, _, u)| u).sum();
-
-            // Dominant side
-            let buy_count = entries.iter().filter(|(_, _, s, _)| s == "buy").count();
-            let sell_count = entries.len() - buy_count;
-            let side = if buy_count >= sell_count {
-                "BUY"
-            } else {
-                "SELL"
-            };
-
-            return Some(ConvergenceAlert {
-                question: trade.question.clone(),
-                asset_id: asset_id.clone(),
-                outcome: trade.outcome.clone(),
-                traders,
-                trader_count: count as u32,
-                window_seconds: 300,
-                side: side.into(),
-                total_usdc,
-            });
+        if count < self.threshold || total_usdc < self.min_total_usdc {
+            return None;
         }
 
-        None
+        if let Some(last) = self.last_alert.get(asset_id) {
+            if now.duration_since(*last) < CONVERGENCE_DEDUP {
+                return None;
+            }
+        }
+        self.last_alert.insert(asset_id.clone(), now);
+
+        let traders: Vec<String> = distinct_traders.into_iter().map(String::from).collect();
+        Some(ConvergenceAlert {
+            question: trade.question.clone(),
+            asset_id: asset_id.clone(),
+            outcome: trade.outcome.clone(),
+            traders,
+            trader_count: count as u32,
+            window_seconds: self.window.as_secs(),
+            side: trade.side.to_uppercase(),
+            total_usdc,
+        })
     }
 
     /// Periodic cleanup: remove entries older than window across all assets.
@@ -809,12 +2222,34 @@ impl ConvergenceDetector {
     }
 }
 
+/// Where a `/ws/signals` connection gets its convergence alerts.
+enum ConvergenceSource {
+    /// Global top-20 feed from `convergence_loop`
+    Shared(broadcast::Receiver<ConvergenceAlert>),
+    /// Custom trader set or tuning: a detector for this connection only
+    Local(ConvergenceDetector),
+}
+
+/// Next shared convergence alert; never resolves for per-connection detectors.
+async fn recv_shared_convergence(
+    rx: &mut Option<broadcast::Receiver<ConvergenceAlert>>,
+) -> Result<ConvergenceAlert, broadcast::error::RecvError> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
 async fn handle_signal_ws(
     mut socket: WebSocket,
     mut rx: broadcast::Receiver<LiveTrade>,
     trader_set: HashSet<String>,
+    convergence: ConvergenceSource,
 ) {
-    let mut detector = ConvergenceDetector::new();
+    let (mut shared_rx, mut detector) = match convergence {
+        ConvergenceSource::Shared(rx) => (Some(rx), None),
+        ConvergenceSource::Local(detector) => (None, Some(detector)),
+    };
     let mut sweep_interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
     sweep_interval.tick().await; // skip immediate tick
 
@@ -837,8 +2272,8 @@ async fn handle_signal_ws(
                             break;
                         }
 
-                        // Check convergence
-                        if let Some(alert) = detector.record_trade(&trade) {
+                        // Check convergence (per-connection detector only)
+                        if let Some(alert) = detector.as_mut().and_then(|d| d.record_trade(&trade)) {
                             let alert_msg = SignalMessage::Convergence(alert);
                             if let Ok(json) = serde_json::to_string(&alert_msg) {
                                 if socket.send(Message::Text(json.into())).await.is_err() {
@@ -857,8 +2292,26 @@ async fn handle_signal_ws(
                     Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
+            result = recv_shared_convergence(&mut shared_rx) => {
+                match result {
+                    Ok(alert) => {
+                        let alert_msg = SignalMessage::Convergence(alert);
+                        if let Ok(json) = serde_json::to_string(&alert_msg) {
+                            if socket.send(Message::Text(json.into())).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("Signal WS client lagged, skipped {n} convergence alerts");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
             _ = sweep_interval.tick() => {
-                detector.sweep();
+                if let Some(detector) = detector.as_mut() {
+                    detector.sweep();
+                }
             }
             msg = socket.recv() => {
                 match msg {
@@ -885,7 +2338,7 @@ pub async fn copytrade_ws_handler(
     Query(params): Query<CopyTradeWsParams>,
     ws: WebSocketUpgrade,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let owner = super::auth::validate_jwt(&params.token, &state.jwt_secret)
+    let owner = super::auth::validate_jwt(&params.token, &state.jwt_secret, &state.revocations)
         .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid token".into()))?;
 
     let rx = state.copytrade_update_tx.subscribe();
@@ -928,3 +2381,154 @@ async fn handle_copytrade_ws(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(trader: &str, side: &str, usdc: &str) -> LiveTrade {
+        LiveTrade {
+            tx_hash: String::new(),
+            block_timestamp: String::new(),
+            trader: trader.into(),
+            side: side.into(),
+            asset_id: "123".into(),
+            amount: "0".into(),
+            price: "0.5".into(),
+            usdc_amount: usdc.into(),
+            question: String::new(),
+            outcome: String::new(),
+            category: String::new(),
+            block_number: 0,
+            log_index: 0,
+            recovered: false,
+            cache_key: "123".into(),
+        }
+    }
+
+    fn fixture(json: &str) -> serde_json::Value {
+        let payload: WebhookPayload = serde_json::from_str(json).expect("valid fixture");
+        payload.event_data.into_iter().next().expect("one event")
+    }
+
+    const ORDERS_MATCHED: &str = include_str!("fixtures/orders_matched.json");
+    const TAKER_SUMMARY: &str = include_str!("fixtures/order_filled_taker_summary.json");
+    const MAKER_FILL: &str = include_str!("fixtures/order_filled_maker.json");
+    const TAKER: &str = "0x7c3db723f1d4d8cb9c550095203b686cb11e5c6b";
+
+    #[test]
+    fn orders_matched_is_attributed_to_taker() {
+        let event = fixture(ORDERS_MATCHED);
+        let trade = build_live_trade(&event, &markets::MarketMap::default()).expect("taker trade");
+        assert_eq!(trade.trader, TAKER);
+        assert_eq!(trade.side, "buy");
+        assert_eq!(trade.usdc_amount, "30000.000000");
+        assert_eq!(trade.amount, "50000.000000");
+        assert_eq!(trade.log_index, 42);
+
+        let Some(Alert::WhaleTrade { trader, .. }) = parse_order_filled(
+            &event,
+            &markets::MarketMap::default(),
+            usdc_to_raw(25_000.0),
+        ) else {
+            panic!("expected a whale alert");
+        };
+        assert_eq!(trader, TAKER);
+    }
+
+    #[test]
+    fn taker_summary_with_exchange_maker_is_dropped() {
+        let event = fixture(TAKER_SUMMARY);
+        assert!(build_live_trade(&event, &markets::MarketMap::default()).is_none());
+    }
+
+    #[test]
+    fn maker_fill_is_unchanged() {
+        let event = fixture(MAKER_FILL);
+        assert!(taker_order_hash(&event).is_none());
+        let trade = build_live_trade(&event, &markets::MarketMap::default()).expect("maker trade");
+        assert_eq!(trade.trader, "0x1f2e3d4c5b6a7980a1b2c3d4e5f60718293a4b5c");
+        assert_eq!(trade.side, "sell");
+    }
+
+    #[test]
+    fn taker_fill_counts_once() {
+        let matched = fixture(ORDERS_MATCHED);
+        let summary = fixture(TAKER_SUMMARY);
+        let hash = taker_order_hash(&matched).expect("taker order hash");
+        assert_eq!(taker_order_hash(&summary), Some(hash));
+
+        let dedup = RecentKeys::new(TAKER_ORDER_DEDUP_TTL);
+        assert!(dedup.first_seen(hash));
+        assert!(!dedup.first_seen(&hash.to_uppercase()));
+    }
+
+    #[test]
+    fn convergence_requires_same_side() {
+        let mut detector = ConvergenceDetector::new(ConvergenceConfig::default());
+        let now = Instant::now();
+        assert!(
+            detector
+                .record_trade_at(&trade("0xa", "buy", "100"), now)
+                .is_none()
+        );
+        assert!(
+            detector
+                .record_trade_at(&trade("0xb", "sell", "100"), now)
+                .is_none()
+        );
+
+        let alert = detector
+            .record_trade_at(&trade("0xc", "buy", "100"), now)
+            .expect("two buyers converge");
+        assert_eq!(alert.side, "BUY");
+        assert_eq!(alert.trader_count, 2);
+        assert_eq!(alert.total_usdc, 200.0);
+    }
+
+    #[test]
+    fn convergence_respects_min_total_usdc() {
+        let mut detector = ConvergenceDetector::new(ConvergenceConfig {
+            min_total_usdc: 500.0,
+            ..ConvergenceConfig::default()
+        });
+        let now = Instant::now();
+        detector.record_trade_at(&trade("0xa", "buy", "100"), now);
+        assert!(
+            detector
+                .record_trade_at(&trade("0xb", "buy", "100"), now)
+                .is_none()
+        );
+        assert!(
+            detector
+                .record_trade_at(&trade("0xc", "buy", "300"), now)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn convergence_dedups_within_window() {
+        let mut detector = ConvergenceDetector::new(ConvergenceConfig::default());
+        let now = Instant::now();
+        detector.record_trade_at(&trade("0xa", "buy", "100"), now);
+        assert!(
+            detector
+                .record_trade_at(&trade("0xb", "buy", "100"), now)
+                .is_some()
+        );
+
+        let soon = now + Duration::from_secs(30);
+        assert!(
+            detector
+                .record_trade_at(&trade("0xc", "buy", "100"), soon)
+                .is_none()
+        );
+
+        let later = now + CONVERGENCE_DEDUP;
+        assert!(
+            detector
+                .record_trade_at(&trade("0xd", "buy", "100"), later)
+                .is_some()
+        );
+    }
+}
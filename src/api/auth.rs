@@ -1,9 +1,11 @@
-use alloy_primitives::{Address, B256, Signature};
-use alloy_sol_types::{SolStruct, eip712_domain};
+use alloy_primitives::{Address, B256, Signature, eip191_hash_message};
+use alloy_sol_types::{SolCall, SolStruct, eip712_domain};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 alloy_sol_types::sol! {
     struct SignIn {
@@ -13,6 +15,13 @@ alloy_sol_types::sol! {
     }
 }
 
+alloy_sol_types::sol! {
+    function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+}
+
+/// EIP-1271 return value for a valid signature (also the function selector).
+const EIP1271_MAGIC: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
 /// EIP-712 domain for PolyDerboard on Polygon.
 fn domain() -> alloy_sol_types::Eip712Domain {
     eip712_domain! {
@@ -43,41 +52,93 @@ impl IntoResponse for AuthError {
     }
 }
 
-/// Recovers the signer from an EIP-712 `SignIn` signature and verifies it matches `address`.
-pub fn recover_eip712_signer(
-    address: &str,
-    nonce: &str,
-    issued_at: &str,
-    signature_hex: &str,
-) -> Result<Address, AuthError> {
-    let addr_lower = address.to_lowercase();
-
-    // Parse the claimed address
-    let claimed: Address = addr_lower
+fn parse_claimed(address: &str) -> Result<Address, AuthError> {
+    address
+        .to_lowercase()
         .parse()
-        .map_err(|_| AuthError::InvalidSignature)?;
+        .map_err(|_| AuthError::InvalidSignature)
+}
 
-    // Check issuedAt is within 5 minutes
+/// Checks issuedAt is within 5 minutes (1 minute of clock skew ahead).
+fn check_issued_at(issued_at: &str) -> Result<(), AuthError> {
     let issued: chrono::DateTime<chrono::Utc> =
         issued_at.parse().map_err(|_| AuthError::InvalidSignature)?;
     let age = chrono::Utc::now() - issued;
     if age.num_seconds() > 300 || age.num_seconds() < -60 {
         return Err(AuthError::Expired);
     }
+    Ok(())
+}
 
-    // Build the EIP-712 struct
+/// EIP-712 signing hash: keccak256("\x19\x01" || domainSeparator || structHash)
+fn signin_hash(wallet: Address, nonce: &str, issued_at: &str) -> B256 {
     let sign_in = SignIn {
-        wallet: claimed,
+        wallet,
         nonce: nonce.to_string(),
         issuedAt: issued_at.to_string(),
     };
+    sign_in.eip712_signing_hash(&domain())
+}
 
-    // Compute signing hash: keccak256("\x19\x01" || domainSeparator || structHash)
-    let signing_hash: B256 = sign_in.eip712_signing_hash(&domain());
-
-    // Decode signature hex (strip 0x prefix if present)
+/// Decodes signature hex (0x prefix optional).
+fn decode_signature(signature_hex: &str) -> Result<Vec<u8>, AuthError> {
     let sig_hex = signature_hex.strip_prefix("0x").unwrap_or(signature_hex);
-    let sig_bytes = hex::decode(sig_hex).map_err(|_| AuthError::InvalidSignature)?;
+    hex::decode(sig_hex).map_err(|_| AuthError::InvalidSignature)
+}
+
+/// The sign-in message a wallet signed.
+#[derive(Clone, Copy)]
+pub enum SignInMessage<'a> {
+    /// EIP-712 typed data (`SignIn` struct)
+    Eip712,
+    /// EIP-4361 text signed with `personal_sign`, bound to the app's URI
+    Siwe { uri: &'a str },
+}
+
+impl SignInMessage<'_> {
+    fn hash(self, wallet: Address, nonce: &str, issued_at: &str) -> B256 {
+        match self {
+            Self::Eip712 => signin_hash(wallet, nonce, issued_at),
+            Self::Siwe { uri } => eip191_hash_message(siwe_message(uri, wallet, nonce, issued_at)),
+        }
+    }
+}
+
+/// EIP-4361 sign-in message for `wallet` on Polygon. The domain is the host of `uri`.
+pub fn siwe_message(uri: &str, wallet: Address, nonce: &str, issued_at: &str) -> String {
+    let domain = uri
+        .split_once("://")
+        .map_or(uri, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    format!(
+        "{domain} wants you to sign in with your Ethereum account:\n\
+         {wallet}\n\
+         \n\
+         Sign in to PolyDerboard.\n\
+         \n\
+         URI: {uri}\n\
+         Version: 1\n\
+         Chain ID: 137\n\
+         Nonce: {nonce}\n\
+         Issued At: {issued_at}"
+    )
+}
+
+/// Recovers an EOA signer of `message` and verifies it matches `address`.
+fn recover_signer(
+    message: SignInMessage,
+    address: &str,
+    nonce: &str,
+    issued_at: &str,
+    signature_hex: &str,
+) -> Result<Address, AuthError> {
+    let claimed = parse_claimed(address)?;
+    check_issued_at(issued_at)?;
+    let signing_hash = message.hash(claimed, nonce, issued_at);
+
+    let sig_bytes = decode_signature(signature_hex)?;
     if sig_bytes.len() != 65 {
         return Err(AuthError::InvalidSignature);
     }
@@ -97,11 +158,185 @@ pub fn recover_eip712_signer(
     Ok(recovered)
 }
 
+/// Verifies a sign-in signature from an EOA or, failing ecrecover, a smart-contract
+/// wallet (Safe etc.) via EIP-1271 `isValidSignature` on the claimed address.
+pub async fn verify_signin_signature(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    message: SignInMessage<'_>,
+    address: &str,
+    nonce: &str,
+    issued_at: &str,
+    signature_hex: &str,
+) -> Result<Address, AuthError> {
+    match recover_signer(message, address, nonce, issued_at, signature_hex) {
+        Err(AuthError::InvalidSignature) => {}
+        other => return other,
+    }
+
+    let claimed = parse_claimed(address)?;
+    check_issued_at(issued_at)?;
+    let signing_hash = message.hash(claimed, nonce, issued_at);
+    let signature = decode_signature(signature_hex)?;
+    if is_valid_contract_signature(http, rpc_url, claimed, signing_hash, signature).await {
+        Ok(claimed)
+    } else {
+        Err(AuthError::InvalidSignature)
+    }
+}
+
+/// `eth_call`s `isValidSignature(hash, signature)` on `wallet`. EOAs return empty
+/// data, so they (and RPC failures) are rejected.
+async fn is_valid_contract_signature(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    wallet: Address,
+    hash: B256,
+    signature: Vec<u8>,
+) -> bool {
+    #[derive(Deserialize)]
+    struct RpcResponse {
+        result: Option<String>,
+    }
+
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "eth_call",
+        "params": [
+            { "to": wallet.to_string(), "data": eip1271_calldata(hash, signature) },
+            "latest"
+        ],
+        "id": 1
+    });
+    let resp = match http
+        .post(rpc_url)
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await
+    {
+        Ok(resp) => resp,
+        Err(e) => {
+            tracing::warn!("EIP-1271 eth_call to {wallet} failed: {e}");
+            return false;
+        }
+    };
+    resp.json::<RpcResponse>()
+        .await
+        .ok()
+        .and_then(|r| r.result)
+        .is_some_and(|result| is_eip1271_magic(&result))
+}
+
+fn eip1271_calldata(hash: B256, signature: Vec<u8>) -> String {
+    let call = isValidSignatureCall {
+        hash,
+        signature: signature.into(),
+    };
+    format!("0x{}", hex::encode(call.abi_encode()))
+}
+
+/// True when the `eth_call` result is the ABI-encoded magic value `0x1626ba7e`.
+fn is_eip1271_magic(result_hex: &str) -> bool {
+    hex::decode(result_hex.trim_start_matches("0x"))
+        .is_ok_and(|b| b.len() == 32 && b[..4] == EIP1271_MAGIC)
+}
+
+/// Lifetime of each issued (or refreshed) token.
+pub const TOKEN_TTL_SECS: u64 = 7 * 24 * 3600;
+/// Refresh is refused this close to the end of the absolute session lifetime.
+const REFRESH_CUTOFF_SECS: u64 = 10 * 60;
+/// Tolerated clock skew for an `auth_time` slightly in the future.
+const CLOCK_SKEW_SECS: u64 = 60;
+
 #[derive(Serialize, Deserialize)]
 struct Claims {
     sub: String,
     iat: u64,
     exp: u64,
+    /// When the wallet signature was verified; kept across refreshes.
+    /// Tokens issued before refresh support lack it and fall back to `iat`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auth_time: Option<u64>,
+    /// Unique token id, the key for single-token revocation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jti: Option<String>,
+}
+
+impl Claims {
+    /// Revocation key; tokens issued before `jti` existed use `sub:iat`.
+    fn token_id(&self) -> String {
+        self.jti
+            .clone()
+            .unwrap_or_else(|| format!("{}:{}", self.sub, self.iat))
+    }
+}
+
+fn new_jti() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Revoked token ids and per-address cutoffs, mirrored from SQLite
+/// (`revoked_tokens`, `session_cutoffs`) so validation never touches the DB.
+#[derive(Default)]
+pub struct RevocationList {
+    /// jti → token expiry (unix seconds)
+    tokens: HashMap<String, u64>,
+    /// address → tokens with `iat` strictly before this are revoked. `iat` only
+    /// has second precision, so the cutoff second itself stays valid and a
+    /// sign-in right after logout-all works; the token presented to logout-all
+    /// is revoked by jti instead.
+    not_before: HashMap<String, u64>,
+}
+
+pub type Revocations = Arc<RwLock<RevocationList>>;
+
+impl RevocationList {
+    pub fn new(tokens: Vec<(String, u64)>, cutoffs: Vec<(String, u64)>) -> Self {
+        Self {
+            tokens: tokens.into_iter().collect(),
+            not_before: cutoffs.into_iter().collect(),
+        }
+    }
+
+    fn is_revoked(&self, claims: &Claims) -> bool {
+        self.tokens.contains_key(&claims.token_id())
+            || self
+                .not_before
+                .get(&claims.sub)
+                .is_some_and(|&cutoff| claims.iat < cutoff)
+    }
+
+    pub fn revoke(&mut self, jti: &str, expires_at: u64) {
+        self.tokens.insert(jti.to_string(), expires_at);
+    }
+
+    pub fn revoke_all(&mut self, address: &str, not_before: u64) {
+        self.not_before.insert(address.to_string(), not_before);
+    }
+
+    /// Drops entries that can no longer match a valid token.
+    pub fn purge_expired(&mut self, now: u64) {
+        self.tokens.retain(|_, exp| *exp >= now);
+        self.not_before
+            .retain(|_, cutoff| cutoff.saturating_add(TOKEN_TTL_SECS) >= now);
+    }
+}
+
+/// A validated token, as needed to revoke it.
+pub struct TokenSession {
+    pub address: String,
+    pub jti: String,
+    pub expires_at: u64,
+}
+
+fn encode_claims(claims: &Claims, secret: &[u8]) -> String {
+    jsonwebtoken::encode(
+        &Header::default(),
+        claims,
+        &EncodingKey::from_secret(secret),
+    )
+    .expect("JWT encoding failed")
 }
 
 /// Issues a JWT for the given wallet address (7-day expiry).
@@ -110,23 +345,495 @@ pub fn issue_jwt(address: &str, secret: &[u8]) -> String {
     let claims = Claims {
         sub: address.to_lowercase(),
         iat: now,
-        exp: now + 7 * 24 * 3600,
+        exp: now + TOKEN_TTL_SECS,
+        auth_time: Some(now),
+        jti: Some(new_jti()),
     };
-    jsonwebtoken::encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret),
-    )
-    .expect("JWT encoding failed")
+    encode_claims(&claims, secret)
+}
+
+/// Claims for a refreshed token: same subject and `auth_time`, a new 7-day
+/// window capped at `auth_time + max_session_secs`.
+fn refreshed_claims(claims: &Claims, now: u64, max_session_secs: u64) -> Result<Claims, AuthError> {
+    let auth_time = claims.auth_time.unwrap_or(claims.iat);
+    if auth_time > now + CLOCK_SKEW_SECS {
+        return Err(AuthError::InvalidToken);
+    }
+    let session_end = auth_time.saturating_add(max_session_secs);
+    if now + REFRESH_CUTOFF_SECS >= session_end {
+        return Err(AuthError::Expired);
+    }
+    Ok(Claims {
+        sub: claims.sub.clone(),
+        iat: now,
+        exp: (now + TOKEN_TTL_SECS).min(session_end),
+        auth_time: Some(auth_time),
+        jti: Some(new_jti()),
+    })
+}
+
+/// Exchanges a valid token for a fresh one within the absolute session lifetime.
+pub fn refresh_jwt(
+    token: &str,
+    secret: &[u8],
+    revocations: &Revocations,
+    max_session_secs: u64,
+) -> Result<String, AuthError> {
+    let claims = decode_claims(token, secret, revocations)?;
+    let now = chrono::Utc::now().timestamp() as u64;
+    let claims = refreshed_claims(&claims, now, max_session_secs)?;
+    Ok(encode_claims(&claims, secret))
 }
 
-/// Validates a JWT and returns the wallet address.
-pub fn validate_jwt(token: &str, secret: &[u8]) -> Result<String, AuthError> {
+fn decode_claims(
+    token: &str,
+    secret: &[u8],
+    revocations: &Revocations,
+) -> Result<Claims, AuthError> {
     let data = jsonwebtoken::decode::<Claims>(
         token,
         &DecodingKey::from_secret(secret),
         &Validation::default(),
     )
     .map_err(|_| AuthError::InvalidToken)?;
-    Ok(data.claims.sub)
+    let revoked = revocations
+        .read()
+        .unwrap_or_else(|p| p.into_inner())
+        .is_revoked(&data.claims);
+    if revoked {
+        return Err(AuthError::InvalidToken);
+    }
+    Ok(data.claims)
+}
+
+/// Validates a JWT and returns what is needed to revoke it.
+pub fn token_session(
+    token: &str,
+    secret: &[u8],
+    revocations: &Revocations,
+) -> Result<TokenSession, AuthError> {
+    let claims = decode_claims(token, secret, revocations)?;
+    Ok(TokenSession {
+        jti: claims.token_id(),
+        address: claims.sub,
+        expires_at: claims.exp,
+    })
+}
+
+/// Validates a JWT (signature, expiry, revocation) and returns the wallet address.
+pub fn validate_jwt(
+    token: &str,
+    secret: &[u8],
+    revocations: &Revocations,
+) -> Result<String, AuthError> {
+    Ok(decode_claims(token, secret, revocations)?.sub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::SignerSync;
+    use alloy::signers::local::PrivateKeySigner;
+
+    const DAY: u64 = 24 * 3600;
+    const SESSION: u64 = 30 * DAY;
+
+    fn claims(iat: u64, auth_time: Option<u64>) -> Claims {
+        Claims {
+            sub: "0xabc".into(),
+            iat,
+            exp: iat + TOKEN_TTL_SECS,
+            auth_time,
+            jti: Some(format!("jti-{iat}")),
+        }
+    }
+
+    fn sign_in_fixture(signer: &PrivateKeySigner, nonce: &str, issued_at: &str) -> String {
+        let hash = signin_hash(signer.address(), nonce, issued_at);
+        let sig = signer.sign_hash_sync(&hash).unwrap();
+        format!("0x{}", hex::encode(sig.as_bytes()))
+    }
+
+    /// JSON-RPC stub answering every call with `result`.
+    async fn mock_rpc(result: &'static str) -> String {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move || async move {
+                axum::Json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}/")
+    }
+
+    const MAGIC_RESULT: &str = "0x1626ba7e00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn eoa_signature_recovers_the_signer() {
+        let signer = PrivateKeySigner::random();
+        let address = signer.address().to_string();
+        let issued_at = chrono::Utc::now().to_rfc3339();
+        let sig = sign_in_fixture(&signer, "abc123", &issued_at);
+
+        let recovered =
+            recover_signer(SignInMessage::Eip712, &address, "abc123", &issued_at, &sig).unwrap();
+        assert_eq!(recovered, signer.address());
+
+        let other = PrivateKeySigner::random().address().to_string();
+        assert!(matches!(
+            recover_signer(SignInMessage::Eip712, &other, "abc123", &issued_at, &sig),
+            Err(AuthError::InvalidSignature)
+        ));
+        assert!(matches!(
+            recover_signer(
+                SignInMessage::Eip712,
+                &address,
+                "other-nonce",
+                &issued_at,
+                &sig
+            ),
+            Err(AuthError::InvalidSignature)
+        ));
+    }
+
+    const APP_URI: &str = "https://app.example.com";
+
+    fn siwe_fixture(signer: &PrivateKeySigner, nonce: &str, issued_at: &str) -> String {
+        let message = siwe_message(APP_URI, signer.address(), nonce, issued_at);
+        let sig = signer.sign_message_sync(message.as_bytes()).unwrap();
+        format!("0x{}", hex::encode(sig.as_bytes()))
+    }
+
+    #[test]
+    fn siwe_message_follows_eip4361() {
+        let wallet: Address = "0x000000000000000000000000000000000000a11e"
+            .parse()
+            .unwrap();
+        let message = siwe_message(APP_URI, wallet, "abc123", "2026-01-01T00:00:00+00:00");
+        let lines: Vec<&str> = message.lines().collect();
+        assert_eq!(
+            lines[0],
+            "app.example.com wants you to sign in with your Ethereum account:"
+        );
+        assert_eq!(lines[1], wallet.to_checksum(None));
+        assert!(lines.contains(&"URI: https://app.example.com"));
+        assert!(lines.contains(&"Chain ID: 137"));
+        assert!(lines.contains(&"Nonce: abc123"));
+        assert_eq!(
+            lines.last().copied(),
+            Some("Issued At: 2026-01-01T00:00:00+00:00")
+        );
+    }
+
+    #[tokio::test]
+    async fn both_schemes_verify_against_the_same_nonce() {
+        let signer = PrivateKeySigner::random();
+        let address = signer.address().to_string();
+        let issued_at = chrono::Utc::now().to_rfc3339();
+        let http = reqwest::Client::new();
+        let rpc = "http://127.0.0.1:9/";
+
+        let typed = sign_in_fixture(&signer, "abc123", &issued_at);
+        let siwe = siwe_fixture(&signer, "abc123", &issued_at);
+        let eip712 = SignInMessage::Eip712;
+        let siwe_scheme = SignInMessage::Siwe { uri: APP_URI };
+
+        for (message, sig) in [(eip712, &typed), (siwe_scheme, &siwe)] {
+            let wallet =
+                verify_signin_signature(&http, rpc, message, &address, "abc123", &issued_at, sig)
+                    .await
+                    .unwrap();
+            assert_eq!(wallet, signer.address());
+        }
+    }
+
+    #[tokio::test]
+    async fn signatures_do_not_cross_schemes() {
+        let signer = PrivateKeySigner::random();
+        let address = signer.address().to_string();
+        let issued_at = chrono::Utc::now().to_rfc3339();
+        // Empty eth_call result: the EIP-1271 fallback rejects too
+        let rpc_url = mock_rpc("0x").await;
+        let http = reqwest::Client::new();
+
+        let siwe = siwe_fixture(&signer, "abc123", &issued_at);
+        let replayed = verify_signin_signature(
+            &http,
+            &rpc_url,
+            SignInMessage::Eip712,
+            &address,
+            "abc123",
+            &issued_at,
+            &siwe,
+        )
+        .await;
+        assert!(matches!(replayed, Err(AuthError::InvalidSignature)));
+
+        let typed = sign_in_fixture(&signer, "abc123", &issued_at);
+        let replayed = verify_signin_signature(
+            &http,
+            &rpc_url,
+            SignInMessage::Siwe { uri: APP_URI },
+            &address,
+            "abc123",
+            &issued_at,
+            &typed,
+        )
+        .await;
+        assert!(matches!(replayed, Err(AuthError::InvalidSignature)));
+
+        // A SIWE signature for another app URI is rejected as well
+        let other_app = verify_signin_signature(
+            &http,
+            &rpc_url,
+            SignInMessage::Siwe {
+                uri: "https://evil.example.com",
+            },
+            &address,
+            "abc123",
+            &issued_at,
+            &siwe,
+        )
+        .await;
+        assert!(matches!(other_app, Err(AuthError::InvalidSignature)));
+    }
+
+    #[test]
+    fn stale_issued_at_is_expired() {
+        let signer = PrivateKeySigner::random();
+        let issued_at = (chrono::Utc::now() - chrono::Duration::minutes(10)).to_rfc3339();
+        let sig = sign_in_fixture(&signer, "abc123", &issued_at);
+        assert!(matches!(
+            recover_signer(
+                SignInMessage::Eip712,
+                &signer.address().to_string(),
+                "abc123",
+                &issued_at,
+                &sig
+            ),
+            Err(AuthError::Expired)
+        ));
+    }
+
+    #[test]
+    fn eip1271_calldata_and_magic_value() {
+        let calldata = eip1271_calldata(B256::ZERO, vec![0xab; 3]);
+        assert!(calldata.starts_with("0x1626ba7e"));
+        assert!(is_eip1271_magic(MAGIC_RESULT));
+        assert!(!is_eip1271_magic("0x"));
+        assert!(!is_eip1271_magic("0x1626ba7e"));
+        assert!(!is_eip1271_magic(
+            "0xffffffff00000000000000000000000000000000000000000000000000000000"
+        ));
+    }
+
+    #[tokio::test]
+    async fn contract_wallet_accepted_when_it_returns_the_magic_value() {
+        let rpc_url = mock_rpc(MAGIC_RESULT).await;
+        let safe = "0x000000000000000000000000000000000000a11e";
+        let issued_at = chrono::Utc::now().to_rfc3339();
+        // Safe-style signatures need not be 65 bytes
+        let sig = format!("0x{}", "11".repeat(130));
+
+        let wallet = verify_signin_signature(
+            &reqwest::Client::new(),
+            &rpc_url,
+            SignInMessage::Eip712,
+            safe,
+            "abc123",
+            &issued_at,
+            &sig,
+        )
+        .await
+        .unwrap();
+        assert_eq!(wallet, safe.parse::<Address>().unwrap());
+    }
+
+    #[tokio::test]
+    async fn contract_wallet_rejected_without_the_magic_value() {
+        let rpc_url = mock_rpc("0x").await;
+        let issued_at = chrono::Utc::now().to_rfc3339();
+        let result = verify_signin_signature(
+            &reqwest::Client::new(),
+            &rpc_url,
+            SignInMessage::Eip712,
+            "0x000000000000000000000000000000000000a11e",
+            "abc123",
+            &issued_at,
+            "0x1234",
+        )
+        .await;
+        assert!(matches!(result, Err(AuthError::InvalidSignature)));
+    }
+
+    #[tokio::test]
+    async fn eoa_path_skips_the_rpc() {
+        let signer = PrivateKeySigner::random();
+        let issued_at = chrono::Utc::now().to_rfc3339();
+        let sig = sign_in_fixture(&signer, "abc123", &issued_at);
+        // Unreachable RPC: the EOA path must not need it
+        let wallet = verify_signin_signature(
+            &reqwest::Client::new(),
+            "http://127.0.0.1:9/",
+            SignInMessage::Eip712,
+            &signer.address().to_string(),
+            "abc123",
+            &issued_at,
+            &sig,
+        )
+        .await
+        .unwrap();
+        assert_eq!(wallet, signer.address());
+    }
+
+    #[test]
+    fn refresh_keeps_auth_time_and_extends_expiry() {
+        let start = 1_700_000_000;
+        let now = start + 5 * DAY;
+        let c = refreshed_claims(&claims(start, Some(start)), now, SESSION).unwrap();
+        assert_eq!(c.sub, "0xabc");
+        assert_eq!(c.iat, now);
+        assert_eq!(c.exp, now + TOKEN_TTL_SECS);
+        assert_eq!(c.auth_time, Some(start));
+    }
+
+    #[test]
+    fn refresh_expiry_is_capped_at_absolute_lifetime() {
+        let start = 1_700_000_000;
+        let now = start + 28 * DAY;
+        let c = refreshed_claims(&claims(now - DAY, Some(start)), now, SESSION).unwrap();
+        assert_eq!(c.exp, start + SESSION);
+    }
+
+    #[test]
+    fn refresh_is_refused_in_the_final_minutes() {
+        let start = 1_700_000_000;
+        let end = start + SESSION;
+        let token = claims(end - DAY, Some(start));
+        assert!(refreshed_claims(&token, end - REFRESH_CUTOFF_SECS - 1, SESSION).is_ok());
+        assert!(matches!(
+            refreshed_claims(&token, end - REFRESH_CUTOFF_SECS, SESSION),
+            Err(AuthError::Expired)
+        ));
+        assert!(matches!(
+            refreshed_claims(&token, end + 1, SESSION),
+            Err(AuthError::Expired)
+        ));
+    }
+
+    #[test]
+    fn legacy_tokens_use_iat_as_auth_time() {
+        let iat = 1_700_000_000;
+        let c = refreshed_claims(&claims(iat, None), iat + DAY, SESSION).unwrap();
+        assert_eq!(c.auth_time, Some(iat));
+    }
+
+    #[test]
+    fn refresh_tolerates_small_clock_skew_only() {
+        let now = 1_700_000_000;
+        let ahead = now + CLOCK_SKEW_SECS;
+        let c = refreshed_claims(&claims(ahead, Some(ahead)), now, SESSION).unwrap();
+        assert_eq!(c.auth_time, Some(ahead));
+        assert!(c.exp <= ahead + SESSION);
+
+        let too_far = now + CLOCK_SKEW_SECS + 1;
+        assert!(matches!(
+            refreshed_claims(&claims(too_far, Some(too_far)), now, SESSION),
+            Err(AuthError::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn refreshed_token_validates_for_the_same_address() {
+        let secret = b"test-secret";
+        let revocations = Revocations::default();
+        let token = issue_jwt("0xABC", secret);
+        let refreshed = refresh_jwt(&token, secret, &revocations, SESSION).unwrap();
+        assert_eq!(
+            validate_jwt(&refreshed, secret, &revocations).unwrap(),
+            "0xabc"
+        );
+        assert!(matches!(
+            refresh_jwt(&token, b"other-secret", &revocations, SESSION),
+            Err(AuthError::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn revoked_token_is_rejected_and_cannot_refresh() {
+        let secret = b"test-secret";
+        let revocations = Revocations::default();
+        let token = issue_jwt("0xabc", secret);
+        let other = issue_jwt("0xabc", secret);
+        let session = token_session(&token, secret, &revocations).unwrap();
+        revocations
+            .write()
+            .unwrap()
+            .revoke(&session.jti, session.expires_at);
+
+        assert!(validate_jwt(&token, secret, &revocations).is_err());
+        assert!(refresh_jwt(&token, secret, &revocations, SESSION).is_err());
+        assert!(validate_jwt(&other, secret, &revocations).is_ok());
+    }
+
+    #[test]
+    fn cutoff_revokes_tokens_issued_before_it() {
+        let list = {
+            let mut list = RevocationList::default();
+            list.revoke_all("0xabc", 1_700_000_000);
+            list
+        };
+        assert!(list.is_revoked(&claims(1_699_999_999, None)));
+        assert!(!list.is_revoked(&claims(1_700_000_000, None)));
+        assert!(!list.is_revoked(&claims(1_700_000_001, None)));
+        let other = Claims {
+            sub: "0xdef".into(),
+            ..claims(1, None)
+        };
+        assert!(!list.is_revoked(&other));
+    }
+
+    #[test]
+    fn sign_in_right_after_logout_all_is_accepted() {
+        let secret = b"test-secret";
+        let revocations = Revocations::default();
+        let token = issue_jwt("0xabc", secret);
+        let session = token_session(&token, secret, &revocations).unwrap();
+        {
+            let now = chrono::Utc::now().timestamp() as u64;
+            let mut list = revocations.write().unwrap();
+            list.revoke(&session.jti, session.expires_at);
+            list.revoke_all(&session.address, now);
+        }
+
+        assert!(validate_jwt(&token, secret, &revocations).is_err());
+        let fresh = issue_jwt("0xabc", secret);
+        assert_eq!(validate_jwt(&fresh, secret, &revocations).unwrap(), "0xabc");
+    }
+
+    #[test]
+    fn legacy_tokens_are_revocable_by_subject_and_iat() {
+        let mut legacy = claims(1_700_000_000, None);
+        legacy.jti = None;
+        let mut list = RevocationList::default();
+        list.revoke(&legacy.token_id(), legacy.exp);
+        assert!(list.is_revoked(&legacy));
+    }
+
+    #[test]
+    fn purge_drops_only_stale_entries() {
+        let now = 1_700_000_000;
+        let mut list = RevocationList::default();
+        list.revoke("old", now - 1);
+        list.revoke("live", now + 1);
+        list.revoke_all("0xold", now - TOKEN_TTL_SECS - 1);
+        list.revoke_all("0xlive", now - 1);
+        list.purge_expired(now);
+        assert!(!list.tokens.contains_key("old"));
+        assert!(list.tokens.contains_key("live"));
+        assert!(!list.not_before.contains_key("0xold"));
+        assert!(list.not_before.contains_key("0xlive"));
+    }
 }
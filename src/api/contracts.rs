@@ -12,6 +12,9 @@ pub const CTF_EXCHANGE: Address = address!("4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8
 /// Polymarket NegRisk CTF Exchange (multi-outcome markets)
 pub const NEG_RISK_EXCHANGE: Address = address!("C5d563A36AE78145C45a50134d48A1215220f80a");
 
+/// Polymarket NegRiskAdapter: the CTF oracle of every neg-risk question
+pub const NEG_RISK_ADAPTER: Address = address!("d91E80cF2E7be2e162c6513ceD06f1dD0dA35296");
+
 pub const USDC_DECIMALS: u32 = 6;
 
 /// Minimum POL balance required for gas (0.005 POL = 5e15 wei)
@@ -676,7 +676,13 @@ pub async fn get_session_positions(
     // Enrich with market metadata + live CLOB prices
     let asset_ids: Vec<String> = positions.iter().map(|p| p.asset_id.clone()).collect();
     let (market_info, clob_prices) = tokio::join!(
-        super::markets::resolve_markets(&state.http, &state.db, &state.market_cache, &asset_ids),
+        super::markets::resolve_markets(
+            &state.http,
+            &state.gamma,
+            &state.db,
+            &state.market_cache,
+            &asset_ids
+        ),
         fetch_clob_midpoints(&state.http, &asset_ids),
     );
 
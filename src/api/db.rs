@@ -1,7 +1,10 @@
 use rusqlite::{Connection, OptionalExtension};
 use std::path::Path;
 
-use super::types::{TraderList, TraderListDetail, TraderListMember};
+use super::types::{
+    BacktestStrategy, PaperPortfolio, PaperPosition, PaperValuePoint, TraderList, TraderListDetail,
+    TraderListMember, TraderNote,
+};
 
 // ---------------------------------------------------------------------------
 // Trading Wallet row type (internal, includes encrypted blobs)
@@ -44,12 +47,25 @@ pub fn init_user_db(path: &str) -> Connection {
             last_login  TEXT NOT NULL
         );
 
+        CREATE TABLE IF NOT EXISTS revoked_tokens (
+            jti         TEXT PRIMARY KEY,
+            address     TEXT NOT NULL,
+            revoked_at  TEXT NOT NULL,
+            expires_at  INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS session_cutoffs (
+            address     TEXT PRIMARY KEY,
+            not_before  INTEGER NOT NULL
+        );
+
         CREATE TABLE IF NOT EXISTS trader_lists (
             id          TEXT PRIMARY KEY,
             owner       TEXT NOT NULL,
             name        TEXT NOT NULL,
             created_at  TEXT NOT NULL,
             updated_at  TEXT NOT NULL,
+            share_token TEXT,
             UNIQUE(owner, name)
         );
 
@@ -115,9 +131,77 @@ pub fn init_user_db(path: &str) -> Connection {
             created_at      TEXT NOT NULL,
             updated_at      TEXT NOT NULL,
             FOREIGN KEY (session_id) REFERENCES copy_trade_sessions(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS trader_notes (
+            owner       TEXT NOT NULL,
+            address     TEXT NOT NULL,
+            alias       TEXT,
+            note        TEXT,
+            updated_at  TEXT NOT NULL,
+            PRIMARY KEY (owner, address)
+        );
+
+        CREATE TABLE IF NOT EXISTS trader_follows (
+            owner       TEXT NOT NULL,
+            address     TEXT NOT NULL,
+            created_at  TEXT NOT NULL,
+            PRIMARY KEY (owner, address)
+        );
+
+        CREATE TABLE IF NOT EXISTS backtest_strategies (
+            id          TEXT PRIMARY KEY,
+            owner       TEXT NOT NULL,
+            name        TEXT NOT NULL,
+            config      TEXT NOT NULL,
+            created_at  TEXT NOT NULL,
+            updated_at  TEXT NOT NULL,
+            UNIQUE(owner, name)
+        );
+
+        CREATE TABLE IF NOT EXISTS paper_portfolios (
+            id          TEXT PRIMARY KEY,
+            owner       TEXT NOT NULL,
+            capital     REAL NOT NULL,
+            cash        REAL NOT NULL,
+            top_n       INTEGER,
+            list_id     TEXT,
+            list_name   TEXT,
+            created_at  TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS paper_positions (
+            portfolio_id TEXT NOT NULL REFERENCES paper_portfolios(id) ON DELETE CASCADE,
+            token_id     TEXT NOT NULL,
+            question     TEXT NOT NULL,
+            outcome      TEXT NOT NULL,
+            entry_price  REAL NOT NULL,
+            shares       REAL NOT NULL,
+            PRIMARY KEY (portfolio_id, token_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS paper_portfolio_values (
+            portfolio_id TEXT NOT NULL REFERENCES paper_portfolios(id) ON DELETE CASCADE,
+            date         TEXT NOT NULL,
+            value        REAL NOT NULL,
+            PRIMARY KEY (portfolio_id, date)
         )",
     )
     .expect("failed to create tables");
+
+    // Databases created before list sharing lack the share_token column
+    if conn
+        .prepare("SELECT share_token FROM trader_lists LIMIT 0")
+        .is_err()
+    {
+        conn.execute_batch("ALTER TABLE trader_lists ADD COLUMN share_token TEXT")
+            .expect("failed to add trader_lists.share_token");
+    }
+    conn.execute_batch(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_trader_lists_share_token
+         ON trader_lists(share_token)",
+    )
+    .expect("failed to create share_token index");
     tracing::info!("SQLite user DB initialized at {path}");
     conn
 }
@@ -180,6 +264,73 @@ fn generate_nonce() -> String {
     hex::encode(bytes)
 }
 
+// ---------------------------------------------------------------------------
+// Token revocation
+// ---------------------------------------------------------------------------
+
+pub fn revoke_token(
+    conn: &Connection,
+    jti: &str,
+    address: &str,
+    expires_at: u64,
+) -> Result<(), rusqlite::Error> {
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT OR IGNORE INTO revoked_tokens (jti, address, revoked_at, expires_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![jti, address, now, expires_at as i64],
+    )?;
+    Ok(())
+}
+
+/// Revokes every token for `address` issued strictly before `not_before` (unix seconds).
+pub fn set_session_cutoff(
+    conn: &Connection,
+    address: &str,
+    not_before: u64,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO session_cutoffs (address, not_before) VALUES (?1, ?2)
+         ON CONFLICT(address) DO UPDATE SET not_before = ?2",
+        rusqlite::params![address, not_before as i64],
+    )?;
+    Ok(())
+}
+
+/// Returns `(jti → expires_at, address → not_before)` for the in-memory revocation list.
+#[allow(clippy::type_complexity)]
+pub fn load_revocations(
+    conn: &Connection,
+) -> Result<(Vec<(String, u64)>, Vec<(String, u64)>), rusqlite::Error> {
+    let tokens = conn
+        .prepare("SELECT jti, expires_at FROM revoked_tokens")?
+        .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let cutoffs = conn
+        .prepare("SELECT address, not_before FROM session_cutoffs")?
+        .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((tokens, cutoffs))
+}
+
+/// Deletes revocations that can no longer match an unexpired token.
+/// `cutoff_ttl` is the longest token lifetime. Returns the number of rows removed.
+pub fn purge_expired_revocations(
+    conn: &Connection,
+    now: u64,
+    cutoff_ttl: u64,
+) -> Result<usize, rusqlite::Error> {
+    let tokens = conn.execute(
+        "DELETE FROM revoked_tokens WHERE expires_at < ?1",
+        rusqlite::params![now as i64],
+    )?;
+    let cutoffs = conn.execute(
+        "DELETE FROM session_cutoffs WHERE not_before + ?1 < ?2",
+        rusqlite::params![cutoff_ttl as i64, now as i64],
+    )?;
+    Ok(tokens + cutoffs)
+}
+
 // ---------------------------------------------------------------------------
 // Trader Lists
 // ---------------------------------------------------------------------------
@@ -205,7 +356,7 @@ impl From<rusqlite::Error> for ListError {
 }
 
 const MAX_LISTS_PER_USER: u32 = 20;
-const MAX_MEMBERS_PER_LIST: u32 = 100;
+pub const MAX_MEMBERS_PER_LIST: u32 = 100;
 
 pub fn create_trader_list(
     conn: &Connection,
@@ -272,11 +423,11 @@ pub fn get_trader_list(
     id: &str,
     owner: &str,
 ) -> Result<TraderListDetail, ListError> {
-    let (name, created_at, updated_at): (String, String, String) = conn
+    let (name, created_at, updated_at, share_token): (String, String, String, Option<String>) = conn
         .query_row(
-            "SELECT name, created_at, updated_at FROM trader_lists WHERE id = ?1 AND owner = ?2",
+            "SELECT name, created_at, updated_at, share_token FROM trader_lists WHERE id = ?1 AND owner = ?2",
             rusqlite::params![id, owner],
-            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
         )
         .map_err(|e| match e {
             rusqlite::Error::QueryReturnedNoRows => ListError::NotFound,
@@ -302,9 +453,105 @@ pub fn get_trader_list(
         members,
         created_at,
         updated_at,
+        share_token,
     })
 }
 
+/// Generates a fresh share token, replacing (and invalidating) any previous one.
+pub fn set_list_share_token(conn: &Connection, id: &str, owner: &str) -> Result<String, ListError> {
+    let token = generate_nonce();
+    let changed = conn.execute(
+        "UPDATE trader_lists SET share_token = ?1 WHERE id = ?2 AND owner = ?3",
+        rusqlite::params![token, id, owner],
+    )?;
+    if changed == 0 {
+        return Err(ListError::NotFound);
+    }
+    Ok(token)
+}
+
+pub fn clear_list_share_token(conn: &Connection, id: &str, owner: &str) -> Result<(), ListError> {
+    let changed = conn.execute(
+        "UPDATE trader_lists SET share_token = NULL WHERE id = ?1 AND owner = ?2",
+        rusqlite::params![id, owner],
+    )?;
+    if changed == 0 {
+        return Err(ListError::NotFound);
+    }
+    Ok(())
+}
+
+/// Resolves a share token to its list. The token itself is omitted from the result.
+pub fn get_shared_list(conn: &Connection, token: &str) -> Result<TraderListDetail, ListError> {
+    let (id, owner): (String, String) = conn
+        .query_row(
+            "SELECT id, owner FROM trader_lists WHERE share_token = ?1",
+            rusqlite::params![token],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => ListError::NotFound,
+            other => ListError::Db(other),
+        })?;
+    let mut detail = get_trader_list(conn, &id, &owner)?;
+    detail.share_token = None;
+    Ok(detail)
+}
+
+/// Copies a list and its members (labels included) into a new list for `owner`,
+/// named `name` or "Copy of <original>". Runs in one transaction.
+pub fn duplicate_trader_list(
+    conn: &Connection,
+    id: &str,
+    owner: &str,
+    name: Option<&str>,
+) -> Result<TraderListDetail, ListError> {
+    let source = get_trader_list(conn, id, owner)?;
+    if source.members.len() as u32 > MAX_MEMBERS_PER_LIST {
+        return Err(ListError::LimitExceeded("Maximum 100 members per list"));
+    }
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => {
+            // Keep the default within the 50-byte name limit
+            let mut name = format!("Copy of {}", source.name);
+            while name.len() > 50 {
+                name.pop();
+            }
+            name.trim_end().to_string()
+        }
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    let list = create_trader_list(&tx, owner, &name)?;
+    for m in &source.members {
+        tx.execute(
+            "INSERT INTO trader_list_members (list_id, address, label, added_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![list.id, m.address, m.label, list.created_at],
+        )?;
+    }
+    tx.commit()?;
+
+    get_trader_list(conn, &list.id, owner)
+}
+
+/// Creates a list with its initial members in one transaction, so a failed
+/// insert never leaves an empty list counting toward `MAX_LISTS_PER_USER`.
+pub fn create_trader_list_with_members(
+    conn: &Connection,
+    owner: &str,
+    name: &str,
+    members: &[(String, Option<String>)],
+) -> Result<TraderListDetail, ListError> {
+    let tx = conn.unchecked_transaction()?;
+    let list = create_trader_list(&tx, owner, name)?;
+    add_list_members(&tx, &list.id, owner, members)?;
+    tx.commit()?;
+
+    get_trader_list(conn, &list.id, owner)
+}
+
 pub fn rename_trader_list(
     conn: &Connection,
     id: &str,
@@ -380,6 +627,33 @@ pub fn add_list_members(
     Ok(())
 }
 
+/// Sets (or clears) one member's label in place, keeping `added_at`.
+pub fn update_member_label(
+    conn: &Connection,
+    list_id: &str,
+    owner: &str,
+    address: &str,
+    label: Option<&str>,
+) -> Result<(), ListError> {
+    let changed = conn.execute(
+        "UPDATE trader_list_members SET label = ?1
+         WHERE list_id = ?2 AND address = ?3
+           AND EXISTS (SELECT 1 FROM trader_lists WHERE id = ?2 AND owner = ?4)",
+        rusqlite::params![label, list_id, address, owner],
+    )?;
+    if changed == 0 {
+        return Err(ListError::NotFound);
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE trader_lists SET updated_at = ?1 WHERE id = ?2",
+        rusqlite::params![now, list_id],
+    )?;
+
+    Ok(())
+}
+
 pub fn remove_list_members(
     conn: &Connection,
     list_id: &str,
@@ -1114,3 +1388,431 @@ pub fn get_list_member_addresses(
 
     Ok(addrs)
 }
+
+// ---------------------------------------------------------------------------
+// Trader Notes (private alias + note per owner/address)
+// ---------------------------------------------------------------------------
+
+pub fn get_trader_note(
+    conn: &Connection,
+    owner: &str,
+    address: &str,
+) -> Result<Option<TraderNote>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT address, alias, note, updated_at FROM trader_notes WHERE owner = ?1 AND address = ?2",
+        rusqlite::params![owner, address],
+        |row| {
+            Ok(TraderNote {
+                address: row.get(0)?,
+                alias: row.get(1)?,
+                note: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+}
+
+pub fn upsert_trader_note(
+    conn: &Connection,
+    owner: &str,
+    address: &str,
+    alias: Option<&str>,
+    note: Option<&str>,
+) -> Result<TraderNote, rusqlite::Error> {
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO trader_notes (owner, address, alias, note, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(owner, address) DO UPDATE SET alias = ?3, note = ?4, updated_at = ?5",
+        rusqlite::params![owner, address, alias, note, now],
+    )?;
+
+    Ok(TraderNote {
+        address: address.to_string(),
+        alias: alias.map(str::to_string),
+        note: note.map(str::to_string),
+        updated_at: now,
+    })
+}
+
+/// Returns false if there was no note to delete.
+pub fn delete_trader_note(
+    conn: &Connection,
+    owner: &str,
+    address: &str,
+) -> Result<bool, rusqlite::Error> {
+    let changed = conn.execute(
+        "DELETE FROM trader_notes WHERE owner = ?1 AND address = ?2",
+        rusqlite::params![owner, address],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Owner's aliases for the given lowercase addresses, in a single query.
+pub fn get_trader_aliases(
+    conn: &Connection,
+    owner: &str,
+    addresses: &[String],
+) -> Result<std::collections::HashMap<String, String>, rusqlite::Error> {
+    if addresses.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+
+    let placeholders = (2..addresses.len() + 2)
+        .map(|i| format!("?{i}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT address, alias FROM trader_notes
+         WHERE owner = ?1 AND alias IS NOT NULL AND address IN ({placeholders})"
+    ))?;
+    let params = std::iter::once(owner).chain(addresses.iter().map(String::as_str));
+    let aliases = stmt
+        .query_map(rusqlite::params_from_iter(params), |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<std::collections::HashMap<String, String>, _>>()?;
+
+    Ok(aliases)
+}
+
+// ---------------------------------------------------------------------------
+// Trader Follows (alerts on followed traders' trades)
+// ---------------------------------------------------------------------------
+
+const MAX_FOLLOWS_PER_USER: u32 = 100;
+
+/// Following an already-followed trader is a no-op.
+pub fn follow_trader(conn: &Connection, owner: &str, address: &str) -> Result<(), ListError> {
+    let already: bool = conn
+        .query_row(
+            "SELECT 1 FROM trader_follows WHERE owner = ?1 AND address = ?2",
+            rusqlite::params![owner, address],
+            |_| Ok(true),
+        )
+        .optional()?
+        .unwrap_or(false);
+    if already {
+        return Ok(());
+    }
+
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM trader_follows WHERE owner = ?1",
+        rusqlite::params![owner],
+        |row| row.get(0),
+    )?;
+    if count >= MAX_FOLLOWS_PER_USER {
+        return Err(ListError::LimitExceeded("Maximum 100 follows per user"));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO trader_follows (owner, address, created_at) VALUES (?1, ?2, ?3)",
+        rusqlite::params![owner, address, now],
+    )?;
+    Ok(())
+}
+
+/// Returns false if `owner` wasn't following `address`.
+pub fn unfollow_trader(
+    conn: &Connection,
+    owner: &str,
+    address: &str,
+) -> Result<bool, rusqlite::Error> {
+    let changed = conn.execute(
+        "DELETE FROM trader_follows WHERE owner = ?1 AND address = ?2",
+        rusqlite::params![owner, address],
+    )?;
+    Ok(changed > 0)
+}
+
+pub fn list_follows(conn: &Connection, owner: &str) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn
+        .prepare("SELECT address FROM trader_follows WHERE owner = ?1 ORDER BY created_at DESC")?;
+    let addrs = stmt
+        .query_map(rusqlite::params![owner], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(addrs)
+}
+
+/// Followed address → owners following it, across all users.
+pub fn get_all_follows(
+    conn: &Connection,
+) -> Result<std::collections::HashMap<String, std::collections::HashSet<String>>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT address, owner FROM trader_follows")?;
+    let mut follows: std::collections::HashMap<String, std::collections::HashSet<String>> =
+        std::collections::HashMap::new();
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (address, owner) = row?;
+        follows.entry(address).or_default().insert(owner);
+    }
+    Ok(follows)
+}
+
+// ---------------------------------------------------------------------------
+// Backtest Strategies
+// ---------------------------------------------------------------------------
+
+const MAX_STRATEGIES_PER_USER: u32 = 20;
+
+fn strategy_from_row(row: &rusqlite::Row) -> rusqlite::Result<BacktestStrategy> {
+    let config: String = row.get(2)?;
+    Ok(BacktestStrategy {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        config: serde_json::from_str(&config).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+/// `config` is the strategy's `BacktestRequest` as JSON.
+pub fn create_backtest_strategy(
+    conn: &Connection,
+    owner: &str,
+    name: &str,
+    config: &serde_json::Value,
+) -> Result<BacktestStrategy, ListError> {
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM backtest_strategies WHERE owner = ?1",
+        rusqlite::params![owner],
+        |row| row.get(0),
+    )?;
+    if count >= MAX_STRATEGIES_PER_USER {
+        return Err(ListError::LimitExceeded("Maximum 20 strategies per user"));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO backtest_strategies (id, owner, name, config, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+        rusqlite::params![id, owner, name, config.to_string(), now],
+    )?;
+
+    Ok(BacktestStrategy {
+        id,
+        name: name.to_string(),
+        config: config.clone(),
+        created_at: now.clone(),
+        updated_at: now,
+    })
+}
+
+pub fn list_backtest_strategies(
+    conn: &Connection,
+    owner: &str,
+) -> Result<Vec<BacktestStrategy>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, config, created_at, updated_at
+         FROM backtest_strategies
+         WHERE owner = ?1
+         ORDER BY created_at DESC",
+    )?;
+    let strategies = stmt
+        .query_map(rusqlite::params![owner], strategy_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(strategies)
+}
+
+/// Returns NotFound if the strategy doesn't exist or isn't owned.
+pub fn get_backtest_strategy(
+    conn: &Connection,
+    id: &str,
+    owner: &str,
+) -> Result<BacktestStrategy, ListError> {
+    conn.query_row(
+        "SELECT id, name, config, created_at, updated_at
+         FROM backtest_strategies WHERE id = ?1 AND owner = ?2",
+        rusqlite::params![id, owner],
+        strategy_from_row,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => ListError::NotFound,
+        other => ListError::Db(other),
+    })
+}
+
+pub fn delete_backtest_strategy(conn: &Connection, id: &str, owner: &str) -> Result<(), ListError> {
+    let changed = conn.execute(
+        "DELETE FROM backtest_strategies WHERE id = ?1 AND owner = ?2",
+        rusqlite::params![id, owner],
+    )?;
+    if changed == 0 {
+        return Err(ListError::NotFound);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Paper Portfolios
+// ---------------------------------------------------------------------------
+
+const MAX_PAPER_PORTFOLIOS_PER_USER: u32 = 20;
+
+/// Stores a snapshot; `portfolio.id` and `created_at` are assigned here.
+pub fn create_paper_portfolio(
+    conn: &Connection,
+    owner: &str,
+    mut portfolio: PaperPortfolio,
+    positions: &[PaperPosition],
+) -> Result<PaperPortfolio, ListError> {
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM paper_portfolios WHERE owner = ?1",
+        rusqlite::params![owner],
+        |row| row.get(0),
+    )?;
+    if count >= MAX_PAPER_PORTFOLIOS_PER_USER {
+        return Err(ListError::LimitExceeded(
+            "Maximum 20 paper portfolios per user",
+        ));
+    }
+
+    portfolio.id = uuid::Uuid::new_v4().to_string();
+    portfolio.created_at = chrono::Utc::now().to_rfc3339();
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO paper_portfolios (id, owner, capital, cash, top_n, list_id, list_name, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            portfolio.id,
+            owner,
+            portfolio.capital,
+            portfolio.cash,
+            portfolio.top_n,
+            portfolio.list_id,
+            portfolio.list_name,
+            portfolio.created_at
+        ],
+    )?;
+    for p in positions {
+        tx.execute(
+            "INSERT OR IGNORE INTO paper_positions (portfolio_id, token_id, question, outcome, entry_price, shares)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                portfolio.id,
+                p.token_id,
+                p.question,
+                p.outcome,
+                p.entry_price,
+                p.shares
+            ],
+        )?;
+    }
+    tx.execute(
+        "INSERT INTO paper_portfolio_values (portfolio_id, date, value) VALUES (?1, ?2, ?3)",
+        rusqlite::params![portfolio.id, &portfolio.created_at[..10], portfolio.capital],
+    )?;
+    tx.commit()?;
+
+    Ok(portfolio)
+}
+
+fn paper_portfolio_from_row(row: &rusqlite::Row) -> rusqlite::Result<PaperPortfolio> {
+    Ok(PaperPortfolio {
+        id: row.get(0)?,
+        capital: row.get(1)?,
+        cash: row.get(2)?,
+        top_n: row.get(3)?,
+        list_id: row.get(4)?,
+        list_name: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn paper_positions(conn: &Connection, id: &str) -> Result<Vec<PaperPosition>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT token_id, question, outcome, entry_price, shares
+         FROM paper_positions WHERE portfolio_id = ?1
+         ORDER BY entry_price * shares DESC",
+    )?;
+    let positions = stmt
+        .query_map(rusqlite::params![id], |row| {
+            Ok(PaperPosition {
+                token_id: row.get(0)?,
+                question: row.get(1)?,
+                outcome: row.get(2)?,
+                entry_price: row.get(3)?,
+                shares: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(positions)
+}
+
+/// Portfolio, positions and daily value points (oldest first). Returns
+/// NotFound if the portfolio doesn't exist or isn't owned.
+pub fn get_paper_portfolio(
+    conn: &Connection,
+    id: &str,
+    owner: &str,
+) -> Result<(PaperPortfolio, Vec<PaperPosition>, Vec<PaperValuePoint>), ListError> {
+    let portfolio = conn
+        .query_row(
+            "SELECT id, capital, cash, top_n, list_id, list_name, created_at
+             FROM paper_portfolios WHERE id = ?1 AND owner = ?2",
+            rusqlite::params![id, owner],
+            paper_portfolio_from_row,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => ListError::NotFound,
+            other => ListError::Db(other),
+        })?;
+    let positions = paper_positions(conn, id)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT date, value FROM paper_portfolio_values WHERE portfolio_id = ?1 ORDER BY date",
+    )?;
+    let curve = stmt
+        .query_map(rusqlite::params![id], |row| {
+            let value: f64 = row.get(1)?;
+            Ok(PaperValuePoint {
+                date: row.get(0)?,
+                value: format!("{value:.2}"),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((portfolio, positions, curve))
+}
+
+/// Every paper portfolio with its positions, for the daily valuation.
+pub fn all_paper_portfolios(
+    conn: &Connection,
+) -> Result<Vec<(PaperPortfolio, Vec<PaperPosition>)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, capital, cash, top_n, list_id, list_name, created_at FROM paper_portfolios",
+    )?;
+    let portfolios = stmt
+        .query_map([], paper_portfolio_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    portfolios
+        .into_iter()
+        .map(|p| {
+            let positions = paper_positions(conn, &p.id)?;
+            Ok((p, positions))
+        })
+        .collect()
+}
+
+/// Upserts the value point for `date` (YYYY-MM-DD).
+pub fn record_paper_value(
+    conn: &Connection,
+    id: &str,
+    date: &str,
+    value: f64,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR REPLACE INTO paper_portfolio_values (portfolio_id, date, value) VALUES (?1, ?2, ?3)",
+        rusqlite::params![id, date, value],
+    )?;
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
-const PREFIX_LEN: usize = 15;
+pub(crate) const PREFIX_LEN: usize = 15;
 
 #[derive(Clone, Debug)]
 pub struct MarketInfo {
@@ -20,15 +22,144 @@ pub struct MarketInfo {
     pub all_token_ids: Vec<String>,
     /// All outcome names for this market (parallel to all_token_ids)
     pub outcomes: Vec<String>,
+    /// Scheduled end date from Gamma (`endDate`, ISO 8601). Not persisted to ClickHouse.
+    pub end_date: Option<String>,
+    /// When Gamma closed the market (`closedTime`), if it has
+    pub closed_time: Option<String>,
+    /// When this entry was last fetched; older than the cache TTL means stale
+    pub updated_at: Instant,
+    /// Lookups since the last fetch; stale entries with more are refreshed first
+    pub touches: Arc<AtomicU32>,
+}
+
+impl MarketInfo {
+    pub fn is_stale(&self, ttl: Duration) -> bool {
+        self.updated_at.elapsed() >= ttl
+    }
 }
 
 /// Cache keyed by the first 15 significant digits of the token ID.
 /// This handles both full-precision decimal IDs and f64-truncated
 /// scientific notation IDs from ClickHouse.
-pub type MarketCache = Arc<RwLock<HashMap<String, MarketInfo>>>;
+pub type MarketCache = Arc<RwLock<MarketMap>>;
 
 pub fn new_cache() -> MarketCache {
-    Arc::new(RwLock::new(HashMap::new()))
+    Arc::new(RwLock::new(MarketMap::default()))
+}
+
+/// Markets by `cache_key` prefix. Distinct token ids can share a prefix, so
+/// each prefix keeps every market seen for it and lookups disambiguate by the
+/// full `gamma_token_id`.
+#[derive(Default)]
+pub struct MarketMap {
+    entries: HashMap<String, Vec<MarketInfo>>,
+    collisions: u64,
+}
+
+impl MarketMap {
+    /// Entry for `token_id`. A full-precision id must match `gamma_token_id`;
+    /// a legacy lossy id falls back to the prefix's first entry.
+    pub fn get(&self, token_id: &str) -> Option<&MarketInfo> {
+        let candidates = self.entries.get(&cache_key(token_id))?;
+        select_entry(candidates, token_id).map(|i| &candidates[i])
+    }
+
+    pub fn get_mut(&mut self, token_id: &str) -> Option<&mut MarketInfo> {
+        let candidates = self.entries.get_mut(&cache_key(token_id))?;
+        let i = select_entry(candidates, token_id)?;
+        Some(&mut candidates[i])
+    }
+
+    /// Insert or replace the entry for `info.gamma_token_id`. Returns `true`
+    /// when the token was not cached before.
+    pub fn insert(&mut self, info: MarketInfo) -> bool {
+        let key = cache_key(&info.gamma_token_id);
+        let entries = self.entries.entry(key.clone()).or_default();
+        if let Some(existing) = entries
+            .iter_mut()
+            .find(|e| same_token(&e.gamma_token_id, &info.gamma_token_id))
+        {
+            *existing = info;
+            return false;
+        }
+        if let Some(other) = entries.first() {
+            self.collisions += 1;
+            tracing::warn!(
+                "Market cache prefix collision #{}: {} and {} share prefix {key}",
+                self.collisions,
+                other.gamma_token_id,
+                info.gamma_token_id
+            );
+        }
+        entries.push(info);
+        true
+    }
+
+    pub fn contains_prefix(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// `(prefix, entry)` for every cached token
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &MarketInfo)> {
+        self.entries
+            .iter()
+            .flat_map(|(key, infos)| infos.iter().map(move |info| (key, info)))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &MarketInfo> {
+        self.entries.values().flatten()
+    }
+
+    /// Distinct tokens found sharing a prefix since startup
+    pub fn collisions(&self) -> u64 {
+        self.collisions
+    }
+}
+
+/// Full-precision decimal token id, as opposed to a legacy f64 rendering.
+fn is_full_precision(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn same_token(a: &str, b: &str) -> bool {
+    a == b || !is_full_precision(a) || !is_full_precision(b)
+}
+
+fn select_entry(candidates: &[MarketInfo], token_id: &str) -> Option<usize> {
+    if !is_full_precision(token_id) {
+        return (!candidates.is_empty()).then_some(0);
+    }
+    candidates
+        .iter()
+        .position(|c| c.gamma_token_id == token_id)
+        // An entry with a lossy id of its own can't be told apart; trust it
+        // only when it is alone
+        .or_else(|| {
+            (candidates.len() == 1 && !is_full_precision(&candidates[0].gamma_token_id))
+                .then_some(0)
+        })
+}
+
+/// Resolve a category name (case-insensitive) against the cache. Returns the
+/// canonical spelling and how many distinct markets it covers (0 if unknown).
+pub async fn category_markets(cache: &MarketCache, category: &str) -> (String, u64) {
+    let c = cache.read().await;
+    let mut name = None;
+    let mut markets = HashSet::new();
+    for info in c.values() {
+        if info.category.eq_ignore_ascii_case(category) {
+            name.get_or_insert_with(|| info.category.clone());
+            markets.insert(
+                info.condition_id
+                    .clone()
+                    .unwrap_or_else(|| info.question.clone()),
+            );
+        }
+    }
+    (
+        name.unwrap_or_else(|| category.to_string()),
+        markets.len() as u64,
+    )
 }
 
 /// Convert scientific notation to an integer string (no-op for already-integer IDs).
@@ -68,10 +199,111 @@ pub(crate) fn cache_key(token_id: &str) -> String {
     }
 }
 
-/// Pre-warm the cache by fetching Gamma events targeted to tokens in ClickHouse.
-/// Queries ClickHouse for all distinct asset_ids, then paginates Gamma events
-/// until every ClickHouse token has a full-precision match (or pagination exhausted).
-pub async fn warm_cache(http: &reqwest::Client, db: &clickhouse::Client, cache: &MarketCache) {
+const GAMMA_RETRIES: u32 = 2;
+const GAMMA_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Longest `Retry-After` waited inline; longer ones go straight to cooldown
+const GAMMA_MAX_RETRY_WAIT: Duration = Duration::from_secs(10);
+const GAMMA_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Shared Gamma cooldown. Tripped when a request still gets 429/5xx after its
+/// retries; until it expires, callers skip Gamma instead of piling on.
+#[derive(Clone, Default)]
+pub struct GammaBackoff {
+    until: Arc<std::sync::Mutex<Option<Instant>>>,
+}
+
+impl GammaBackoff {
+    pub fn is_degraded(&self) -> bool {
+        self.until
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .is_some_and(|t| Instant::now() < t)
+    }
+
+    fn trip(&self, cooldown: Duration) {
+        let until = Instant::now() + cooldown;
+        let mut guard = self.until.lock().unwrap_or_else(|p| p.into_inner());
+        if guard.is_none_or(|t| t < until) {
+            *guard = Some(until);
+            tracing::warn!(
+                "Gamma rate-limited, pausing requests for {}s",
+                cooldown.as_secs()
+            );
+        }
+    }
+}
+
+/// GET a Gamma URL as JSON. 429/5xx are retried twice with jittered
+/// exponential backoff, or after `Retry-After` when Gamma sends one.
+pub(crate) async fn gamma_get<T: serde::de::DeserializeOwned>(
+    http: &reqwest::Client,
+    gamma: &GammaBackoff,
+    url: &str,
+    timeout: Duration,
+) -> Result<T, String> {
+    for attempt in 0..=GAMMA_RETRIES {
+        if gamma.is_degraded() {
+            return Err("Gamma cooldown active".into());
+        }
+        let resp = http
+            .get(url)
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = resp.status();
+        if status.is_success() {
+            return resp.json().await.map_err(|e| e.to_string());
+        }
+        if status != reqwest::StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+            return Err(format!("Gamma returned {status}"));
+        }
+
+        let retry_after = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        if attempt == GAMMA_RETRIES || retry_after.is_some_and(|d| d > GAMMA_MAX_RETRY_WAIT) {
+            gamma.trip(retry_after.unwrap_or(GAMMA_COOLDOWN));
+            return Err(format!(
+                "Gamma returned {status} after {} attempt(s)",
+                attempt + 1
+            ));
+        }
+        let jitter = Duration::from_millis((rand::random::<f64>() * 250.0) as u64);
+        let backoff = GAMMA_RETRY_BASE_DELAY * 2u32.pow(attempt);
+        tokio::time::sleep(retry_after.unwrap_or(backoff) + jitter).await;
+    }
+    Err("Gamma retries exhausted".into())
+}
+
+const GAMMA_EVENTS_PAGE: u32 = 100;
+const WARM_MAX_OFFSET: u32 = 100_000;
+/// Pages of newest and highest-volume events re-fetched on every cycle
+const WARM_REFRESH_PAGES: u32 = 3;
+/// Every Nth cycle retries prefixes a previous full scan could not find
+const WARM_RETRY_UNRESOLVED_EVERY: u32 = 36;
+
+/// State carried between `warm_cache` cycles.
+#[derive(Default)]
+pub struct CacheWarmer {
+    /// Prefixes a delta scan exhausted Gamma without finding
+    unresolved: HashSet<String>,
+    cycles: u32,
+}
+
+/// Fill the cache for tokens traded in ClickHouse. Each cycle re-fetches the
+/// newest and highest-volume Gamma events (new markets, status changes), then
+/// paginates the full feed only until the still-uncovered prefixes are found.
+pub async fn warm_cache(
+    http: &reqwest::Client,
+    gamma: &GammaBackoff,
+    db: &clickhouse::Client,
+    cache: &MarketCache,
+    warmer: &mut CacheWarmer,
+) {
     // 1. Get all distinct token prefixes from ClickHouse
     let target_prefixes: HashSet<String> = match db
         .query("SELECT DISTINCT asset_id FROM poly_dearboard.trades")
@@ -90,101 +322,150 @@ pub async fn warm_cache(http: &reqwest::Client, db: &clickhouse::Client, cache:
         return;
     }
 
-    let target_count = target_prefixes.len();
-    tracing::info!("Warming cache for {target_count} distinct ClickHouse tokens...");
+    let retry_unresolved = warmer.cycles.is_multiple_of(WARM_RETRY_UNRESOLVED_EVERY);
+    warmer.cycles += 1;
+    if retry_unresolved {
+        warmer.unresolved.clear();
+    }
 
-    // 2. Paginate Gamma events, caching only tokens that match ClickHouse prefixes
-    let mut covered: HashSet<String> = HashSet::new();
-    let mut offset = 0u32;
-    let batch = 100u32;
-    let max_offset = 100_000u32;
+    // 2. Delta: ClickHouse prefixes not cached yet (minus known Gamma misses)
+    let mut missing: HashSet<String> = {
+        let c = cache.read().await;
+        target_prefixes
+            .iter()
+            .filter(|k| !c.contains_prefix(k) && !warmer.unresolved.contains(*k))
+            .cloned()
+            .collect()
+    };
+    let target_count = target_prefixes.len();
+    tracing::info!(
+        "Warming cache: {} of {target_count} ClickHouse tokens uncovered",
+        missing.len()
+    );
 
-    loop {
-        let url = format!(
-            "https://gamma-api.polymarket.com/events?limit={batch}&offset={offset}&order=volume24hr&ascending=false"
-        );
+    let mut added = 0usize;
+    let mut pages = 0u32;
 
-        let resp = match http
-            .get(&url)
-            .timeout(std::time::Duration::from_secs(15))
-            .send()
-            .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                tracing::warn!("Market cache warm failed at offset {offset}: {e}");
+    // 3. Recent + high-volume events: new markets and status changes
+    for order in ["startDate", "volume24hr"] {
+        for page in 0..WARM_REFRESH_PAGES {
+            let Some(events) =
+                fetch_events_page(http, gamma, order, page * GAMMA_EVENTS_PAGE).await
+            else {
                 break;
-            }
-        };
-
-        let events: Vec<GammaEvent> = match resp.json().await {
-            Ok(e) => e,
-            Err(e) => {
-                tracing::warn!("Market cache parse failed at offset {offset}: {e}");
+            };
+            pages += 1;
+            let count = events.len();
+            added += cache_events(cache, &events, &target_prefixes, &mut missing).await;
+            if count < GAMMA_EVENTS_PAGE as usize {
                 break;
             }
-        };
-
-        let count = events.len();
-
-        {
-            let mut c = cache.write().await;
-            for event in &events {
-                let category = event.first_tag();
-                for market in &event.markets {
-                    let ids = market.parsed_token_ids();
-                    let outcomes = market.parsed_outcomes();
-                    let active = market.is_active();
-                    for (i, id) in ids.iter().enumerate() {
-                        let key = cache_key(id);
-                        if target_prefixes.contains(&key) {
-                            let outcome = outcomes.get(i).cloned().unwrap_or_default();
-                            c.insert(
-                                key.clone(),
-                                MarketInfo {
-                                    question: market.question.clone().unwrap_or_default(),
-                                    outcome,
-                                    category: category.clone(),
-                                    active,
-                                    gamma_token_id: id.clone(),
-                                    condition_id: market.condition_id.clone(),
-                                    outcome_index: i,
-                                    all_token_ids: ids.clone(),
-                                    outcomes: outcomes.clone(),
-                                },
-                            );
-                            covered.insert(key);
-                        }
-                    }
-                }
-            }
         }
+    }
 
-        if covered.len() >= target_count {
+    // 4. Paginate the full feed only until the delta is covered
+    let mut offset = 0u32;
+    let mut exhausted = false;
+    while !missing.is_empty() {
+        let Some(events) = fetch_events_page(http, gamma, "volume24hr", offset).await else {
             break;
-        }
-        if count < batch as usize {
+        };
+        pages += 1;
+        let count = events.len();
+        added += cache_events(cache, &events, &target_prefixes, &mut missing).await;
+
+        if count < GAMMA_EVENTS_PAGE as usize {
+            exhausted = true;
             break;
         }
-        offset += batch;
-        if offset >= max_offset {
+        offset += GAMMA_EVENTS_PAGE;
+        if offset >= WARM_MAX_OFFSET {
+            exhausted = true;
             break;
         }
 
         if offset % 5000 == 0 {
             tracing::info!(
-                "Warm cache progress: {}/{} tokens covered ({offset} events scanned)",
-                covered.len(),
-                target_count
+                "Warm cache progress: {} tokens still uncovered ({offset} events scanned)",
+                missing.len()
             );
         }
     }
+    if exhausted && !missing.is_empty() {
+        warmer.unresolved.extend(missing.iter().cloned());
+    }
 
     tracing::info!(
-        "Warmed market cache: {}/{} ClickHouse tokens covered ({offset} events scanned)",
-        covered.len(),
-        target_count
+        "Warmed market cache: +{added} new entries, {} uncovered ({} skipped as not in Gamma) of {target_count} ClickHouse tokens, {pages} Gamma pages, {} prefix collisions",
+        missing.len(),
+        warmer.unresolved.len(),
+        cache.read().await.collisions()
+    );
+}
+
+async fn fetch_events_page(
+    http: &reqwest::Client,
+    gamma: &GammaBackoff,
+    order: &str,
+    offset: u32,
+) -> Option<Vec<GammaEvent>> {
+    let url = format!(
+        "https://gamma-api.polymarket.com/events?limit={GAMMA_EVENTS_PAGE}&offset={offset}&order={order}&ascending=false"
     );
+
+    match gamma_get(http, gamma, &url, Duration::from_secs(15)).await {
+        Ok(e) => Some(e),
+        Err(e) => {
+            tracing::warn!("Market cache warm failed at {order} offset {offset}: {e}");
+            None
+        }
+    }
+}
+
+/// Cache every token of `events` traded in ClickHouse. Returns how many
+/// prefixes were not cached before.
+async fn cache_events(
+    cache: &MarketCache,
+    events: &[GammaEvent],
+    target_prefixes: &HashSet<String>,
+    missing: &mut HashSet<String>,
+) -> usize {
+    let mut added = 0;
+    let mut c = cache.write().await;
+    for event in events {
+        let category = event.first_tag();
+        for market in &event.markets {
+            let ids = market.parsed_token_ids();
+            let outcomes = market.parsed_outcomes();
+            let active = market.is_active();
+            for (i, id) in ids.iter().enumerate() {
+                let key = cache_key(id);
+                if target_prefixes.contains(&key) {
+                    let outcome = outcomes.get(i).cloned().unwrap_or_default();
+                    missing.remove(&key);
+                    let new = c.insert(MarketInfo {
+                        question: market.question.clone().unwrap_or_default(),
+                        outcome,
+                        category: category.clone(),
+                        active,
+                        gamma_token_id: id.clone(),
+                        condition_id: market.onchain_condition_id(),
+                        outcome_index: i,
+                        all_token_ids: ids.clone(),
+                        outcomes: outcomes.clone(),
+                        end_date: market.end_date.clone(),
+                        closed_time: market.closed_time.clone(),
+                        updated_at: Instant::now(),
+                        touches: Default::default(),
+                    });
+                    if new {
+                        added += 1;
+                    }
+                }
+            }
+        }
+    }
+    added
 }
 
 #[derive(clickhouse::Row, serde::Deserialize)]
@@ -192,17 +473,61 @@ struct AssetIdRow {
     asset_id: String,
 }
 
+/// What `populate_resolved_prices` has already read and written, carried
+/// between cycles so each one only inserts the delta.
+#[derive(Default)]
+pub struct ResolvedPricesState {
+    /// `written` has been loaded from the table (or deliberately left empty)
+    seeded: bool,
+    /// Highest `condition_resolution` block read so far
+    last_block: Option<u64>,
+    /// On-chain resolutions read so far: bare condition_id → (payout_numerators, block)
+    resolutions: HashMap<String, (Vec<String>, u64)>,
+    /// Asset ids already present in `resolved_prices`
+    written: HashSet<String>,
+}
+
 /// Cross-reference the warm cache with on-chain ConditionResolution events,
-/// compute exact resolved prices, and write them to the resolved_prices table.
-pub async fn populate_resolved_prices(db: &clickhouse::Client, cache: &MarketCache) {
-    use super::types::{ConditionResolutionRow, ResolvedPriceRow};
+/// compute exact resolved prices, and append rows for assets not yet in the
+/// resolved_prices table. Never truncates: ReplacingMergeTree + FINAL dedup
+/// any overlap, so PnL queries always see the existing prices.
+pub async fn populate_resolved_prices(
+    db: &clickhouse::Client,
+    cache: &MarketCache,
+    state: &mut ResolvedPricesState,
+) {
+    use super::types::ConditionResolutionRow;
+
+    // 1. On first run, learn which assets the table already holds
+    if !state.seeded {
+        match db
+            .query("SELECT DISTINCT asset_id FROM poly_dearboard.resolved_prices")
+            .fetch_all::<AssetIdRow>()
+            .await
+        {
+            Ok(rows) => {
+                state.written = rows.into_iter().map(|r| r.asset_id).collect();
+                state.seeded = true;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to query existing resolved_prices: {e}");
+                return;
+            }
+        }
+    }
 
-    // 1. Query all condition resolutions from ClickHouse
+    // 2. Condition resolutions since the last processed block
+    //    Normalize keys by stripping 0x prefix — rindexer stores WITH 0x,
+    //    Gamma API also stores WITH 0x, but we strip both sides for consistent matching.
+    let block_filter = state
+        .last_block
+        .map(|b| format!(" WHERE block_number > {b}"))
+        .unwrap_or_default();
     let resolutions: Vec<ConditionResolutionRow> = match db
-        .query(
+        .query(&format!(
             "SELECT condition_id, payout_numerators, block_number
-             FROM poly_dearboard_conditional_tokens.condition_resolution",
-        )
+             FROM poly_dearboard_conditional_tokens.condition_resolution{block_filter}"
+        ))
         .fetch_all()
         .await
     {
@@ -212,96 +537,183 @@ pub async fn populate_resolved_prices(db: &clickhouse::Client, cache: &MarketCac
             return;
         }
     };
+    let new_resolutions = resolutions.len();
+    for r in resolutions {
+        state.last_block = state.last_block.max(Some(r.block_number));
+        let bare = r
+            .condition_id
+            .strip_prefix("0x")
+            .unwrap_or(&r.condition_id)
+            .to_string();
+        state
+            .resolutions
+            .insert(bare, (r.payout_numerators, r.block_number));
+    }
 
-    if resolutions.is_empty() {
+    if state.resolutions.is_empty() {
         tracing::info!("No condition resolutions found, skipping resolved prices");
         return;
     }
 
-    // 2. Build condition_id → (payout_numerators, block_number) map
-    //    Normalize keys by stripping 0x prefix — rindexer stores WITH 0x,
-    //    Gamma API also stores WITH 0x, but we strip both sides for consistent matching.
-    let resolution_map: HashMap<String, (&Vec<String>, u64)> = resolutions
-        .iter()
-        .map(|r| {
-            let bare = r
-                .condition_id
-                .strip_prefix("0x")
-                .unwrap_or(&r.condition_id)
-                .to_string();
-            (bare, (&r.payout_numerators, r.block_number))
-        })
-        .collect();
-
-    tracing::info!(
-        "Found {} on-chain condition resolutions",
-        resolution_map.len()
-    );
-
-    // 3. Query distinct ClickHouse asset_ids
-    let ch_assets: Vec<AssetIdRow> = match db
+    // 3. Query distinct ClickHouse asset_ids; markets that entered the cache
+    //    since the last cycle may match resolutions read earlier
+    let ch_assets: Vec<String> = match db
         .query("SELECT DISTINCT asset_id FROM poly_dearboard.trades")
-        .fetch_all()
+        .fetch_all::<AssetIdRow>()
         .await
     {
-        Ok(rows) => rows,
+        Ok(rows) => rows.into_iter().map(|r| r.asset_id).collect(),
         Err(e) => {
             tracing::warn!("Failed to query asset_ids for resolved prices: {e}");
             return;
         }
     };
 
-    // 4. For each CH asset_id, look up cache entry → check resolution → compute price
-    let cache_read = cache.read().await;
-    let mut rows: Vec<ResolvedPriceRow> = Vec::new();
+    let rows = pending_resolved_prices(&ch_assets, &*cache.read().await, state);
+    if rows.is_empty() {
+        tracing::debug!("No new resolved prices ({new_resolutions} new resolutions)");
+        return;
+    }
 
-    for asset in &ch_assets {
-        let key = cache_key(&asset.asset_id);
-        let info = match cache_read.get(&key) {
-            Some(i) => i,
-            None => continue,
-        };
-        let cid = match &info.condition_id {
-            Some(c) => c,
-            None => continue,
-        };
-        // On-chain condition_id has no 0x prefix; Gamma stores it with 0x — strip for lookup
-        let bare_cid = cid.strip_prefix("0x").unwrap_or(cid);
-        let (numerators, block) = match resolution_map.get(bare_cid) {
-            Some(r) => r,
-            None => continue, // Not resolved on-chain
-        };
+    // 4. Batch INSERT only the new rows
+    let mut inserter = match db.insert("poly_dearboard.resolved_prices") {
+        Ok(i) => i,
+        Err(e) => {
+            tracing::warn!("Failed to create inserter for resolved_prices: {e}");
+            return;
+        }
+    };
 
-        // resolved_price = numerators[outcome_index] / sum(numerators)
-        let nums: Vec<f64> = numerators.iter().filter_map(|s| s.parse().ok()).collect();
-        let total: f64 = nums.iter().sum();
-        if total <= 0.0 || info.outcome_index >= nums.len() {
-            continue;
+    let count = rows.len();
+    for row in &rows {
+        if let Err(e) = inserter.write(row).await {
+            tracing::warn!("Failed to write resolved_price row: {e}");
+            return;
         }
-        let price = nums[info.outcome_index] / total;
+    }
 
-        rows.push(ResolvedPriceRow {
-            asset_id: asset.asset_id.clone(),
-            resolved_price: format!("{:.6}", price),
-            condition_id: cid.clone(),
-            block_number: *block,
-        });
+    if let Err(e) = inserter.end().await {
+        tracing::warn!("Failed to flush resolved_prices: {e}");
+        return;
     }
 
-    drop(cache_read);
+    state.written.extend(rows.into_iter().map(|r| r.asset_id));
+    tracing::info!("Populated {count} new resolved prices from on-chain data");
+}
 
-    if rows.is_empty() {
-        tracing::info!("No resolved prices to populate (no cache↔resolution overlap)");
-        return;
+/// Recovery path: forget the incremental state and re-insert a row for every
+/// resolved asset. Existing rows are replaced in place rather than wiped first.
+pub async fn rebuild_resolved_prices(
+    db: &clickhouse::Client,
+    cache: &MarketCache,
+    state: &mut ResolvedPricesState,
+) {
+    *state = ResolvedPricesState {
+        seeded: true,
+        ..Default::default()
+    };
+    tracing::info!("Rebuilding resolved prices from all on-chain resolutions");
+    populate_resolved_prices(db, cache, state).await;
+}
+
+/// Rows for traded assets whose market has resolved on-chain and that are not
+/// yet in `state.written`.
+fn pending_resolved_prices(
+    asset_ids: &[String],
+    cache: &MarketMap,
+    state: &ResolvedPricesState,
+) -> Vec<super::types::ResolvedPriceRow> {
+    asset_ids
+        .iter()
+        .filter(|asset_id| !state.written.contains(*asset_id))
+        .filter_map(|asset_id| {
+            let info = cache.get(asset_id)?;
+            let cid = info.condition_id.as_ref()?;
+            // On-chain condition_id has no 0x prefix; Gamma stores it with 0x — strip for lookup
+            let bare_cid = cid.strip_prefix("0x").unwrap_or(cid);
+            let (numerators, block) = state.resolutions.get(bare_cid)?;
+            resolved_price_row(asset_id, info, numerators, *block)
+        })
+        .collect()
+}
+
+/// Resolved-price row for one token of a resolved condition. `None` when the
+/// payout vector doesn't line up with the market's tokens (e.g. a neg-risk
+/// condition matched to a Gamma market listing other outcomes): such assets
+/// are left out so PnL keeps marking them to market instead of a wrong payout.
+fn resolved_price_row(
+    asset_id: &str,
+    info: &MarketInfo,
+    numerators: &[String],
+    block_number: u64,
+) -> Option<super::types::ResolvedPriceRow> {
+    if !info.all_token_ids.is_empty() && info.all_token_ids.len() != numerators.len() {
+        tracing::debug!(
+            "Skipping resolved price for {asset_id}: {} payouts for {} tokens",
+            numerators.len(),
+            info.all_token_ids.len()
+        );
+        return None;
+    }
+    let price = resolved_price(numerators, info.outcome_index)?;
+    Some(super::types::ResolvedPriceRow {
+        asset_id: asset_id.to_string(),
+        resolved_price: format!("{:.6}", price),
+        condition_id: info.condition_id.clone().unwrap_or_default(),
+        block_number,
+        resolution_kind: resolution_kind(numerators).to_string(),
+    })
+}
+
+/// `invalid` when every outcome pays the same (UMA 50/50): each token redeems
+/// for 1/n, which is a real price but not a win or a loss.
+fn resolution_kind(numerators: &[String]) -> &'static str {
+    let values: Vec<u128> = numerators.iter().map(|n| n.parse().unwrap_or(0)).collect();
+    if values.len() > 1 && values[0] > 0 && values.iter().all(|&n| n == values[0]) {
+        "invalid"
+    } else {
+        "normal"
+    }
+}
+
+/// resolved_price = numerators[outcome_index] / sum(numerators)
+fn resolved_price(numerators: &[String], outcome_index: usize) -> Option<f64> {
+    let nums: Vec<f64> = numerators.iter().filter_map(|s| s.parse().ok()).collect();
+    let total: f64 = nums.iter().sum();
+    if total <= 0.0 || outcome_index >= nums.len() {
+        return None;
     }
+    Some(nums[outcome_index] / total)
+}
 
-    // 5. Truncate + batch INSERT
-    if let Err(e) = db
-        .query("TRUNCATE TABLE IF EXISTS poly_dearboard.resolved_prices")
-        .execute()
+/// Insert resolved prices for a single newly resolved condition, so PnL
+/// reflects it before the next `populate_resolved_prices` cycle.
+pub async fn populate_resolved_prices_for_condition(
+    db: clickhouse::Client,
+    cache: MarketCache,
+    condition_id: String,
+    numerators: Vec<String>,
+    block_number: u64,
+) {
+    use super::types::ResolvedPriceRow;
+
+    let bare_cid = condition_id.strip_prefix("0x").unwrap_or(&condition_id);
+    let rows: Vec<ResolvedPriceRow> = cache
+        .read()
         .await
-    {
-        tracing::warn!("Failed to truncate resolved_prices: {e}");
+        .values()
+        .filter_map(|info| {
+            let cid = info.condition_id.as_ref()?;
+            if cid.strip_prefix("0x").unwrap_or(cid) != bare_cid {
+                return None;
+            }
+            resolved_price_row(&info.gamma_token_id, info, &numerators, block_number)
+        })
+        .collect();
+
+    if rows.is_empty() {
+        tracing::debug!("No cached markets for resolved condition {condition_id}");
+        return;
     }
 
     let mut inserter = match db.insert("poly_dearboard.resolved_prices") {
@@ -311,7 +723,6 @@ pub async fn populate_resolved_prices(db: &clickhouse::Client, cache: &MarketCac
             return;
         }
     };
-
     let count = rows.len();
     for row in rows {
         if let Err(e) = inserter.write(&row).await {
@@ -319,23 +730,33 @@ pub async fn populate_resolved_prices(db: &clickhouse::Client, cache: &MarketCac
             return;
         }
     }
-
     if let Err(e) = inserter.end().await {
         tracing::warn!("Failed to flush resolved_prices: {e}");
         return;
     }
-
-    tracing::info!("Populated {count} resolved prices from on-chain data");
+    tracing::info!("Populated {count} resolved prices for condition {condition_id}");
 }
 
-/// Flush the in-memory market cache to ClickHouse `market_metadata` table.
-/// Uses INSERT (not TRUNCATE+INSERT) because ReplacingMergeTree handles dedup.
-pub async fn persist_cache_to_clickhouse(db: &clickhouse::Client, cache: &MarketCache) {
+/// Upsert cache entries fetched since `since` (all of them when `None`) into
+/// the ClickHouse `market_metadata` table, which SQL category filters join
+/// against. Uses INSERT (not TRUNCATE+INSERT) because ReplacingMergeTree keeps
+/// the newest row per asset_id. Returns the `since` for the next call; on
+/// failure the same window is retried.
+pub async fn persist_cache_to_clickhouse(
+    db: &clickhouse::Client,
+    cache: &MarketCache,
+    since: Option<Instant>,
+) -> Option<Instant> {
     use super::types::MarketMetadataRow;
 
+    let snapshot_at = Instant::now();
     let cache_read = cache.read().await;
-    if cache_read.is_empty() {
-        return;
+    let changed: Vec<&MarketInfo> = cache_read
+        .values()
+        .filter(|info| since.is_none_or(|since| info.updated_at >= since))
+        .collect();
+    if changed.is_empty() {
+        return Some(snapshot_at);
     }
 
     let now = chrono::Utc::now().timestamp() as u32;
@@ -344,12 +765,12 @@ pub async fn persist_cache_to_clickhouse(db: &clickhouse::Client, cache: &Market
         Ok(i) => i,
         Err(e) => {
             tracing::warn!("Failed to create inserter for market_metadata: {e}");
-            return;
+            return since;
         }
     };
 
     let mut count = 0u64;
-    for (_key, info) in cache_read.iter() {
+    for info in changed {
         let row = MarketMetadataRow {
             asset_id: info.gamma_token_id.clone(),
             question: info.question.clone(),
@@ -361,11 +782,12 @@ pub async fn persist_cache_to_clickhouse(db: &clickhouse::Client, cache: &Market
             active: if info.active { 1 } else { 0 },
             all_token_ids: info.all_token_ids.clone(),
             outcomes: info.outcomes.clone(),
+            end_date: info.end_date.clone().unwrap_or_default(),
             updated_at: now,
         };
         if let Err(e) = inserter.write(&row).await {
             tracing::warn!("Failed to write market_metadata row: {e}");
-            return;
+            return since;
         }
         count += 1;
     }
@@ -373,10 +795,11 @@ pub async fn persist_cache_to_clickhouse(db: &clickhouse::Client, cache: &Market
 
     if let Err(e) = inserter.end().await {
         tracing::warn!("Failed to flush market_metadata: {e}");
-        return;
+        return since;
     }
 
     tracing::info!("Persisted {count} market metadata entries to ClickHouse");
+    Some(snapshot_at)
 }
 
 /// Resolve token IDs to market info.
@@ -387,6 +810,7 @@ pub async fn persist_cache_to_clickhouse(db: &clickhouse::Client, cache: &Market
 /// 3. For remaining misses, try individual Gamma API calls
 pub async fn resolve_markets(
     http: &reqwest::Client,
+    gamma: &GammaBackoff,
     db: &clickhouse::Client,
     cache: &MarketCache,
     token_ids: &[String],
@@ -397,8 +821,10 @@ pub async fn resolve_markets(
     {
         let c = cache.read().await;
         for id in token_ids {
-            let key = cache_key(id);
-            if let Some(info) = c.get(&key) {
+            if let Some(info) = c.get(id) {
+                // Stale entries are still served; the touch moves them up the
+                // refresh queue
+                info.touches.fetch_add(1, Ordering::Relaxed);
                 result.insert(id.clone(), info.clone());
             } else {
                 uncached.push(id.clone());
@@ -416,7 +842,7 @@ pub async fn resolve_markets(
         let in_clause = placeholders.join(",");
         let query = format!(
             "SELECT asset_id, question, outcome, category, condition_id, gamma_token_id, \
-                    outcome_index, active, all_token_ids, outcomes \
+                    outcome_index, active, all_token_ids, outcomes, end_date \
              FROM poly_dearboard.market_metadata FINAL \
              WHERE asset_id IN ({in_clause})"
         );
@@ -433,6 +859,7 @@ pub async fn resolve_markets(
             active: u8,
             all_token_ids: Vec<String>,
             outcomes: Vec<String>,
+            end_date: String,
         }
 
         if let Ok(rows) = db.query(&query).fetch_all::<MetadataRow>().await {
@@ -452,8 +879,12 @@ pub async fn resolve_markets(
                     outcome_index: row.outcome_index as usize,
                     all_token_ids: row.all_token_ids,
                     outcomes: row.outcomes,
+                    end_date: (!row.end_date.is_empty()).then_some(row.end_date),
+                    closed_time: None,
+                    updated_at: Instant::now(),
+                    touches: Default::default(),
                 };
-                c.insert(cache_key(&row.asset_id), info.clone());
+                c.insert(info.clone());
                 result.insert(row.asset_id, info);
             }
         }
@@ -464,40 +895,227 @@ pub async fn resolve_markets(
         return result;
     }
 
-    // Tier 3: Resolve remaining via Gamma API (max 10 concurrent)
-    let sem = Arc::new(tokio::sync::Semaphore::new(10));
-    let mut handles = Vec::new();
+    // Tier 3: Resolve remaining via batched Gamma API lookups, unless Gamma
+    // is rate-limiting us
+    if gamma.is_degraded() {
+        return result;
+    }
+    let new_entries = fetch_market_infos(http, gamma, &uncached).await;
 
-    for id in &uncached {
+    if !new_entries.is_empty() {
+        let mut c = cache.write().await;
+        for (id, info) in new_entries {
+            c.insert(info.clone());
+            result.insert(id, info);
+        }
+    }
+
+    result
+}
+
+const CACHE_TTL_DEFAULT_SECS: u64 = 3600;
+const CACHE_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const CACHE_REFRESH_BATCH: usize = 100;
+
+/// `MARKET_CACHE_TTL_SECS` (default 3600): age after which an entry is
+/// re-fetched from Gamma.
+pub fn cache_ttl_from_env() -> Duration {
+    let secs = std::env::var("MARKET_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(CACHE_TTL_DEFAULT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Re-fetch stale entries from Gamma, most-touched first, so `active` and
+/// `closed_time` follow markets closing between warm passes.
+pub async fn refresh_stale_loop(
+    http: reqwest::Client,
+    gamma: GammaBackoff,
+    cache: MarketCache,
+    ttl: Duration,
+) {
+    let mut interval = tokio::time::interval(CACHE_REFRESH_INTERVAL);
+    interval.tick().await; // skip immediate tick
+    loop {
+        interval.tick().await;
+        refresh_stale(&http, &gamma, &cache, ttl).await;
+    }
+}
+
+async fn refresh_stale(
+    http: &reqwest::Client,
+    gamma: &GammaBackoff,
+    cache: &MarketCache,
+    ttl: Duration,
+) {
+    if gamma.is_degraded() {
+        return;
+    }
+    let mut stale: Vec<(String, u32)> = {
+        let c = cache.read().await;
+        c.values()
+            .filter(|info| info.is_stale(ttl))
+            .map(|info| {
+                (
+                    info.gamma_token_id.clone(),
+                    info.touches.load(Ordering::Relaxed),
+                )
+            })
+            .collect()
+    };
+    if stale.is_empty() {
+        return;
+    }
+    let stale_count = stale.len();
+    stale.sort_by_key(|(_, touches)| std::cmp::Reverse(*touches));
+    stale.truncate(CACHE_REFRESH_BATCH);
+
+    let token_ids: Vec<String> = stale.into_iter().map(|(id, _)| id).collect();
+    let mut fetched = fetch_market_infos(http, gamma, &token_ids).await;
+
+    let (mut refreshed, mut closed) = (0usize, 0usize);
+    let mut c = cache.write().await;
+    for token_id in &token_ids {
+        let Some(existing) = c.get_mut(token_id) else {
+            continue;
+        };
+        match fetched.remove(token_id) {
+            Some(mut info) => {
+                // Token lookups carry no event tags
+                if info.category.is_empty() {
+                    info.category = std::mem::take(&mut existing.category);
+                }
+                if existing.active && !info.active {
+                    closed += 1;
+                }
+                *existing = info;
+                refreshed += 1;
+            }
+            // Keep serving the old entry; retry after another TTL
+            None => existing.updated_at = Instant::now(),
+        }
+    }
+    tracing::info!(
+        "Market cache refresh: {refreshed} of {stale_count} stale entries re-fetched, {closed} now closed"
+    );
+}
+
+/// Ids per batched Gamma `/markets` request
+const GAMMA_BATCH_SIZE: usize = 20;
+
+/// Look up many tokens with one Gamma request per `GAMMA_BATCH_SIZE` ids
+/// (max 4 concurrent). Ids the batches did not cover are retried one by one.
+async fn fetch_market_infos(
+    http: &reqwest::Client,
+    gamma: &GammaBackoff,
+    token_ids: &[String],
+) -> HashMap<String, MarketInfo> {
+    let sem = Arc::new(tokio::sync::Semaphore::new(4));
+    let mut handles = Vec::new();
+    for chunk in token_ids.chunks(GAMMA_BATCH_SIZE) {
         let http = http.clone();
-        let id = id.clone();
+        let gamma = gamma.clone();
+        let chunk = chunk.to_vec();
         let permit = Arc::clone(&sem).acquire_owned().await.unwrap();
-
         handles.push(tokio::spawn(async move {
             let _permit = permit;
-            fetch_market_info(&http, &id).await
+            fetch_market_batch(&http, &gamma, &chunk).await
         }));
     }
 
-    let mut new_entries = Vec::new();
-    for (i, handle) in handles.into_iter().enumerate() {
-        if let Ok(Some(info)) = handle.await {
-            new_entries.push((uncached[i].clone(), info));
+    let mut found = HashMap::with_capacity(token_ids.len());
+    for handle in handles {
+        if let Ok(batch) = handle.await {
+            found.extend(batch);
         }
     }
 
-    if !new_entries.is_empty() {
-        let mut c = cache.write().await;
-        for (id, info) in &new_entries {
-            c.insert(cache_key(id), info.clone());
-            result.insert(id.clone(), info.clone());
+    let missing: Vec<&String> = token_ids
+        .iter()
+        .filter(|id| !found.contains_key(*id))
+        .collect();
+    if missing.is_empty() || gamma.is_degraded() {
+        return found;
+    }
+    tracing::debug!(
+        "Gamma batch lookup missed {} of {} ids, falling back per id",
+        missing.len(),
+        token_ids.len()
+    );
+
+    // Per-id fallback (max 10 concurrent)
+    let sem = Arc::new(tokio::sync::Semaphore::new(10));
+    let mut handles = Vec::with_capacity(missing.len());
+    for id in missing {
+        let http = http.clone();
+        let gamma = gamma.clone();
+        let id = id.clone();
+        let permit = Arc::clone(&sem).acquire_owned().await.unwrap();
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let info = fetch_market_info(&http, &gamma, &id).await;
+            (id, info)
+        }));
+    }
+    for handle in handles {
+        if let Ok((id, Some(info))) = handle.await {
+            found.insert(id, info);
         }
     }
+    found
+}
 
-    result
+/// One Gamma request for several tokens. Each input id is mapped back to the
+/// returned market listing it in `clobTokenIds`; ids with no match are omitted.
+async fn fetch_market_batch(
+    http: &reqwest::Client,
+    gamma: &GammaBackoff,
+    token_ids: &[String],
+) -> HashMap<String, MarketInfo> {
+    let query = token_ids
+        .iter()
+        .map(|id| format!("clob_token_ids={}", to_integer_id(id)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let url = format!(
+        "https://gamma-api.polymarket.com/markets?{query}&limit={}",
+        token_ids.len()
+    );
+
+    let markets: Vec<GammaMarket> =
+        match gamma_get(http, gamma, &url, Duration::from_secs(10)).await {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::debug!("Gamma batch lookup of {} ids failed: {e}", token_ids.len());
+                return HashMap::new();
+            }
+        };
+    match_batch(&markets, token_ids)
+}
+
+/// Map each input id to the market listing it in `clobTokenIds`.
+fn match_batch(markets: &[GammaMarket], token_ids: &[String]) -> HashMap<String, MarketInfo> {
+    let market_ids: Vec<Vec<String>> = markets.iter().map(|m| m.parsed_token_ids()).collect();
+
+    token_ids
+        .iter()
+        .filter_map(|id| {
+            let (market, index) = markets
+                .iter()
+                .zip(&market_ids)
+                .find_map(|(m, ids)| token_index(ids, id).map(|i| (m, i)))?;
+            Some((id.clone(), market_info(market, id, Some(index))))
+        })
+        .collect()
 }
 
-async fn fetch_market_info(http: &reqwest::Client, token_id: &str) -> Option<MarketInfo> {
+async fn fetch_market_info(
+    http: &reqwest::Client,
+    gamma: &GammaBackoff,
+    token_id: &str,
+) -> Option<MarketInfo> {
     // Gamma API requires integer token IDs — never scientific notation.
     // After UInt256 migration, token_id is a full-precision integer string.
     // For legacy scientific notation IDs, convert to integer form for Gamma lookup.
@@ -508,41 +1126,53 @@ async fn fetch_market_info(http: &reqwest::Client, token_id: &str) -> Option<Mar
         lookup_id
     );
 
-    let resp = http
-        .get(&url)
-        .timeout(std::time::Duration::from_secs(5))
-        .send()
+    let markets: Vec<GammaMarket> = gamma_get(http, gamma, &url, Duration::from_secs(5))
         .await
         .ok()?;
-
-    let markets: Vec<GammaMarket> = resp.json().await.ok()?;
     let market = markets.into_iter().next()?;
+    let index = token_index(&market.parsed_token_ids(), token_id);
+    Some(market_info(&market, token_id, index))
+}
 
+/// Position of `token_id` in a market's `clobTokenIds`: exact for
+/// full-precision ids, by prefix for legacy scientific-notation ids (whose
+/// integer form never equals the real id).
+fn token_index(ids: &[String], token_id: &str) -> Option<usize> {
+    if is_full_precision(token_id) {
+        ids.iter().position(|id| id == token_id)
+    } else {
+        let key = cache_key(token_id);
+        ids.iter().position(|id| cache_key(id) == key)
+    }
+}
+
+/// Cache entry for `token_id` at `index` in `market`. Without an index the
+/// entry keeps the market's question but no outcome.
+fn market_info(market: &GammaMarket, token_id: &str, index: Option<usize>) -> MarketInfo {
     let ids = market.parsed_token_ids();
     let outcomes = market.parsed_outcomes();
-    let matched_idx = ids.iter().position(|id| id == &lookup_id);
-    let outcome = matched_idx
+    let outcome = index
         .and_then(|idx| outcomes.get(idx).cloned())
         .unwrap_or_default();
+    let gamma_token_id = index
+        .and_then(|idx| ids.get(idx).cloned())
+        .unwrap_or_else(|| to_integer_id(token_id));
 
-    let gamma_token_id = ids
-        .iter()
-        .find(|id| cache_key(id) == cache_key(token_id))
-        .cloned()
-        .unwrap_or_else(|| lookup_id);
-
-    let active = market.is_active();
-    Some(MarketInfo {
-        question: market.question.unwrap_or_default(),
+    MarketInfo {
+        question: market.question.clone().unwrap_or_default(),
         outcome,
         category: String::new(),
-        active,
+        active: market.is_active(),
         gamma_token_id,
-        condition_id: market.condition_id,
-        outcome_index: matched_idx.unwrap_or(0),
+        condition_id: market.onchain_condition_id(),
+        outcome_index: index.unwrap_or(0),
         all_token_ids: ids,
         outcomes,
-    })
+        end_date: market.end_date.clone(),
+        closed_time: market.closed_time.clone(),
+        updated_at: Instant::now(),
+        touches: Default::default(),
+    }
 }
 
 #[derive(serde::Deserialize)]
@@ -583,6 +1213,15 @@ struct GammaMarket {
     closed: Option<bool>,
     /// CTF condition ID — links to on-chain ConditionResolution events
     condition_id: Option<String>,
+    #[serde(default)]
+    neg_risk: Option<bool>,
+    /// Neg-risk question ID (bytes32); the NegRiskAdapter prepares the CTF condition from it
+    #[serde(default, rename = "questionID")]
+    question_id: Option<String>,
+    #[serde(default)]
+    end_date: Option<String>,
+    #[serde(default)]
+    closed_time: Option<String>,
 }
 
 impl GammaMarket {
@@ -604,4 +1243,258 @@ impl GammaMarket {
             .and_then(|s| serde_json::from_str(s).ok())
             .unwrap_or_default()
     }
+
+    /// Condition the CTF actually resolves. Neg-risk questions are prepared by
+    /// the NegRiskAdapter, so derive theirs from `questionID` rather than rely
+    /// on Gamma's `conditionId`.
+    fn onchain_condition_id(&self) -> Option<String> {
+        self.question_id
+            .as_deref()
+            .filter(|_| self.neg_risk.unwrap_or(false))
+            .and_then(neg_risk_condition_id)
+            .or_else(|| self.condition_id.clone())
+    }
+}
+
+/// CTF `getConditionId(NegRiskAdapter, questionId, 2)`:
+/// `keccak256(oracle ‖ questionId ‖ uint256(2))`, 0x-prefixed.
+fn neg_risk_condition_id(question_id: &str) -> Option<String> {
+    use alloy::primitives::{B256, U256, keccak256};
+
+    let question_id: B256 = question_id.parse().ok()?;
+    let mut packed = Vec::with_capacity(20 + 32 + 32);
+    packed.extend_from_slice(super::contracts::NEG_RISK_ADAPTER.as_slice());
+    packed.extend_from_slice(question_id.as_slice());
+    packed.extend_from_slice(&U256::from(2).to_be_bytes::<32>());
+    Some(format!("{:#x}", keccak256(&packed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Distinct uint256 token ids sharing their first 15 digits
+    const TOKEN_A: &str =
+        "123456789012345111111111111111111111111111111111111111111111111111111111111111";
+    const TOKEN_B: &str =
+        "123456789012345222222222222222222222222222222222222222222222222222222222222222";
+
+    fn info(token_id: &str, question: &str) -> MarketInfo {
+        MarketInfo {
+            question: question.into(),
+            outcome: "Yes".into(),
+            category: String::new(),
+            active: true,
+            gamma_token_id: token_id.into(),
+            condition_id: None,
+            outcome_index: 0,
+            all_token_ids: vec![token_id.into()],
+            outcomes: vec!["Yes".into()],
+            end_date: None,
+            closed_time: None,
+            updated_at: Instant::now(),
+            touches: Default::default(),
+        }
+    }
+
+    #[test]
+    fn colliding_prefixes_keep_both_markets() {
+        assert_eq!(cache_key(TOKEN_A), cache_key(TOKEN_B));
+        let mut map = MarketMap::default();
+        assert!(map.insert(info(TOKEN_A, "A")));
+        assert!(map.insert(info(TOKEN_B, "B")));
+
+        assert_eq!(map.values().count(), 2);
+        assert_eq!(map.collisions(), 1);
+        assert_eq!(map.get(TOKEN_A).map(|i| i.question.as_str()), Some("A"));
+        assert_eq!(map.get(TOKEN_B).map(|i| i.question.as_str()), Some("B"));
+    }
+
+    #[test]
+    fn full_precision_miss_does_not_borrow_a_colliding_entry() {
+        let mut map = MarketMap::default();
+        map.insert(info(TOKEN_A, "A"));
+        assert!(map.get(TOKEN_B).is_none());
+    }
+
+    #[test]
+    fn legacy_lossy_id_falls_back_to_prefix_entry() {
+        let mut map = MarketMap::default();
+        map.insert(info(TOKEN_A, "A"));
+        let lossy = "1.23456789012345e77";
+        assert_eq!(cache_key(lossy), cache_key(TOKEN_A));
+        assert_eq!(map.get(lossy).map(|i| i.question.as_str()), Some("A"));
+    }
+
+    #[test]
+    fn batch_maps_ids_back_to_their_markets() {
+        let markets: Vec<GammaMarket> = serde_json::from_value(serde_json::json!([
+            {
+                "question": "Market A?",
+                "outcomes": "[\"Yes\", \"No\"]",
+                "clobTokenIds": format!("[\"{TOKEN_A}\", \"111\"]"),
+            },
+            {
+                "question": "Market B?",
+                "outcomes": "[\"Yes\", \"No\"]",
+                "clobTokenIds": format!("[\"222\", \"{TOKEN_B}\"]"),
+            },
+        ]))
+        .expect("gamma markets");
+        let ids = vec![TOKEN_B.to_string(), TOKEN_A.to_string(), "333".to_string()];
+
+        let found = match_batch(&markets, &ids);
+        assert_eq!(found.len(), 2);
+        let b = &found[TOKEN_B];
+        assert_eq!(
+            (b.question.as_str(), b.outcome.as_str()),
+            ("Market B?", "No")
+        );
+        assert_eq!(b.gamma_token_id, TOKEN_B);
+        let a = &found[TOKEN_A];
+        assert_eq!((a.question.as_str(), a.outcome_index), ("Market A?", 0));
+    }
+
+    #[test]
+    fn lossy_ids_match_by_prefix_full_ids_exactly() {
+        let ids = vec!["111".to_string(), TOKEN_A.to_string()];
+        assert_eq!(token_index(&ids, TOKEN_A), Some(1));
+        assert_eq!(token_index(&ids, "1.23456789012345e77"), Some(1));
+        // Same prefix, different full-precision token
+        assert_eq!(token_index(&ids, TOKEN_B), None);
+    }
+
+    #[test]
+    fn reinserting_a_token_replaces_it() {
+        let mut map = MarketMap::default();
+        map.insert(info(TOKEN_A, "old"));
+        assert!(!map.insert(info(TOKEN_A, "new")));
+        assert_eq!(map.values().count(), 1);
+        assert_eq!(map.collisions(), 0);
+        assert_eq!(map.get(TOKEN_A).map(|i| i.question.as_str()), Some("new"));
+    }
+
+    fn resolved_state(written: &[&str]) -> ResolvedPricesState {
+        ResolvedPricesState {
+            seeded: true,
+            last_block: Some(12),
+            resolutions: HashMap::from([
+                ("c1".to_string(), (vec!["1".into(), "0".into()], 10)),
+                ("c2".to_string(), (vec!["0".into(), "1".into()], 12)),
+            ]),
+            written: written.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// Binary market whose Yes token is `token_id`
+    fn binary(token_id: &str, condition_id: &str) -> MarketInfo {
+        MarketInfo {
+            condition_id: Some(condition_id.into()),
+            all_token_ids: vec![token_id.into(), "1".into()],
+            outcomes: vec!["Yes".into(), "No".into()],
+            ..info(token_id, "Q")
+        }
+    }
+
+    fn resolved_cache() -> MarketMap {
+        let mut map = MarketMap::default();
+        map.insert(binary(TOKEN_A, "0xc1"));
+        map.insert(binary(TOKEN_B, "0xc2"));
+        map
+    }
+
+    #[test]
+    fn incremental_cycle_only_inserts_unwritten_assets() {
+        let assets = vec![TOKEN_A.to_string(), TOKEN_B.to_string()];
+        let rows = pending_resolved_prices(&assets, &resolved_cache(), &resolved_state(&[TOKEN_A]));
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].asset_id, TOKEN_B);
+        assert_eq!(rows[0].resolved_price, "0.000000");
+        assert_eq!(rows[0].block_number, 12);
+    }
+
+    #[test]
+    fn incremental_cycle_without_delta_touches_nothing() {
+        let assets = vec![TOKEN_A.to_string(), TOKEN_B.to_string()];
+        let state = resolved_state(&[TOKEN_A, TOKEN_B]);
+        // Already-written rows are neither re-emitted nor dropped from the state
+        assert!(pending_resolved_prices(&assets, &resolved_cache(), &state).is_empty());
+        assert_eq!(state.written.len(), 2);
+    }
+
+    #[test]
+    fn rebuild_state_re_emits_every_resolved_asset() {
+        let assets = vec![TOKEN_A.to_string(), TOKEN_B.to_string()];
+        let rows = pending_resolved_prices(&assets, &resolved_cache(), &resolved_state(&[]));
+        let ids: HashSet<&str> = rows.iter().map(|r| r.asset_id.as_str()).collect();
+        assert_eq!(ids, HashSet::from([TOKEN_A, TOKEN_B]));
+    }
+
+    fn payouts(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn even_payouts_are_flagged_invalid() {
+        assert_eq!(resolution_kind(&payouts(&["1", "1"])), "invalid");
+        assert_eq!(resolution_kind(&payouts(&["1", "0"])), "normal");
+        assert_eq!(resolution_kind(&payouts(&["0", "1"])), "normal");
+        assert_eq!(resolution_kind(&payouts(&["0", "0"])), "normal");
+
+        let row = resolved_price_row(TOKEN_A, &binary(TOKEN_A, "0xc1"), &payouts(&["1", "1"]), 7)
+            .unwrap();
+        assert_eq!(row.resolved_price, "0.500000");
+        assert_eq!(row.resolution_kind, "invalid");
+    }
+
+    #[test]
+    fn winning_and_losing_sides_price_at_one_and_zero() {
+        let mut no = binary(TOKEN_A, "0xc1");
+        no.outcome_index = 1;
+        let yes_wins = payouts(&["1", "0"]);
+        let yes = resolved_price_row(TOKEN_A, &binary(TOKEN_A, "0xc1"), &yes_wins, 7).unwrap();
+        let no = resolved_price_row("1", &no, &yes_wins, 7).unwrap();
+        assert_eq!(
+            (yes.resolved_price.as_str(), no.resolved_price.as_str()),
+            ("1.000000", "0.000000")
+        );
+        assert_eq!(yes.resolution_kind, "normal");
+    }
+
+    #[test]
+    fn misaligned_payout_vector_is_skipped() {
+        // A 3-outcome payout vector can't be indexed by a binary market's outcome_index
+        let row = resolved_price_row(
+            TOKEN_A,
+            &binary(TOKEN_A, "0xc1"),
+            &payouts(&["0", "1", "0"]),
+            7,
+        );
+        assert!(row.is_none());
+    }
+
+    #[test]
+    fn neg_risk_condition_id_derives_from_question_id() {
+        let question_id = "0xe3b1bc389210504ebcb9cffe4b0ed06ccac50561e0f24abb6379984cec030f00";
+        let derived = "0xdd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917";
+        assert_eq!(neg_risk_condition_id(question_id).as_deref(), Some(derived));
+        assert_eq!(neg_risk_condition_id("not-hex"), None);
+
+        let market = |neg_risk: bool| -> GammaMarket {
+            serde_json::from_value(serde_json::json!({
+                "conditionId": "0xgamma",
+                "negRisk": neg_risk,
+                "questionID": question_id,
+            }))
+            .unwrap()
+        };
+        assert_eq!(
+            market(true).onchain_condition_id().as_deref(),
+            Some(derived)
+        );
+        assert_eq!(
+            market(false).onchain_condition_id().as_deref(),
+            Some("0xgamma")
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use axum::extract::FromRequestParts;
+use axum::extract::{FromRequestParts, OptionalFromRequestParts, Path};
 use axum::http::StatusCode;
 use axum::http::request::Parts;
 
@@ -17,6 +17,14 @@ pub fn validate_eth_address(s: &str) -> Result<String, StatusCode> {
     }
 }
 
+/// The token from an `Authorization: Bearer <token>` header.
+pub fn bearer_token(headers: &axum::http::HeaderMap) -> Option<&str> {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
 /// Extracted wallet address from a validated JWT.
 pub struct AuthUser(pub String);
 
@@ -27,19 +35,51 @@ impl FromRequestParts<AppState> for AuthUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let header = parts
-            .headers
-            .get("authorization")
-            .and_then(|v| v.to_str().ok())
-            .ok_or(StatusCode::UNAUTHORIZED)?;
-
-        let token = header
-            .strip_prefix("Bearer ")
-            .ok_or(StatusCode::UNAUTHORIZED)?;
+        let token = bearer_token(&parts.headers).ok_or(StatusCode::UNAUTHORIZED)?;
 
-        let address = super::auth::validate_jwt(token, &state.jwt_secret)
+        let address = super::auth::validate_jwt(token, &state.jwt_secret, &state.revocations)
             .map_err(|_| StatusCode::UNAUTHORIZED)?;
 
         Ok(AuthUser(address))
     }
 }
+
+/// `Option<AuthUser>`: `None` when no Authorization header is sent, but a
+/// present-and-invalid token is still rejected.
+impl OptionalFromRequestParts<AppState> for AuthUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if !parts.headers.contains_key("authorization") {
+            return Ok(None);
+        }
+        <AuthUser as FromRequestParts<AppState>>::from_request_parts(parts, state)
+            .await
+            .map(Some)
+    }
+}
+
+/// Trader address from the `{address}` path segment, validated and lowercased.
+/// Malformed input is rejected with 400 before the handler runs any query.
+pub struct ValidatedAddress(pub String);
+
+impl FromRequestParts<AppState> for ValidatedAddress {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let Path(raw) =
+            <Path<String> as FromRequestParts<AppState>>::from_request_parts(parts, state)
+                .await
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.body_text()))?;
+
+        validate_eth_address(&raw)
+            .map(ValidatedAddress)
+            .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid address".to_string()))
+    }
+}
@@ -7,6 +7,8 @@ pub mod db;
 pub mod engine;
 pub mod markets;
 pub mod middleware;
+pub mod notifier;
+pub mod rate_limit;
 pub mod routes;
 pub mod scanner;
 pub mod server;
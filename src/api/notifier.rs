@@ -0,0 +1,348 @@
+use std::collections::HashSet;
+use std::env;
+use std::time::Duration;
+
+use tokio::sync::{broadcast, mpsc};
+
+use super::alerts::Alert;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+const DEFAULT_KINDS: &[&str] = &["WhaleTrade", "MarketResolution"];
+const DEFAULT_MIN_USDC: f64 = 25_000.0;
+/// Per-target backlog; alerts beyond it are dropped rather than delaying others
+const TARGET_QUEUE_CAPACITY: usize = 100;
+const MAX_ATTEMPTS: u32 = 4;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const POLYGONSCAN_TX: &str = "https://polygonscan.com/tx/";
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// How a target expects its request body.
+#[derive(Clone, Copy, Debug)]
+enum TargetFormat {
+    /// `{"content": text}`
+    Discord,
+    /// Bot API `sendMessage`
+    Telegram,
+    /// The alert as broadcast on /ws/alerts
+    Json,
+}
+
+/// No `Debug`: `url` embeds the Telegram bot token.
+#[derive(Clone)]
+struct Target {
+    name: &'static str,
+    url: String,
+    format: TargetFormat,
+    /// Telegram only
+    chat_id: Option<String>,
+}
+
+pub struct NotifierConfig {
+    targets: Vec<Target>,
+    kinds: HashSet<String>,
+    min_usdc: f64,
+}
+
+impl NotifierConfig {
+    /// `None` when no target is configured.
+    ///
+    /// Targets: `ALERT_DISCORD_WEBHOOK_URL`, `ALERT_TELEGRAM_BOT_TOKEN` +
+    /// `ALERT_TELEGRAM_CHAT_ID`, `ALERT_JSON_WEBHOOK_URL`. Filters:
+    /// `ALERT_NOTIFY_KINDS` (comma-separated, default WhaleTrade,MarketResolution)
+    /// and `ALERT_NOTIFY_MIN_USDC` (default 25000) for alerts with an amount.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+        let mut targets = Vec::new();
+        if let Some(url) = var("ALERT_DISCORD_WEBHOOK_URL") {
+            targets.push(Target {
+                name: "discord",
+                url,
+                format: TargetFormat::Discord,
+                chat_id: None,
+            });
+        }
+        match (
+            var("ALERT_TELEGRAM_BOT_TOKEN"),
+            var("ALERT_TELEGRAM_CHAT_ID"),
+        ) {
+            (Some(token), Some(chat_id)) => targets.push(Target {
+                name: "telegram",
+                url: format!("https://api.telegram.org/bot{token}/sendMessage"),
+                format: TargetFormat::Telegram,
+                chat_id: Some(chat_id),
+            }),
+            (Some(_), None) | (None, Some(_)) => {
+                tracing::warn!(
+                    "Notifier: ALERT_TELEGRAM_BOT_TOKEN and ALERT_TELEGRAM_CHAT_ID must both be set"
+                );
+            }
+            (None, None) => {}
+        }
+        if let Some(url) = var("ALERT_JSON_WEBHOOK_URL") {
+            targets.push(Target {
+                name: "json",
+                url,
+                format: TargetFormat::Json,
+                chat_id: None,
+            });
+        }
+        if targets.is_empty() {
+            return None;
+        }
+
+        let kinds = var("ALERT_NOTIFY_KINDS")
+            .map(|v| {
+                v.split(',')
+                    .map(|k| k.trim().to_string())
+                    .filter(|k| !k.is_empty())
+                    .collect()
+            })
+            .unwrap_or_else(|| DEFAULT_KINDS.iter().map(|k| k.to_string()).collect());
+        let min_usdc = var("ALERT_NOTIFY_MIN_USDC")
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .unwrap_or(DEFAULT_MIN_USDC);
+
+        Some(Self {
+            targets,
+            kinds,
+            min_usdc,
+        })
+    }
+
+    fn wants(&self, alert: &Alert) -> bool {
+        if !self.kinds.contains(alert.kind()) {
+            return false;
+        }
+        let usdc = match alert {
+            Alert::WhaleTrade { usdc_amount, .. } | Alert::SmartMoneyEntry { usdc_amount, .. } => {
+                usdc_amount.parse::<f64>().unwrap_or(0.0)
+            }
+            _ => return true,
+        };
+        usdc >= self.min_usdc
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Dispatcher
+// ---------------------------------------------------------------------------
+
+/// Mirror matching alerts to external chat/webhook targets. Each target has
+/// its own bounded queue and worker, so a slow or failing target never holds
+/// up the others or the broadcast channel.
+pub async fn run(
+    config: NotifierConfig,
+    http: reqwest::Client,
+    mut rx: broadcast::Receiver<Alert>,
+) {
+    let names: Vec<&str> = config.targets.iter().map(|t| t.name).collect();
+    tracing::info!(
+        "Notifier: targets={names:?} kinds={:?} min_usdc={}",
+        config.kinds,
+        config.min_usdc
+    );
+
+    let queues: Vec<(&'static str, mpsc::Sender<Alert>)> = config
+        .targets
+        .iter()
+        .map(|target| {
+            let (tx, target_rx) = mpsc::channel(TARGET_QUEUE_CAPACITY);
+            tokio::spawn(deliver_loop(target.clone(), http.clone(), target_rx));
+            (target.name, tx)
+        })
+        .collect();
+
+    loop {
+        let alert = match rx.recv().await {
+            Ok(a) => a,
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                tracing::warn!("Notifier: lagged, skipped {n} alerts");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        if !config.wants(&alert) {
+            continue;
+        }
+        for (name, tx) in &queues {
+            if tx.try_send(alert.clone()).is_err() {
+                tracing::warn!(
+                    "Notifier: {name} queue full, dropping {} alert",
+                    alert.kind()
+                );
+            }
+        }
+    }
+}
+
+async fn deliver_loop(target: Target, http: reqwest::Client, mut rx: mpsc::Receiver<Alert>) {
+    while let Some(alert) = rx.recv().await {
+        let Some(body) = request_body(&target, &alert) else {
+            continue;
+        };
+        let mut delay = RETRY_BASE_DELAY;
+        for attempt in 1..=MAX_ATTEMPTS {
+            let result = http
+                .post(&target.url)
+                .json(&body)
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await;
+            let retryable = match result {
+                Ok(resp) if resp.status().is_success() => break,
+                Ok(resp) => {
+                    let status = resp.status();
+                    tracing::warn!(
+                        "Notifier: {} returned {status} (attempt {attempt}/{MAX_ATTEMPTS})",
+                        target.name
+                    );
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => {
+                    // The URL may carry a bot token; keep it out of the logs
+                    tracing::warn!(
+                        "Notifier: {} request failed: {} (attempt {attempt}/{MAX_ATTEMPTS})",
+                        target.name,
+                        e.without_url()
+                    );
+                    true
+                }
+            };
+            if !retryable || attempt == MAX_ATTEMPTS {
+                tracing::warn!(
+                    "Notifier: giving up on {} alert for {}",
+                    alert.kind(),
+                    target.name
+                );
+                break;
+            }
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+}
+
+fn request_body(target: &Target, alert: &Alert) -> Option<serde_json::Value> {
+    match target.format {
+        TargetFormat::Json => serde_json::to_value(alert).ok(),
+        TargetFormat::Discord => Some(serde_json::json!({ "content": format_alert(alert)? })),
+        TargetFormat::Telegram => Some(serde_json::json!({
+            "chat_id": target.chat_id,
+            "text": format_alert(alert)?,
+            "disable_web_page_preview": true,
+        })),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Message formatting
+// ---------------------------------------------------------------------------
+
+/// Plain-text message for chat targets; `None` for kinds without a format.
+fn format_alert(alert: &Alert) -> Option<String> {
+    let text = match alert {
+        Alert::WhaleTrade {
+            side,
+            trader,
+            usdc_amount,
+            question,
+            outcome,
+            tx_hash,
+            ..
+        } => format!(
+            "Whale {} {}\n{}\nTrader: {}\n{POLYGONSCAN_TX}{tx_hash}",
+            side.to_uppercase(),
+            format_usd(usdc_amount),
+            market_line(question.as_deref(), outcome.as_deref()),
+            shorten_address(trader),
+        ),
+        Alert::MarketResolution {
+            question,
+            winning_outcome,
+            invalid,
+            tx_hash,
+            ..
+        } => {
+            let result = match winning_outcome {
+                Some(outcome) => format!("Winner: {outcome}"),
+                None if *invalid => "Resolved 50/50 (invalid)".into(),
+                None => "Winner: unknown".into(),
+            };
+            format!(
+                "Market resolved\n{}\n{result}\n{POLYGONSCAN_TX}{tx_hash}",
+                question.as_deref().unwrap_or("Unknown market"),
+            )
+        }
+        Alert::SmartMoneyEntry {
+            trader,
+            rank,
+            question,
+            outcome,
+            usdc_amount,
+            price,
+            tx_hash,
+            ..
+        } => format!(
+            "Top #{rank} trader {} opened a position: {} at {price}\n{}\n{POLYGONSCAN_TX}{tx_hash}",
+            shorten_address(trader),
+            format_usd(usdc_amount),
+            market_line(Some(question), Some(outcome)),
+        ),
+        Alert::PriceSwing {
+            question,
+            outcome,
+            old_price,
+            new_price,
+            direction,
+            window_seconds,
+            tx_hash,
+            ..
+        } => format!(
+            "Price swing {direction}: {old_price} → {new_price} in {}m\n{}\n{POLYGONSCAN_TX}{tx_hash}",
+            window_seconds / 60,
+            market_line(Some(question), Some(outcome)),
+        ),
+        _ => return None,
+    };
+    Some(text)
+}
+
+fn market_line(question: Option<&str>, outcome: Option<&str>) -> String {
+    let question = question
+        .filter(|q| !q.is_empty())
+        .unwrap_or("Unknown market");
+    match outcome.filter(|o| !o.is_empty()) {
+        Some(outcome) => format!("{question} — {outcome}"),
+        None => question.to_string(),
+    }
+}
+
+/// `0x1234…abcd`
+fn shorten_address(address: &str) -> String {
+    if address.len() <= 12 {
+        return address.to_string();
+    }
+    format!("{}…{}", &address[..6], &address[address.len() - 4..])
+}
+
+/// `"52300.5"` → `$52,300`
+fn format_usd(amount: &str) -> String {
+    let whole = amount.parse::<f64>().unwrap_or(0.0).round() as u64;
+    let digits = whole.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    format!("${out}")
+}
@@ -0,0 +1,522 @@
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use super::server::AppState;
+use super::types::RateLimitStats;
+
+/// Budget a request is charged against.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RouteClass {
+    /// `/api/auth/*`, always keyed by client IP
+    Auth,
+    /// Backtest runs (`/api/lab/backtest`, `/api/lab/strategies/{id}/run`)
+    Backtest,
+    Default,
+}
+
+impl RouteClass {
+    /// Classifies a path, with or without the `/api` prefix.
+    pub fn for_path(path: &str) -> Self {
+        let path = path.strip_prefix("/api").unwrap_or(path);
+        if path.starts_with("/auth/") {
+            Self::Auth
+        } else if path == "/lab/backtest"
+            || (path.starts_with("/lab/strategies/") && path.ends_with("/run"))
+        {
+            Self::Backtest
+        } else {
+            Self::Default
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::Backtest => "backtest",
+            Self::Default => "default",
+        }
+    }
+}
+
+/// Requests per minute per class (0 disables that class) and the tracked-key bound.
+pub struct RateLimitConfig {
+    pub auth_per_min: u32,
+    pub backtest_per_min: u32,
+    pub default_per_min: u32,
+    pub max_keys: usize,
+    /// Take the client IP from the last `X-Forwarded-For` entry, the one the
+    /// trusted proxy appended (earlier entries are client-supplied)
+    pub trust_proxy: bool,
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: u32| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            auth_per_min: read("RATE_LIMIT_AUTH_PER_MIN", 10),
+            backtest_per_min: read("RATE_LIMIT_BACKTEST_PER_MIN", 10),
+            default_per_min: read("RATE_LIMIT_DEFAULT_PER_MIN", 300),
+            max_keys: read("RATE_LIMIT_MAX_KEYS", 10_000).max(1) as usize,
+            trust_proxy: env::var("RATE_LIMIT_TRUST_PROXY").is_ok_and(|v| v == "true" || v == "1"),
+        }
+    }
+
+    fn per_min(&self, class: RouteClass) -> u32 {
+        match class {
+            RouteClass::Auth => self.auth_per_min,
+            RouteClass::Backtest => self.backtest_per_min,
+            RouteClass::Default => self.default_per_min,
+        }
+    }
+}
+
+/// Token bucket holding up to `per_min` requests, refilled continuously.
+struct Bucket {
+    tokens: f64,
+    per_min: u32,
+    updated: Instant,
+    /// Position in `Buckets::lru`
+    seq: u64,
+}
+
+impl Bucket {
+    fn refilled(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        (self.tokens + elapsed * self.per_min as f64 / 60.0).min(self.per_min as f64)
+    }
+
+    fn refill(&mut self, now: Instant) {
+        self.tokens = self.refilled(now);
+        self.updated = now;
+    }
+}
+
+/// Buckets plus their keys from least to most recently used.
+#[derive(Default)]
+struct Buckets {
+    map: HashMap<String, Bucket>,
+    lru: BTreeMap<u64, String>,
+    next_seq: u64,
+}
+
+impl Buckets {
+    /// Marks `key` as just used.
+    fn touch(&mut self, key: &str) {
+        let seq = self.next_seq;
+        if let Some(bucket) = self.map.get_mut(key) {
+            self.lru.remove(&bucket.seq);
+            bucket.seq = seq;
+            self.lru.insert(seq, key.to_string());
+            self.next_seq += 1;
+        }
+    }
+
+    fn insert(&mut self, key: String, mut bucket: Bucket) {
+        bucket.seq = self.next_seq;
+        self.next_seq += 1;
+        self.lru.insert(bucket.seq, key.clone());
+        self.map.insert(key, bucket);
+    }
+
+    /// Drops the least recently used bucket; false if there was none.
+    fn evict_lru(&mut self) -> bool {
+        match self.lru.pop_first() {
+            Some((_, key)) => self.map.remove(&key).is_some(),
+            None => false,
+        }
+    }
+}
+
+/// Keyed in-memory token-bucket limiter, bounded to `max_keys` buckets. A new
+/// key past the bound evicts the least recently used one, so a flood of fresh
+/// keys never locks out other new clients.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<Buckets>,
+    allowed: AtomicU64,
+    limited: AtomicU64,
+    evicted: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(Buckets::default()),
+            allowed: AtomicU64::new(0),
+            limited: AtomicU64::new(0),
+            evicted: AtomicU64::new(0),
+        }
+    }
+
+    /// Charges one request to every identity's bucket for `class`; it passes only
+    /// if all of them have a token. `Err` carries the Retry-After in seconds.
+    pub fn check(&self, class: RouteClass, identities: &[String]) -> Result<(), u64> {
+        self.check_at(class, identities, Instant::now())
+    }
+
+    fn check_at(&self, class: RouteClass, identities: &[String], now: Instant) -> Result<(), u64> {
+        let per_min = self.config.per_min(class);
+        if per_min == 0 {
+            return Ok(());
+        }
+        let keys: Vec<String> = identities
+            .iter()
+            .map(|identity| format!("{}:{identity}", class.name()))
+            .collect();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|p| p.into_inner());
+
+        // Touch known keys first so making room for a new one never evicts them.
+        // Refused requests count as use too, so a throttled client stays tracked.
+        for key in &keys {
+            buckets.touch(key);
+        }
+        for key in &keys {
+            if buckets.map.contains_key(key) {
+                continue;
+            }
+            if buckets.map.len() >= self.config.max_keys && buckets.evict_lru() {
+                self.evicted.fetch_add(1, Ordering::Relaxed);
+            }
+            buckets.insert(
+                key.clone(),
+                Bucket {
+                    tokens: per_min as f64,
+                    per_min,
+                    updated: now,
+                    seq: 0,
+                },
+            );
+        }
+
+        let mut retry_after = 0;
+        for key in &keys {
+            let Some(bucket) = buckets.map.get_mut(key) else {
+                continue;
+            };
+            bucket.refill(now);
+            if bucket.tokens < 1.0 {
+                let wait = ((1.0 - bucket.tokens) * 60.0 / per_min as f64)
+                    .ceil()
+                    .max(1.0) as u64;
+                retry_after = retry_after.max(wait);
+            }
+        }
+        if retry_after > 0 {
+            self.limited.fetch_add(1, Ordering::Relaxed);
+            return Err(retry_after);
+        }
+        for key in &keys {
+            if let Some(bucket) = buckets.map.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        self.allowed.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn stats(&self) -> RateLimitStats {
+        RateLimitStats {
+            tracked_keys: self
+                .buckets
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .map
+                .len(),
+            max_keys: self.config.max_keys,
+            allowed: self.allowed.load(Ordering::Relaxed),
+            limited: self.limited.load(Ordering::Relaxed),
+            evicted: self.evicted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Axum middleware: charges each request to its route class, against the client
+/// IP and, when a valid token is sent, the authenticated address too, so neither
+/// rotating IPs nor fresh tokens reset a limit. Auth routes are keyed by IP only.
+/// Over-limit requests get 429 + Retry-After.
+pub async fn rate_limit(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let class = RouteClass::for_path(req.uri().path());
+    let address = match class {
+        RouteClass::Auth => None,
+        _ => super::middleware::bearer_token(req.headers()).and_then(|token| {
+            super::auth::validate_jwt(token, &state.jwt_secret, &state.revocations).ok()
+        }),
+    };
+    let mut identities = vec![format!(
+        "ip:{}",
+        client_ip(&req, state.rate_limiter.config.trust_proxy)
+    )];
+    identities.extend(address.map(|address| format!("addr:{address}")));
+
+    match state.rate_limiter.check(class, &identities) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            "Rate limit exceeded",
+        )
+            .into_response(),
+    }
+}
+
+fn client_ip(req: &Request, trust_proxy: bool) -> String {
+    if trust_proxy {
+        let forwarded = req
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit(',').next())
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty());
+        if let Some(ip) = forwarded {
+            return ip.to_string();
+        }
+    }
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn limiter(auth: u32, default: u32, max_keys: usize) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            auth_per_min: auth,
+            backtest_per_min: 2,
+            default_per_min: default,
+            max_keys,
+            trust_proxy: false,
+        })
+    }
+
+    #[test]
+    fn classifies_routes() {
+        assert_eq!(RouteClass::for_path("/api/auth/nonce"), RouteClass::Auth);
+        assert_eq!(RouteClass::for_path("/auth/verify"), RouteClass::Auth);
+        assert_eq!(
+            RouteClass::for_path("/api/lab/backtest"),
+            RouteClass::Backtest
+        );
+        assert_eq!(
+            RouteClass::for_path("/lab/strategies/abc/run"),
+            RouteClass::Backtest
+        );
+        assert_eq!(RouteClass::for_path("/lab/strategies"), RouteClass::Default);
+        assert_eq!(
+            RouteClass::for_path("/api/leaderboard"),
+            RouteClass::Default
+        );
+    }
+
+    #[test]
+    fn burst_is_capped_then_limited_with_retry_after() {
+        let rl = limiter(10, 300, 100);
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert!(
+                rl.check_at(RouteClass::Auth, &["ip:1.2.3.4".into()], now)
+                    .is_ok()
+            );
+        }
+        // 10/min refills one token every 6s
+        assert_eq!(
+            rl.check_at(RouteClass::Auth, &["ip:1.2.3.4".into()], now),
+            Err(6)
+        );
+        assert_eq!(rl.stats().allowed, 10);
+        assert_eq!(rl.stats().limited, 1);
+    }
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let rl = limiter(10, 300, 100);
+        let now = Instant::now();
+        for _ in 0..10 {
+            rl.check_at(RouteClass::Auth, &["ip:1.2.3.4".into()], now)
+                .unwrap();
+        }
+        let later = now + Duration::from_secs(3);
+        assert_eq!(
+            rl.check_at(RouteClass::Auth, &["ip:1.2.3.4".into()], later),
+            Err(3)
+        );
+        let later = now + Duration::from_secs(6);
+        assert!(
+            rl.check_at(RouteClass::Auth, &["ip:1.2.3.4".into()], later)
+                .is_ok()
+        );
+        assert!(
+            rl.check_at(RouteClass::Auth, &["ip:1.2.3.4".into()], later)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn keys_and_classes_have_separate_budgets() {
+        let rl = limiter(1, 1, 100);
+        let now = Instant::now();
+        assert!(
+            rl.check_at(RouteClass::Auth, &["ip:1.1.1.1".into()], now)
+                .is_ok()
+        );
+        assert!(
+            rl.check_at(RouteClass::Auth, &["ip:1.1.1.1".into()], now)
+                .is_err()
+        );
+        assert!(
+            rl.check_at(RouteClass::Auth, &["ip:2.2.2.2".into()], now)
+                .is_ok()
+        );
+        assert!(
+            rl.check_at(RouteClass::Default, &["ip:1.1.1.1".into()], now)
+                .is_ok()
+        );
+        assert!(
+            rl.check_at(RouteClass::Backtest, &["addr:0xabc".into()], now)
+                .is_ok()
+        );
+        assert!(
+            rl.check_at(RouteClass::Backtest, &["addr:0xabc".into()], now)
+                .is_ok()
+        );
+        assert!(
+            rl.check_at(RouteClass::Backtest, &["addr:0xabc".into()], now)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn zero_budget_disables_limiting() {
+        let rl = limiter(0, 300, 100);
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert!(
+                rl.check_at(RouteClass::Auth, &["ip:1.2.3.4".into()], now)
+                    .is_ok()
+            );
+        }
+        assert_eq!(rl.stats().tracked_keys, 0);
+    }
+
+    #[test]
+    fn tracked_keys_stay_bounded() {
+        let rl = limiter(10, 10, 3);
+        let now = Instant::now();
+        for i in 0..50 {
+            assert!(
+                rl.check_at(RouteClass::Default, &[format!("ip:10.0.0.{i}")], now)
+                    .is_ok()
+            );
+        }
+        let stats = rl.stats();
+        assert_eq!(stats.tracked_keys, 3);
+        assert_eq!(stats.evicted, 47);
+    }
+
+    #[test]
+    fn new_clients_are_admitted_when_the_map_is_full_of_drained_buckets() {
+        let rl = limiter(10, 1, 2);
+        let now = Instant::now();
+        for ip in ["ip:a", "ip:b"] {
+            rl.check_at(RouteClass::Default, &[ip.into()], now).unwrap();
+        }
+        assert!(
+            rl.check_at(RouteClass::Default, &["ip:c".into()], now)
+                .is_ok()
+        );
+        assert_eq!(rl.stats().evicted, 1);
+    }
+
+    #[test]
+    fn throttled_key_stays_throttled_when_the_map_is_full() {
+        let rl = limiter(10, 1, 3);
+        let now = Instant::now();
+        let throttled = ["ip:1.1.1.1".to_string()];
+        rl.check_at(RouteClass::Default, &throttled, now).unwrap();
+
+        // Each retry keeps its bucket recent, so the flood evicts the others
+        for i in 0..20 {
+            assert!(
+                rl.check_at(RouteClass::Default, &[format!("ip:10.0.0.{i}")], now)
+                    .is_ok()
+            );
+            assert!(rl.check_at(RouteClass::Default, &throttled, now).is_err());
+        }
+        assert_eq!(rl.stats().tracked_keys, 3);
+        let buckets = rl.buckets.lock().unwrap();
+        assert!(buckets.map.contains_key("default:ip:1.1.1.1"));
+    }
+
+    #[test]
+    fn ip_and_address_are_both_charged() {
+        let rl = limiter(10, 1, 100);
+        let now = Instant::now();
+        let ids = |ip: &str, addr: &str| [format!("ip:{ip}"), format!("addr:{addr}")];
+        assert!(
+            rl.check_at(RouteClass::Default, &ids("1.1.1.1", "0xa"), now)
+                .is_ok()
+        );
+        // Rotating the IP doesn't reset the address budget, nor a fresh token the IP's
+        assert!(
+            rl.check_at(RouteClass::Default, &ids("2.2.2.2", "0xa"), now)
+                .is_err()
+        );
+        assert!(
+            rl.check_at(RouteClass::Default, &ids("1.1.1.1", "0xb"), now)
+                .is_err()
+        );
+        // A refused request charges neither identity
+        assert!(
+            rl.check_at(RouteClass::Default, &ids("2.2.2.2", "0xb"), now)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn least_recently_used_bucket_is_evicted() {
+        let rl = limiter(10, 10, 3);
+        let now = Instant::now();
+        for ip in ["ip:a", "ip:b", "ip:c"] {
+            rl.check_at(RouteClass::Default, &[ip.into()], now).unwrap();
+        }
+        // "a" is the oldest key but was just used; "b" is now the least recent
+        rl.check_at(RouteClass::Default, &["ip:a".into()], now)
+            .unwrap();
+        rl.check_at(RouteClass::Default, &["ip:d".into()], now)
+            .unwrap();
+
+        let buckets = rl.buckets.lock().unwrap();
+        assert!(buckets.map.contains_key("default:ip:a"));
+        assert!(!buckets.map.contains_key("default:ip:b"));
+        assert_eq!(buckets.map.len(), buckets.lru.len());
+    }
+
+    #[test]
+    fn forwarded_ip_is_the_entry_the_proxy_appended() {
+        let req = axum::http::Request::builder()
+            .header("x-forwarded-for", "6.6.6.6, 10.0.0.1, 203.0.113.7")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(client_ip(&req, true), "203.0.113.7");
+        assert_eq!(client_ip(&req, false), "unknown");
+    }
+}
@@ -7,18 +7,63 @@ use axum::{
 
 use serde::Deserialize;
 
-use super::middleware::AuthUser;
-use super::server::AppState;
+use super::alerts::HISTORY_ALERT_KINDS;
+use super::middleware::{AuthUser, ValidatedAddress};
+use super::server::{AppState, CacheLookup};
 use super::types::*;
 use super::{db, markets, middleware};
 
-const ALLOWED_SORT_COLUMNS: &[&str] = &["realized_pnl", "total_volume", "trade_count"];
+const ALLOWED_SORT_COLUMNS: &[&str] = &[
+    "realized_pnl",
+    "unrealized_pnl",
+    "total_volume",
+    "trade_count",
+    "roi",
+    "win_rate",
+];
+
+/// Settled positions a trader needs before `sort=win_rate` ranks them.
+const WIN_RATE_MIN_SETTLED: u64 = 10;
+const ALLOWED_TIMEFRAMES: &[&str] = &["all", "1h", "24h", "7d", "30d"];
+
+/// Label-filtered leaderboard: rows fetched per ClickHouse round-trip, and the
+/// hard cap on ranked traders scanned before returning a partial page.
+const LABEL_SCAN_BATCH: u32 = 200;
+const LABEL_SCAN_CAP: u32 = 2000;
+
+/// Max rows per `format=csv` leaderboard export.
+const CSV_EXPORT_LIMIT: u32 = 5000;
+
+/// All-time PnL sort key over `trader_positions p` — shared by the leaderboard
+/// and the hourly rank snapshot so both rankings agree.
+const ALL_TIME_PNL_EXPR: &str = "sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price)))";
+
+/// Per-position PnL over `trader_positions p`; `include_fees` nets out `total_fee`.
+fn all_time_position_pnl(include_fees: bool) -> &'static str {
+    if include_fees {
+        "(p.sell_usdc - p.buy_usdc - p.total_fee) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))"
+    } else {
+        "(p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))"
+    }
+}
+
+/// Per-position PnL over `windowed_positions_query` rows; `include_fees` nets out `fees`.
+fn windowed_position_pnl(include_fees: bool) -> &'static str {
+    if include_fees {
+        "(p.cash_flow - p.fees) + p.net_tokens * coalesce(rp.resolved_price, toFloat64(lp.latest_price))"
+    } else {
+        "p.cash_flow + p.net_tokens * coalesce(rp.resolved_price, toFloat64(lp.latest_price))"
+    }
+}
+
+/// How many top-PnL ranks each hourly `leaderboard_snapshots` run records.
+const RANK_SNAPSHOT_SIZE: u32 = 1000;
 
 /// Exchange contracts that appear as `maker` in taker-summary OrderFilled events.
 /// These are protocol intermediaries, not real traders. Safety net filter —
 /// with maker-only MVs the exchange should never appear as trader, but keep
 /// this in case of edge cases or future schema changes.
-const EXCHANGE_CONTRACTS: &[&str] = &[
+pub(crate) const EXCHANGE_CONTRACTS: &[&str] = &[
     "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E", // CTF Exchange
     "0xC5d563A36AE78145C45a50134d48A1215220f80a", // NegRisk CTF Exchange
     "0x02A86f51aA7B8b1c17c30364748d5Ae4a0727E23", // Polymarket Relayer
@@ -32,144 +77,430 @@ pub(crate) fn exclude_clause() -> String {
         .join(",")
 }
 
-/// Background cache warmer — runs the default leaderboard query and populates the cache.
-pub async fn warm_leaderboard(state: &AppState) -> Result<(), String> {
-    let sort = "realized_pnl";
-    let order = "desc";
-    let limit: u32 = 25;
-    let offset: u32 = 0;
-    let timeframe = "all";
-    let cache_key = format!("{sort}:{order}:{limit}:{offset}:{timeframe}");
-
-    let exclude = exclude_clause();
-    let sort_expr = "sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price)))";
-
-    let query = format!(
-        "WITH resolved AS (
-            SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-            FROM poly_dearboard.resolved_prices FINAL
-        )
-        SELECT
-            toString(p.trader) AS address,
-            toString(sum(p.total_volume)) AS total_volume,
-            sum(p.trade_count) AS trade_count,
-            count() AS markets_traded,
-            toString(ROUND(sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))), 6)) AS realized_pnl,
-            toString(sum(p.total_fee)) AS total_fees,
-            ifNull(toString(min(p.first_ts)), '') AS first_trade,
-            ifNull(toString(max(p.last_ts)), '') AS last_trade
-        FROM poly_dearboard.trader_positions p
-        LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-        LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-        WHERE p.trader NOT IN ({exclude})
-        GROUP BY p.trader
-        ORDER BY {sort_expr} {order}
-        LIMIT ? OFFSET ?"
-    );
-
-    let traders = state
-        .db
-        .query(&query)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all::<TraderSummary>()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let total: u64 = state
-        .db
-        .query("SELECT uniqExactMerge(unique_traders) FROM poly_dearboard.global_stats")
-        .fetch_one()
-        .await
-        .map_err(|e| e.to_string())?;
+/// Leaderboard variants kept warm by default as `(sort, order, limit, timeframe)` —
+/// the views the UI opens on. Override with `LEADERBOARD_WARM_VARIANTS`
+/// (comma-separated `sort:order:limit:timeframe`).
+const DEFAULT_WARM_VARIANTS: &[(&str, &str, u32, &str)] = &[
+    ("realized_pnl", "desc", 25, "all"),
+    ("total_volume", "desc", 25, "all"),
+    ("trade_count", "desc", 25, "all"),
+    ("realized_pnl", "desc", 25, "24h"),
+    ("realized_pnl", "desc", 25, "7d"),
+    ("realized_pnl", "desc", 25, "30d"),
+];
 
-    let addresses: Vec<String> = traders.iter().map(|t| t.address.to_lowercase()).collect();
-    let (labels, label_details) = match tokio::time::timeout(
-        std::time::Duration::from_secs(2),
-        batch_compute_labels(state, &addresses),
-    )
-    .await
-    {
-        Ok(pair) => pair,
-        Err(_) => (
-            std::collections::HashMap::new(),
-            std::collections::HashMap::new(),
-        ),
-    };
+/// Pause between warm queries so a cycle doesn't hammer ClickHouse.
+const WARM_VARIANT_DELAY: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Warm variants from `LEADERBOARD_WARM_VARIANTS`, falling back to the defaults
+/// when unset or unparseable.
+pub fn warm_variants() -> Vec<(String, String, u32, String)> {
+    let parsed = std::env::var("LEADERBOARD_WARM_VARIANTS")
+        .ok()
+        .and_then(|v| {
+            v.split(',')
+                .map(|spec| {
+                    let mut parts = spec.trim().split(':');
+                    let sort = parts.next()?.to_string();
+                    let order = parts.next()?.to_string();
+                    let limit = parts.next()?.parse().ok()?;
+                    let timeframe = parts.next()?.to_string();
+                    Some((sort, order, limit, timeframe))
+                })
+                .collect::<Option<Vec<_>>>()
+        });
+    match parsed {
+        Some(v) if !v.is_empty() => v,
+        _ => DEFAULT_WARM_VARIANTS
+            .iter()
+            .map(|&(s, o, l, t)| (s.to_string(), o.to_string(), l, t.to_string()))
+            .collect(),
+    }
+}
 
-    let response = LeaderboardResponse {
-        traders,
-        total,
-        limit,
-        offset,
-        labels,
-        label_details,
-    };
+/// Background cache warmer — runs each leaderboard variant sequentially through
+/// the same refresh path as the handler and logs per-variant timings.
+pub async fn warm_leaderboard(
+    state: &AppState,
+    variants: &[(String, String, u32, String)],
+) -> Result<(), String> {
+    let mut timings = Vec::with_capacity(variants.len());
+    let mut failures = 0;
+
+    for (i, (sort, order, limit, timeframe)) in variants.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(WARM_VARIANT_DELAY).await;
+        }
+        let params = LeaderboardParams {
+            sort: Some(sort.clone()),
+            order: Some(order.clone()),
+            limit: Some(*limit),
+            timeframe: Some(timeframe.clone()),
+            ..Default::default()
+        };
+        let t0 = std::time::Instant::now();
+        let result = match LeaderboardQuery::parse(&params, String::new(), None) {
+            Ok(query) => refresh_leaderboard(state, &query).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        let variant = format!("{sort}:{order}:{limit}:{timeframe}");
+        match result {
+            Ok(()) => timings.push(format!("{variant}={}ms", t0.elapsed().as_millis())),
+            Err((_, e)) => {
+                failures += 1;
+                tracing::warn!("leaderboard warm failed for {variant}: {e}");
+            }
+        }
+    }
 
-    let mut cache = state.leaderboard_cache.write().await;
-    cache.insert(
-        cache_key,
-        super::server::CachedResponse {
-            data: response,
-            expires: std::time::Instant::now() + std::time::Duration::from_secs(30),
-        },
+    tracing::info!(
+        "leaderboard cache warmed {}/{} variants [{}]",
+        timings.len(),
+        variants.len(),
+        timings.join(", ")
     );
-
-    tracing::debug!("leaderboard cache warmed");
+    if failures > 0 {
+        return Err(format!("{failures} leaderboard variant(s) failed to warm"));
+    }
     Ok(())
 }
 
 pub async fn leaderboard(
     State(state): State<AppState>,
+    user: Option<AuthUser>,
     Query(params): Query<LeaderboardParams>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let sort = params.sort.as_deref().unwrap_or("realized_pnl");
-    let order = params.order.as_deref().unwrap_or("desc");
-    let limit = params.limit.unwrap_or(100).min(500);
-    let offset = params.offset.unwrap_or(0);
-    let timeframe = params.timeframe.as_deref().unwrap_or("all");
+    let caller = user.map(|AuthUser(owner)| owner);
+    let (owner, members) = list_scope(&state, caller.as_deref(), &params).await?;
+    let query = LeaderboardQuery::parse(&params, owner, members)?;
+
+    if query.csv {
+        let response = compute_leaderboard(&state, &query).await?;
+        let filename = format!(
+            "leaderboard-{}-{}.csv",
+            query.timeframe,
+            chrono::Utc::now().format("%Y-%m-%d")
+        );
+        return Ok((
+            [
+                (
+                    axum::http::header::CONTENT_TYPE,
+                    "text/csv; charset=utf-8".to_string(),
+                ),
+                (
+                    axum::http::header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{filename}\""),
+                ),
+            ],
+            leaderboard_csv(&response.traders),
+        )
+            .into_response());
+    }
 
-    // Check cache (30s TTL)
-    let cache_key = format!("{sort}:{order}:{limit}:{offset}:{timeframe}");
-    {
-        let cache = state.leaderboard_cache.read().await;
-        if let Some(entry) = cache.get(&cache_key) {
-            if entry.expires > std::time::Instant::now() {
-                tracing::info!("leaderboard: cache hit ({cache_key})");
-                return Ok(Json(entry.data.clone()));
+    // Check cache (30s TTL). Expired entries are still served for a grace window
+    // while a single background task recomputes them.
+    let cache_key = query.cache_key();
+    match state.leaderboard_cache.lookup(&cache_key).await {
+        CacheLookup::Fresh(mut data) => {
+            tracing::info!("leaderboard: cache hit ({cache_key})");
+            apply_aliases(&state, caller.as_deref(), &mut data.traders);
+            return Ok(([("x-cache", "hit")], Json(data)).into_response());
+        }
+        CacheLookup::Stale(mut data) => {
+            apply_aliases(&state, caller.as_deref(), &mut data.traders);
+            if let Some(guard) = state.leaderboard_cache.begin_refresh(&cache_key) {
+                tracing::info!("leaderboard: serving stale, refreshing ({cache_key})");
+                let state = state.clone();
+                tokio::spawn(async move {
+                    let _guard = guard;
+                    if let Err((_, e)) = refresh_leaderboard(&state, &query).await {
+                        tracing::warn!("leaderboard background refresh failed: {e}");
+                    }
+                });
             }
+            return Ok(([("x-cache", "stale")], Json(data)).into_response());
         }
+        CacheLookup::Miss => {}
     }
 
-    if !ALLOWED_SORT_COLUMNS.contains(&sort) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!("Invalid sort column. Allowed: {ALLOWED_SORT_COLUMNS:?}"),
-        ));
+    let mut response = refresh_leaderboard(&state, &query).await?;
+    apply_aliases(&state, caller.as_deref(), &mut response.traders);
+    Ok(([("x-cache", "miss")], Json(response)).into_response())
+}
+
+/// Custom leaderboard scope: with `list_id`, the caller and the members of their
+/// list; otherwise no owner and every trader.
+async fn list_scope(
+    state: &AppState,
+    caller: Option<&str>,
+    params: &LeaderboardParams,
+) -> Result<(String, Option<Vec<String>>), (StatusCode, String)> {
+    let list_id = params.list_id.clone().unwrap_or_default();
+    if list_id.is_empty() {
+        return Ok((String::new(), None));
     }
-    if order != "asc" && order != "desc" {
+    let Some(owner) = caller.map(str::to_string) else {
         return Err((
-            StatusCode::BAD_REQUEST,
-            "Invalid order. Allowed: asc, desc".into(),
+            StatusCode::UNAUTHORIZED,
+            "Sign in to rank a trader list".into(),
         ));
+    };
+    let members = {
+        let owner = owner.clone();
+        with_list_db(state, move |conn| {
+            db::get_list_member_addresses(conn, &list_id, &owner)
+        })
+        .await?
+    };
+    Ok((owner, Some(members)))
+}
+
+/// Fills in the caller's private aliases. Runs per request after the cache, since
+/// cached leaderboards are shared between users. Aliases are skipped on DB errors.
+fn apply_aliases(state: &AppState, owner: Option<&str>, traders: &mut [TraderSummary]) {
+    let Some(owner) = owner else {
+        return;
+    };
+    let addresses: Vec<String> = traders.iter().map(|t| t.address.to_lowercase()).collect();
+    let aliases = {
+        let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+        db::get_trader_aliases(&conn, owner, &addresses)
+    };
+    match aliases {
+        Ok(mut aliases) => {
+            for t in traders.iter_mut() {
+                t.alias = aliases.remove(&t.address.to_lowercase());
+            }
+        }
+        Err(e) => tracing::warn!("leaderboard: alias lookup failed: {e}"),
+    }
+}
+
+/// Validated leaderboard request — everything that shapes the response.
+#[derive(Clone)]
+struct LeaderboardQuery {
+    sort: String,
+    order: String,
+    limit: u32,
+    offset: u32,
+    timeframe: String,
+    min_volume: f64,
+    csv: bool,
+    label: Option<BehavioralLabel>,
+    cursor: Option<LeaderboardCursor>,
+    category: Option<String>,
+    /// Net trading fees out of every PnL figure
+    include_fees: bool,
+    /// Raw `label` / `cursor` / `list_id` params, kept for the cache key
+    label_param: String,
+    cursor_param: String,
+    list_id: String,
+    /// Caller for list leaderboards (empty otherwise)
+    owner: String,
+    members: Option<Vec<String>>,
+}
+
+impl LeaderboardQuery {
+    fn parse(
+        params: &LeaderboardParams,
+        owner: String,
+        members: Option<Vec<String>>,
+    ) -> Result<Self, (StatusCode, String)> {
+        let sort = params.sort.as_deref().unwrap_or("realized_pnl");
+        let order = params.order.as_deref().unwrap_or("desc");
+        let format = params.format.as_deref().unwrap_or("json");
+        let csv = format == "csv";
+        // CSV exports are pulled into spreadsheets, so allow much larger pages
+        let limit = params
+            .limit
+            .unwrap_or(100)
+            .min(if csv { CSV_EXPORT_LIMIT } else { 500 });
+        let offset = params.offset.unwrap_or(0);
+        let timeframe = params.timeframe.as_deref().unwrap_or("all");
+        // ROI ranking defaults to a $1k volume floor so dust accounts don't dominate
+        let min_volume = params
+            .min_volume
+            .unwrap_or(if sort == "roi" { 1000.0 } else { 0.0 })
+            .max(0.0);
+        let label = params.label.as_deref().unwrap_or("");
+        let cursor_param = params.cursor.as_deref().unwrap_or("");
+
+        if format != "json" && format != "csv" {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Invalid format. Allowed: json, csv".into(),
+            ));
+        }
+        if !ALLOWED_SORT_COLUMNS.contains(&sort) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid sort column. Allowed: {ALLOWED_SORT_COLUMNS:?}"),
+            ));
+        }
+        if order != "asc" && order != "desc" {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Invalid order. Allowed: asc, desc".into(),
+            ));
+        }
+        if !ALLOWED_TIMEFRAMES.contains(&timeframe) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid timeframe. Allowed: {ALLOWED_TIMEFRAMES:?}"),
+            ));
+        }
+        // Cursor and offset pagination are mutually exclusive
+        if !cursor_param.is_empty() && params.offset.is_some() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Specify cursor or offset, not both".into(),
+            ));
+        }
+        let cursor = if cursor_param.is_empty() {
+            None
+        } else {
+            Some(
+                LeaderboardCursor::decode(cursor_param)
+                    .ok_or((StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))?,
+            )
+        };
+
+        let label_filter = if label.is_empty() {
+            None
+        } else {
+            Some(BehavioralLabel::from_str(label).ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Invalid label. Allowed: {:?}",
+                        BehavioralLabel::ALL
+                            .iter()
+                            .map(|l| l.as_str())
+                            .collect::<Vec<_>>()
+                    ),
+                )
+            })?)
+        };
+
+        Ok(Self {
+            sort: sort.to_string(),
+            order: order.to_string(),
+            limit,
+            offset,
+            timeframe: timeframe.to_string(),
+            min_volume,
+            csv,
+            label: label_filter,
+            cursor,
+            category: params
+                .category
+                .as_deref()
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string),
+            include_fees: params.include_fees.unwrap_or(false),
+            label_param: label.to_string(),
+            cursor_param: cursor_param.to_string(),
+            list_id: params.list_id.clone().unwrap_or_default(),
+            owner,
+            members,
+        })
+    }
+
+    /// Keyed per timeframe so 1h/24h/7d/30d never collide, and per list + owner
+    /// so custom leaderboards never leak across users.
+    fn cache_key(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            self.sort,
+            self.order,
+            self.limit,
+            self.offset,
+            self.timeframe,
+            self.min_volume,
+            self.label_param,
+            self.cursor_param,
+            self.list_id,
+            self.owner,
+            self.category.as_deref().unwrap_or(""),
+            self.include_fees
+        )
+    }
+
+    /// Whether this is the ranking `snapshot_leaderboard_ranks` records (all-time
+    /// realized PnL, descending, everyone), the only one `rank_change_24h` describes.
+    fn matches_rank_snapshot(&self) -> bool {
+        self.sort == "realized_pnl"
+            && self.order == "desc"
+            && self.timeframe == "all"
+            && !self.include_fees
+            && self.category.is_none()
+            && self.members.is_none()
     }
+}
+
+/// Recomputes a leaderboard page and stores it in the cache (30s TTL).
+async fn refresh_leaderboard(
+    state: &AppState,
+    q: &LeaderboardQuery,
+) -> Result<LeaderboardResponse, (StatusCode, String)> {
+    let response = compute_leaderboard(state, q).await?;
+    state
+        .leaderboard_cache
+        .insert(
+            q.cache_key(),
+            response.clone(),
+            std::time::Duration::from_secs(30),
+        )
+        .await;
+    Ok(response)
+}
 
+/// Runs the ranking query for a validated request (no caching).
+async fn compute_leaderboard(
+    state: &AppState,
+    q: &LeaderboardQuery,
+) -> Result<LeaderboardResponse, (StatusCode, String)> {
+    let sort = q.sort.as_str();
+    let order = q.order.as_str();
+    let timeframe = q.timeframe.as_str();
+    let (limit, offset, min_volume, csv) = (q.limit, q.offset, q.min_volume, q.csv);
+    let cursor = q.cursor.as_ref();
+
+    // Category leaderboard: unknown categories rank nobody rather than erroring
+    let filters = leaderboard_filters(state, q).await;
+    if let Some((name, 0)) = &filters.category {
+        return Ok(LeaderboardResponse {
+            traders: Vec::new(),
+            total: 0,
+            limit,
+            offset,
+            labels: std::collections::HashMap::new(),
+            label_details: std::collections::HashMap::new(),
+            scanned: 0,
+            next_cursor: None,
+            category: Some(name.clone()),
+            markets_covered: Some(0),
+            incomplete_metrics: Vec::new(),
+        });
+    }
+    let (filter, p_filter) = (&filters.filter, &filters.p_filter);
     let exclude = exclude_clause();
 
-    let (traders, total) = if timeframe == "all" {
+    let (query, total) = if timeframe == "all" {
         // All-time: read from pre-aggregated trader_positions table
-        let sort_expr = match sort {
-            "realized_pnl" => {
-                "sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price)))"
-            }
-            "total_volume" => "sum(p.total_volume)",
-            "trade_count" => "sum(p.trade_count)",
-            _ => unreachable!(),
-        };
+        let net = "toFloat64(p.buy_amount - p.sell_amount)";
+        let win_columns = win_rate_columns(net);
+        let pos_pnl = all_time_position_pnl(q.include_fees);
+        let volume_floor = volume_floor_cond(true, min_volume);
+        let rank_expr = leaderboard_rank_expr(sort, true, q.include_fees);
+        let having = having_clause(&[&volume_floor]);
+        let page_having =
+            having_clause(&[&volume_floor, &cursor_predicate(cursor, &rank_expr, order)]);
 
         let query = format!(
             "WITH resolved AS (
-                SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price, resolution_kind
                 FROM poly_dearboard.resolved_prices FINAL
             )
             SELECT
@@ -177,79 +508,91 @@ pub async fn leaderboard(
                 toString(sum(p.total_volume)) AS total_volume,
                 sum(p.trade_count) AS trade_count,
                 count() AS markets_traded,
-                toString(ROUND(sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))), 6)) AS realized_pnl,
+                toString(ROUND(sum({pos_pnl}), 6)) AS realized_pnl,
+                toString(ROUND(sumIf({pos_pnl}, rp.resolved_price IS NOT NULL OR abs(toFloat64(p.buy_amount - p.sell_amount)) < 0.000001), 6)) AS realized_pnl_closed,
+                toString(ROUND(sumIf({pos_pnl}, rp.resolved_price IS NULL AND abs(toFloat64(p.buy_amount - p.sell_amount)) >= 0.000001), 6)) AS unrealized_pnl,
+                round(if(sum(p.total_volume) > 0, sum({pos_pnl}) / toFloat64(sum(p.total_volume)), 0), 6) AS roi,
+                {win_columns}
                 toString(sum(p.total_fee)) AS total_fees,
                 ifNull(toString(min(p.first_ts)), '') AS first_trade,
                 ifNull(toString(max(p.last_ts)), '') AS last_trade
             FROM poly_dearboard.trader_positions p
             LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
             LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-            WHERE p.trader NOT IN ({exclude})
+            WHERE p.trader NOT IN ({exclude}){p_filter}
             GROUP BY p.trader
-            ORDER BY {sort_expr} {order}
+            {page_having}
+            ORDER BY {rank_expr} {order} NULLS LAST, lower(p.trader) ASC
             LIMIT ? OFFSET ?"
         );
 
-        let traders = state
-            .db
-            .query(&query)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all::<TraderSummary>()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-        let total: u64 = state
-            .db
-            .query("SELECT uniqExactMerge(unique_traders) FROM poly_dearboard.global_stats")
-            .fetch_one()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-        (traders, total)
-    } else {
-        // Time-windowed (1h/24h): read from raw trades (within TTL) + asset_latest_price
-        let prewhere = match timeframe {
-            "1h" => "PREWHERE block_timestamp >= now() - INTERVAL 1 HOUR",
-            "24h" => "PREWHERE block_timestamp >= now() - INTERVAL 24 HOUR",
-            _ => "",
+        let total: u64 = if min_volume > 0.0 {
+            state
+                .db
+                .query(&format!(
+                    "SELECT count() FROM (
+                        SELECT p.trader FROM poly_dearboard.trader_positions p
+                        WHERE p.trader NOT IN ({exclude}){p_filter}
+                        GROUP BY p.trader
+                        {having}
+                    )"
+                ))
+                .fetch_one()
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        } else if q.members.is_some() || filters.category.is_some() {
+            // Members / category traders with at least one trade
+            state
+                .db
+                .query(&format!(
+                    "SELECT uniqExact(trader) FROM poly_dearboard.trader_positions
+                    WHERE trader NOT IN ({exclude}){filter}"
+                ))
+                .fetch_one()
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        } else {
+            state
+                .db
+                .query("SELECT uniqExactMerge(unique_traders) FROM poly_dearboard.global_stats")
+                .fetch_one()
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         };
 
-        let sort_expr = match sort {
-            "realized_pnl" => {
-                "sum(p.cash_flow + p.net_tokens * coalesce(rp.resolved_price, toFloat64(lp.latest_price)))"
-            }
-            "total_volume" => "sum(p.volume)",
-            "trade_count" => "sum(p.trades)",
-            _ => unreachable!(),
-        };
+        (query, total)
+    } else {
+        // Time-windowed: 1h/24h read raw trades (within TTL), 7d/30d read pnl_daily
+        let net = "toFloat64(p.net_tokens)";
+        let win_columns = win_rate_columns(net);
+        let (positions_cte, total_query) = windowed_positions_query(timeframe, &exclude, filter);
+
+        let pos_pnl = windowed_position_pnl(q.include_fees);
+        let volume_floor = volume_floor_cond(false, min_volume);
+        let rank_expr = leaderboard_rank_expr(sort, false, q.include_fees);
+        let having = having_clause(&[&volume_floor]);
+        let page_having =
+            having_clause(&[&volume_floor, &cursor_predicate(cursor, &rank_expr, order)]);
 
         let query = format!(
             "WITH
                 resolved AS (
-                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price, resolution_kind
                     FROM poly_dearboard.resolved_prices FINAL
                 ),
                 positions AS (
-                    SELECT trader, asset_id,
-                           sumIf(amount, side = 'buy') - sumIf(amount, side = 'sell') AS net_tokens,
-                           sumIf(usdc_amount, side = 'sell') - sumIf(usdc_amount, side = 'buy') AS cash_flow,
-                           sum(usdc_amount) AS volume,
-                           count() AS trades,
-                           sum(fee) AS fees,
-                           min(if(block_timestamp = toDateTime('1970-01-01 00:00:00'), NULL, block_timestamp)) AS first_ts,
-                           max(if(block_timestamp = toDateTime('1970-01-01 00:00:00'), NULL, block_timestamp)) AS last_ts
-                    FROM poly_dearboard.trades
-                    {prewhere}
-                    WHERE trader NOT IN ({exclude})
-                    GROUP BY trader, asset_id
+                    {positions_cte}
                 )
             SELECT
                 toString(p.trader) AS address,
-                toString(sum(p.volume)) AS total_volume,
+                toString(ROUND(sum(p.volume), 6)) AS total_volume,
                 sum(p.trades) AS trade_count,
                 count() AS markets_traded,
-                toString(ROUND(sum(p.cash_flow + p.net_tokens * coalesce(rp.resolved_price, toFloat64(lp.latest_price))), 6)) AS realized_pnl,
+                toString(ROUND(sum({pos_pnl}), 6)) AS realized_pnl,
+                toString(ROUND(sumIf({pos_pnl}, rp.resolved_price IS NOT NULL OR abs(p.net_tokens) < 0.000001), 6)) AS realized_pnl_closed,
+                toString(ROUND(sumIf({pos_pnl}, rp.resolved_price IS NULL AND abs(p.net_tokens) >= 0.000001), 6)) AS unrealized_pnl,
+                round(if(sum(p.volume) > 0, sum({pos_pnl}) / toFloat64(sum(p.volume)), 0), 6) AS roi,
+                {win_columns}
                 toString(sum(p.fees)) AS total_fees,
                 ifNull(toString(min(p.first_ts)), '') AS first_trade,
                 ifNull(toString(max(p.last_ts)), '') AS last_trade
@@ -257,92 +600,728 @@ pub async fn leaderboard(
             LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
             LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
             GROUP BY p.trader
-            ORDER BY {sort_expr} {order}
+            {page_having}
+            ORDER BY {rank_expr} {order} NULLS LAST, lower(p.trader) ASC
             LIMIT ? OFFSET ?"
         );
 
-        let traders = state
-            .db
-            .query(&query)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all::<TraderSummary>()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
+        let total_query = if min_volume > 0.0 {
+            format!(
+                "SELECT count() FROM (
+                    SELECT p.trader FROM ({positions_cte}) p
+                    GROUP BY p.trader
+                    {having}
+                )"
+            )
+        } else {
+            total_query
+        };
         let total: u64 = state
             .db
-            .query(&format!(
-                "SELECT uniqExact(trader) FROM poly_dearboard.trades {prewhere} WHERE trader NOT IN ({exclude})"
-            ))
+            .query(&total_query)
             .fetch_one()
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-        (traders, total)
+        (query, total)
     };
 
-    // Batch-compute labels for the current page of traders (with timeout)
-    let addresses: Vec<String> = traders.iter().map(|t| t.address.to_lowercase()).collect();
-    let (labels, label_details) = match tokio::time::timeout(
-        std::time::Duration::from_secs(2),
-        batch_compute_labels(&state, &addresses),
-    )
-    .await
-    {
-        Ok(pair) => pair,
-        Err(_) => {
-            tracing::warn!("batch_compute_labels timed out after 2s");
-            (
-                std::collections::HashMap::new(),
-                std::collections::HashMap::new(),
+    let incomplete_metrics = match timeframe {
+        "7d" => incomplete_pnl_daily_metrics(state, 7).await,
+        "30d" => incomplete_pnl_daily_metrics(state, 30).await,
+        _ => Vec::new(),
+    };
+
+    let (traders, labels, label_details, scanned, next_cursor) = match q.label {
+        None => {
+            let traders = state
+                .db
+                .query(&query)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all::<TraderSummary>()
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+            // Batch-compute labels for the current page of traders (with timeout).
+            // CSV rows don't carry labels, so exports skip the work entirely.
+            let addresses: Vec<String> = if csv {
+                Vec::new()
+            } else {
+                traders.iter().map(|t| t.address.to_lowercase()).collect()
+            };
+            let (labels, label_details) = match tokio::time::timeout(
+                std::time::Duration::from_secs(2),
+                batch_compute_labels(state, &addresses),
             )
+            .await
+            {
+                Ok(pair) => pair,
+                Err(_) => {
+                    tracing::warn!("batch_compute_labels timed out after 2s");
+                    (
+                        std::collections::HashMap::new(),
+                        std::collections::HashMap::new(),
+                    )
+                }
+            };
+            let scanned = traders.len() as u32;
+            let next_cursor = (scanned == limit)
+                .then(|| traders.last().map(|t| LeaderboardCursor::for_row(sort, t)))
+                .flatten();
+            (traders, labels, label_details, scanned, next_cursor)
+        }
+        Some(wanted) => {
+            // Labels are computed in Rust, so scan the ranking in batches starting at
+            // `offset`, keeping only traders that carry the label until the page is
+            // full or the scan cap is hit. Clients page with `offset + scanned`.
+            let mut traders = Vec::new();
+            let mut labels = std::collections::HashMap::new();
+            let mut label_details = std::collections::HashMap::new();
+            let mut scanned: u32 = 0;
+            let mut last_consumed: Option<TraderSummary> = None;
+            let mut exhausted = false;
+
+            while (traders.len() as u32) < limit && scanned < LABEL_SCAN_CAP {
+                let batch = state
+                    .db
+                    .query(&query)
+                    .bind(LABEL_SCAN_BATCH)
+                    .bind(offset + scanned)
+                    .fetch_all::<TraderSummary>()
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+                if batch.is_empty() {
+                    exhausted = true;
+                    break;
+                }
+                let batch_len = batch.len() as u32;
+
+                let addresses: Vec<String> =
+                    batch.iter().map(|t| t.address.to_lowercase()).collect();
+                let Ok((mut batch_labels, mut batch_details)) = tokio::time::timeout(
+                    std::time::Duration::from_secs(2),
+                    batch_compute_labels(state, &addresses),
+                )
+                .await
+                else {
+                    tracing::warn!("batch_compute_labels timed out after 2s (label scan)");
+                    break;
+                };
+
+                // Page may fill mid-batch: only count rows actually consumed
+                let mut consumed: u32 = 0;
+                for t in batch {
+                    if traders.len() as u32 >= limit {
+                        break;
+                    }
+                    consumed += 1;
+                    last_consumed = Some(t.clone());
+                    let addr = t.address.to_lowercase();
+                    let matches = batch_labels
+                        .get(&addr)
+                        .is_some_and(|ls| ls.contains(&wanted));
+                    if matches {
+                        if let Some(ls) = batch_labels.remove(&addr) {
+                            labels.insert(addr.clone(), ls);
+                        }
+                        if let Some(d) = batch_details.remove(&addr) {
+                            label_details.insert(addr, d);
+                        }
+                        traders.push(t);
+                    }
+                }
+                scanned += consumed;
+                if batch_len < LABEL_SCAN_BATCH {
+                    exhausted = consumed == batch_len;
+                    break;
+                }
+            }
+            let next_cursor = if exhausted {
+                None
+            } else {
+                last_consumed.map(|t| LeaderboardCursor::for_row(sort, &t))
+            };
+            (traders, labels, label_details, scanned, next_cursor)
         }
     };
 
-    let response = LeaderboardResponse {
+    let mut traders = traders;
+    if !csv && q.matches_rank_snapshot() {
+        apply_rank_changes(state, &mut traders).await;
+    }
+
+    Ok(LeaderboardResponse {
         traders,
         total,
         limit,
         offset,
         labels,
         label_details,
-    };
-
-    // Cache for 30 seconds
-    {
-        let mut cache = state.leaderboard_cache.write().await;
-        cache.insert(
-            cache_key,
-            super::server::CachedResponse {
-                data: response.clone(),
-                expires: std::time::Instant::now() + std::time::Duration::from_secs(30),
-            },
-        );
-    }
-
-    Ok(Json(response))
+        scanned,
+        next_cursor,
+        markets_covered: filters.category.as_ref().map(|(_, n)| *n),
+        category: filters.category.map(|(name, _)| name),
+        incomplete_metrics,
+    })
 }
 
-pub async fn trader_stats(
-    State(state): State<AppState>,
-    Path(address): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let address = address.to_lowercase();
+/// Category and list-member predicates of a leaderboard request, shared with
+/// `/rank` so both rank the same traders and positions.
+struct LeaderboardFilters {
+    /// Resolved category name and the markets the cache knows in it
+    category: Option<(String, u64)>,
+    /// ` AND ...` on bare `trader` / `asset_id` (windowed sources, counts)
+    filter: String,
+    /// The same over `trader_positions p`
+    p_filter: String,
+}
 
-    let result = state
-        .db
-        .query(
-            "WITH resolved AS (
+async fn leaderboard_filters(state: &AppState, q: &LeaderboardQuery) -> LeaderboardFilters {
+    let category = match &q.category {
+        Some(c) => Some(markets::category_markets(&state.market_cache, c).await),
+        None => None,
+    };
+    // Restrict to assets of the category via the persisted market_metadata dimension
+    let category_in = category.as_ref().map(|(name, _)| {
+        format!(
+            "SELECT asset_id FROM poly_dearboard.market_metadata FINAL WHERE category = '{}'",
+            name.replace('\\', "\\\\").replace('\'', "\\'")
+        )
+    });
+    let (category_filter, p_category_filter) = match &category_in {
+        Some(sub) => (
+            format!(" AND asset_id IN ({sub})"),
+            format!(" AND p.asset_id IN ({sub})"),
+        ),
+        None => (String::new(), String::new()),
+    };
+    // Extra trader predicate for list leaderboards (an empty list matches nobody)
+    let member_filter = match &q.members {
+        None => String::new(),
+        Some(addrs) if addrs.is_empty() => " AND 0".to_string(),
+        Some(addrs) => format!(
+            " AND lower(trader) IN ({})",
+            addrs
+                .iter()
+                .map(|a| format!("'{}'", a.to_lowercase().replace('\'', "''")))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    };
+    LeaderboardFilters {
+        category,
+        filter: format!("{member_filter}{category_filter}"),
+        p_filter: format!("{member_filter}{p_category_filter}"),
+    }
+}
+
+/// Rounded sort key the leaderboard orders by, over `trader_positions p`
+/// (`all_time`) or `windowed_positions_query` rows joined to `lp` / `rp`.
+/// `/rank` ranks by the same key so both agree.
+fn leaderboard_rank_expr(sort: &str, all_time: bool, include_fees: bool) -> String {
+    let (net, volume, trades, pos_pnl) = if all_time {
+        (
+            "toFloat64(p.buy_amount - p.sell_amount)",
+            "p.total_volume",
+            "p.trade_count",
+            all_time_position_pnl(include_fees),
+        )
+    } else {
+        (
+            "toFloat64(p.net_tokens)",
+            "p.volume",
+            "p.trades",
+            windowed_position_pnl(include_fees),
+        )
+    };
+    let sort_expr = match sort {
+        "realized_pnl" => format!("sum({pos_pnl})"),
+        // Open positions only: unresolved and still holding tokens
+        "unrealized_pnl" => {
+            format!("sumIf({pos_pnl}, rp.resolved_price IS NULL AND abs({net}) >= 0.000001)")
+        }
+        "total_volume" => format!("sum({volume})"),
+        "trade_count" => format!("sum({trades})"),
+        // NULL for zero-volume traders so they sort last in either direction
+        "roi" => {
+            format!("if(sum({volume}) > 0, sum({pos_pnl}) / toFloat64(sum({volume})), NULL)")
+        }
+        "win_rate" => win_rate_sort_expr(net),
+        _ => unreachable!(),
+    };
+    format!("round(toFloat64({sort_expr}), 6)")
+}
+
+/// `min_volume` HAVING condition (empty when there is no floor).
+fn volume_floor_cond(all_time: bool, min_volume: f64) -> String {
+    if min_volume <= 0.0 {
+        return String::new();
+    }
+    let volume = if all_time {
+        "p.total_volume"
+    } else {
+        "p.volume"
+    };
+    format!("toFloat64(sum({volume})) >= {min_volume}")
+}
+
+/// `pnl_daily` metrics not complete over the last `days` days (window
+/// `day > today() - days`), per `pnl_daily_coverage`. A failed lookup is logged
+/// and treated as full coverage.
+async fn incomplete_pnl_daily_metrics(state: &AppState, days: u32) -> Vec<String> {
+    let first_day = days - 1;
+    match state
+        .db
+        .query(&format!(
+            "SELECT metric FROM poly_dearboard.pnl_daily_coverage FINAL
+            WHERE complete_from > today() - {first_day}
+            ORDER BY metric"
+        ))
+        .fetch_all::<String>()
+        .await
+    {
+        Ok(metrics) => metrics,
+        Err(e) => {
+            tracing::warn!("pnl_daily coverage lookup failed: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Hourly task: records the top `RANK_SNAPSHOT_SIZE` all-time PnL ranks into
+/// `leaderboard_snapshots`, the baseline for `rank_change_24h`.
+pub async fn snapshot_leaderboard_ranks(state: &AppState) -> Result<(), String> {
+    let exclude = exclude_clause();
+    let rank_expr = format!("round(toFloat64({ALL_TIME_PNL_EXPR}), 6)");
+
+    state
+        .db
+        .query(&format!(
+            "INSERT INTO poly_dearboard.leaderboard_snapshots (snapshot_at, trader, rank)
+            WITH resolved AS (
                 SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
                 FROM poly_dearboard.resolved_prices FINAL
             )
+            SELECT now(), trader, toUInt32(row_number() OVER (ORDER BY pnl DESC NULLS LAST, trader ASC))
+            FROM (
+                SELECT lower(toString(p.trader)) AS trader, {rank_expr} AS pnl
+                FROM poly_dearboard.trader_positions p
+                LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+                LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+                WHERE p.trader NOT IN ({exclude})
+                GROUP BY p.trader
+                ORDER BY {rank_expr} DESC NULLS LAST, lower(p.trader) ASC
+                LIMIT {RANK_SNAPSHOT_SIZE}
+            )"
+        ))
+        .execute()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!("leaderboard rank snapshot written");
+    Ok(())
+}
+
+/// Fills `rank_change_24h` from the latest rank snapshot vs. the most recent one
+/// at least 24h old. Traders missing from either snapshot keep `None`.
+async fn apply_rank_changes(state: &AppState, traders: &mut [TraderSummary]) {
+    #[derive(clickhouse::Row, serde::Deserialize)]
+    struct RankChangeRow {
+        trader: String,
+        delta: i64,
+    }
+
+    if traders.is_empty() {
+        return;
+    }
+    let in_list = traders
+        .iter()
+        .map(|t| format!("'{}'", t.address.to_lowercase().replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let rows = match state
+        .db
+        .query(&format!(
+            "WITH
+                (SELECT max(snapshot_at) FROM poly_dearboard.leaderboard_snapshots) AS latest,
+                (SELECT max(snapshot_at) FROM poly_dearboard.leaderboard_snapshots
+                 WHERE snapshot_at <= now() - INTERVAL 24 HOUR) AS prior
+            SELECT cur.trader, toInt64(old.rank) - toInt64(cur.rank) AS delta
+            FROM (
+                SELECT trader, rank FROM poly_dearboard.leaderboard_snapshots
+                WHERE snapshot_at = latest AND trader IN ({in_list})
+            ) AS cur
+            INNER JOIN (
+                SELECT trader, rank FROM poly_dearboard.leaderboard_snapshots
+                WHERE snapshot_at = prior AND trader IN ({in_list})
+            ) AS old ON cur.trader = old.trader"
+        ))
+        .fetch_all::<RankChangeRow>()
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!("rank change lookup failed: {e}");
+            return;
+        }
+    };
+
+    let deltas: std::collections::HashMap<String, i64> =
+        rows.into_iter().map(|r| (r.trader, r.delta)).collect();
+    for t in traders.iter_mut() {
+        t.rank_change_24h = deltas.get(&t.address.to_lowercase()).copied();
+    }
+}
+
+/// Renders leaderboard rows as CSV (header + one line per trader). Numeric
+/// string fields are emitted exactly as ClickHouse returned them.
+fn leaderboard_csv(traders: &[TraderSummary]) -> String {
+    let mut out = String::from(
+        "address,total_volume,trade_count,markets_traded,realized_pnl,realized_pnl_closed,unrealized_pnl,roi,win_rate,settled_count,total_fees,first_trade,last_trade\n",
+    );
+    for t in traders {
+        let fields = [
+            csv_field(&t.address),
+            csv_field(&t.total_volume),
+            t.trade_count.to_string(),
+            t.markets_traded.to_string(),
+            csv_field(&t.realized_pnl),
+            csv_field(&t.realized_pnl_closed),
+            csv_field(&t.unrealized_pnl),
+            t.roi.to_string(),
+            t.win_rate.to_string(),
+            t.settled_count.to_string(),
+            csv_field(&t.total_fees),
+            csv_field(&t.first_trade),
+            csv_field(&t.last_trade),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote, or line break (RFC 4180).
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Opaque keyset cursor for leaderboard pagination: the last row's sort value
+/// (rounded like the ORDER BY key, `None` for a NULL ROI) plus its address as tie-break.
+#[derive(Clone)]
+struct LeaderboardCursor {
+    value: Option<f64>,
+    address: String,
+}
+
+impl LeaderboardCursor {
+    fn for_row(sort: &str, t: &TraderSummary) -> String {
+        use base64::Engine;
+
+        let value = match sort {
+            "total_volume" => t.total_volume.clone(),
+            "trade_count" => t.trade_count.to_string(),
+            "unrealized_pnl" => t.unrealized_pnl.clone(),
+            // Zero-volume traders rank with a NULL ROI (sorted last)
+            "roi" if t.total_volume.parse::<f64>().unwrap_or(0.0) == 0.0 => "null".to_string(),
+            "roi" => t.roi.to_string(),
+            // Below the settled-count guard the sort key is NULL
+            "win_rate" if t.settled_count < WIN_RATE_MIN_SETTLED => "null".to_string(),
+            "win_rate" => t.win_rate.to_string(),
+            _ => t.realized_pnl.clone(),
+        };
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(format!("{value}|{}", t.address.to_lowercase()))
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        use base64::Engine;
+
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()?;
+        let raw = String::from_utf8(raw).ok()?;
+        let (value, address) = raw.split_once('|')?;
+        let value = match value {
+            "null" => None,
+            v => Some(v.parse::<f64>().ok().filter(|v| v.is_finite())?),
+        };
+        // Validated hex address — safe to inline into SQL
+        let address = middleware::validate_eth_address(address).ok()?;
+        Some(Self { value, address })
+    }
+}
+
+/// Keyset predicate selecting rows strictly after `cursor` in
+/// `ORDER BY rank_expr {order} NULLS LAST, lower(p.trader) ASC`.
+fn cursor_predicate(cursor: Option<&LeaderboardCursor>, rank_expr: &str, order: &str) -> String {
+    let Some(c) = cursor else {
+        return String::new();
+    };
+    let addr = &c.address;
+    match c.value {
+        None => format!("({rank_expr} IS NULL AND lower(p.trader) > '{addr}')"),
+        Some(v) => {
+            let cmp = if order == "asc" { ">" } else { "<" };
+            format!(
+                "({rank_expr} {cmp} {v} OR {rank_expr} IS NULL OR ({rank_expr} = {v} AND lower(p.trader) > '{addr}'))"
+            )
+        }
+    }
+}
+
+/// A settled position, by the same rule as behavioral labels: still holding
+/// tokens, and resolved on-chain or priced within 5¢ of 0/1. Invalid (50/50)
+/// resolutions have no winning side and don't count. `net` is the position's
+/// net token expression; the `resolved` CTE must select `resolution_kind`.
+fn settled_cond(net: &str) -> String {
+    format!(
+        "abs({net}) >= 1e-9 AND (rp.resolved_price IS NOT NULL OR toFloat64(lp.latest_price) >= 0.95 OR toFloat64(lp.latest_price) <= 0.05) AND ifNull(rp.resolution_kind, '') != 'invalid'"
+    )
+}
+
+/// A settled position on the winning side of its (effective) outcome.
+fn win_cond(net: &str) -> String {
+    let eff = "coalesce(rp.resolved_price, if(toFloat64(lp.latest_price) >= 0.95, 1.0, 0.0))";
+    format!(
+        "{} AND (({net} > 0 AND {eff} > 0.5) OR ({net} < 0 AND {eff} < 0.5))",
+        settled_cond(net)
+    )
+}
+
+/// `win_rate, settled_count` select columns for `TraderSummary`.
+fn win_rate_columns(net: &str) -> String {
+    let (settled, win) = (settled_cond(net), win_cond(net));
+    format!(
+        "round(if(countIf({settled}) > 0, countIf({win}) * 100.0 / countIf({settled}), 0), 6) AS win_rate,
+                toUInt64(countIf({settled})) AS settled_count,"
+    )
+}
+
+/// Win-rate sort key; NULL below `WIN_RATE_MIN_SETTLED` so tiny samples sort last.
+fn win_rate_sort_expr(net: &str) -> String {
+    let (settled, win) = (settled_cond(net), win_cond(net));
+    format!(
+        "if(countIf({settled}) >= {WIN_RATE_MIN_SETTLED}, countIf({win}) * 100.0 / countIf({settled}), NULL)"
+    )
+}
+
+/// Joins non-empty HAVING conditions (empty string when none apply).
+fn having_clause(conds: &[&str]) -> String {
+    let conds: Vec<&str> = conds.iter().copied().filter(|c| !c.is_empty()).collect();
+    if conds.is_empty() {
+        String::new()
+    } else {
+        format!("HAVING {}", conds.join(" AND "))
+    }
+}
+
+/// Per-(trader, asset) positions for a windowed timeframe, plus the matching
+/// active-trader count query. 1h/24h read raw `trades` (within TTL); 7d/30d read
+/// the `pnl_daily` aggregate (beyond TTL). Both produce the same column shape
+/// (net_tokens, cash_flow, volume, trades, fees, first_ts, last_ts); from `pnl_daily`
+/// first_ts/last_ts are day-granular.
+/// `extra_filter` is an optional ` AND ...` predicate on `trader` / `asset_id`.
+fn windowed_positions_query(
+    timeframe: &str,
+    exclude: &str,
+    extra_filter: &str,
+) -> (String, String) {
+    match timeframe {
+        "7d" | "30d" => {
+            let days = if timeframe == "7d" { 7 } else { 30 };
+            (
+                format!(
+                    "SELECT trader, asset_id,
+                           sum(buy_amount) - sum(sell_amount) AS net_tokens,
+                           sum(sell_usdc) - sum(buy_usdc) AS cash_flow,
+                           sum(buy_usdc) + sum(sell_usdc) AS volume,
+                           sum(trade_count) AS trades,
+                           sum(fee) AS fees,
+                           min(day) AS first_ts,
+                           max(day) AS last_ts
+                    FROM poly_dearboard.pnl_daily
+                    WHERE day > today() - {days}
+                      AND trader NOT IN ({exclude}){extra_filter}
+                    GROUP BY trader, asset_id"
+                ),
+                format!(
+                    "SELECT uniqExact(trader) FROM poly_dearboard.pnl_daily WHERE day > today() - {days} AND trader NOT IN ({exclude}){extra_filter}"
+                ),
+            )
+        }
+        _ => {
+            let prewhere = match timeframe {
+                "1h" => "PREWHERE block_timestamp >= now() - INTERVAL 1 HOUR",
+                _ => "PREWHERE block_timestamp >= now() - INTERVAL 24 HOUR",
+            };
+            (
+                format!(
+                    "SELECT trader, asset_id,
+                           sumIf(amount, side = 'buy') - sumIf(amount, side = 'sell') AS net_tokens,
+                           sumIf(usdc_amount, side = 'sell') - sumIf(usdc_amount, side = 'buy') AS cash_flow,
+                           sum(usdc_amount) AS volume,
+                           count() AS trades,
+                           sum(fee) AS fees,
+                           min(if(block_timestamp = toDateTime('1970-01-01 00:00:00'), NULL, block_timestamp)) AS first_ts,
+                           max(if(block_timestamp = toDateTime('1970-01-01 00:00:00'), NULL, block_timestamp)) AS last_ts
+                    FROM poly_dearboard.trades
+                    {prewhere}
+                    WHERE trader NOT IN ({exclude}){extra_filter}
+                    GROUP BY trader, asset_id"
+                ),
+                format!(
+                    "SELECT uniqExact(trader) FROM poly_dearboard.trades {prewhere} WHERE trader NOT IN ({exclude}){extra_filter}"
+                ),
+            )
+        }
+    }
+}
+
+/// Rank of one trader on the leaderboard described by the same query params
+/// (sort, timeframe, min_volume, category, include_fees, list_id), using the
+/// leaderboard's own filters and sort key so `/rank` agrees with its pages.
+pub async fn trader_rank(
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
+    ValidatedAddress(address): ValidatedAddress,
+    Query(params): Query<TraderRankParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let params = LeaderboardParams {
+        sort: params.sort,
+        timeframe: params.timeframe,
+        min_volume: params.min_volume,
+        category: params.category,
+        include_fees: params.include_fees,
+        list_id: params.list_id,
+        ..Default::default()
+    };
+    let caller = user.map(|AuthUser(owner)| owner);
+    let (owner, members) = list_scope(&state, caller.as_deref(), &params).await?;
+    let q = LeaderboardQuery::parse(&params, owner, members)?;
+    let (sort, timeframe, min_volume) = (q.sort.as_str(), q.timeframe.as_str(), q.min_volume);
+
+    let filters = leaderboard_filters(&state, &q).await;
+    let not_found = || {
+        let msg = if min_volume > 0.0 {
+            format!("Trader not found or below min_volume {min_volume}")
+        } else {
+            "Trader not found".into()
+        };
+        (StatusCode::NOT_FOUND, msg)
+    };
+    if let Some((_, 0)) = &filters.category {
+        return Err(not_found());
+    }
+
+    let exclude = exclude_clause();
+    let all_time = timeframe == "all";
+    let rank_expr = leaderboard_rank_expr(sort, all_time, q.include_fees);
+    let having = having_clause(&[&volume_floor_cond(all_time, min_volume)]);
+    let (source, where_clause) = if all_time {
+        (
+            "poly_dearboard.trader_positions p".to_string(),
+            format!("WHERE p.trader NOT IN ({exclude}){}", filters.p_filter),
+        )
+    } else {
+        let (positions_cte, _) = windowed_positions_query(timeframe, &exclude, &filters.filter);
+        (format!("({positions_cte}) p"), String::new())
+    };
+
+    #[derive(clickhouse::Row, serde::Deserialize)]
+    struct RankRow {
+        rank: u64,
+        total: u64,
+        found: u64,
+        value: f64,
+    }
+
+    let row = state
+        .db
+        .query(&format!(
+            "WITH
+                resolved AS (
+                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price, resolution_kind
+                    FROM poly_dearboard.resolved_prices FINAL
+                ),
+                ranked AS (
+                    SELECT p.trader AS trader, {rank_expr} AS v
+                    FROM {source}
+                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+                    {where_clause}
+                    GROUP BY p.trader
+                    {having}
+                ),
+                me AS (
+                    SELECT toFloat64(ifNull(any(v), 0)) AS v, count() AS n
+                    FROM ranked
+                    WHERE lower(trader) = ?
+                )
+            SELECT
+                countIf(v > (SELECT v FROM me)) + 1 AS rank,
+                count() AS total,
+                (SELECT n FROM me) AS found,
+                (SELECT v FROM me) AS value
+            FROM ranked"
+        ))
+        .bind(&address)
+        .fetch_one::<RankRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if row.found == 0 {
+        return Err(not_found());
+    }
+
+    // Share of ranked traders this trader beats (rank 1 of 100 → 99.0)
+    let percentile = if row.total > 0 {
+        (row.total - row.rank.min(row.total)) as f64 / row.total as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(Json(TraderRankResponse {
+        address,
+        sort: sort.to_string(),
+        timeframe: timeframe.to_string(),
+        rank: row.rank,
+        total: row.total,
+        percentile: (percentile * 100.0).round() / 100.0,
+        value: row.value,
+    }))
+}
+
+pub async fn trader_stats(
+    State(state): State<AppState>,
+    ValidatedAddress(address): ValidatedAddress,
+    Query(params): Query<TraderStatsParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pos_pnl = all_time_position_pnl(params.include_fees.unwrap_or(false));
+    let win_columns = win_rate_columns("toFloat64(p.buy_amount - p.sell_amount)");
+    let result = state
+        .db
+        .query(&format!(
+            "WITH resolved AS (
+                SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price, resolution_kind
+                FROM poly_dearboard.resolved_prices FINAL
+            )
             SELECT
                 toString(p.trader) AS address,
                 toString(sum(p.total_volume)) AS total_volume,
                 sum(p.trade_count) AS trade_count,
                 count() AS markets_traded,
-                toString(ROUND(sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))), 6)) AS realized_pnl,
+                toString(ROUND(sum({pos_pnl}), 6)) AS realized_pnl,
+                toString(ROUND(sumIf({pos_pnl}, rp.resolved_price IS NOT NULL OR abs(toFloat64(p.buy_amount - p.sell_amount)) < 0.000001), 6)) AS realized_pnl_closed,
+                toString(ROUND(sumIf({pos_pnl}, rp.resolved_price IS NULL AND abs(toFloat64(p.buy_amount - p.sell_amount)) >= 0.000001), 6)) AS unrealized_pnl,
+                round(if(sum(p.total_volume) > 0, sum({pos_pnl}) / toFloat64(sum(p.total_volume)), 0), 6) AS roi,
+                {win_columns}
                 toString(sum(p.total_fee)) AS total_fees,
                 ifNull(toString(min(p.first_ts)), '') AS first_trade,
                 ifNull(toString(max(p.last_ts)), '') AS last_trade
@@ -350,8 +1329,8 @@ pub async fn trader_stats(
             LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
             LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
             WHERE lower(p.trader) = ?
-            GROUP BY p.trader",
-        )
+            GROUP BY p.trader"
+        ))
         .bind(&address)
         .fetch_optional::<TraderSummary>()
         .await
@@ -365,44 +1344,44 @@ pub async fn trader_stats(
 
 pub async fn trader_trades(
     State(state): State<AppState>,
-    Path(address): Path<String>,
+    ValidatedAddress(address): ValidatedAddress,
     Query(params): Query<TradesParams>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let address = address.to_lowercase();
     let limit = params.limit.unwrap_or(50).min(200);
     let offset = params.offset.unwrap_or(0);
-    let side_filter = params.side.as_deref().unwrap_or("");
-
-    if !side_filter.is_empty() && side_filter != "buy" && side_filter != "sell" {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Invalid side filter. Allowed: buy, sell".into(),
-        ));
-    }
+    let filters = TradeFilters::parse(&params)?;
+    let filter_sql = filters.where_sql();
+    // Default is block order; `sort=usdc_amount` pulls the largest fills first
+    let order_by = match params.sort.as_deref().unwrap_or("") {
+        "" => "block_number DESC, log_index DESC",
+        "usdc_amount" => "usdc_amount DESC, block_number DESC, log_index DESC",
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Invalid sort. Allowed: usdc_amount".into(),
+            ));
+        }
+    };
 
-    let mut trades = state
-        .db
-        .query(
-            "SELECT
-                toString(tx_hash) AS tx_hash,
-                block_number,
-                ifNull(toString(block_timestamp), '') AS block_timestamp,
-                exchange,
-                side,
-                asset_id,
-                toString(amount) AS amount,
-                toString(price) AS price,
-                toString(usdc_amount) AS usdc_amount,
-                toString(fee) AS fee
-            FROM poly_dearboard.trades
-            WHERE lower(trader) = ?
-              AND (side = ? OR ? = '')
-            ORDER BY block_number DESC, log_index DESC
-            LIMIT ? OFFSET ?",
-        )
-        .bind(&address)
-        .bind(side_filter)
-        .bind(side_filter)
+    let query = format!(
+        "SELECT
+            toString(tx_hash) AS tx_hash,
+            block_number,
+            ifNull(toString(block_timestamp), '') AS block_timestamp,
+            exchange,
+            side,
+            asset_id,
+            toString(amount) AS amount,
+            toString(price) AS price,
+            toString(usdc_amount) AS usdc_amount,
+            toString(fee) AS fee
+        FROM poly_dearboard.trades
+        WHERE lower(trader) = ?{filter_sql}
+        ORDER BY {order_by}
+        LIMIT ? OFFSET ?"
+    );
+    let mut trades = filters
+        .bind(state.db.query(&query).bind(&address))
         .bind(limit)
         .bind(offset)
         .fetch_all::<TradeRecord>()
@@ -412,8 +1391,14 @@ pub async fn trader_trades(
     // Replace ClickHouse asset_ids with full-precision Gamma token IDs (or integer fallback)
     {
         let token_ids: Vec<String> = trades.iter().map(|t| t.asset_id.clone()).collect();
-        let market_info =
-            markets::resolve_markets(&state.http, &state.db, &state.market_cache, &token_ids).await;
+        let market_info = markets::resolve_markets(
+            &state.http,
+            &state.gamma,
+            &state.db,
+            &state.market_cache,
+            &token_ids,
+        )
+        .await;
         for trade in &mut trades {
             trade.asset_id = market_info
                 .get(&trade.asset_id)
@@ -422,14 +1407,10 @@ pub async fn trader_trades(
         }
     }
 
-    let total: u64 = state
-        .db
-        .query(
-            "SELECT count() FROM poly_dearboard.trades WHERE lower(trader) = ? AND (side = ? OR ? = '')",
-        )
-        .bind(&address)
-        .bind(side_filter)
-        .bind(side_filter)
+    let count_query =
+        format!("SELECT count() FROM poly_dearboard.trades WHERE lower(trader) = ?{filter_sql}");
+    let total: u64 = filters
+        .bind(state.db.query(&count_query).bind(&address))
         .fetch_one()
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -442,45 +1423,259 @@ pub async fn trader_trades(
     }))
 }
 
-pub async fn hot_markets(
-    State(state): State<AppState>,
-    Query(params): Query<HotMarketsParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let limit = params.limit.unwrap_or(20).min(100);
-    let period = params.period.as_deref().unwrap_or("24h");
+/// Validated `trader_trades` filters, shared by the rows and count queries so
+/// `total` always matches the filtered rows.
+struct TradeFilters {
+    side: Option<String>,
+    /// Token ids (full-precision or legacy scientific notation), validated numeric
+    asset_ids: Vec<String>,
+    min_usdc: Option<f64>,
+    /// Unix seconds, inclusive
+    from: Option<i64>,
+    /// Unix seconds, exclusive
+    to: Option<i64>,
+}
 
-    // Fetch extra rows since Yes/No tokens will be merged into one event
-    let fetch_limit = limit * 3;
+impl TradeFilters {
+    fn parse(params: &TradesParams) -> Result<Self, (StatusCode, String)> {
+        let side = params.side.as_deref().filter(|s| !s.is_empty());
+        if side.is_some_and(|s| s != "buy" && s != "sell") {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Invalid side filter. Allowed: buy, sell".into(),
+            ));
+        }
 
-    let rows = if period == "7d" {
-        // Beyond 3-day TTL: read from pre-aggregated asset_stats_daily
-        state
-            .db
-            .query(
-                "SELECT
-                    asset_id,
-                    toString(sum(volume)) AS volume,
-                    sum(trade_count) AS trade_count,
-                    uniqExactMerge(unique_traders) AS unique_traders,
-                    toString(argMaxMerge(last_price_state)) AS last_price,
-                    ifNull(toString(max(last_trade)), '') AS last_trade
-                FROM poly_dearboard.asset_stats_daily AS asd
-                WHERE day >= today() - 7
-                GROUP BY asset_id
-                ORDER BY sum(asd.volume) DESC
-                LIMIT ?",
-            )
-            .bind(fetch_limit)
-            .fetch_all::<MarketStatsRow>()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        // Comma-separated so both outcomes of a market can be fetched together
+        let asset_ids: Vec<String> = params
+            .asset_id
+            .as_deref()
+            .map(|s| {
+                s.split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Validate token IDs to prevent SQL injection (must be numeric, possibly scientific notation)
+        for id in &asset_ids {
+            if !id
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+            {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Invalid asset_id format".to_string(),
+                ));
+            }
+        }
+
+        if params.min_usdc.is_some_and(|m| !m.is_finite() || m < 0.0) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Invalid min_usdc: must be a non-negative number".into(),
+            ));
+        }
+
+        let from = parse_time_bound("from", params.from.as_deref())?;
+        let to = parse_time_bound("to", params.to.as_deref())?;
+        if let (Some(f), Some(t)) = (from, to) {
+            if f >= t {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Invalid range: `from` must be earlier than `to`".into(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            side: side.map(str::to_string),
+            asset_ids,
+            min_usdc: params.min_usdc,
+            from,
+            to,
+        })
+    }
+
+    /// ` AND ...` predicates with `?` placeholders, in `bind` order.
+    fn where_sql(&self) -> String {
+        let mut sql = String::new();
+        if self.side.is_some() {
+            sql.push_str(" AND side = ?");
+        }
+        if !self.asset_ids.is_empty() {
+            // Exact match for full-precision ids; legacy rows stored in scientific
+            // notation are matched on the market cache key (first 15 significant digits)
+            let exact = self
+                .asset_ids
+                .iter()
+                .map(|id| format!("'{id}'"))
+                .collect::<Vec<_>>()
+                .join(",");
+            let prefixes = self
+                .asset_ids
+                .iter()
+                .map(|id| format!("'{}'", markets::cache_key(id)))
+                .collect::<Vec<_>>()
+                .join(",");
+            sql.push_str(&format!(
+                " AND (asset_id IN ({exact}) OR (positionCaseInsensitive(asset_id, 'e') > 0 \
+                 AND substring(replaceAll(splitByChar('e', lower(asset_id))[1], '.', ''), 1, 15) IN ({prefixes})))"
+            ));
+        }
+        if self.min_usdc.is_some() {
+            sql.push_str(" AND toFloat64(usdc_amount) >= ?");
+        }
+        if self.from.is_some() {
+            sql.push_str(" AND block_timestamp >= toDateTime(?)");
+        }
+        if self.to.is_some() {
+            sql.push_str(" AND block_timestamp < toDateTime(?)");
+        }
+        sql
+    }
+
+    fn bind(&self, mut query: clickhouse::query::Query) -> clickhouse::query::Query {
+        if let Some(side) = &self.side {
+            query = query.bind(side);
+        }
+        if let Some(min_usdc) = self.min_usdc {
+            query = query.bind(min_usdc);
+        }
+        if let Some(from) = self.from {
+            query = query.bind(from);
+        }
+        if let Some(to) = self.to {
+            query = query.bind(to);
+        }
+        query
+    }
+}
+
+/// Parses a `from`/`to` bound: unix seconds, an RFC 3339 timestamp, or a
+/// `YYYY-MM-DD` date (midnight UTC).
+fn parse_time_bound(name: &str, value: Option<&str>) -> Result<Option<i64>, (StatusCode, String)> {
+    let Some(raw) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    let parsed = if raw.chars().all(|c| c.is_ascii_digit()) {
+        raw.parse::<i64>().ok()
+    } else if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(raw) {
+        Some(dt.timestamp())
     } else {
-        // Within 3-day TTL: read from raw trades
-        let interval = match period {
-            "1h" => "1 HOUR",
-            _ => "24 HOUR",
+        chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|dt| dt.and_utc().timestamp())
+    };
+    parsed.map(Some).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid `{name}`: expected unix seconds, RFC 3339 timestamp, or YYYY-MM-DD"),
+        )
+    })
+}
+
+const HOT_MARKETS_PERIODS: &[&str] = &["1h", "24h", "7d", "30d"];
+const HOT_MARKETS_MAX_DAYS: u32 = 90;
+const HOT_MARKETS_SORTS: &[&str] = &["volume", "trade_count", "unique_traders"];
+/// Rows fetched per requested market: Yes/No tokens merge into one event
+const HOT_MARKETS_FETCH_FACTOR: u32 = 3;
+
+pub async fn hot_markets(
+    State(state): State<AppState>,
+    Query(params): Query<HotMarketsParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let limit = params.limit.unwrap_or(20).min(100);
+    // `days=N` takes precedence over `period`; day windows read asset_stats_daily
+    let (period, window_days) = match (params.days, params.period.as_deref().unwrap_or("24h")) {
+        (Some(days), _) if (1..=HOT_MARKETS_MAX_DAYS).contains(&days) => {
+            (format!("{days}d"), Some(days))
+        }
+        (Some(_), _) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid days: must be between 1 and {HOT_MARKETS_MAX_DAYS}"),
+            ));
+        }
+        (None, period @ ("1h" | "24h")) => (period.to_string(), None),
+        (None, "7d") => ("7d".to_string(), Some(7)),
+        (None, "30d") => ("30d".to_string(), Some(30)),
+        (None, _) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid period. Allowed: {HOT_MARKETS_PERIODS:?}"),
+            ));
+        }
+    };
+    let sort = params.sort.as_deref().unwrap_or("volume");
+    if !HOT_MARKETS_SORTS.contains(&sort) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid sort. Allowed: {HOT_MARKETS_SORTS:?}"),
+        ));
+    }
+    if params.min_volume.is_some_and(|m| !m.is_finite() || m < 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid min_volume: must be a non-negative number".into(),
+        ));
+    }
+    let category = params
+        .category
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+
+    // Fetch extra rows since Yes/No tokens will be merged into one event
+    let fetch_limit = limit * HOT_MARKETS_FETCH_FACTOR;
+    // Filter by category in SQL via the persisted market_metadata dimension
+    let category_filter = category
+        .map(|c| {
+            format!(
+                " AND asset_id IN (SELECT asset_id FROM poly_dearboard.market_metadata FINAL \
+                 WHERE lower(category) = '{}')",
+                c.to_lowercase().replace('\\', "\\\\").replace('\'', "\\'")
+            )
+        })
+        .unwrap_or_default();
+
+    let mut rows = if let Some(days) = window_days {
+        // Beyond 3-day TTL: read from pre-aggregated asset_stats_daily
+        let order_by = match sort {
+            "trade_count" => "sum(asd.trade_count)",
+            "unique_traders" => "uniqExactMerge(asd.unique_traders)",
+            _ => "sum(asd.volume)",
         };
+        state
+            .db
+            .query(&format!(
+                "SELECT
+                    asset_id,
+                    toString(sum(volume)) AS volume,
+                    sum(trade_count) AS trade_count,
+                    uniqExactMerge(unique_traders) AS unique_traders,
+                    toString(argMaxMerge(last_price_state)) AS last_price,
+                    ifNull(toString(max(last_trade)), '') AS last_trade
+                FROM poly_dearboard.asset_stats_daily AS asd
+                WHERE day >= today() - {days}{category_filter}
+                GROUP BY asset_id
+                ORDER BY {order_by} DESC
+                LIMIT ?"
+            ))
+            .bind(fetch_limit)
+            .fetch_all::<MarketStatsRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    } else {
+        // Within 3-day TTL: read from raw trades
+        let interval = if period == "1h" { "1 HOUR" } else { "24 HOUR" };
         let exclude = exclude_clause();
+        let order_by = match sort {
+            "trade_count" => "count()",
+            "unique_traders" => "uniqExact(trader)",
+            _ => "sum(usdc_amount)",
+        };
 
         let query = format!(
             "SELECT
@@ -492,9 +1687,9 @@ pub async fn hot_markets(
                 ifNull(toString(max(block_timestamp)), '') AS last_trade
             FROM poly_dearboard.trades
             PREWHERE block_timestamp >= now() - INTERVAL {interval}
-            WHERE trader NOT IN ({exclude})
+            WHERE trader NOT IN ({exclude}){category_filter}
             GROUP BY asset_id
-            ORDER BY sum(usdc_amount) DESC
+            ORDER BY {order_by} DESC
             LIMIT ?"
         );
 
@@ -508,22 +1703,335 @@ pub async fn hot_markets(
     };
 
     let token_ids: Vec<String> = rows.iter().map(|r| r.asset_id.clone()).collect();
-    let market_info =
-        markets::resolve_markets(&state.http, &state.db, &state.market_cache, &token_ids).await;
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &token_ids,
+    )
+    .await;
+    let prices_24h = fetch_prices_24h_ago(&state, &token_ids).await;
+
+    // The in-memory cache stays authoritative if the persisted category is stale
+    if let Some(category) = category {
+        rows.retain(|r| {
+            market_info
+                .get(&r.asset_id)
+                .is_some_and(|i| i.category.eq_ignore_ascii_case(category))
+        });
+    }
+    let merged = merge_market_rows(rows, &market_info, &prices_24h);
+
+    let mut markets: Vec<HotMarket> = merged.into_values().collect();
+    if let Some(min_volume) = params.min_volume {
+        markets.retain(|m| m.volume.parse::<f64>().unwrap_or(0.0) >= min_volume);
+    }
+    // Counts are summed across merged tokens, so sort after the merge
+    match sort {
+        "trade_count" => markets.sort_by_key(|m| std::cmp::Reverse(m.trade_count)),
+        "unique_traders" => markets.sort_by_key(|m| std::cmp::Reverse(m.unique_traders)),
+        _ => markets.sort_by(|a, b| {
+            let va: f64 = a.volume.parse().unwrap_or(0.0);
+            let vb: f64 = b.volume.parse().unwrap_or(0.0);
+            vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
+        }),
+    }
+    markets.truncate(limit as usize);
+
+    if params.include_sparkline.unwrap_or(false) {
+        // Only the returned events' tokens; Yes/No buckets are summed per event
+        let returned: std::collections::HashSet<&str> =
+            markets.iter().map(|m| m.question.as_str()).collect();
+        let sparkline_ids: Vec<String> = token_ids
+            .iter()
+            .filter(|id| returned.contains(event_key(id, &market_info).as_str()))
+            .cloned()
+            .collect();
+        let by_asset = fetch_sparklines(&state, &sparkline_ids, window_days.is_some()).await;
+        let mut by_event: std::collections::HashMap<String, Vec<f64>> =
+            std::collections::HashMap::new();
+        for (asset_id, volumes) in by_asset {
+            let sums = by_event
+                .entry(event_key(&asset_id, &market_info))
+                .or_insert_with(|| vec![0.0; volumes.len()]);
+            for (sum, v) in sums.iter_mut().zip(volumes) {
+                *sum += v;
+            }
+        }
+        let empty = if window_days.is_some() {
+            HOT_MARKETS_SPARKLINE_DAYS
+        } else {
+            HOT_MARKETS_SPARKLINE_HOURS
+        };
+        for m in &mut markets {
+            let volumes = by_event
+                .remove(&m.question)
+                .unwrap_or_else(|| vec![0.0; empty as usize]);
+            m.sparkline = Some(volumes.iter().map(|v| format!("{v:.2}")).collect());
+        }
+    }
+
+    Ok(Json(HotMarketsResponse { period, markets }))
+}
+
+const HOT_MARKETS_SPARKLINE_HOURS: i64 = 24;
+const HOT_MARKETS_SPARKLINE_DAYS: i64 = 7;
+
+/// Per-asset volume sparklines, oldest bucket first and zero-filled: the last 24
+/// hours (`toStartOfHour` over `trades`) or, when `daily`, the last 7 days
+/// (`asset_stats_daily`). One grouped query for all assets.
+async fn fetch_sparklines(
+    state: &AppState,
+    asset_ids: &[String],
+    daily: bool,
+) -> std::collections::HashMap<String, Vec<f64>> {
+    use chrono::DurationRound;
+    if asset_ids.is_empty() {
+        return std::collections::HashMap::new();
+    }
+    let now = chrono::Utc::now().naive_utc();
+    let buckets: Vec<String> = if daily {
+        (0..HOT_MARKETS_SPARKLINE_DAYS)
+            .rev()
+            .map(|d| {
+                (now.date() - chrono::Duration::days(d))
+                    .format("%Y-%m-%d")
+                    .to_string()
+            })
+            .collect()
+    } else {
+        let hour = now
+            .duration_trunc(chrono::Duration::hours(1))
+            .unwrap_or(now);
+        (0..HOT_MARKETS_SPARKLINE_HOURS)
+            .rev()
+            .map(|h| {
+                (hour - chrono::Duration::hours(h))
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .collect()
+    };
+    let index: std::collections::HashMap<&str, usize> = buckets
+        .iter()
+        .enumerate()
+        .map(|(i, b)| (b.as_str(), i))
+        .collect();
+
+    let id_list = asset_ids
+        .iter()
+        .map(|id| format!("'{}'", id.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",");
+    let query = if daily {
+        format!(
+            "SELECT
+                asset_id,
+                toString(day) AS bucket,
+                toString(sum(volume)) AS volume
+            FROM poly_dearboard.asset_stats_daily
+            WHERE day >= today() - {}
+              AND asset_id IN ({id_list})
+            GROUP BY asset_id, day",
+            HOT_MARKETS_SPARKLINE_DAYS - 1
+        )
+    } else {
+        let exclude = exclude_clause();
+        format!(
+            "SELECT
+                asset_id,
+                toString(toStartOfHour(block_timestamp)) AS bucket,
+                toString(sum(usdc_amount)) AS volume
+            FROM poly_dearboard.trades
+            PREWHERE block_timestamp >= toStartOfHour(now()) - INTERVAL {} HOUR
+            WHERE asset_id IN ({id_list})
+              AND trader NOT IN ({exclude})
+            GROUP BY asset_id, bucket",
+            HOT_MARKETS_SPARKLINE_HOURS - 1
+        )
+    };
+    let rows = state
+        .db
+        .query(&query)
+        .fetch_all::<SparklineRow>()
+        .await
+        .unwrap_or_default();
+
+    let mut sparklines: std::collections::HashMap<String, Vec<f64>> = asset_ids
+        .iter()
+        .map(|id| (id.clone(), vec![0.0; buckets.len()]))
+        .collect();
+    for r in rows {
+        if let (Some(&i), Some(volumes)) = (
+            index.get(r.bucket.as_str()),
+            sparklines.get_mut(&r.asset_id),
+        ) {
+            volumes[i] += r.volume.parse::<f64>().unwrap_or(0.0);
+        }
+    }
+    sparklines
+}
+
+const TRENDING_DEFAULT_DAYS: u32 = 3;
+const TRENDING_MAX_DAYS: u32 = 30;
+/// Default event volume floor (USDC); thinner new markets are mostly noise
+const TRENDING_MIN_VOLUME: f64 = 1000.0;
+const TRENDING_FETCH_FACTOR: u32 = 5;
+
+/// Markets first traded within the last `days` days (today included), ranked by
+/// volume growth from the first to the second half of that window.
+pub async fn trending_markets(
+    State(state): State<AppState>,
+    Query(params): Query<TrendingMarketsParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let days = params.days.unwrap_or(TRENDING_DEFAULT_DAYS);
+    if !(1..=TRENDING_MAX_DAYS).contains(&days) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid days: must be between 1 and {TRENDING_MAX_DAYS}"),
+        ));
+    }
+    let limit = params.limit.unwrap_or(20).min(100);
+    let min_volume = params.min_volume.unwrap_or(TRENDING_MIN_VOLUME);
+    if !min_volume.is_finite() || min_volume < 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid min_volume: must be a non-negative number".into(),
+        ));
+    }
+
+    // Day offsets from today: window start, and the start of the second half
+    // (which gets the extra day when `days` is odd)
+    let start_offset = days - 1;
+    let mid_offset = start_offset - days / 2;
+    let rows = state
+        .db
+        .query(&format!(
+            "SELECT
+                asset_id,
+                toString(sum(volume)) AS volume,
+                sum(trade_count) AS trade_count,
+                uniqExactMerge(unique_traders) AS unique_traders,
+                toString(argMaxMerge(last_price_state)) AS last_price,
+                ifNull(toString(max(last_trade)), '') AS last_trade,
+                toString(min(day)) AS first_seen,
+                toString(sumIf(volume, day < today() - {mid_offset})) AS first_half_volume,
+                toString(sumIf(volume, day >= today() - {mid_offset})) AS second_half_volume
+            FROM poly_dearboard.asset_stats_daily AS asd
+            WHERE asset_id IN (
+                SELECT asset_id FROM poly_dearboard.asset_stats_daily
+                WHERE day >= today() - {start_offset}
+            )
+            GROUP BY asset_id
+            HAVING min(day) >= today() - {start_offset}
+            ORDER BY sum(asd.volume) DESC
+            LIMIT ?"
+        ))
+        .bind(limit * TRENDING_FETCH_FACTOR)
+        .fetch_all::<TrendingMarketRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let token_ids: Vec<String> = rows.iter().map(|r| r.asset_id.clone()).collect();
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &token_ids,
+    )
+    .await;
+    let prices_24h = fetch_prices_24h_ago(&state, &token_ids).await;
+
+    // Per-event (first_seen, first-half volume, second-half volume), merged like the stats
+    let mut windows: std::collections::HashMap<String, (String, f64, f64)> =
+        std::collections::HashMap::new();
+    let mut stats = Vec::with_capacity(rows.len());
+    for r in rows {
+        let window = windows
+            .entry(event_key(&r.asset_id, &market_info))
+            .or_insert_with(|| (r.first_seen.clone(), 0.0, 0.0));
+        if r.first_seen < window.0 {
+            window.0.clone_from(&r.first_seen);
+        }
+        window.1 += r.first_half_volume.parse::<f64>().unwrap_or(0.0);
+        window.2 += r.second_half_volume.parse::<f64>().unwrap_or(0.0);
+        stats.push(MarketStatsRow {
+            asset_id: r.asset_id,
+            volume: r.volume,
+            trade_count: r.trade_count,
+            unique_traders: r.unique_traders,
+            last_price: r.last_price,
+            last_trade: r.last_trade,
+        });
+    }
+
+    let mut markets: Vec<TrendingMarket> = merge_market_rows(stats, &market_info, &prices_24h)
+        .into_iter()
+        .filter(|(_, m)| m.volume.parse::<f64>().unwrap_or(0.0) >= min_volume)
+        .filter_map(|(key, market)| {
+            let (first_seen, first_half, second_half) = windows.remove(&key)?;
+            Some(TrendingMarket {
+                market,
+                first_seen,
+                volume_growth_pct: (first_half > 0.0)
+                    .then(|| ((second_half - first_half) / first_half * 1000.0).round() / 10.0),
+            })
+        })
+        .collect();
+    // Markets that only started in the second half have no growth figure; they
+    // rank after the rest, by volume
+    markets.sort_by(|a, b| match (a.volume_growth_pct, b.volume_growth_pct) {
+        (Some(ga), Some(gb)) => gb.partial_cmp(&ga).unwrap_or(std::cmp::Ordering::Equal),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => {
+            let va: f64 = a.market.volume.parse().unwrap_or(0.0);
+            let vb: f64 = b.market.volume.parse().unwrap_or(0.0);
+            vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
+        }
+    });
+    markets.truncate(limit as usize);
+
+    Ok(Json(TrendingMarketsResponse { days, markets }))
+}
+
+/// Event a token belongs to for Yes/No merging: its market question, or a
+/// shortened id when unresolved.
+fn event_key(
+    asset_id: &str,
+    market_info: &std::collections::HashMap<String, markets::MarketInfo>,
+) -> String {
+    market_info
+        .get(asset_id)
+        .map(|i| i.question.clone())
+        .unwrap_or_else(|| shorten_id(asset_id))
+}
 
-    // Merge tokens belonging to the same event (Yes/No → one row)
+/// Merges per-token stats into one `HotMarket` per event (Yes/No → one row),
+/// keyed by `event_key`.
+fn merge_market_rows(
+    rows: Vec<MarketStatsRow>,
+    market_info: &std::collections::HashMap<String, markets::MarketInfo>,
+    prices_24h: &std::collections::HashMap<String, f64>,
+) -> std::collections::HashMap<String, HotMarket> {
     let mut merged: std::collections::HashMap<String, HotMarket> = std::collections::HashMap::new();
 
     for r in rows {
         let info = market_info.get(&r.asset_id);
-        let question = info
-            .map(|i| i.question.clone())
-            .unwrap_or_else(|| shorten_id(&r.asset_id));
+        let question = event_key(&r.asset_id, market_info);
         // Prefer full-precision Gamma token ID; fall back to integer form (never scientific notation)
         let display_id = info
             .map(|i| i.gamma_token_id.clone())
             .unwrap_or_else(|| markets::to_integer_id(&r.asset_id));
         let vol: f64 = r.volume.parse().unwrap_or(0.0);
+        let price_change_24h = prices_24h.get(&r.asset_id).and_then(|then| {
+            r.last_price
+                .parse::<f64>()
+                .ok()
+                .map(|now| format!("{:.6}", now - then))
+        });
 
         if let Some(existing) = merged.get_mut(&question) {
             // Merge into existing event
@@ -539,6 +2047,7 @@ pub async fn hot_markets(
             // Keep the higher-volume token as the representative token_id
             if vol > existing_vol {
                 existing.token_id = display_id;
+                existing.price_change_24h = price_change_24h;
             }
         } else {
             merged.insert(
@@ -554,27 +2063,107 @@ pub async fn hot_markets(
                     unique_traders: r.unique_traders,
                     last_price: r.last_price,
                     last_trade: r.last_trade,
+                    price_change_24h,
+                    sparkline: None,
                 },
             );
         }
     }
 
-    let mut markets: Vec<HotMarket> = merged.into_values().collect();
-    markets.sort_by(|a, b| {
-        let va: f64 = a.volume.parse().unwrap_or(0.0);
-        let vb: f64 = b.volume.parse().unwrap_or(0.0);
-        vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
-    });
-    markets.truncate(limit as usize);
+    merged
+}
+
+/// Last traded price per asset as of 24 hours ago. Assets with no trade before
+/// then (within the raw trade TTL) are absent.
+async fn fetch_prices_24h_ago(
+    state: &AppState,
+    asset_ids: &[String],
+) -> std::collections::HashMap<String, f64> {
+    if asset_ids.is_empty() {
+        return std::collections::HashMap::new();
+    }
+    let exclude = exclude_clause();
+    let id_list = asset_ids
+        .iter()
+        .map(|id| format!("'{}'", id.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",");
+    state
+        .db
+        .query(&format!(
+            "SELECT
+                asset_id,
+                toString(argMax(price, block_number * 1000000 + log_index)) AS price
+            FROM poly_dearboard.trades
+            PREWHERE block_timestamp <= now() - INTERVAL 24 HOUR
+              AND block_timestamp > toDateTime('1970-01-01 00:00:00')
+            WHERE asset_id IN ({id_list})
+              AND trader NOT IN ({exclude})
+            GROUP BY asset_id"
+        ))
+        .fetch_all::<AssetPriceRow>()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|r| r.price.parse::<f64>().ok().map(|p| (r.asset_id, p)))
+        .collect()
+}
+
+/// Over-fetch factor for `recent_trades` when filtering by category in Rust
+const LIVE_FEED_CATEGORY_FETCH_FACTOR: u32 = 5;
+
+/// Page cap for `recent_trades` polls with a `cursor`
+const LIVE_FEED_CURSOR_MAX_PAGE: u32 = 500;
 
-    Ok(Json(HotMarketsResponse { markets }))
+/// Opaque polling cursor for the live feed: the `(block_number, log_index)` of
+/// the newest trade the client has seen.
+struct FeedCursor {
+    block_number: u64,
+    log_index: u64,
+}
+
+impl FeedCursor {
+    fn for_row(r: &RecentTradeRow) -> String {
+        use base64::Engine;
+
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(format!("{}|{}", r.block_number, r.log_index))
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        use base64::Engine;
+
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()?;
+        let raw = String::from_utf8(raw).ok()?;
+        let (block_number, log_index) = raw.split_once('|')?;
+        Some(Self {
+            block_number: block_number.parse().ok()?,
+            log_index: log_index.parse().ok()?,
+        })
+    }
 }
 
 pub async fn recent_trades(
     State(state): State<AppState>,
     Query(params): Query<LiveFeedParams>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let limit = params.limit.unwrap_or(50).min(200);
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(|c| {
+            FeedCursor::decode(c).ok_or((StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))
+        })
+        .transpose()?;
+    let limit = if cursor.is_some() {
+        params
+            .limit
+            .unwrap_or(LIVE_FEED_CURSOR_MAX_PAGE)
+            .min(LIVE_FEED_CURSOR_MAX_PAGE)
+    } else {
+        params.limit.unwrap_or(50).min(200)
+    };
     let exclude = exclude_clause();
 
     // Support comma-separated token IDs for multi-outcome markets (Yes + No)
@@ -606,29 +2195,270 @@ pub async fn recent_trades(
     // Pass through as-is for exact matching.
     let token_ids: Vec<String> = token_ids.into_iter().map(String::from).collect();
 
-    let query = if token_ids.is_empty() {
-        format!(
-            "SELECT
-                toString(tx_hash) AS tx_hash,
-                ifNull(toString(block_timestamp), '') AS block_timestamp,
-                toString(trader) AS trader,
-                side,
-                asset_id,
-                toString(amount) AS amount,
-                toString(price) AS price,
-                toString(usdc_amount) AS usdc_amount
-            FROM poly_dearboard.trades
-            WHERE trader NOT IN ({exclude})
-            ORDER BY block_number DESC, log_index DESC
-            LIMIT ?"
-        )
-    } else {
+    let trader = params
+        .trader
+        .as_deref()
+        .map(|t| {
+            middleware::validate_eth_address(t.trim()).map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    "Invalid trader address".to_string(),
+                )
+            })
+        })
+        .transpose()?;
+    if params.min_usdc.is_some_and(|m| !m.is_finite() || m < 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid min_usdc: must be a non-negative number".into(),
+        ));
+    }
+    let side = params.side.as_deref().map(str::to_lowercase);
+    if side.as_deref().is_some_and(|s| s != "buy" && s != "sell") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid side: must be buy or sell".into(),
+        ));
+    }
+    let category = params
+        .category
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+
+    let mut conditions = vec![format!("trader NOT IN ({exclude})")];
+    if !token_ids.is_empty() {
         let in_list = token_ids
             .iter()
             .map(|id| format!("'{}'", id.replace('\'', "''")))
             .collect::<Vec<_>>()
             .join(",");
-        format!(
+        conditions.push(format!("asset_id IN ({in_list})"));
+    }
+    // Bound in this order below
+    if trader.is_some() {
+        conditions.push("lower(trader) = ?".into());
+    }
+    if params.min_usdc.is_some() {
+        conditions.push("toFloat64(usdc_amount) >= ?".into());
+    }
+    if side.is_some() {
+        conditions.push("side = ?".into());
+    }
+    // Parsed integers — safe to inline
+    if let Some(c) = &cursor {
+        conditions.push(format!(
+            "(block_number, log_index) > ({}, {})",
+            c.block_number, c.log_index
+        ));
+    }
+    let where_clause = conditions.join(" AND ");
+    // Polls walk forward from the cursor oldest-first so a capped page never
+    // skips trades; rows are flipped back to newest-first before returning
+    let order = if cursor.is_some() { "ASC" } else { "DESC" };
+
+    let query = format!(
+        "SELECT
+            toString(tx_hash) AS tx_hash,
+            ifNull(toString(block_timestamp), '') AS block_timestamp,
+            toString(trader) AS trader,
+            side,
+            asset_id,
+            toString(amount) AS amount,
+            toString(price) AS price,
+            toString(usdc_amount) AS usdc_amount,
+            block_number,
+            log_index
+        FROM poly_dearboard.trades
+        WHERE {where_clause}
+        ORDER BY block_number {order}, log_index {order}
+        LIMIT ?"
+    );
+
+    // Category is only known after market resolution, so over-fetch and filter
+    let fetch_limit = if category.is_some() {
+        limit * LIVE_FEED_CATEGORY_FETCH_FACTOR
+    } else {
+        limit
+    };
+    let bind_filters = |mut q: clickhouse::query::Query| {
+        if let Some(trader) = &trader {
+            q = q.bind(trader);
+        }
+        if let Some(min_usdc) = params.min_usdc {
+            q = q.bind(min_usdc);
+        }
+        if let Some(side) = &side {
+            q = q.bind(side);
+        }
+        q
+    };
+    let mut rows = bind_filters(state.db.query(&query))
+        .bind(fetch_limit)
+        .fetch_all::<RecentTradeRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // Trades after the cursor, before the category filter and page cap
+    let total_new = if cursor.is_some() {
+        Some(
+            bind_filters(state.db.query(&format!(
+                "SELECT count() FROM poly_dearboard.trades WHERE {where_clause}"
+            )))
+            .fetch_one::<u64>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        )
+    } else {
+        None
+    };
+
+    let token_ids: Vec<String> = rows
+        .iter()
+        .map(|r| r.asset_id.clone())
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &token_ids,
+    )
+    .await;
+
+    let next_cursor = if let Some(category) = category {
+        let matches = |r: &RecentTradeRow| {
+            market_info
+                .get(&r.asset_id)
+                .is_some_and(|i| i.category.eq_ignore_ascii_case(category))
+        };
+        // Consume rows only up to the one that fills the page, so a cursor
+        // taken from the last consumed row doesn't skip unseen matches
+        let mut kept = 0;
+        if let Some(end) = rows.iter().position(|r| {
+            kept += usize::from(matches(r));
+            kept == limit as usize
+        }) {
+            rows.truncate(end + 1);
+        }
+        let next_cursor = next_feed_cursor(&rows, &params.cursor, cursor.is_some());
+        rows.retain(matches);
+        next_cursor
+    } else {
+        next_feed_cursor(&rows, &params.cursor, cursor.is_some())
+    };
+
+    Ok(Json(feed_response(
+        rows,
+        &market_info,
+        cursor.is_some(),
+        next_cursor,
+        total_new,
+        LiveFeedFilters {
+            token_id: params.token_id,
+            trader,
+            min_usdc: params.min_usdc,
+            side,
+            category: category.map(String::from),
+        },
+    )))
+}
+
+/// Cursor for the next poll: newest consumed row, or the client's cursor when
+/// nothing new came in. `rows` are oldest-first when `polling`.
+fn next_feed_cursor(
+    rows: &[RecentTradeRow],
+    current: &Option<String>,
+    polling: bool,
+) -> Option<String> {
+    let newest = if polling { rows.last() } else { rows.first() };
+    newest.map(FeedCursor::for_row).or_else(|| current.clone())
+}
+
+fn feed_response(
+    mut rows: Vec<RecentTradeRow>,
+    market_info: &std::collections::HashMap<String, markets::MarketInfo>,
+    polling: bool,
+    next_cursor: Option<String>,
+    total_new: Option<u64>,
+    filters: LiveFeedFilters,
+) -> LiveFeedResponse {
+    if polling {
+        rows.reverse();
+    }
+    let trades = rows
+        .into_iter()
+        .map(|r| feed_trade(r, market_info))
+        .collect();
+
+    LiveFeedResponse {
+        trades,
+        filters,
+        next_cursor,
+        total_new,
+    }
+}
+
+/// Enrich a trade row with market info for the feed.
+fn feed_trade(
+    r: RecentTradeRow,
+    market_info: &std::collections::HashMap<String, markets::MarketInfo>,
+) -> FeedTrade {
+    let info = market_info.get(&r.asset_id);
+    FeedTrade {
+        question: info
+            .map(|i| i.question.clone())
+            .unwrap_or_else(|| shorten_id(&r.asset_id)),
+        outcome: info.map(|i| i.outcome.clone()).unwrap_or_default(),
+        category: info.map(|i| i.category.clone()).unwrap_or_default(),
+        tx_hash: r.tx_hash,
+        block_timestamp: r.block_timestamp,
+        trader: r.trader,
+        side: r.side,
+        asset_id: info
+            .map(|i| i.gamma_token_id.clone())
+            .unwrap_or_else(|| markets::to_integer_id(&r.asset_id)),
+        amount: r.amount,
+        price: r.price,
+        usdc_amount: r.usdc_amount,
+    }
+}
+
+const WHALE_PERIODS: &[&str] = &["1h", "24h"];
+
+/// Fills of at least `min_usdc` in the period, newest first, with the traders'
+/// behavioral labels.
+pub async fn recent_whales(
+    State(state): State<AppState>,
+    Query(params): Query<WhaleTradesParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let period = params.period.as_deref().unwrap_or("24h");
+    let interval = match period {
+        "1h" => "1 HOUR",
+        "24h" => "24 HOUR",
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid period. Allowed: {WHALE_PERIODS:?}"),
+            ));
+        }
+    };
+    // Defaults to the same bar as `/ws/alerts` whale alerts
+    let min_usdc = params.min_usdc.unwrap_or(state.whale_alerts.default_usdc);
+    if !min_usdc.is_finite() || min_usdc < 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid min_usdc: must be a non-negative number".into(),
+        ));
+    }
+    let limit = params.limit.unwrap_or(50).min(200);
+    let exclude = exclude_clause();
+
+    let rows = state
+        .db
+        .query(&format!(
             "SELECT
                 toString(tx_hash) AS tx_hash,
                 ifNull(toString(block_timestamp), '') AS block_timestamp,
@@ -637,18 +2467,17 @@ pub async fn recent_trades(
                 asset_id,
                 toString(amount) AS amount,
                 toString(price) AS price,
-                toString(usdc_amount) AS usdc_amount
+                toString(usdc_amount) AS usdc_amount,
+                block_number,
+                log_index
             FROM poly_dearboard.trades
-            WHERE trader NOT IN ({exclude})
-              AND asset_id IN ({in_list})
+            PREWHERE block_timestamp >= now() - INTERVAL {interval}
+            WHERE toFloat64(usdc_amount) >= ?
+              AND trader NOT IN ({exclude})
             ORDER BY block_number DESC, log_index DESC
             LIMIT ?"
-        )
-    };
-
-    let rows = state
-        .db
-        .query(&query)
+        ))
+        .bind(min_usdc)
         .bind(limit)
         .fetch_all::<RecentTradeRow>()
         .await
@@ -660,34 +2489,46 @@ pub async fn recent_trades(
         .collect::<std::collections::HashSet<_>>()
         .into_iter()
         .collect();
-    let market_info =
-        markets::resolve_markets(&state.http, &state.db, &state.market_cache, &token_ids).await;
+    let mut addresses: Vec<String> = rows.iter().map(|r| r.trader.to_lowercase()).collect();
+    addresses.sort();
+    addresses.dedup();
+    let (market_info, labels) = tokio::join!(
+        markets::resolve_markets(
+            &state.http,
+            &state.gamma,
+            &state.db,
+            &state.market_cache,
+            &token_ids
+        ),
+        tokio::time::timeout(
+            std::time::Duration::from_secs(2),
+            batch_compute_labels(&state, &addresses),
+        ),
+    );
+    let labels = match labels {
+        Ok((labels, _)) => labels,
+        Err(_) => {
+            tracing::warn!("batch_compute_labels timed out after 2s (whales)");
+            std::collections::HashMap::new()
+        }
+    };
 
     let trades = rows
         .into_iter()
-        .map(|r| {
-            let info = market_info.get(&r.asset_id);
-            FeedTrade {
-                question: info
-                    .map(|i| i.question.clone())
-                    .unwrap_or_else(|| shorten_id(&r.asset_id)),
-                outcome: info.map(|i| i.outcome.clone()).unwrap_or_default(),
-                category: info.map(|i| i.category.clone()).unwrap_or_default(),
-                tx_hash: r.tx_hash,
-                block_timestamp: r.block_timestamp,
-                trader: r.trader,
-                side: r.side,
-                asset_id: info
-                    .map(|i| i.gamma_token_id.clone())
-                    .unwrap_or_else(|| markets::to_integer_id(&r.asset_id)),
-                amount: r.amount,
-                price: r.price,
-                usdc_amount: r.usdc_amount,
-            }
+        .map(|r| WhaleTrade {
+            labels: labels
+                .get(&r.trader.to_lowercase())
+                .cloned()
+                .unwrap_or_default(),
+            trade: feed_trade(r, &market_info),
         })
         .collect();
 
-    Ok(Json(LiveFeedResponse { trades }))
+    Ok(Json(WhaleTradesResponse {
+        period: period.to_string(),
+        min_usdc,
+        trades,
+    }))
 }
 
 pub async fn health(
@@ -711,15 +2552,48 @@ pub async fn health(
         trade_count: stats.trade_count,
         trader_count: stats.trader_count,
         latest_block: stats.latest_block,
+        leaderboard_cache: state.leaderboard_cache.stats().await,
+        webhook: state
+            .webhook_counters
+            .stats(super::alerts::WEBHOOK_QUEUE_CAPACITY - state.webhook_tx.capacity()),
+        live_feed: state.live_feed.stats(),
+        gamma_degraded: state.gamma.is_degraded(),
+        rate_limit: state.rate_limiter.stats(),
     }))
 }
 
-pub async fn trader_positions(
+/// Recovery: rebuild `resolved_prices` from every on-chain resolution in the
+/// background. Requires `ADMIN_API_TOKEN` in the `x-admin-token` header; the
+/// endpoint is disabled when it is unset.
+pub async fn rebuild_resolved_prices(
     State(state): State<AppState>,
-    Path(address): Path<String>,
+    headers: axum::http::HeaderMap,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let address = address.to_lowercase();
+    let expected = std::env::var("ADMIN_API_TOKEN").unwrap_or_default();
+    if expected.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "Admin API disabled".into()));
+    }
+    let provided = headers
+        .get("x-admin-token")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if provided != expected {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid admin token".into()));
+    }
+
+    tokio::spawn(async move {
+        // Waits for any in-flight incremental cycle
+        let mut resolved_prices = state.resolved_prices.lock().await;
+        markets::rebuild_resolved_prices(&state.db, &state.market_cache, &mut resolved_prices)
+            .await;
+    });
+    Ok(StatusCode::ACCEPTED)
+}
 
+pub async fn trader_positions(
+    State(state): State<AppState>,
+    ValidatedAddress(address): ValidatedAddress,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
     let rows = state
         .db
         .query(
@@ -752,8 +2626,14 @@ pub async fn trader_positions(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     let token_ids: Vec<String> = rows.iter().map(|r| r.asset_id.clone()).collect();
-    let market_info =
-        markets::resolve_markets(&state.http, &state.db, &state.market_cache, &token_ids).await;
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &token_ids,
+    )
+    .await;
 
     let mut open = Vec::new();
     let mut closed = Vec::new();
@@ -773,7 +2653,19 @@ pub async fn trader_positions(
             .unwrap_or(false);
         // Trader fully exited (bought then sold everything)
         let user_exited = r.side_summary == "closed";
-        let settled = on_chain_resolved || api_resolved || price_settled || user_exited;
+        // Strongest signal that fired, reported so the UI can explain the classification
+        let resolution_source = if on_chain_resolved {
+            "on_chain"
+        } else if api_resolved {
+            "gamma_inactive"
+        } else if price_settled {
+            "price_settled"
+        } else if user_exited {
+            "user_exited"
+        } else {
+            "open"
+        };
+        let settled = resolution_source != "open";
 
         let info = market_info.get(&r.asset_id);
         let pos = OpenPosition {
@@ -791,6 +2683,8 @@ pub async fn trader_positions(
             pnl: r.pnl,
             volume: r.volume,
             trade_count: r.trade_count,
+            end_date: info.and_then(|i| i.end_date.clone().or_else(|| i.closed_time.clone())),
+            resolution_source,
         };
 
         if settled {
@@ -803,31 +2697,282 @@ pub async fn trader_positions(
     Ok(Json(PositionsResponse { open, closed }))
 }
 
+const PNL_CHART_GRANULARITIES: &[&str] = &["hour", "day", "week"];
+const PNL_CHART_MODES: &[&str] = &["absolute", "percent"];
+const PNL_CHART_TOP_ASSETS: usize = 5;
+const PNL_CHART_MAX_TOP_ASSETS: usize = 20;
+
+/// Days of raw `trades` history kept before TTL eviction (hourly charts need it).
+fn raw_trades_ttl_days() -> u32 {
+    std::env::var("RAW_TRADES_TTL_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3)
+}
+
+/// Serves from `pnl_chart_cache` (keyed on address + every query param) before
+/// recomputing; errors are never cached.
 pub async fn pnl_chart(
     State(state): State<AppState>,
-    Path(address): Path<String>,
+    ValidatedAddress(address): ValidatedAddress,
     Query(params): Query<PnlChartParams>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let address = address.to_lowercase();
+    let cache_key = format!(
+        "{address}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}",
+        params.timeframe,
+        params.granularity,
+        params.breakdown,
+        params.top_assets,
+        params.include_fees,
+        params.from,
+        params.to,
+        params.include_drawdown,
+        params.mode,
+        params.benchmark,
+    );
+    if let Some(cached) = state.pnl_chart_cache.get(&cache_key).await {
+        return Ok(Json(cached));
+    }
+    let response = build_pnl_chart(&state, &address, &params).await?;
+    state
+        .pnl_chart_cache
+        .insert(cache_key, response.clone())
+        .await;
+    Ok(Json(response))
+}
+
+async fn build_pnl_chart(
+    state: &AppState,
+    address: &str,
+    params: &PnlChartParams,
+) -> Result<PnlChartResponse, (StatusCode, String)> {
     let timeframe = params.timeframe.as_deref().unwrap_or("all");
+    let granularity = params
+        .granularity
+        .as_deref()
+        .unwrap_or(if timeframe == "24h" { "hour" } else { "day" });
+    if !PNL_CHART_GRANULARITIES.contains(&granularity) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid granularity. Allowed: {PNL_CHART_GRANULARITIES:?}"),
+        ));
+    }
+
+    let top_assets = match params.breakdown.as_deref() {
+        None | Some("") => None,
+        Some("asset") => Some(
+            params
+                .top_assets
+                .unwrap_or(PNL_CHART_TOP_ASSETS)
+                .clamp(1, PNL_CHART_MAX_TOP_ASSETS),
+        ),
+        Some(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Invalid breakdown. Allowed: [\"asset\"]".into(),
+            ));
+        }
+    };
+
+    let mode = params.mode.as_deref().unwrap_or("absolute");
+    if !PNL_CHART_MODES.contains(&mode) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid mode. Allowed: {PNL_CHART_MODES:?}"),
+        ));
+    }
+    let opts = PnlChartOptions {
+        top_assets,
+        include_drawdown: params.include_drawdown.unwrap_or(false),
+        percent: mode == "percent",
+    };
+    let benchmark = match params.benchmark.as_deref() {
+        None | Some("") => false,
+        Some("top10") => true,
+        Some(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Invalid benchmark. Allowed: [\"top10\"]".into(),
+            ));
+        }
+    };
+
+    // Fees are a cash outflow, so `include_fees` just nets them out of cash flow
+    let include_fees = params.include_fees.unwrap_or(false);
+    let (daily_fee, trade_fee) = if include_fees {
+        (" - sum(fee)", " - sum(toFloat64(fee))")
+    } else {
+        ("", "")
+    };
+
+    // Custom `from`/`to` window takes precedence over `timeframe`
+    let today = chrono::Utc::now().date_naive();
+    let from = parse_chart_day("from", params.from.as_deref(), today)?;
+    let to = parse_chart_day("to", params.to.as_deref(), today)?;
+    if from.is_some() || to.is_some() {
+        if let (Some(f), Some(t)) = (from, to) {
+            if f >= t {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Invalid range: `from` must be earlier than `to`".into(),
+                ));
+            }
+        }
+        if granularity == "hour" {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "granularity=hour is not available with from/to; use day or week".into(),
+            ));
+        }
+        let bucket = if granularity == "week" {
+            "toMonday(day)"
+        } else {
+            "day"
+        };
+
+        let mut asset_state = std::collections::HashMap::new();
+        let mut deployed = 0.0;
+        if let Some(from) = from {
+            let initial = state
+                .db
+                .query(&format!(
+                    "SELECT
+                        asset_id,
+                        toString(sum(buy_amount) - sum(sell_amount)) AS net_tokens,
+                        toString(sum(sell_usdc) - sum(buy_usdc){daily_fee}) AS cash_flow,
+                        toString(argMaxMerge(last_price_state)) AS last_price,
+                        toString(sum(buy_usdc)) AS buy_usdc
+                    FROM poly_dearboard.pnl_daily
+                    WHERE lower(trader) = ?
+                      AND day < toDate(?)
+                    GROUP BY asset_id"
+                ))
+                .bind(address)
+                .bind(from.to_string())
+                .fetch_all::<PnlInitialStateRow>()
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            deployed = apply_initial_state(&mut asset_state, initial);
+        }
+
+        let mut day_where = String::new();
+        if from.is_some() {
+            day_where.push_str(" AND day >= toDate(?)");
+        }
+        if to.is_some() {
+            day_where.push_str(" AND day < toDate(?)");
+        }
+        let mut query = state
+            .db
+            .query(&format!(
+                "SELECT
+                    toString({bucket}) AS date,
+                    asset_id,
+                    toString(sum(buy_amount) - sum(sell_amount)) AS net_token_delta,
+                    toString(sum(sell_usdc) - sum(buy_usdc){daily_fee}) AS cash_flow_delta,
+                    toString(argMaxMerge(last_price_state)) AS last_price,
+                    toString(sum(buy_usdc)) AS buy_usdc_delta
+                FROM poly_dearboard.pnl_daily
+                WHERE lower(trader) = ?{day_where}
+                GROUP BY {bucket}, asset_id
+                ORDER BY {bucket}, asset_id"
+            ))
+            .bind(address);
+        for day in [from, to].into_iter().flatten() {
+            query = query.bind(day.to_string());
+        }
+        let rows = query
+            .fetch_all::<PnlDailyRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        // Historical windows keep the last in-window price: only a window ending
+        // today gets the resolved-price overlay
+        let live = to.is_none_or(|t| t == today);
+        let last_day = to.map(|t| t - chrono::Duration::days(1)).unwrap_or(today);
+        let axis = BucketAxis::new(
+            granularity,
+            from.map(|d| d.and_time(chrono::NaiveTime::MIN)),
+            last_day.and_time(chrono::NaiveTime::MIN),
+        );
+        let mut response =
+            pnl_chart_response(state, rows, asset_state, deployed, &axis, live, &opts).await;
+        if benchmark {
+            let window = DailyWindow {
+                bucket,
+                initial_where: from.map(|f| format!("day < toDate('{f}')")),
+                window_where: [
+                    from.map(|f| format!(" AND day >= toDate('{f}')")),
+                    to.map(|t| format!(" AND day < toDate('{t}')")),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+                fee: daily_fee,
+            };
+            attach_benchmark(state, &mut response, &window, &axis, live).await?;
+        }
+        return Ok(response);
+    }
+
+    let window_hours: Option<u32> = match timeframe {
+        "24h" => Some(24),
+        "7d" => Some(7 * 24),
+        "30d" => Some(30 * 24),
+        _ => None, // "all"
+    };
+    let ttl_days = raw_trades_ttl_days();
+    match (granularity, window_hours) {
+        ("hour", None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "granularity=hour requires a bounded timeframe (24h, 7d or 30d)".into(),
+            ));
+        }
+        ("hour", Some(hours)) if hours > ttl_days * 24 => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "granularity=hour is only available within the last {ttl_days} days \
+                     (raw trade TTL); use granularity=day for timeframe={timeframe}"
+                ),
+            ));
+        }
+        ("week", Some(24)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "granularity=week is wider than timeframe=24h".into(),
+            ));
+        }
+        _ => {}
+    }
 
     // For windowed views: compute initial portfolio state before the window
     let mut asset_state: std::collections::HashMap<String, (f64, f64, f64)> =
         std::collections::HashMap::new();
 
-    // 24h timeframe: raw trades (within TTL), hourly granularity
-    // 7d/30d/all: pnl_daily aggregate table, daily granularity
-    let use_aggregate = timeframe != "24h";
+    // Hourly buckets and the 24h view: raw trades (within TTL)
+    // Daily/weekly buckets on 7d/30d/all: pnl_daily aggregate table
+    let use_aggregate = granularity != "hour" && timeframe != "24h";
+    if benchmark && !use_aggregate {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "benchmark requires daily or weekly buckets on 7d, 30d or all".into(),
+        ));
+    }
 
     if use_aggregate {
         // Read from pnl_daily for 7d/30d/all
-        let day_filter = match timeframe {
-            "7d" => Some(7),
-            "30d" => Some(30),
-            _ => None, // "all"
+        let (day_filter, day_where) = chart_day_window(timeframe);
+        // Weeks are keyed by their ISO start (Monday) so date strings still sort
+        let bucket = if granularity == "week" {
+            "toMonday(day)"
+        } else {
+            "day"
         };
 
         // Initial state: all pnl_daily rows BEFORE the window
+        let mut deployed = 0.0;
         if let Some(days) = day_filter {
             let initial = state
                 .db
@@ -835,115 +2980,676 @@ pub async fn pnl_chart(
                     "SELECT
                         asset_id,
                         toString(sum(buy_amount) - sum(sell_amount)) AS net_tokens,
-                        toString(sum(sell_usdc) - sum(buy_usdc)) AS cash_flow,
-                        toString(argMaxMerge(last_price_state)) AS last_price
+                        toString(sum(sell_usdc) - sum(buy_usdc){daily_fee}) AS cash_flow,
+                        toString(argMaxMerge(last_price_state)) AS last_price,
+                        toString(sum(buy_usdc)) AS buy_usdc
                     FROM poly_dearboard.pnl_daily
                     WHERE lower(trader) = ?
                       AND day < today() - {days}
                     GROUP BY asset_id"
                 ))
-                .bind(&address)
+                .bind(address)
                 .fetch_all::<PnlInitialStateRow>()
                 .await
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-            for row in initial {
-                let tokens = row.net_tokens.parse::<f64>().unwrap_or(0.0);
-                let cash = row.cash_flow.parse::<f64>().unwrap_or(0.0);
-                let price = row.last_price.parse::<f64>().unwrap_or(0.0);
-                asset_state.insert(row.asset_id, (tokens, cash, price));
-            }
+            deployed = apply_initial_state(&mut asset_state, initial);
         }
 
         // Window deltas from pnl_daily
-        let day_where = day_filter
-            .map(|d| format!("AND day >= today() - {d}"))
-            .unwrap_or_default();
-
         let rows = state
             .db
             .query(&format!(
                 "SELECT
-                    toString(day) AS date,
+                    toString({bucket}) AS date,
                     asset_id,
                     toString(sum(buy_amount) - sum(sell_amount)) AS net_token_delta,
-                    toString(sum(sell_usdc) - sum(buy_usdc)) AS cash_flow_delta,
-                    toString(argMaxMerge(last_price_state)) AS last_price
+                    toString(sum(sell_usdc) - sum(buy_usdc){daily_fee}) AS cash_flow_delta,
+                    toString(argMaxMerge(last_price_state)) AS last_price,
+                    toString(sum(buy_usdc)) AS buy_usdc_delta
                 FROM poly_dearboard.pnl_daily
                 WHERE lower(trader) = ?
                   {day_where}
-                GROUP BY day, asset_id
-                ORDER BY day, asset_id"
+                GROUP BY {bucket}, asset_id
+                ORDER BY {bucket}, asset_id"
             ))
-            .bind(&address)
+            .bind(address)
             .fetch_all::<PnlDailyRow>()
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-        if rows.is_empty() && asset_state.is_empty() {
-            return Ok(Json(PnlChartResponse { points: vec![] }));
+        let axis = BucketAxis::new(
+            granularity,
+            day_filter.map(|d| {
+                (today - chrono::Duration::days(d as i64)).and_time(chrono::NaiveTime::MIN)
+            }),
+            chrono::Utc::now().naive_utc(),
+        );
+        let mut response =
+            pnl_chart_response(state, rows, asset_state, deployed, &axis, true, &opts).await;
+        if benchmark {
+            let window = DailyWindow {
+                bucket,
+                initial_where: day_filter.map(|d| format!("day < today() - {d}")),
+                window_where: day_where,
+                fee: daily_fee,
+            };
+            attach_benchmark(state, &mut response, &window, &axis, true).await?;
         }
-
-        let resolved = fetch_resolved_prices(&state).await;
-        let points = compute_pnl_points(rows, &mut asset_state, &resolved);
-        return Ok(Json(PnlChartResponse { points }));
+        return Ok(response);
     }
 
-    // 24h timeframe: read from raw trades with hourly granularity
+    // Raw trades within the TTL: hourly buckets, or daily for 24h + granularity=day
+    let hours = window_hours.unwrap_or(24);
+    let bucket = if granularity == "hour" {
+        "toStartOfHour(block_timestamp)"
+    } else {
+        "toDate(block_timestamp)"
+    };
+
     let initial = state
         .db
-        .query(
+        .query(&format!(
             "SELECT
                 asset_id,
                 toString(sumIf(toFloat64(amount), side='buy') - sumIf(toFloat64(amount), side='sell')) AS net_tokens,
-                toString(sumIf(toFloat64(usdc_amount), side='sell') - sumIf(toFloat64(usdc_amount), side='buy')) AS cash_flow,
-                toString(argMax(toFloat64(price), block_number * 1000000 + log_index)) AS last_price
+                toString(sumIf(toFloat64(usdc_amount), side='sell') - sumIf(toFloat64(usdc_amount), side='buy'){trade_fee}) AS cash_flow,
+                toString(argMax(toFloat64(price), block_number * 1000000 + log_index)) AS last_price,
+                toString(sumIf(toFloat64(usdc_amount), side='buy')) AS buy_usdc
             FROM poly_dearboard.trades
             PREWHERE block_timestamp > toDateTime('1970-01-01 00:00:00')
-              AND block_timestamp < now() - INTERVAL 24 HOUR
+              AND block_timestamp < now() - INTERVAL {hours} HOUR
             WHERE lower(trader) = ?
             GROUP BY asset_id"
-        )
-        .bind(&address)
+        ))
+        .bind(address)
         .fetch_all::<PnlInitialStateRow>()
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let deployed = apply_initial_state(&mut asset_state, initial);
+
+    let rows = state
+        .db
+        .query(&format!(
+            "SELECT
+                ifNull(toString({bucket}), '') AS date,
+                asset_id,
+                toString(sumIf(toFloat64(amount), side = 'buy') - sumIf(toFloat64(amount), side = 'sell')) AS net_token_delta,
+                toString(sumIf(toFloat64(usdc_amount), side = 'sell') - sumIf(toFloat64(usdc_amount), side = 'buy'){trade_fee}) AS cash_flow_delta,
+                toString(argMax(toFloat64(price), block_number * 1000000 + log_index)) AS last_price,
+                toString(sumIf(toFloat64(usdc_amount), side = 'buy')) AS buy_usdc_delta
+            FROM poly_dearboard.trades
+            PREWHERE block_timestamp >= now() - INTERVAL {hours} HOUR
+            WHERE lower(trader) = ?
+              AND block_timestamp > toDateTime('1970-01-01 00:00:00')
+            GROUP BY {bucket}, asset_id
+            ORDER BY {bucket}, asset_id"
+        ))
+        .bind(address)
+        .fetch_all::<PnlDailyRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let axis = BucketAxis::new(
+        granularity,
+        Some(now - chrono::Duration::hours(hours as i64)),
+        now,
+    );
+    Ok(pnl_chart_response(state, rows, asset_state, deployed, &axis, true, &opts).await)
+}
+
+/// Parses a `from`/`to` chart bound (see `parse_time_bound`) to a UTC day; future days are rejected.
+fn parse_chart_day(
+    name: &str,
+    value: Option<&str>,
+    today: chrono::NaiveDate,
+) -> Result<Option<chrono::NaiveDate>, (StatusCode, String)> {
+    let Some(ts) = parse_time_bound(name, value)? else {
+        return Ok(None);
+    };
+    let day = chrono::DateTime::from_timestamp(ts, 0)
+        .map(|dt| dt.date_naive())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid `{name}`: out of range"),
+            )
+        })?;
+    if day > today {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid `{name}`: date is in the future"),
+        ));
+    }
+    Ok(Some(day))
+}
 
+/// Seeds `asset_state` with the pre-window (tokens, cash flow, last price) per asset.
+/// Returns the capital deployed (total buy USDC) before the window.
+fn apply_initial_state(
+    asset_state: &mut std::collections::HashMap<String, (f64, f64, f64)>,
+    initial: Vec<PnlInitialStateRow>,
+) -> f64 {
+    let mut deployed = 0.0;
     for row in initial {
         let tokens = row.net_tokens.parse::<f64>().unwrap_or(0.0);
         let cash = row.cash_flow.parse::<f64>().unwrap_or(0.0);
         let price = row.last_price.parse::<f64>().unwrap_or(0.0);
+        deployed += row.buy_usdc.parse::<f64>().unwrap_or(0.0);
         asset_state.insert(row.asset_id, (tokens, cash, price));
     }
+    deployed
+}
+
+/// Output options shared by every `pnl_chart` data path.
+struct PnlChartOptions {
+    /// Per-asset series for the top N assets (`breakdown=asset`)
+    top_assets: Option<usize>,
+    include_drawdown: bool,
+    /// Report PnL as % of cumulative capital deployed (`mode=percent`)
+    percent: bool,
+}
+
+/// Builds the chart from window rows on top of the pre-window `asset_state` and
+/// `deployed` capital. `live` marks the final point at resolved or latest market prices.
+async fn pnl_chart_response(
+    state: &AppState,
+    rows: Vec<PnlDailyRow>,
+    mut asset_state: std::collections::HashMap<String, (f64, f64, f64)>,
+    deployed: f64,
+    axis: &BucketAxis,
+    live: bool,
+    opts: &PnlChartOptions,
+) -> PnlChartResponse {
+    let mode = if opts.percent { "percent" } else { "absolute" };
+    if rows.is_empty() && asset_state.is_empty() {
+        return PnlChartResponse {
+            mode,
+            max_drawdown: opts.include_drawdown.then(|| "0.00".into()),
+            points: vec![],
+            series: opts.top_assets.map(|_| vec![]),
+            benchmark: None,
+        };
+    }
+
+    let final_prices = if live {
+        let mut asset_ids: Vec<String> = asset_state.keys().cloned().collect();
+        asset_ids.extend(rows.iter().map(|r| r.asset_id.clone()));
+        asset_ids.sort_unstable();
+        asset_ids.dedup();
+        fetch_final_prices(state, &asset_ids).await
+    } else {
+        std::collections::HashMap::new()
+    };
+    let (points, snapshots, max_drawdown) =
+        compute_pnl_points(rows, &mut asset_state, deployed, &final_prices, axis, opts);
+    let series = match opts.top_assets {
+        Some(n) => Some(pnl_asset_series(state, &points, &snapshots, n).await),
+        None => None,
+    };
+    PnlChartResponse {
+        mode,
+        max_drawdown: max_drawdown.map(|dd| format!("{:.2}", dd)),
+        points,
+        series,
+        benchmark: None,
+    }
+}
+
+const BENCHMARK_TOP_N: u32 = 10;
+const BENCHMARK_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// `pnl_daily` window a benchmark curve is computed over.
+struct DailyWindow {
+    /// Bucket expression (`day` or `toMonday(day)`)
+    bucket: &'static str,
+    /// Condition selecting pre-window rows, if the window has a start
+    initial_where: Option<String>,
+    /// ` AND ...` conditions selecting in-window rows
+    window_where: String,
+    /// Fee term subtracted from cash flow (`include_fees`)
+    fee: &'static str,
+}
+
+/// Fills `response.benchmark` from the top-trader curve, aligned to `points`.
+async fn attach_benchmark(
+    state: &AppState,
+    response: &mut PnlChartResponse,
+    window: &DailyWindow,
+    axis: &BucketAxis,
+    live: bool,
+) -> Result<(), (StatusCode, String)> {
+    let curve = benchmark_curve(state, window, axis, live).await?;
+    response.benchmark = Some(
+        response
+            .points
+            .iter()
+            .map(|p| curve.get(&p.date).map(|v| format!("{:.2}", v)))
+            .collect(),
+    );
+    Ok(())
+}
+
+/// Average percent-return curve (bucket label → %) of the current top traders,
+/// each normalized by its own deployed capital before averaging; traders count as
+/// 0% before their first trade. Identical for every caller, so it is cached
+/// globally for `BENCHMARK_CACHE_TTL`.
+async fn benchmark_curve(
+    state: &AppState,
+    window: &DailyWindow,
+    axis: &BucketAxis,
+    live: bool,
+) -> Result<std::collections::HashMap<String, f64>, (StatusCode, String)> {
+    let key = format!(
+        "{}|{:?}|{}|{}|{live}",
+        window.bucket, window.initial_where, window.window_where, window.fee
+    );
+    if let Some((curve, expires)) = state.benchmark_cache.read().await.get(&key) {
+        if *expires > std::time::Instant::now() {
+            return Ok(curve.clone());
+        }
+    }
+
+    let traders = top_pnl_traders(state, BENCHMARK_TOP_N).await?;
+    if traders.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+    let addresses: Vec<String> = traders.iter().map(|t| t.address.to_lowercase()).collect();
+    let in_list = addresses
+        .iter()
+        .map(|a| format!("'{}'", a.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",");
+    let fee = window.fee;
+    let bucket = window.bucket;
+
+    let mut initial_by_trader: std::collections::HashMap<String, Vec<PnlInitialStateRow>> =
+        std::collections::HashMap::new();
+    if let Some(initial_where) = &window.initial_where {
+        let initial = state
+            .db
+            .query(&format!(
+                "SELECT
+                    toString(trader) AS trader,
+                    asset_id,
+                    toString(sum(buy_amount) - sum(sell_amount)) AS net_tokens,
+                    toString(sum(sell_usdc) - sum(buy_usdc){fee}) AS cash_flow,
+                    toString(argMaxMerge(last_price_state)) AS last_price,
+                    toString(sum(buy_usdc)) AS buy_usdc
+                FROM poly_dearboard.pnl_daily
+                WHERE lower(trader) IN ({in_list})
+                  AND {initial_where}
+                GROUP BY trader, asset_id"
+            ))
+            .fetch_all::<PnlInitialStateTraderRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        for row in initial {
+            initial_by_trader
+                .entry(row.trader.to_lowercase())
+                .or_default()
+                .push(PnlInitialStateRow {
+                    asset_id: row.asset_id,
+                    net_tokens: row.net_tokens,
+                    cash_flow: row.cash_flow,
+                    last_price: row.last_price,
+                    buy_usdc: row.buy_usdc,
+                });
+        }
+    }
 
     let rows = state
         .db
-        .query(
+        .query(&format!(
             "SELECT
-                ifNull(toString(toStartOfHour(block_timestamp)), '') AS date,
+                toString(trader) AS trader,
+                toString({bucket}) AS date,
                 asset_id,
-                toString(sumIf(toFloat64(amount), side = 'buy') - sumIf(toFloat64(amount), side = 'sell')) AS net_token_delta,
-                toString(sumIf(toFloat64(usdc_amount), side = 'sell') - sumIf(toFloat64(usdc_amount), side = 'buy')) AS cash_flow_delta,
-                toString(argMax(toFloat64(price), block_number * 1000000 + log_index)) AS last_price
-            FROM poly_dearboard.trades
-            PREWHERE block_timestamp >= now() - INTERVAL 24 HOUR
+                toString(sum(buy_amount) - sum(sell_amount)) AS net_token_delta,
+                toString(sum(sell_usdc) - sum(buy_usdc){fee}) AS cash_flow_delta,
+                toString(argMaxMerge(last_price_state)) AS last_price,
+                toString(sum(buy_usdc)) AS buy_usdc_delta
+            FROM poly_dearboard.pnl_daily
+            WHERE lower(trader) IN ({in_list})
+              {}
+            GROUP BY trader, {bucket}, asset_id
+            ORDER BY {bucket}, trader, asset_id",
+            window.window_where
+        ))
+        .fetch_all::<PnlDailyTraderRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // Without a window start ("all"), the cohort axis starts at its earliest trade
+    let cohort_axis = BucketAxis {
+        start: axis.start.or_else(|| {
+            rows.iter()
+                .filter_map(|r| parse_bucket_start(&r.date))
+                .min()
+        }),
+        end: axis.end,
+        step: axis.step,
+        fmt: axis.fmt,
+    };
+    let final_prices = if live {
+        let mut asset_ids: Vec<String> = rows.iter().map(|r| r.asset_id.clone()).collect();
+        asset_ids.extend(
+            initial_by_trader
+                .values()
+                .flatten()
+                .map(|r| r.asset_id.clone()),
+        );
+        asset_ids.sort_unstable();
+        asset_ids.dedup();
+        fetch_final_prices(state, &asset_ids).await
+    } else {
+        std::collections::HashMap::new()
+    };
+
+    let mut rows_by_trader: std::collections::HashMap<String, Vec<PnlDailyRow>> =
+        std::collections::HashMap::new();
+    for row in rows {
+        rows_by_trader
+            .entry(row.trader.to_lowercase())
+            .or_default()
+            .push(PnlDailyRow {
+                date: row.date,
+                asset_id: row.asset_id,
+                net_token_delta: row.net_token_delta,
+                cash_flow_delta: row.cash_flow_delta,
+                last_price: row.last_price,
+                buy_usdc_delta: row.buy_usdc_delta,
+            });
+    }
+
+    let opts = PnlChartOptions {
+        top_assets: None,
+        include_drawdown: false,
+        percent: true,
+    };
+    let mut curve: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+    for address in &addresses {
+        let mut asset_state = std::collections::HashMap::new();
+        let deployed = initial_by_trader
+            .remove(address)
+            .map(|initial| apply_initial_state(&mut asset_state, initial))
+            .unwrap_or(0.0);
+        let rows = rows_by_trader.remove(address).unwrap_or_default();
+        let (points, _, _) = compute_pnl_points(
+            rows,
+            &mut asset_state,
+            deployed,
+            &final_prices,
+            &cohort_axis,
+            &opts,
+        );
+        for point in points {
+            *curve.entry(point.date).or_default() += point.pnl.parse::<f64>().unwrap_or(0.0);
+        }
+    }
+    let cohort = addresses.len() as f64;
+    for value in curve.values_mut() {
+        *value /= cohort;
+    }
+
+    let now = std::time::Instant::now();
+    let mut cache = state.benchmark_cache.write().await;
+    cache.retain(|_, (_, expires)| *expires > now);
+    cache.insert(key, (curve.clone(), now + BENCHMARK_CACHE_TTL));
+    Ok(curve)
+}
+
+/// Splits the aggregate line into the `top_n` assets by absolute final PnL plus
+/// an "Other" series for the rest. Values align with `points`; an asset is null
+/// until its first trade (or the window start, if held before it).
+async fn pnl_asset_series(
+    state: &AppState,
+    points: &[PnlChartPoint],
+    snapshots: &[std::collections::HashMap<String, f64>],
+    top_n: usize,
+) -> Vec<PnlSeries> {
+    let Some(last) = snapshots.last() else {
+        return vec![];
+    };
+    let mut ranked: Vec<(&String, f64)> = last.iter().map(|(a, v)| (a, *v)).collect();
+    ranked.sort_by(|a, b| {
+        b.1.abs()
+            .partial_cmp(&a.1.abs())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(b.0))
+    });
+    let top: Vec<String> = ranked
+        .into_iter()
+        .take(top_n)
+        .map(|(a, _)| a.clone())
+        .collect();
+
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &top,
+    )
+    .await;
+
+    let mut series: Vec<PnlSeries> = top
+        .iter()
+        .map(|asset_id| {
+            let info = market_info.get(asset_id);
+            PnlSeries {
+                asset_id: Some(
+                    info.map(|i| i.gamma_token_id.clone())
+                        .unwrap_or_else(|| markets::to_integer_id(asset_id)),
+                ),
+                question: info
+                    .map(|i| i.question.clone())
+                    .unwrap_or_else(|| shorten_id(asset_id)),
+                outcome: info.map(|i| i.outcome.clone()).unwrap_or_default(),
+                values: snapshots
+                    .iter()
+                    .map(|snap| snap.get(asset_id).map(|v| format!("{:.2}", v)))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let other = points
+        .iter()
+        .zip(snapshots)
+        .map(|(point, snap)| {
+            let total: f64 = point.pnl.parse().unwrap_or(0.0);
+            let top_sum: f64 = top.iter().filter_map(|a| snap.get(a)).sum();
+            Some(format!("{:.2}", total - top_sum))
+        })
+        .collect();
+    series.push(PnlSeries {
+        asset_id: None,
+        question: "Other".into(),
+        outcome: String::new(),
+        values: other,
+    });
+
+    series
+}
+
+/// Day window for `pnl_daily`-backed charts ("all" and unknown → None) and its WHERE fragment.
+fn chart_day_window(timeframe: &str) -> (Option<u32>, String) {
+    let days = match timeframe {
+        "7d" => Some(7),
+        "30d" => Some(30),
+        _ => None, // "all"
+    };
+    let clause = days
+        .map(|d| format!("AND day >= today() - {d}"))
+        .unwrap_or_default();
+    (days, clause)
+}
+
+const VOLUME_CHART_TIMEFRAMES: &[&str] = &["all", "24h", "7d", "30d"];
+
+/// Daily traded volume, trade count and distinct markets (hourly for 24h, like `pnl_chart`).
+/// Quiet days/hours inside the window are emitted as zeros.
+pub async fn volume_chart(
+    State(state): State<AppState>,
+    ValidatedAddress(address): ValidatedAddress,
+    Query(params): Query<PnlChartParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let timeframe = params.timeframe.as_deref().unwrap_or("all");
+    if !VOLUME_CHART_TIMEFRAMES.contains(&timeframe) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid timeframe. Allowed: {VOLUME_CHART_TIMEFRAMES:?}"),
+        ));
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+
+    // 24h: raw trades (within TTL), hourly buckets
+    if timeframe == "24h" {
+        let rows = state
+            .db
+            .query(
+                "SELECT
+                    ifNull(toString(toStartOfHour(block_timestamp)), '') AS date,
+                    toString(ROUND(sum(toFloat64(usdc_amount)), 6)) AS volume,
+                    toUInt64(count()) AS trade_count,
+                    toUInt64(uniqExact(asset_id)) AS markets_traded
+                FROM poly_dearboard.trades
+                PREWHERE block_timestamp >= now() - INTERVAL 24 HOUR
+                WHERE lower(trader) = ?
+                  AND block_timestamp > toDateTime('1970-01-01 00:00:00')
+                GROUP BY toStartOfHour(block_timestamp)
+                ORDER BY toStartOfHour(block_timestamp)",
+            )
+            .bind(&address)
+            .fetch_all::<VolumeChartRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        let since = now - chrono::Duration::hours(24);
+        let start = since
+            .date()
+            .and_hms_opt(chrono::Timelike::hour(&since), 0, 0);
+        let points = fill_volume_points(
+            rows,
+            start,
+            now,
+            chrono::Duration::hours(1),
+            "%Y-%m-%d %H:%M:%S",
+        );
+        return Ok(Json(VolumeChartResponse {
+            timeframe: timeframe.to_string(),
+            points,
+        }));
+    }
+
+    // 7d/30d/all: pnl_daily aggregate table
+    let (day_filter, day_where) = chart_day_window(timeframe);
+    let rows = state
+        .db
+        .query(&format!(
+            "SELECT
+                toString(day) AS date,
+                toString(ROUND(sum(buy_usdc) + sum(sell_usdc), 6)) AS volume,
+                toUInt64(sum(trade_count)) AS trade_count,
+                toUInt64(uniqExact(asset_id)) AS markets_traded
+            FROM poly_dearboard.pnl_daily
             WHERE lower(trader) = ?
-              AND block_timestamp > toDateTime('1970-01-01 00:00:00')
-            GROUP BY toStartOfHour(block_timestamp), asset_id
-            ORDER BY toStartOfHour(block_timestamp), asset_id"
-        )
+              {day_where}
+            GROUP BY day
+            ORDER BY day"
+        ))
         .bind(&address)
-        .fetch_all::<PnlDailyRow>()
+        .fetch_all::<VolumeChartRow>()
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    if rows.is_empty() && asset_state.is_empty() {
-        return Ok(Json(PnlChartResponse { points: vec![] }));
+    let start = day_filter
+        .and_then(|d| (now.date() - chrono::Duration::days(d as i64)).and_hms_opt(0, 0, 0));
+    let points = fill_volume_points(rows, start, now, chrono::Duration::days(1), "%Y-%m-%d");
+    Ok(Json(VolumeChartResponse {
+        timeframe: timeframe.to_string(),
+        points,
+    }))
+}
+
+/// One point per `step` from `start` (or the first row when None) through `end`,
+/// taking values from `rows` where their `date` matches and zeros elsewhere.
+fn fill_volume_points(
+    rows: Vec<VolumeChartRow>,
+    start: Option<chrono::NaiveDateTime>,
+    end: chrono::NaiveDateTime,
+    step: chrono::Duration,
+    fmt: &str,
+) -> Vec<VolumeChartPoint> {
+    let start = match start.or_else(|| rows.first().and_then(|r| parse_bucket_start(&r.date))) {
+        Some(s) => s,
+        None => return Vec::new(),
+    };
+
+    let mut by_date: std::collections::HashMap<String, VolumeChartRow> =
+        rows.into_iter().map(|r| (r.date.clone(), r)).collect();
+    let mut points = Vec::new();
+    let mut cursor = start;
+    while cursor <= end {
+        let date = cursor.format(fmt).to_string();
+        points.push(match by_date.remove(&date) {
+            Some(r) => VolumeChartPoint {
+                date,
+                volume: r.volume,
+                trade_count: r.trade_count,
+                markets_traded: r.markets_traded,
+            },
+            None => VolumeChartPoint {
+                date,
+                volume: "0".into(),
+                trade_count: 0,
+                markets_traded: 0,
+            },
+        });
+        cursor += step;
     }
+    points
+}
+
+/// Latest traded price per asset (`asset_latest_price`) for the PnL final point
+async fn fetch_latest_prices(
+    state: &AppState,
+    asset_ids: &[String],
+) -> std::collections::HashMap<String, f64> {
+    if asset_ids.is_empty() {
+        return std::collections::HashMap::new();
+    }
+    let id_list = asset_ids
+        .iter()
+        .map(|id| format!("'{}'", id.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",");
+    state
+        .db
+        .query(&format!(
+            "SELECT asset_id, toString(latest_price) AS latest_price
+            FROM poly_dearboard.asset_latest_price FINAL
+            WHERE asset_id IN ({id_list})"
+        ))
+        .fetch_all::<LatestPriceLookup>()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|r| r.latest_price.parse::<f64>().ok().map(|p| (r.asset_id, p)))
+        .collect()
+}
 
-    let resolved = fetch_resolved_prices(&state).await;
-    let points = compute_pnl_points(rows, &mut asset_state, &resolved);
-    Ok(Json(PnlChartResponse { points }))
+/// Final-point marks for live PnL charts: resolved prices where available,
+/// else the latest traded price.
+async fn fetch_final_prices(
+    state: &AppState,
+    asset_ids: &[String],
+) -> std::collections::HashMap<String, f64> {
+    let mut prices = fetch_latest_prices(state, asset_ids).await;
+    prices.extend(fetch_resolved_prices(state).await);
+    prices
 }
 
 /// Fetch resolved_prices lookup for PnL final-point overlay
@@ -964,75 +3670,216 @@ async fn fetch_resolved_prices(state: &AppState) -> std::collections::HashMap<St
         .collect()
 }
 
-/// Process bucket-by-bucket rows into PnL chart points
-fn compute_pnl_points(
-    rows: Vec<PnlDailyRow>,
-    asset_state: &mut std::collections::HashMap<String, (f64, f64, f64)>,
-    resolved: &std::collections::HashMap<String, f64>,
-) -> Vec<PnlChartPoint> {
-    let mut points: Vec<PnlChartPoint> = Vec::new();
-    let mut current_date = String::new();
+/// Running peak and maximum drawdown (peak minus value) over a value series.
+struct Drawdown {
+    peak: f64,
+    max: f64,
+}
 
-    for row in &rows {
-        if !current_date.is_empty() && row.date != current_date {
-            let pnl: f64 = asset_state
-                .values()
-                .map(|(tokens, cash, price)| cash + tokens * price)
-                .sum();
-            points.push(PnlChartPoint {
-                date: current_date.clone(),
-                pnl: format!("{:.2}", pnl),
-            });
+impl Drawdown {
+    fn new(initial_peak: f64) -> Self {
+        Self {
+            peak: initial_peak,
+            max: 0.0,
         }
-        current_date.clone_from(&row.date);
-
-        let delta_tokens = row.net_token_delta.parse::<f64>().unwrap_or(0.0);
-        let delta_cash = row.cash_flow_delta.parse::<f64>().unwrap_or(0.0);
-        let price = row.last_price.parse::<f64>().unwrap_or(0.0);
-
-        let entry = asset_state
-            .entry(row.asset_id.clone())
-            .or_insert((0.0, 0.0, 0.0));
-        entry.0 += delta_tokens;
-        entry.1 += delta_cash;
-        entry.2 = price;
     }
 
-    // Final point: use resolved prices where available (COALESCE equivalent)
-    if !current_date.is_empty() {
-        let pnl: f64 = asset_state
-            .iter()
-            .map(|(asset_id, (tokens, cash, price))| {
-                let final_price = resolved.get(asset_id).copied().unwrap_or(*price);
-                cash + tokens * final_price
-            })
-            .sum();
-        points.push(PnlChartPoint {
-            date: current_date,
-            pnl: format!("{:.2}", pnl),
-        });
+    /// Feeds the next value; returns the running peak and the drawdown below it.
+    fn step(&mut self, value: f64) -> (f64, f64) {
+        self.peak = self.peak.max(value);
+        let dd = self.peak - value;
+        self.max = self.max.max(dd);
+        (self.peak, dd)
     }
+}
 
-    points
+fn pnl_chart_point(date: String, pnl: f64, drawdown: Option<&mut Drawdown>) -> PnlChartPoint {
+    let (peak, drawdown) = match drawdown.map(|d| d.step(pnl)) {
+        Some((peak, dd)) => (Some(format!("{:.2}", peak)), Some(format!("{:.2}", dd))),
+        None => (None, None),
+    };
+    PnlChartPoint {
+        date,
+        pnl: format!("{:.2}", pnl),
+        peak,
+        drawdown,
+    }
 }
 
-pub async fn resolve_market(
-    State(state): State<AppState>,
-    Query(params): Query<ResolveParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let token_ids: Vec<String> = params
-        .token_ids
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect();
+/// Bucket axis of a PnL chart: one point per `step` from `start` (or the first
+/// row when None) through `end`, labelled with `fmt` like the query's buckets.
+struct BucketAxis {
+    start: Option<chrono::NaiveDateTime>,
+    end: chrono::NaiveDateTime,
+    step: chrono::Duration,
+    fmt: &'static str,
+}
+
+impl BucketAxis {
+    /// Aligns `start`/`end` to `granularity` buckets (`toStartOfHour`, `day`, `toMonday(day)`).
+    fn new(
+        granularity: &str,
+        start: Option<chrono::NaiveDateTime>,
+        end: chrono::NaiveDateTime,
+    ) -> Self {
+        use chrono::DurationRound;
+        match granularity {
+            "hour" => {
+                let align = |t: chrono::NaiveDateTime| {
+                    t.duration_trunc(chrono::Duration::hours(1)).unwrap_or(t)
+                };
+                Self {
+                    start: start.map(align),
+                    end: align(end),
+                    step: chrono::Duration::hours(1),
+                    fmt: "%Y-%m-%d %H:%M:%S",
+                }
+            }
+            "week" => {
+                let align = |t: chrono::NaiveDateTime| {
+                    let day = t.date();
+                    let offset = chrono::Datelike::weekday(&day).num_days_from_monday();
+                    (day - chrono::Duration::days(offset as i64)).and_time(chrono::NaiveTime::MIN)
+                };
+                Self {
+                    start: start.map(align),
+                    end: align(end),
+                    step: chrono::Duration::weeks(1),
+                    fmt: "%Y-%m-%d",
+                }
+            }
+            _ => {
+                let align = |t: chrono::NaiveDateTime| t.date().and_time(chrono::NaiveTime::MIN);
+                Self {
+                    start: start.map(align),
+                    end: align(end),
+                    step: chrono::Duration::days(1),
+                    fmt: "%Y-%m-%d",
+                }
+            }
+        }
+    }
+}
+
+/// Parses a bucket label: a `YYYY-MM-DD` date (midnight) or a full timestamp.
+fn parse_bucket_start(date: &str) -> Option<chrono::NaiveDateTime> {
+    parse_position_ts(date).or_else(|| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .map(|d| d.and_time(chrono::NaiveTime::MIN))
+    })
+}
+
+/// Process bucket-by-bucket rows into one PnL chart point per `axis` bucket,
+/// carrying asset state forward through buckets without trades, plus the max
+/// drawdown when `include_drawdown`. The final point is marked at `final_prices`
+/// where available. In percent mode every value is scaled by the capital
+/// deployed up to its bucket (0% while nothing has been deployed).
+fn compute_pnl_points(
+    rows: Vec<PnlDailyRow>,
+    asset_state: &mut std::collections::HashMap<String, (f64, f64, f64)>,
+    mut deployed: f64,
+    final_prices: &std::collections::HashMap<String, f64>,
+    axis: &BucketAxis,
+    opts: &PnlChartOptions,
+) -> (
+    Vec<PnlChartPoint>,
+    Vec<std::collections::HashMap<String, f64>>,
+    Option<f64>,
+) {
+    let mut points: Vec<PnlChartPoint> = Vec::new();
+    // Peak starts at the first point, so the window opens at zero drawdown
+    let mut drawdown = opts
+        .include_drawdown
+        .then(|| Drawdown::new(f64::NEG_INFINITY));
+    let track_assets = opts.top_assets.is_some();
+    // Per-point PnL of every asset seen so far (only when `track_assets`)
+    let mut snapshots: Vec<std::collections::HashMap<String, f64>> = Vec::new();
+
+    let mut rows = rows.into_iter().peekable();
+    let Some(mut cursor) = axis
+        .start
+        .or_else(|| rows.peek().and_then(|r| parse_bucket_start(&r.date)))
+    else {
+        return (points, snapshots, drawdown.map(|d| d.max));
+    };
+
+    // Rows past `end` (clock skew) still extend the axis rather than being dropped
+    while cursor <= axis.end || rows.peek().is_some() {
+        let date = cursor.format(axis.fmt).to_string();
+        // Labels sort chronologically, so this also folds in any off-axis rows
+        while let Some(row) = rows.next_if(|r| r.date <= date) {
+            let delta_tokens = row.net_token_delta.parse::<f64>().unwrap_or(0.0);
+            let delta_cash = row.cash_flow_delta.parse::<f64>().unwrap_or(0.0);
+            let price = row.last_price.parse::<f64>().unwrap_or(0.0);
+            deployed += row.buy_usdc_delta.parse::<f64>().unwrap_or(0.0);
+
+            let entry = asset_state.entry(row.asset_id).or_insert((0.0, 0.0, 0.0));
+            entry.0 += delta_tokens;
+            entry.1 += delta_cash;
+            entry.2 = price;
+        }
+
+        // Final point: use `final_prices` where available (COALESCE equivalent)
+        let is_last = cursor + axis.step > axis.end && rows.peek().is_none();
+        let scale = match (opts.percent, deployed > 0.0) {
+            (false, _) => 1.0,
+            (true, true) => 100.0 / deployed,
+            (true, false) => 0.0,
+        };
+        let mark = |asset_id: &String, (tokens, cash, price): &(f64, f64, f64)| {
+            let price = if is_last {
+                final_prices.get(asset_id).copied().unwrap_or(*price)
+            } else {
+                *price
+            };
+            (cash + tokens * price) * scale
+        };
+        let pnl: f64 = if track_assets {
+            let per_asset: std::collections::HashMap<String, f64> = asset_state
+                .iter()
+                .map(|(asset_id, s)| (asset_id.clone(), mark(asset_id, s)))
+                .collect();
+            let pnl: f64 = per_asset.values().sum();
+            snapshots.push(per_asset);
+            pnl
+        } else {
+            asset_state
+                .iter()
+                .map(|(asset_id, s)| mark(asset_id, s))
+                .sum()
+        };
+        points.push(pnl_chart_point(date, pnl, drawdown.as_mut()));
+
+        cursor += axis.step;
+    }
+
+    (points, snapshots, drawdown.map(|d| d.max))
+}
+
+pub async fn resolve_market(
+    State(state): State<AppState>,
+    Query(params): Query<ResolveParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let token_ids: Vec<String> = params
+        .token_ids
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
 
     if token_ids.is_empty() {
         return Err((StatusCode::BAD_REQUEST, "token_ids required".to_string()));
     }
 
-    let info =
-        markets::resolve_markets(&state.http, &state.db, &state.market_cache, &token_ids).await;
+    let info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &token_ids,
+    )
+    .await;
 
     let mut resolved: std::collections::HashMap<String, ResolvedMarket> =
         std::collections::HashMap::new();
@@ -1057,1785 +3904,5116 @@ pub async fn resolve_market(
     Ok(Json(resolved))
 }
 
-// -- Wallet Auth (EIP-712 + JWT) --
+const MARKET_SEARCH_MIN_QUERY: usize = 3;
+const MARKET_SEARCH_MAX_LIMIT: u32 = 100;
+/// Matching questions beyond this (active first) are dropped before ranking
+const MARKET_SEARCH_MAX_CANDIDATES: usize = 500;
 
-#[derive(Deserialize)]
-pub struct NonceParams {
-    pub address: String,
+/// Search cached markets by question/outcome text. Yes/No tokens are merged
+/// into one entry per question, ranked by 24h volume.
+pub async fn search_markets(
+    State(state): State<AppState>,
+    Query(params): Query<MarketSearchParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let query = params.q.as_deref().unwrap_or("").trim().to_lowercase();
+    if query.chars().count() < MARKET_SEARCH_MIN_QUERY {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("q must be at least {MARKET_SEARCH_MIN_QUERY} characters"),
+        ));
+    }
+    let limit = params.limit.unwrap_or(20).clamp(1, MARKET_SEARCH_MAX_LIMIT) as usize;
+    let terms: Vec<&str> = query.split_whitespace().collect();
+
+    // The cache holds one entry per token; keep the first per question
+    let mut candidates: Vec<markets::MarketInfo> = {
+        let cache = state.market_cache.read().await;
+        let mut seen = std::collections::HashSet::new();
+        cache
+            .values()
+            .filter(|info| {
+                let haystack =
+                    format!("{} {}", info.question, info.outcomes.join(" ")).to_lowercase();
+                terms.iter().all(|t| haystack.contains(t))
+            })
+            .filter(|info| seen.insert(info.question.clone()))
+            .cloned()
+            .collect()
+    };
+    candidates.sort_by_key(|info| !info.active);
+    candidates.truncate(MARKET_SEARCH_MAX_CANDIDATES);
+
+    let mut volumes: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+    if !candidates.is_empty() {
+        let id_list = candidates
+            .iter()
+            .flat_map(|info| info.all_token_ids.iter())
+            .map(|id| format!("'{}'", id.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(",");
+        let exclude = exclude_clause();
+        let rows = state
+            .db
+            .query(&format!(
+                "SELECT asset_id, toString(sum(usdc_amount)) AS volume
+                FROM poly_dearboard.trades
+                PREWHERE block_timestamp >= now() - INTERVAL 24 HOUR
+                WHERE asset_id IN ({id_list}) AND trader NOT IN ({exclude})
+                GROUP BY asset_id"
+            ))
+            .fetch_all::<AssetVolumeRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        for r in rows {
+            volumes.insert(r.asset_id, r.volume.parse().unwrap_or(0.0));
+        }
+    }
+
+    let mut ranked: Vec<(f64, MarketSearchResult)> = candidates
+        .into_iter()
+        .map(|info| {
+            let token_volumes: Vec<f64> = info
+                .all_token_ids
+                .iter()
+                .map(|id| volumes.get(id).copied().unwrap_or(0.0))
+                .collect();
+            let total: f64 = token_volumes.iter().sum();
+            // Represent the question by its higher-volume side, as hot_markets does
+            let top = token_volumes
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| **v > 0.0)
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(i, _)| i)
+                .unwrap_or(info.outcome_index);
+            let market = ResolvedMarket {
+                outcome: info
+                    .outcomes
+                    .get(top)
+                    .cloned()
+                    .unwrap_or_else(|| info.outcome.clone()),
+                gamma_token_id: info
+                    .all_token_ids
+                    .get(top)
+                    .cloned()
+                    .unwrap_or_else(|| info.gamma_token_id.clone()),
+                question: info.question,
+                category: info.category,
+                active: info.active,
+                all_token_ids: info.all_token_ids,
+                outcomes: info.outcomes,
+            };
+            let result = MarketSearchResult {
+                market,
+                volume_24h: format!("{total:.6}"),
+            };
+            (total, result)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let markets = ranked.into_iter().take(limit).map(|(_, m)| m).collect();
+    Ok(Json(MarketSearchResponse { query, markets }))
 }
 
-#[derive(Deserialize)]
-pub struct VerifyBody {
-    pub address: String,
-    pub signature: String,
-    pub nonce: String,
-    pub issued_at: String,
+/// Trim a `{token_id}` path segment and reject anything but a decimal/scientific ID.
+fn parse_token_id(token_id: &str) -> Result<String, (StatusCode, String)> {
+    let token_id = token_id.trim();
+    if token_id.is_empty()
+        || !token_id
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+'))
+    {
+        return Err((StatusCode::BAD_REQUEST, "Invalid token_id".into()));
+    }
+    Ok(token_id.to_string())
 }
 
-pub async fn auth_nonce(
+/// Resolve either token of a pair (matched by `cache_key`) to its market, plus
+/// a SQL `IN` list of both sides' asset IDs.
+async fn market_for_token(
+    state: &AppState,
+    token_id: &str,
+) -> Result<(markets::MarketInfo, String), (StatusCode, String)> {
+    let token_id = parse_token_id(token_id)?;
+
+    let info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        std::slice::from_ref(&token_id),
+    )
+    .await
+    .remove(&token_id)
+    .ok_or((StatusCode::NOT_FOUND, "Market not found".to_string()))?;
+
+    let mut asset_ids = info.all_token_ids.clone();
+    if !asset_ids.contains(&token_id) {
+        asset_ids.push(token_id);
+    }
+    let id_list = asset_ids
+        .iter()
+        .map(|id| format!("'{}'", id.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok((info, id_list))
+}
+
+/// One market (both sides aggregated) by either of its token IDs.
+pub async fn market_detail(
     State(state): State<AppState>,
-    Query(params): Query<NonceParams>,
+    Path(token_id): Path<String>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let user_db = state.user_db.clone();
-    let address = params.address.to_lowercase();
+    let (info, id_list) = market_for_token(&state, &token_id).await?;
+    let exclude = exclude_clause();
 
-    let (nonce, issued_at) = tokio::task::spawn_blocking(move || {
-        let conn = user_db.lock().expect("user_db lock poisoned");
-        super::db::get_or_create_user(&conn, &address)
-    })
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    let (stats, totals, resolved) = tokio::try_join!(
+        state
+            .db
+            .query(&format!(
+                "SELECT
+                    asset_id,
+                    toString(sum(volume)) AS volume,
+                    toString(sumIf(volume, day > today() - 7)) AS volume_7d,
+                    sum(trade_count) AS trade_count,
+                    toString(argMaxMerge(last_price_state)) AS last_price,
+                    ifNull(toString(max(last_trade)), '') AS last_trade
+                FROM poly_dearboard.asset_stats_daily
+                WHERE asset_id IN ({id_list})
+                GROUP BY asset_id"
+            ))
+            .fetch_all::<MarketDetailStatsRow>(),
+        // Traders on both sides count once
+        state
+            .db
+            .query(&format!(
+                "SELECT
+                    (SELECT uniqExactMerge(unique_traders)
+                     FROM poly_dearboard.asset_stats_daily
+                     WHERE asset_id IN ({id_list})) AS unique_traders,
+                    (SELECT toString(sum(usdc_amount))
+                     FROM poly_dearboard.trades
+                     PREWHERE block_timestamp >= now() - INTERVAL 24 HOUR
+                     WHERE asset_id IN ({id_list})
+                       AND trader NOT IN ({exclude})) AS volume_24h"
+            ))
+            .fetch_one::<MarketDetailTotalsRow>(),
+        state
+            .db
+            .query(&format!(
+                "SELECT asset_id, resolved_price, condition_id, block_number
+                FROM poly_dearboard.resolved_prices FINAL
+                WHERE asset_id IN ({id_list})"
+            ))
+            .fetch_all::<MarketDetailResolvedRow>(),
+    )
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok(Json(
-        serde_json::json!({ "nonce": nonce, "issuedAt": issued_at }),
-    ))
+    // Stats rows may use a different ID form; match sides by cache key
+    let side = |asset_id: &str| {
+        let key = markets::cache_key(asset_id);
+        info.all_token_ids
+            .iter()
+            .position(|id| markets::cache_key(id) == key)
+    };
+
+    let mut volume = 0.0;
+    let mut volume_7d = 0.0;
+    let mut trade_count = 0;
+    let mut last_price = String::new();
+    let mut last_trade = String::new();
+    let mut last_prices = vec![None; info.all_token_ids.len()];
+    for r in stats {
+        volume += r.volume.parse::<f64>().unwrap_or(0.0);
+        volume_7d += r.volume_7d.parse::<f64>().unwrap_or(0.0);
+        trade_count += r.trade_count;
+        if let Some(i) = side(&r.asset_id) {
+            last_prices[i] = Some(r.last_price.clone());
+        }
+        if r.last_trade > last_trade {
+            last_trade = r.last_trade;
+            last_price = r.last_price;
+        }
+    }
+
+    let resolution = resolved.first().map(|first| {
+        let mut resolved_prices = vec![None; info.all_token_ids.len()];
+        for r in &resolved {
+            if let Some(i) = side(&r.asset_id) {
+                resolved_prices[i] = Some(r.resolved_price.clone());
+            }
+        }
+        let winning_outcome = resolved_prices
+            .iter()
+            .position(|p| p.as_deref().and_then(|p| p.parse::<f64>().ok()) == Some(1.0))
+            .and_then(|i| info.outcomes.get(i).cloned());
+        MarketResolution {
+            condition_id: first.condition_id.clone(),
+            block_number: first.block_number,
+            resolved_prices,
+            winning_outcome,
+        }
+    });
+
+    Ok(Json(MarketDetailResponse {
+        token_id: info.gamma_token_id,
+        question: info.question,
+        category: info.category,
+        active: info.active,
+        all_token_ids: info.all_token_ids,
+        outcomes: info.outcomes,
+        end_date: info.end_date,
+        volume: format!("{volume:.6}"),
+        volume_24h: format!("{:.6}", totals.volume_24h.parse::<f64>().unwrap_or(0.0)),
+        volume_7d: format!("{volume_7d:.6}"),
+        trade_count,
+        unique_traders: totals.unique_traders,
+        last_price,
+        last_trade,
+        last_prices,
+        resolution,
+    }))
 }
 
-pub async fn auth_verify(
+const MARKET_FLOW_PERIODS: &[&str] = &["1h", "24h"];
+const MARKET_FLOW_WHALE_MIN_USDC: f64 = 1000.0;
+
+/// Hourly buy/sell flow for one token, overall and for trades of at least
+/// `whale_min_usdc`.
+pub async fn market_flow(
     State(state): State<AppState>,
-    Json(body): Json<VerifyBody>,
-) -> Result<impl IntoResponse, super::auth::AuthError> {
-    let address = body.address.to_lowercase();
-    let signature = body.signature.clone();
-    let nonce = body.nonce.clone();
-    let issued_at = body.issued_at.clone();
-    let jwt_secret = state.jwt_secret.clone();
+    Path(token_id): Path<String>,
+    Query(params): Query<MarketFlowParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    use chrono::DurationRound;
+    let token_id = parse_token_id(&token_id)?;
+    let period = params.period.as_deref().unwrap_or("24h");
+    let hours = match period {
+        "1h" => 1,
+        "24h" => 24,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid period. Allowed: {MARKET_FLOW_PERIODS:?}"),
+            ));
+        }
+    };
+    let whale_min = params.whale_min_usdc.unwrap_or(MARKET_FLOW_WHALE_MIN_USDC);
+    if !whale_min.is_finite() || whale_min < 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid whale_min_usdc: must be a non-negative number".into(),
+        ));
+    }
 
-    // Atomic: verify signature + check nonce + rotate — all under the lock
-    let user_db = state.user_db.clone();
-    let token = tokio::task::spawn_blocking(move || -> Result<String, super::auth::AuthError> {
-        // Verify EIP-712 signature
-        super::auth::recover_eip712_signer(&address, &nonce, &issued_at, &signature)?;
+    let exclude = exclude_clause();
+    let (info, rows) = tokio::join!(
+        markets::resolve_markets(
+            &state.http,
+            &state.gamma,
+            &state.db,
+            &state.market_cache,
+            std::slice::from_ref(&token_id),
+        ),
+        state
+            .db
+            .query(&format!(
+                "SELECT
+                    toString(toStartOfHour(block_timestamp)) AS bucket,
+                    toString(sumIf(usdc_amount, side = 'buy')) AS buy_volume,
+                    toString(sumIf(usdc_amount, side = 'sell')) AS sell_volume,
+                    countIf(side = 'buy') AS buy_count,
+                    countIf(side = 'sell') AS sell_count,
+                    toString(sumIf(usdc_amount, side = 'buy' AND toFloat64(usdc_amount) >= ?)) AS whale_buy_volume,
+                    toString(sumIf(usdc_amount, side = 'sell' AND toFloat64(usdc_amount) >= ?)) AS whale_sell_volume,
+                    countIf(side = 'buy' AND toFloat64(usdc_amount) >= ?) AS whale_buy_count,
+                    countIf(side = 'sell' AND toFloat64(usdc_amount) >= ?) AS whale_sell_count
+                FROM poly_dearboard.trades
+                PREWHERE block_timestamp >= now() - INTERVAL {hours} HOUR
+                WHERE asset_id = ?
+                  AND trader NOT IN ({exclude})
+                GROUP BY bucket"
+            ))
+            .bind(whale_min)
+            .bind(whale_min)
+            .bind(whale_min)
+            .bind(whale_min)
+            .bind(&token_id)
+            .fetch_all::<MarketFlowRow>(),
+    );
+    let rows = rows.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let info = info.get(&token_id);
+
+    let mut by_bucket: std::collections::HashMap<String, MarketFlowRow> =
+        rows.into_iter().map(|r| (r.bucket.clone(), r)).collect();
+    let mut flow = FlowTotals::default();
+    let mut whale = FlowTotals::default();
+    let mut buckets = Vec::new();
+    // Zero-fill every hour of the window, oldest first
+    let now = chrono::Utc::now().naive_utc();
+    let align =
+        |t: chrono::NaiveDateTime| t.duration_trunc(chrono::Duration::hours(1)).unwrap_or(t);
+    let mut t = align(now - chrono::Duration::hours(hours));
+    while t <= align(now) {
+        let bucket = t.format("%Y-%m-%d %H:%M:%S").to_string();
+        let (b_flow, b_whale) = match by_bucket.remove(&bucket) {
+            Some(r) => (
+                FlowTotals::new(&r.buy_volume, &r.sell_volume, r.buy_count, r.sell_count),
+                FlowTotals::new(
+                    &r.whale_buy_volume,
+                    &r.whale_sell_volume,
+                    r.whale_buy_count,
+                    r.whale_sell_count,
+                ),
+            ),
+            None => (FlowTotals::default(), FlowTotals::default()),
+        };
+        flow.add(&b_flow);
+        whale.add(&b_whale);
+        buckets.push(MarketFlowBucket {
+            bucket,
+            flow: b_flow.into(),
+            whale: b_whale.into(),
+        });
+        t += chrono::Duration::hours(1);
+    }
 
-        // Verify nonce + issued_at match DB, then rotate
-        let conn = user_db.lock().expect("user_db lock poisoned");
-        let valid = super::db::verify_and_rotate_nonce(&conn, &address, &nonce, &issued_at)
-            .map_err(|_| super::auth::AuthError::InvalidToken)?;
+    Ok(Json(MarketFlowResponse {
+        token_id: info
+            .map(|i| i.gamma_token_id.clone())
+            .unwrap_or_else(|| markets::to_integer_id(&token_id)),
+        question: info.map(|i| i.question.clone()).unwrap_or_default(),
+        outcome: info.map(|i| i.outcome.clone()).unwrap_or_default(),
+        period: period.to_string(),
+        whale_min_usdc: whale_min,
+        flow: flow.into(),
+        whale: whale.into(),
+        buckets,
+    }))
+}
 
-        if !valid {
-            return Err(super::auth::AuthError::NonceMismatch);
+/// Running buy/sell sums for `market_flow`
+#[derive(Default)]
+struct FlowTotals {
+    buy_volume: f64,
+    sell_volume: f64,
+    buy_count: u64,
+    sell_count: u64,
+}
+
+impl FlowTotals {
+    fn new(buy_volume: &str, sell_volume: &str, buy_count: u64, sell_count: u64) -> Self {
+        Self {
+            buy_volume: buy_volume.parse().unwrap_or(0.0),
+            sell_volume: sell_volume.parse().unwrap_or(0.0),
+            buy_count,
+            sell_count,
         }
+    }
 
-        Ok(super::auth::issue_jwt(&address, &jwt_secret))
-    })
-    .await
-    .map_err(|_| super::auth::AuthError::InvalidToken)??;
+    fn add(&mut self, other: &Self) {
+        self.buy_volume += other.buy_volume;
+        self.sell_volume += other.sell_volume;
+        self.buy_count += other.buy_count;
+        self.sell_count += other.sell_count;
+    }
+}
 
-    let address = body.address.to_lowercase();
-    Ok(Json(
-        serde_json::json!({ "token": token, "address": address }),
-    ))
+impl From<FlowTotals> for MarketFlowStats {
+    fn from(t: FlowTotals) -> Self {
+        Self {
+            buy_volume: format!("{:.6}", t.buy_volume),
+            sell_volume: format!("{:.6}", t.sell_volume),
+            net_flow: format!("{:.6}", t.buy_volume - t.sell_volume),
+            buy_count: t.buy_count,
+            sell_count: t.sell_count,
+        }
+    }
 }
 
-pub async fn smart_money(
+const MARKET_CONCENTRATION_PERIODS: &[&str] = &["1h", "24h", "7d", "30d", "all"];
+const MARKET_CONCENTRATION_TOP_N: u32 = 5;
+
+/// How concentrated a market's volume is across traders: the Herfindahl index
+/// (sum of squared volume shares) and the top traders' share, both 0-1.
+pub async fn market_concentration(
     State(state): State<AppState>,
-    Query(params): Query<SmartMoneyParams>,
+    Path(token_id): Path<String>,
+    Query(params): Query<MarketConcentrationParams>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let period = params.period.as_deref().unwrap_or("24h");
+    let (info, id_list) = market_for_token(&state, &token_id).await?;
     let exclude = exclude_clause();
-    let top = params.top.unwrap_or(10).clamp(1, 50);
-    let timeframe = params.timeframe.as_deref().unwrap_or("all");
 
-    let rows = if timeframe == "all" {
-        // All-time: read from pre-aggregated trader_positions
-        let query = format!(
-            "WITH
-                resolved AS (
-                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-                    FROM poly_dearboard.resolved_prices FINAL
-                ),
-                trader_pnl AS (
-                    SELECT p.trader,
-                           sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) AS total_pnl
-                    FROM poly_dearboard.trader_positions p
-                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-                    WHERE p.trader NOT IN ({exclude})
-                    GROUP BY p.trader
-                    ORDER BY total_pnl DESC
-                    LIMIT {top}
-                ),
-                smart_positions AS (
-                    SELECT p.asset_id AS asset_id,
-                           (p.buy_amount - p.sell_amount) AS net_tokens,
-                           toFloat64(lp.latest_price) AS price,
-                           toFloat64(p.buy_amount - p.sell_amount) * toFloat64(lp.latest_price) AS exposure
-                    FROM poly_dearboard.trader_positions p
-                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-                    WHERE p.trader IN (SELECT trader FROM trader_pnl)
-                      AND rp.resolved_price IS NULL
-                      AND toFloat64(lp.latest_price) > 0.01
-                      AND toFloat64(lp.latest_price) < 0.99
-                      AND abs(p.buy_amount - p.sell_amount) > 0.01
-                )
-            SELECT
-                asset_id,
-                count() AS smart_trader_count,
-                countIf(net_tokens > 0) AS long_count,
-                countIf(net_tokens < 0) AS short_count,
-                toString(sum(if(net_tokens > 0, exposure, toFloat64(0)))) AS long_exposure,
-                toString(sum(if(net_tokens < 0, abs(exposure), toFloat64(0)))) AS short_exposure,
-                toString(avg(price)) AS avg_price
-            FROM smart_positions
-            GROUP BY asset_id
-            ORDER BY count() DESC, sum(abs(exposure)) DESC
-            LIMIT 20"
-        );
+    // Level 1: volume per trader over the period, from the cheapest source that covers it
+    let per_trader = match period {
+        "1h" | "24h" => {
+            let interval = if period == "1h" { "1 HOUR" } else { "24 HOUR" };
+            format!(
+                "SELECT trader, toFloat64(sum(usdc_amount)) AS v
+                FROM poly_dearboard.trades
+                PREWHERE block_timestamp >= now() - INTERVAL {interval}
+                WHERE asset_id IN ({id_list}) AND trader NOT IN ({exclude})
+                GROUP BY trader"
+            )
+        }
+        "7d" | "30d" => {
+            let days = if period == "7d" { 7 } else { 30 };
+            format!(
+                "SELECT trader, sum(buy_usdc) + sum(sell_usdc) AS v
+                FROM poly_dearboard.pnl_daily
+                WHERE day >= today() - {days}
+                  AND asset_id IN ({id_list}) AND trader NOT IN ({exclude})
+                GROUP BY trader"
+            )
+        }
+        "all" => format!(
+            "SELECT trader, toFloat64(sum(total_volume)) AS v
+            FROM poly_dearboard.trader_positions
+            WHERE asset_id IN ({id_list}) AND trader NOT IN ({exclude})
+            GROUP BY trader"
+        ),
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid period. Allowed: {MARKET_CONCENTRATION_PERIODS:?}"),
+            ));
+        }
+    };
 
+    // Level 2: squared-share sum over traders; sum(v²) / sum(v)² = Σ share²
+    let (totals, top) = tokio::try_join!(
         state
             .db
-            .query(&query)
-            .fetch_all::<SmartMoneyRow>()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    } else {
-        // Time-windowed (1h/24h): read from raw trades (within TTL) + asset_latest_price
-        let prewhere = match timeframe {
-            "1h" => "PREWHERE block_timestamp >= now() - INTERVAL 1 HOUR",
-            "24h" => "PREWHERE block_timestamp >= now() - INTERVAL 24 HOUR",
-            _ => "",
-        };
-
-        let query = format!(
-            "WITH
-                resolved AS (
-                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-                    FROM poly_dearboard.resolved_prices FINAL
-                ),
-                trader_pnl AS (
-                    SELECT trader,
-                           sum(cash_flow + net_tokens * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) AS total_pnl
-                    FROM (
-                        SELECT trader, asset_id,
-                               sumIf(amount, side = 'buy') - sumIf(amount, side = 'sell') AS net_tokens,
-                               sumIf(usdc_amount, side = 'sell') - sumIf(usdc_amount, side = 'buy') AS cash_flow
-                        FROM poly_dearboard.trades
-                        {prewhere}
-                        WHERE trader NOT IN ({exclude})
-                        GROUP BY trader, asset_id
-                    ) p
-                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-                    GROUP BY trader
-                    ORDER BY total_pnl DESC
-                    LIMIT {top}
-                ),
-                smart_positions AS (
-                    SELECT p.asset_id AS asset_id,
-                           p.net_tokens AS net_tokens,
-                           toFloat64(lp.latest_price) AS price,
-                           p.net_tokens * toFloat64(lp.latest_price) AS exposure
-                    FROM (
-                        SELECT trader, asset_id,
-                               sumIf(amount, side = 'buy') - sumIf(amount, side = 'sell') AS net_tokens
-                        FROM poly_dearboard.trades
-                        WHERE trader IN (SELECT trader FROM trader_pnl)
-                        GROUP BY trader, asset_id
-                        HAVING abs(net_tokens) > 0.01
-                    ) p
-                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-                    WHERE rp.resolved_price IS NULL
-                      AND toFloat64(lp.latest_price) > 0.01
-                      AND toFloat64(lp.latest_price) < 0.99
-                )
-            SELECT
-                asset_id,
-                count() AS smart_trader_count,
-                countIf(net_tokens > 0) AS long_count,
-                countIf(net_tokens < 0) AS short_count,
-                toString(sum(if(net_tokens > 0, exposure, 0))) AS long_exposure,
-                toString(sum(if(net_tokens < 0, abs(exposure), 0))) AS short_exposure,
-                toString(avg(price)) AS avg_price
-            FROM smart_positions
-            GROUP BY asset_id
-            ORDER BY count() DESC, sum(abs(exposure)) DESC
-            LIMIT 20"
-        );
-
+            .query(&format!(
+                "SELECT
+                    count() AS traders,
+                    toString(sum(v)) AS volume,
+                    if(sum(v) > 0, sum(v * v) / pow(sum(v), 2), 0) AS herfindahl
+                FROM ({per_trader})
+                WHERE v > 0"
+            ))
+            .fetch_one::<ConcentrationTotalsRow>(),
         state
             .db
-            .query(&query)
-            .fetch_all::<SmartMoneyRow>()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    };
-
-    let token_ids: Vec<String> = rows.iter().map(|r| r.asset_id.clone()).collect();
-    let market_info =
-        markets::resolve_markets(&state.http, &state.db, &state.market_cache, &token_ids).await;
-
-    // Merge Yes/No tokens of the same market into one entry
-    let mut merged: std::collections::HashMap<String, SmartMoneyMarket> =
-        std::collections::HashMap::new();
-
-    for r in rows {
-        let info = market_info.get(&r.asset_id);
-
-        // Skip resolved/inactive/uncached markets
-        if info.map(|i| !i.active).unwrap_or(true) {
-            continue;
-        }
-
-        let info = info.unwrap(); // safe: None handled above
-        let question = info.question.clone();
-        let token_id = info.gamma_token_id.clone();
-        let outcome = info.outcome.clone();
-
-        let long_exp: f64 = r.long_exposure.parse().unwrap_or(0.0);
-        let short_exp: f64 = r.short_exposure.parse().unwrap_or(0.0);
+            .query(&format!(
+                "SELECT toString(trader) AS address, v AS volume
+                FROM ({per_trader})
+                WHERE v > 0
+                ORDER BY v DESC
+                LIMIT ?"
+            ))
+            .bind(MARKET_CONCENTRATION_TOP_N)
+            .fetch_all::<ConcentrationTraderRow>(),
+    )
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-        if let Some(existing) = merged.get_mut(&question) {
-            let existing_long: f64 = existing.long_exposure.parse().unwrap_or(0.0);
-            let existing_short: f64 = existing.short_exposure.parse().unwrap_or(0.0);
-            existing.long_exposure = format!("{:.6}", existing_long + long_exp);
-            existing.short_exposure = format!("{:.6}", existing_short + short_exp);
-            existing.long_count += r.long_count;
-            existing.short_count += r.short_count;
-            existing.smart_trader_count = existing.smart_trader_count.max(r.smart_trader_count);
+    let total: f64 = totals.volume.parse().unwrap_or(0.0);
+    let share = |v: f64| {
+        if total > 0.0 {
+            (v / total).clamp(0.0, 1.0)
         } else {
-            merged.insert(
-                question.clone(),
-                SmartMoneyMarket {
-                    token_id,
-                    question,
-                    outcome,
-                    smart_trader_count: r.smart_trader_count,
-                    long_count: r.long_count,
-                    short_count: r.short_count,
-                    long_exposure: r.long_exposure,
-                    short_exposure: r.short_exposure,
-                    avg_price: r.avg_price,
-                },
-            );
+            0.0
         }
-    }
-
-    let mut markets: Vec<SmartMoneyMarket> = merged.into_values().collect();
-    markets.sort_by(|a, b| {
-        b.smart_trader_count
-            .cmp(&a.smart_trader_count)
-            .then_with(|| {
-                let a_total: f64 = a.long_exposure.parse::<f64>().unwrap_or(0.0)
-                    + a.short_exposure.parse::<f64>().unwrap_or(0.0);
-                let b_total: f64 = b.long_exposure.parse::<f64>().unwrap_or(0.0)
-                    + b.short_exposure.parse::<f64>().unwrap_or(0.0);
-                b_total
-                    .partial_cmp(&a_total)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
-    });
-    markets.truncate(10);
+    };
+    let top_traders: Vec<ConcentrationTrader> = top
+        .into_iter()
+        .map(|r| ConcentrationTrader {
+            address: r.address.to_lowercase(),
+            volume: format!("{:.6}", r.volume),
+            share: share(r.volume),
+        })
+        .collect();
 
-    Ok(Json(SmartMoneyResponse { markets, top }))
+    Ok(Json(MarketConcentrationResponse {
+        question: info.question,
+        all_token_ids: info.all_token_ids,
+        period: period.to_string(),
+        traders: totals.traders,
+        volume: format!("{total:.6}"),
+        herfindahl: totals.herfindahl.clamp(0.0, 1.0),
+        top_share: top_traders.iter().map(|t| t.share).sum::<f64>().min(1.0),
+        top_traders,
+    }))
 }
 
-pub async fn trader_profile(
+const MARKET_HOLDERS_LIMIT: u32 = 50;
+/// Positions with fewer net tokens than this are dust, not holdings
+const MARKET_HOLDERS_MIN_NET_TOKENS: f64 = 1.0;
+
+/// Top holders of a market by absolute net exposure (net tokens × latest price),
+/// across both tokens of the pair, split into longs and shorts.
+pub async fn market_holders(
     State(state): State<AppState>,
-    Path(address): Path<String>,
+    Path(token_id): Path<String>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let address = address.to_lowercase();
-
-    // Query 1: aggregate stats
-    let agg = state
-        .db
-        .query(
-            "WITH resolved AS (
-                SELECT asset_id, resolved_price FROM poly_dearboard.resolved_prices FINAL
-            )
-            SELECT
-                toString(ROUND(avg(tp.total_volume), 6)) AS avg_position_size,
-                avg(if(tp.first_ts IS NOT NULL AND tp.last_ts IS NOT NULL,
-                    dateDiff('hour', tp.first_ts, tp.last_ts), 0)) AS avg_hold_time_hours,
-                count() AS total_positions,
-                countIf(rp.asset_id != '') AS resolved_positions
-            FROM poly_dearboard.trader_positions tp FINAL
-            LEFT JOIN resolved rp ON tp.asset_id = rp.asset_id
-            WHERE lower(tp.trader) = ?",
-        )
-        .bind(&address)
-        .fetch_optional::<ProfileAggRow>()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-    let agg = match agg {
-        Some(a) => a,
-        None => return Err((StatusCode::NOT_FOUND, "Trader not found".into())),
-    };
+    let (info, id_list) = market_for_token(&state, &token_id).await?;
+    let exclude = exclude_clause();
+    let pos_pnl = all_time_position_pnl(false);
 
-    // Query 2: all positions with PnL (for biggest win/loss, categories, labels)
-    let positions = state
+    let rows = state
         .db
-        .query(
+        .query(&format!(
             "WITH resolved AS (
                 SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
                 FROM poly_dearboard.resolved_prices FINAL
             )
             SELECT
-                tp.asset_id,
-                toString(ROUND((tp.sell_usdc - tp.buy_usdc)
-                    + (tp.buy_amount - tp.sell_amount)
-                    * coalesce(rp.resolved_price, toFloat64(lp.latest_price)), 6)) AS pnl,
-                toString(tp.total_volume) AS total_volume,
-                tp.trade_count,
-                toString(tp.buy_amount - tp.sell_amount) AS net_tokens,
-                ifNull(toString(tp.first_ts), '') AS first_ts,
-                ifNull(toString(tp.last_ts), '') AS last_ts,
-                ifNull(toString(rp.resolved_price), '') AS resolved_price,
-                if(rp.resolved_price IS NOT NULL, 1, 0) AS on_chain_resolved,
-                toString(coalesce(toFloat64(lp.latest_price), 0)) AS latest_price,
-                toString(tp.buy_usdc) AS buy_usdc,
-                toString(tp.sell_usdc) AS sell_usdc,
-                toString(tp.buy_amount) AS buy_amount
-            FROM poly_dearboard.trader_positions tp FINAL
+                toString(p.trader) AS trader,
+                p.asset_id,
+                toString(p.buy_amount - p.sell_amount) AS net_tokens,
+                toString(ROUND(toFloat64(p.buy_amount - p.sell_amount)
+                    * coalesce(toFloat64(lp.latest_price), 0), 6)) AS exposure,
+                toString(p.buy_usdc - p.sell_usdc) AS cost_basis,
+                toString(ROUND({pos_pnl}, 6)) AS pnl
+            FROM (
+                SELECT trader, asset_id,
+                       sum(buy_amount) AS buy_amount, sum(sell_amount) AS sell_amount,
+                       sum(buy_usdc) AS buy_usdc, sum(sell_usdc) AS sell_usdc
+                FROM poly_dearboard.trader_positions
+                WHERE asset_id IN ({id_list})
+                  AND trader NOT IN ({exclude})
+                GROUP BY trader, asset_id
+                HAVING abs(toFloat64(buy_amount - sell_amount)) >= ?
+            ) p
             LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) lp
-                ON tp.asset_id = lp.asset_id
-            LEFT JOIN resolved rp ON tp.asset_id = rp.asset_id
-            WHERE lower(tp.trader) = ?",
-        )
-        .bind(&address)
-        .fetch_all::<ProfilePositionRow>()
+                ON p.asset_id = lp.asset_id
+            LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+            ORDER BY abs(toFloat64(p.buy_amount - p.sell_amount)
+                * coalesce(toFloat64(lp.latest_price), 0)) DESC
+            LIMIT ?"
+        ))
+        .bind(MARKET_HOLDERS_MIN_NET_TOKENS)
+        .bind(MARKET_HOLDERS_LIMIT)
+        .fetch_all::<MarketHolderRow>()
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    // Resolve market metadata for all positions
-    let token_ids: Vec<String> = positions.iter().map(|p| p.asset_id.clone()).collect();
-    let market_info =
-        markets::resolve_markets(&state.http, &state.db, &state.market_cache, &token_ids).await;
-
-    // Biggest win / biggest loss
-    let mut best_win: Option<(f64, &ProfilePositionRow)> = None;
-    let mut best_loss: Option<(f64, &ProfilePositionRow)> = None;
-
-    for p in &positions {
-        let pnl: f64 = p.pnl.parse().unwrap_or(0.0);
-        if pnl > 0.0 && best_win.map(|(v, _)| pnl > v).unwrap_or(true) {
-            best_win = Some((pnl, p));
-        }
-        if pnl < 0.0 && best_loss.map(|(v, _)| pnl < v).unwrap_or(true) {
-            best_loss = Some((pnl, p));
+    let addresses: Vec<String> = rows.iter().map(|r| r.trader.to_lowercase()).collect();
+    let mut labels = match tokio::time::timeout(
+        std::time::Duration::from_secs(2),
+        batch_compute_labels(&state, &addresses),
+    )
+    .await
+    {
+        Ok((labels, _)) => labels,
+        Err(_) => {
+            tracing::warn!("batch_compute_labels timed out after 2s (market holders)");
+            std::collections::HashMap::new()
         }
-    }
+    };
 
-    let to_highlight = |row: &ProfilePositionRow| -> PositionHighlight {
-        let info = market_info.get(&row.asset_id);
-        PositionHighlight {
-            asset_id: info
-                .map(|i| i.gamma_token_id.clone())
-                .unwrap_or_else(|| markets::to_integer_id(&row.asset_id)),
-            question: info
-                .map(|i| i.question.clone())
-                .unwrap_or_else(|| shorten_id(&row.asset_id)),
-            outcome: info.map(|i| i.outcome.clone()).unwrap_or_default(),
-            pnl: row.pnl.clone(),
+    let mut longs = Vec::new();
+    let mut shorts = Vec::new();
+    for r in rows {
+        let side = info
+            .all_token_ids
+            .iter()
+            .position(|id| markets::cache_key(id) == markets::cache_key(&r.asset_id));
+        let short = r.net_tokens.starts_with('-');
+        let address = r.trader.to_lowercase();
+        let holder = MarketHolder {
+            labels: labels.remove(&address).unwrap_or_default(),
+            address,
+            token_id: side
+                .map(|i| info.all_token_ids[i].clone())
+                .unwrap_or_else(|| markets::to_integer_id(&r.asset_id)),
+            outcome: side
+                .and_then(|i| info.outcomes.get(i).cloned())
+                .unwrap_or_default(),
+            net_tokens: r.net_tokens,
+            exposure: r.exposure,
+            cost_basis: r.cost_basis,
+            pnl: r.pnl,
+        };
+        if short {
+            shorts.push(holder);
+        } else {
+            longs.push(holder);
         }
-    };
+    }
 
-    let biggest_win = best_win.map(|(_, r)| to_highlight(r));
-    let biggest_loss = best_loss.map(|(_, r)| to_highlight(r));
+    Ok(Json(MarketHoldersResponse {
+        question: info.question,
+        all_token_ids: info.all_token_ids,
+        outcomes: info.outcomes,
+        longs,
+        shorts,
+    }))
+}
 
-    // Category breakdown (hybrid SQL + Rust via MarketCache)
-    let mut cat_map: std::collections::HashMap<String, (f64, u64, f64)> =
-        std::collections::HashMap::new();
-    let mut total_volume: f64 = 0.0;
-    let mut total_trade_count: u64 = 0;
-    let mut earliest_ts: Option<&str> = None;
-    let mut latest_ts: Option<&str> = None;
+/// Latest shared top-trader convergence alerts, newest first.
+pub async fn recent_convergence(State(state): State<AppState>) -> impl IntoResponse {
+    let alerts = state
+        .convergence_history
+        .read()
+        .await
+        .iter()
+        .cloned()
+        .collect();
+    Json(RecentConvergenceResponse { alerts })
+}
 
-    for p in &positions {
-        let category = market_info
-            .get(&p.asset_id)
-            .map(|i| i.category.clone())
-            .unwrap_or_else(|| "Unknown".to_string());
-        let vol: f64 = p.total_volume.parse().unwrap_or(0.0);
-        let pnl: f64 = p.pnl.parse().unwrap_or(0.0);
-        let entry = cat_map.entry(category).or_insert((0.0, 0, 0.0));
-        entry.0 += vol;
-        entry.1 += p.trade_count;
-        entry.2 += pnl;
-        total_volume += vol;
-        total_trade_count += p.trade_count;
+/// Alerts persisted from `/ws/alerts`, newest first.
+pub async fn alert_history(
+    State(state): State<AppState>,
+    Query(params): Query<AlertHistoryParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let limit = params.limit.unwrap_or(100).min(500);
+    if params
+        .kind
+        .as_deref()
+        .is_some_and(|k| !HISTORY_ALERT_KINDS.contains(&k))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid kind. Allowed: {HISTORY_ALERT_KINDS:?}"),
+        ));
+    }
+    let since = parse_time_bound("since", params.since.as_deref())?;
 
-        if !p.first_ts.is_empty() {
-            if earliest_ts.map(|e| p.first_ts.as_str() < e).unwrap_or(true) {
-                earliest_ts = Some(&p.first_ts);
-            }
-        }
-        if !p.last_ts.is_empty() {
-            if latest_ts.map(|l| p.last_ts.as_str() > l).unwrap_or(true) {
-                latest_ts = Some(&p.last_ts);
-            }
-        }
+    let mut conditions = Vec::new();
+    if params.kind.is_some() {
+        conditions.push("kind = ?");
     }
+    if since.is_some() {
+        conditions.push("timestamp >= toDateTime(?)");
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
 
-    let mut category_breakdown: Vec<CategoryStats> = cat_map
-        .into_iter()
-        .map(|(cat, (vol, tc, pnl))| CategoryStats {
-            category: cat,
-            volume: format!("{:.6}", vol),
-            trade_count: tc,
-            pnl: format!("{:.6}", pnl),
-        })
+    let mut q = state.db.query(&format!(
+        "SELECT payload
+        FROM poly_dearboard.alerts_history FINAL
+        {where_clause}
+        ORDER BY timestamp DESC, block_number DESC
+        LIMIT ?"
+    ));
+    if let Some(kind) = &params.kind {
+        q = q.bind(kind);
+    }
+    if let Some(since) = since {
+        q = q.bind(since);
+    }
+    let payloads = q
+        .bind(limit)
+        .fetch_all::<String>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let alerts = payloads
+        .iter()
+        .filter_map(|p| serde_json::from_str(p).ok())
         .collect();
-    category_breakdown.sort_by(|a, b| {
-        let va: f64 = a.volume.parse().unwrap_or(0.0);
-        let vb: f64 = b.volume.parse().unwrap_or(0.0);
-        vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
-    });
+    Ok(Json(AlertHistoryResponse { alerts }))
+}
 
-    // Compute active span
-    let active_span_days = match (earliest_ts, latest_ts) {
-        (Some(e), Some(l)) => {
-            let early = chrono::NaiveDateTime::parse_from_str(e, "%Y-%m-%d %H:%M:%S")
-                .or_else(|_| chrono::NaiveDateTime::parse_from_str(e, "%Y-%m-%dT%H:%M:%S"));
-            let late = chrono::NaiveDateTime::parse_from_str(l, "%Y-%m-%d %H:%M:%S")
-                .or_else(|_| chrono::NaiveDateTime::parse_from_str(l, "%Y-%m-%dT%H:%M:%S"));
-            match (early, late) {
-                (Ok(e), Ok(l)) => (l - e).num_hours() as f64 / 24.0,
-                _ => 0.0,
-            }
-        }
-        _ => 0.0,
-    };
+// -- Wallet Auth (EIP-712 + JWT) --
 
-    let (labels, label_details) = compute_labels(
-        &positions,
-        &market_info,
-        &category_breakdown,
-        total_volume,
-        total_trade_count,
-        positions.len() as u64,
-        active_span_days,
-    );
+#[derive(Deserialize)]
+pub struct NonceParams {
+    pub address: String,
+}
 
-    Ok(Json(TraderProfile {
-        avg_position_size: agg.avg_position_size,
-        avg_hold_time_hours: agg.avg_hold_time_hours,
-        biggest_win,
-        biggest_loss,
-        category_breakdown,
-        total_positions: agg.total_positions,
-        resolved_positions: agg.resolved_positions,
-        labels,
-        label_details,
-    }))
+#[derive(Deserialize)]
+pub struct VerifyBody {
+    pub address: String,
+    pub signature: String,
+    pub nonce: String,
+    pub issued_at: String,
+    /// `eip712` (default) or `siwe` (`personal_sign` over `/auth/siwe-message`)
+    #[serde(default)]
+    pub scheme: SignInScheme,
 }
 
-/// Batch-compute labels for a list of traders (used by leaderboard).
-/// Returns empty map on error — leaderboard still works without labels.
-async fn batch_compute_labels(
-    state: &AppState,
-    addresses: &[String],
-) -> (
-    std::collections::HashMap<String, Vec<BehavioralLabel>>,
-    std::collections::HashMap<String, LabelDetails>,
-) {
-    let mut result = std::collections::HashMap::new();
-    let mut details_map = std::collections::HashMap::new();
-    if addresses.is_empty() {
-        return (result, details_map);
-    }
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SignInScheme {
+    #[default]
+    Eip712,
+    Siwe,
+}
 
-    let in_list = addresses
-        .iter()
-        .map(|a| format!("'{}'", a.replace('\'', "''")))
-        .collect::<Vec<_>>()
-        .join(",");
+pub async fn auth_nonce(
+    State(state): State<AppState>,
+    Query(params): Query<NonceParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let user_db = state.user_db.clone();
+    let address = params.address.to_lowercase();
 
-    let t0 = std::time::Instant::now();
-    let positions: Vec<BatchPositionRow> = match state
-        .db
-        .query(&format!(
-            "WITH resolved AS (
-                SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-                FROM poly_dearboard.resolved_prices FINAL
-            ),
-            filtered AS (
-                SELECT trader, asset_id,
-                       sum(buy_usdc) AS buy_usdc, sum(sell_usdc) AS sell_usdc,
-                       sum(buy_amount) AS buy_amount, sum(sell_amount) AS sell_amount,
-                       sum(total_volume) AS total_volume, sum(trade_count) AS trade_count,
-                       min(first_ts) AS first_ts, max(last_ts) AS last_ts
-                FROM poly_dearboard.trader_positions
-                WHERE lower(trader) IN ({in_list})
-                GROUP BY trader, asset_id
-            )
-            SELECT
-                toString(tp.trader) AS trader,
-                tp.asset_id,
-                toString(ROUND((tp.sell_usdc - tp.buy_usdc)
-                    + (tp.buy_amount - tp.sell_amount)
-                    * coalesce(rp.resolved_price, toFloat64(lp.latest_price)), 6)) AS pnl,
-                toString(tp.total_volume) AS total_volume,
-                tp.trade_count,
-                toString(tp.buy_amount - tp.sell_amount) AS net_tokens,
-                ifNull(toString(tp.first_ts), '') AS first_ts,
-                ifNull(toString(tp.last_ts), '') AS last_ts,
-                ifNull(toString(rp.resolved_price), '') AS resolved_price,
-                if(rp.resolved_price IS NOT NULL, 1, 0) AS on_chain_resolved,
-                toString(coalesce(toFloat64(lp.latest_price), 0)) AS latest_price,
-                toString(tp.buy_usdc) AS buy_usdc,
-                toString(tp.sell_usdc) AS sell_usdc,
-                toString(tp.buy_amount) AS buy_amount
-            FROM filtered tp
-            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) lp
-                ON tp.asset_id = lp.asset_id
-            LEFT JOIN resolved rp ON tp.asset_id = rp.asset_id"
-        ))
-        .fetch_all()
-        .await
-    {
-        Ok(rows) => {
-            tracing::debug!("batch labels: CH query returned {} rows in {:?}", rows.len(), t0.elapsed());
-            rows
-        }
-        Err(e) => {
-            tracing::warn!("Failed to batch-query positions for labels: {e}");
-            return (result, details_map);
-        }
+    let (nonce, issued_at) = tokio::task::spawn_blocking(move || {
+        let conn = user_db.lock().expect("user_db lock poisoned");
+        super::db::get_or_create_user(&conn, &address)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        serde_json::json!({ "nonce": nonce, "issuedAt": issued_at }),
+    ))
+}
+
+/// Issues a nonce like `/auth/nonce` and returns the EIP-4361 message to sign
+/// with `personal_sign` (then `POST /auth/verify` with `scheme: "siwe"`).
+pub async fn auth_siwe_message(
+    State(state): State<AppState>,
+    Query(params): Query<NonceParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let wallet: alloy_primitives::Address = middleware::validate_eth_address(&params.address)
+        .ok()
+        .and_then(|a| a.parse().ok())
+        .ok_or((StatusCode::BAD_REQUEST, "Invalid address".to_string()))?;
+    let user_db = state.user_db.clone();
+    let address = params.address.to_lowercase();
+
+    let (nonce, issued_at) = tokio::task::spawn_blocking(move || {
+        let conn = user_db.lock().expect("user_db lock poisoned");
+        super::db::get_or_create_user(&conn, &address)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let message = super::auth::siwe_message(&state.siwe_uri, wallet, &nonce, &issued_at);
+    Ok(Json(
+        serde_json::json!({ "message": message, "nonce": nonce, "issuedAt": issued_at }),
+    ))
+}
+
+pub async fn auth_verify(
+    State(state): State<AppState>,
+    Json(body): Json<VerifyBody>,
+) -> Result<impl IntoResponse, super::auth::AuthError> {
+    let address = body.address.to_lowercase();
+    let signature = body.signature.clone();
+    let nonce = body.nonce.clone();
+    let issued_at = body.issued_at.clone();
+    let jwt_secret = state.jwt_secret.clone();
+
+    // Verify the signature (EOA, or EIP-1271 contract wallet via RPC)
+    let message = match body.scheme {
+        SignInScheme::Eip712 => super::auth::SignInMessage::Eip712,
+        SignInScheme::Siwe => super::auth::SignInMessage::Siwe {
+            uri: &state.siwe_uri,
+        },
     };
+    super::auth::verify_signin_signature(
+        &state.http,
+        &state.erpc_url,
+        message,
+        &address,
+        &nonce,
+        &issued_at,
+        &signature,
+    )
+    .await?;
 
-    // Cache-only market info lookup (no Gamma API calls — leaderboard must stay fast)
-    let market_info: std::collections::HashMap<String, markets::MarketInfo> = {
-        let cache = state.market_cache.read().await;
-        let mut info = std::collections::HashMap::new();
-        for p in &positions {
-            let key = markets::cache_key(&p.asset_id);
-            if let Some(m) = cache.get(&key) {
-                info.insert(p.asset_id.clone(), m.clone());
-            }
+    // Atomic: check nonce + rotate under the lock
+    let user_db = state.user_db.clone();
+    let token = tokio::task::spawn_blocking(move || -> Result<String, super::auth::AuthError> {
+        // Verify nonce + issued_at match DB, then rotate
+        let conn = user_db.lock().expect("user_db lock poisoned");
+        let valid = super::db::verify_and_rotate_nonce(&conn, &address, &nonce, &issued_at)
+            .map_err(|_| super::auth::AuthError::InvalidToken)?;
+
+        if !valid {
+            return Err(super::auth::AuthError::NonceMismatch);
         }
-        info
-    };
+
+        Ok(super::auth::issue_jwt(&address, &jwt_secret))
+    })
+    .await
+    .map_err(|_| super::auth::AuthError::InvalidToken)??;
+
+    let address = body.address.to_lowercase();
+    Ok(Json(
+        serde_json::json!({ "token": token, "address": address }),
+    ))
+}
+
+/// Issues a fresh token for a valid one, up to the absolute session lifetime
+/// (`JWT_MAX_SESSION_DAYS`); after that the wallet must sign in again.
+pub async fn auth_refresh(
+    State(state): State<AppState>,
+    AuthUser(address): AuthUser,
+    headers: axum::http::HeaderMap,
+) -> Result<impl IntoResponse, super::auth::AuthError> {
+    let token = middleware::bearer_token(&headers).ok_or(super::auth::AuthError::InvalidToken)?;
+    let token = super::auth::refresh_jwt(
+        token,
+        &state.jwt_secret,
+        &state.revocations,
+        state.jwt_max_session_secs,
+    )?;
+    Ok(Json(
+        serde_json::json!({ "token": token, "address": address }),
+    ))
+}
+
+/// Revokes the presented token.
+pub async fn auth_logout(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let session = presented_session(&state, &headers)?;
+    let (jti, address, expires_at) = (session.jti.clone(), session.address, session.expires_at);
+    with_user_db(&state, move |conn| {
+        db::revoke_token(conn, &jti, &address, expires_at)
+    })
+    .await?;
+    state
+        .revocations
+        .write()
+        .unwrap_or_else(|p| p.into_inner())
+        .revoke(&session.jti, session.expires_at);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revokes every token issued to the caller before this second, plus the
+/// presented one. Tokens from the current second stay valid so an immediate
+/// re-sign-in is not rejected.
+pub async fn auth_logout_all(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let session = presented_session(&state, &headers)?;
+    let now = chrono::Utc::now().timestamp() as u64;
+    let (jti, address, expires_at) = (
+        session.jti.clone(),
+        session.address.clone(),
+        session.expires_at,
+    );
+    with_user_db(&state, move |conn| {
+        let tx = conn.unchecked_transaction()?;
+        db::revoke_token(&tx, &jti, &address, expires_at)?;
+        db::set_session_cutoff(&tx, &address, now)?;
+        tx.commit()
+    })
+    .await?;
+    let mut revocations = state.revocations.write().unwrap_or_else(|p| p.into_inner());
+    revocations.revoke(&session.jti, session.expires_at);
+    revocations.revoke_all(&session.address, now);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn presented_session(
+    state: &AppState,
+    headers: &axum::http::HeaderMap,
+) -> Result<super::auth::TokenSession, (StatusCode, String)> {
+    middleware::bearer_token(headers)
+        .and_then(|token| {
+            super::auth::token_session(token, &state.jwt_secret, &state.revocations).ok()
+        })
+        .ok_or((StatusCode::UNAUTHORIZED, "Invalid token".into()))
+}
+
+/// Runs a user-DB write on the blocking pool.
+async fn with_user_db(
+    state: &AppState,
+    f: impl FnOnce(&rusqlite::Connection) -> Result<(), rusqlite::Error> + Send + 'static,
+) -> Result<(), (StatusCode, String)> {
+    let user_db = state.user_db.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = user_db.lock().unwrap_or_else(|p| p.into_inner());
+        f(&conn)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+pub async fn smart_money(
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
+    Query(params): Query<SmartMoneyParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let exclude = exclude_clause();
+    let timeframe = params.timeframe.as_deref().unwrap_or("all");
+
+    // List mode: the caller's list members replace the global top-N PnL set
+    let list = match params.list_id.clone() {
+        None => None,
+        Some(_) if params.top.is_some() => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Specify list_id or top, not both".into(),
+            ));
+        }
+        Some(list_id) => {
+            let Some(AuthUser(owner)) = user else {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    "Sign in to use a trader list".into(),
+                ));
+            };
+            let detail = with_list_db(&state, move |conn| {
+                db::get_trader_list(conn, &list_id, &owner)
+            })
+            .await?;
+            if detail.members.is_empty() {
+                return Err((StatusCode::BAD_REQUEST, "List has no members".into()));
+            }
+            Some(detail)
+        }
+    };
+    let top = match &list {
+        Some(detail) => detail.members.len() as u32,
+        None => params.top.unwrap_or(10).clamp(1, 50),
+    };
+    let in_list = list.as_ref().map(|detail| {
+        detail
+            .members
+            .iter()
+            .map(|m| format!("'{}'", m.address.to_lowercase()))
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+    // Filter for positions held by the smart set (`col` is the trader column)
+    let smart_filter = |col: &str| match &in_list {
+        Some(_) => format!("lower({col}) IN (SELECT trader FROM trader_pnl)"),
+        None => format!("{col} IN (SELECT trader FROM trader_pnl)"),
+    };
+
+    let rows = if timeframe == "all" {
+        // All-time: read from pre-aggregated trader_positions
+        let trader_pnl = match &in_list {
+            Some(in_list) => format!("trader_pnl AS (SELECT arrayJoin([{in_list}]) AS trader)"),
+            None => format!(
+                "trader_pnl AS (
+                    SELECT p.trader,
+                           sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) AS total_pnl
+                    FROM poly_dearboard.trader_positions p
+                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+                    WHERE p.trader NOT IN ({exclude})
+                    GROUP BY p.trader
+                    ORDER BY total_pnl DESC
+                    LIMIT {top}
+                )"
+            ),
+        };
+        let held_by_smart = smart_filter("p.trader");
+        let query = format!(
+            "WITH
+                resolved AS (
+                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                    FROM poly_dearboard.resolved_prices FINAL
+                ),
+                {trader_pnl},
+                smart_positions AS (
+                    SELECT p.asset_id AS asset_id,
+                           (p.buy_amount - p.sell_amount) AS net_tokens,
+                           toFloat64(lp.latest_price) AS price,
+                           toFloat64(p.buy_amount - p.sell_amount) * toFloat64(lp.latest_price) AS exposure
+                    FROM poly_dearboard.trader_positions p
+                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+                    WHERE {held_by_smart}
+                      AND rp.resolved_price IS NULL
+                      AND toFloat64(lp.latest_price) > 0.01
+                      AND toFloat64(lp.latest_price) < 0.99
+                      AND abs(p.buy_amount - p.sell_amount) > 0.01
+                )
+            SELECT
+                asset_id,
+                count() AS smart_trader_count,
+                countIf(net_tokens > 0) AS long_count,
+                countIf(net_tokens < 0) AS short_count,
+                toString(sum(if(net_tokens > 0, exposure, toFloat64(0)))) AS long_exposure,
+                toString(sum(if(net_tokens < 0, abs(exposure), toFloat64(0)))) AS short_exposure,
+                toString(avg(price)) AS avg_price
+            FROM smart_positions
+            GROUP BY asset_id
+            ORDER BY count() DESC, sum(abs(exposure)) DESC
+            LIMIT 20"
+        );
+
+        state
+            .db
+            .query(&query)
+            .fetch_all::<SmartMoneyRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    } else {
+        // Time-windowed (1h/24h): read from raw trades (within TTL) + asset_latest_price
+        let prewhere = match timeframe {
+            "1h" => "PREWHERE block_timestamp >= now() - INTERVAL 1 HOUR",
+            "24h" => "PREWHERE block_timestamp >= now() - INTERVAL 24 HOUR",
+            _ => "",
+        };
+
+        let trader_pnl = match &in_list {
+            Some(in_list) => format!("trader_pnl AS (SELECT arrayJoin([{in_list}]) AS trader)"),
+            None => format!(
+                "trader_pnl AS (
+                    SELECT trader,
+                           sum(cash_flow + net_tokens * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) AS total_pnl
+                    FROM (
+                        SELECT trader, asset_id,
+                               sumIf(amount, side = 'buy') - sumIf(amount, side = 'sell') AS net_tokens,
+                               sumIf(usdc_amount, side = 'sell') - sumIf(usdc_amount, side = 'buy') AS cash_flow
+                        FROM poly_dearboard.trades
+                        {prewhere}
+                        WHERE trader NOT IN ({exclude})
+                        GROUP BY trader, asset_id
+                    ) p
+                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+                    GROUP BY trader
+                    ORDER BY total_pnl DESC
+                    LIMIT {top}
+                )"
+            ),
+        };
+        let held_by_smart = smart_filter("trader");
+        let query = format!(
+            "WITH
+                resolved AS (
+                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                    FROM poly_dearboard.resolved_prices FINAL
+                ),
+                {trader_pnl},
+                smart_positions AS (
+                    SELECT p.asset_id AS asset_id,
+                           p.net_tokens AS net_tokens,
+                           toFloat64(lp.latest_price) AS price,
+                           p.net_tokens * toFloat64(lp.latest_price) AS exposure
+                    FROM (
+                        SELECT trader, asset_id,
+                               sumIf(amount, side = 'buy') - sumIf(amount, side = 'sell') AS net_tokens
+                        FROM poly_dearboard.trades
+                        WHERE {held_by_smart}
+                        GROUP BY trader, asset_id
+                        HAVING abs(net_tokens) > 0.01
+                    ) p
+                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+                    WHERE rp.resolved_price IS NULL
+                      AND toFloat64(lp.latest_price) > 0.01
+                      AND toFloat64(lp.latest_price) < 0.99
+                )
+            SELECT
+                asset_id,
+                count() AS smart_trader_count,
+                countIf(net_tokens > 0) AS long_count,
+                countIf(net_tokens < 0) AS short_count,
+                toString(sum(if(net_tokens > 0, exposure, 0))) AS long_exposure,
+                toString(sum(if(net_tokens < 0, abs(exposure), 0))) AS short_exposure,
+                toString(avg(price)) AS avg_price
+            FROM smart_positions
+            GROUP BY asset_id
+            ORDER BY count() DESC, sum(abs(exposure)) DESC
+            LIMIT 20"
+        );
+
+        state
+            .db
+            .query(&query)
+            .fetch_all::<SmartMoneyRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    };
+
+    let markets = merge_smart_money_rows(&state, rows).await;
+    Ok(Json(SmartMoneyResponse {
+        markets,
+        top,
+        list_name: list.map(|detail| detail.name),
+    }))
+}
+
+/// Enriches per-asset smart money rows, merges Yes/No tokens of the same
+/// market, and keeps the top 10 by trader count, then exposure.
+async fn merge_smart_money_rows(
+    state: &AppState,
+    rows: Vec<SmartMoneyRow>,
+) -> Vec<SmartMoneyMarket> {
+    let token_ids: Vec<String> = rows.iter().map(|r| r.asset_id.clone()).collect();
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &token_ids,
+    )
+    .await;
+
+    // Merge Yes/No tokens of the same market into one entry
+    let mut merged: std::collections::HashMap<String, SmartMoneyMarket> =
+        std::collections::HashMap::new();
+
+    for r in rows {
+        let info = market_info.get(&r.asset_id);
+
+        // Skip resolved/inactive/uncached markets
+        if info.map(|i| !i.active).unwrap_or(true) {
+            continue;
+        }
+
+        let info = info.unwrap(); // safe: None handled above
+        let question = info.question.clone();
+        let token_id = info.gamma_token_id.clone();
+        let outcome = info.outcome.clone();
+
+        let long_exp: f64 = r.long_exposure.parse().unwrap_or(0.0);
+        let short_exp: f64 = r.short_exposure.parse().unwrap_or(0.0);
+
+        if let Some(existing) = merged.get_mut(&question) {
+            let existing_long: f64 = existing.long_exposure.parse().unwrap_or(0.0);
+            let existing_short: f64 = existing.short_exposure.parse().unwrap_or(0.0);
+            existing.long_exposure = format!("{:.6}", existing_long + long_exp);
+            existing.short_exposure = format!("{:.6}", existing_short + short_exp);
+            existing.long_count += r.long_count;
+            existing.short_count += r.short_count;
+            existing.smart_trader_count = existing.smart_trader_count.max(r.smart_trader_count);
+        } else {
+            merged.insert(
+                question.clone(),
+                SmartMoneyMarket {
+                    token_id,
+                    question,
+                    outcome,
+                    smart_trader_count: r.smart_trader_count,
+                    long_count: r.long_count,
+                    short_count: r.short_count,
+                    long_exposure: r.long_exposure,
+                    short_exposure: r.short_exposure,
+                    avg_price: r.avg_price,
+                },
+            );
+        }
+    }
+
+    let mut markets: Vec<SmartMoneyMarket> = merged.into_values().collect();
+    markets.sort_by(|a, b| {
+        b.smart_trader_count
+            .cmp(&a.smart_trader_count)
+            .then_with(|| {
+                let a_total: f64 = a.long_exposure.parse::<f64>().unwrap_or(0.0)
+                    + a.short_exposure.parse::<f64>().unwrap_or(0.0);
+                let b_total: f64 = b.long_exposure.parse::<f64>().unwrap_or(0.0)
+                    + b.short_exposure.parse::<f64>().unwrap_or(0.0);
+                b_total
+                    .partial_cmp(&a_total)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    });
+    markets.truncate(10);
+    markets
+}
+
+/// Every position held by `address` with its all-time PnL (resolved price, else latest).
+async fn fetch_profile_positions(
+    state: &AppState,
+    address: &str,
+) -> Result<Vec<ProfilePositionRow>, (StatusCode, String)> {
+    state
+        .db
+        .query(
+            "WITH resolved AS (
+                SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                FROM poly_dearboard.resolved_prices FINAL
+            )
+            SELECT
+                tp.asset_id,
+                toString(ROUND((tp.sell_usdc - tp.buy_usdc)
+                    + (tp.buy_amount - tp.sell_amount)
+                    * coalesce(rp.resolved_price, toFloat64(lp.latest_price)), 6)) AS pnl,
+                toString(tp.total_volume) AS total_volume,
+                tp.trade_count,
+                toString(tp.buy_amount - tp.sell_amount) AS net_tokens,
+                ifNull(toString(tp.first_ts), '') AS first_ts,
+                ifNull(toString(tp.last_ts), '') AS last_ts,
+                ifNull(toString(rp.resolved_price), '') AS resolved_price,
+                if(rp.resolved_price IS NOT NULL, 1, 0) AS on_chain_resolved,
+                toString(coalesce(toFloat64(lp.latest_price), 0)) AS latest_price,
+                toString(tp.buy_usdc) AS buy_usdc,
+                toString(tp.sell_usdc) AS sell_usdc,
+                toString(tp.buy_amount) AS buy_amount,
+                toString(tp.total_fee) AS total_fee
+            FROM poly_dearboard.trader_positions tp FINAL
+            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) lp
+                ON tp.asset_id = lp.asset_id
+            LEFT JOIN resolved rp ON tp.asset_id = rp.asset_id
+            WHERE lower(tp.trader) = ?",
+        )
+        .bind(address)
+        .fetch_all::<ProfilePositionRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+pub async fn trader_profile(
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
+    ValidatedAddress(address): ValidatedAddress,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // Query 1: aggregate stats
+    let agg = state
+        .db
+        .query(
+            "WITH resolved AS (
+                SELECT asset_id, resolved_price FROM poly_dearboard.resolved_prices FINAL
+            )
+            SELECT
+                toString(ROUND(avg(tp.total_volume), 6)) AS avg_position_size,
+                avg(if(tp.first_ts IS NOT NULL AND tp.last_ts IS NOT NULL,
+                    dateDiff('hour', tp.first_ts, tp.last_ts), 0)) AS avg_hold_time_hours,
+                count() AS total_positions,
+                countIf(rp.asset_id != '') AS resolved_positions
+            FROM poly_dearboard.trader_positions tp FINAL
+            LEFT JOIN resolved rp ON tp.asset_id = rp.asset_id
+            WHERE lower(tp.trader) = ?",
+        )
+        .bind(&address)
+        .fetch_optional::<ProfileAggRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let agg = match agg {
+        Some(a) => a,
+        None => return Err((StatusCode::NOT_FOUND, "Trader not found".into())),
+    };
+
+    // Query 2: all positions with PnL (for biggest win/loss, categories, labels)
+    let positions = fetch_profile_positions(&state, &address).await?;
+
+    // Resolve market metadata for all positions
+    let token_ids: Vec<String> = positions.iter().map(|p| p.asset_id.clone()).collect();
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &token_ids,
+    )
+    .await;
+
+    // Biggest win / biggest loss
+    let mut best_win: Option<(f64, &ProfilePositionRow)> = None;
+    let mut best_loss: Option<(f64, &ProfilePositionRow)> = None;
+
+    for p in &positions {
+        let pnl: f64 = p.pnl.parse().unwrap_or(0.0);
+        if pnl > 0.0 && best_win.map(|(v, _)| pnl > v).unwrap_or(true) {
+            best_win = Some((pnl, p));
+        }
+        if pnl < 0.0 && best_loss.map(|(v, _)| pnl < v).unwrap_or(true) {
+            best_loss = Some((pnl, p));
+        }
+    }
+
+    let biggest_win = best_win.map(|(_, r)| position_highlight(r, &market_info));
+    let biggest_loss = best_loss.map(|(_, r)| position_highlight(r, &market_info));
+
+    // Category breakdown (hybrid SQL + Rust via MarketCache)
+    let mut cat_map: std::collections::HashMap<String, (f64, u64, f64)> =
+        std::collections::HashMap::new();
+    let mut total_volume: f64 = 0.0;
+    let mut total_trade_count: u64 = 0;
+    let mut earliest_ts: Option<&str> = None;
+    let mut latest_ts: Option<&str> = None;
+
+    for p in &positions {
+        let category = market_info
+            .get(&p.asset_id)
+            .map(|i| i.category.clone())
+            .unwrap_or_else(|| "Unknown".to_string());
+        let vol: f64 = p.total_volume.parse().unwrap_or(0.0);
+        let pnl: f64 = p.pnl.parse().unwrap_or(0.0);
+        let entry = cat_map.entry(category).or_insert((0.0, 0, 0.0));
+        entry.0 += vol;
+        entry.1 += p.trade_count;
+        entry.2 += pnl;
+        total_volume += vol;
+        total_trade_count += p.trade_count;
+
+        if !p.first_ts.is_empty() {
+            if earliest_ts.map(|e| p.first_ts.as_str() < e).unwrap_or(true) {
+                earliest_ts = Some(&p.first_ts);
+            }
+        }
+        if !p.last_ts.is_empty() {
+            if latest_ts.map(|l| p.last_ts.as_str() > l).unwrap_or(true) {
+                latest_ts = Some(&p.last_ts);
+            }
+        }
+    }
+
+    let mut category_breakdown: Vec<CategoryStats> = cat_map
+        .into_iter()
+        .map(|(cat, (vol, tc, pnl))| CategoryStats {
+            category: cat,
+            volume: format!("{:.6}", vol),
+            trade_count: tc,
+            pnl: format!("{:.6}", pnl),
+        })
+        .collect();
+    category_breakdown.sort_by(|a, b| {
+        let va: f64 = a.volume.parse().unwrap_or(0.0);
+        let vb: f64 = b.volume.parse().unwrap_or(0.0);
+        vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    // Compute active span
+    let active_span_days = match (earliest_ts, latest_ts) {
+        (Some(e), Some(l)) => match (parse_position_ts(e), parse_position_ts(l)) {
+            (Some(e), Some(l)) => (l - e).num_hours() as f64 / 24.0,
+            _ => 0.0,
+        },
+        _ => 0.0,
+    };
+
+    let (median_hold_time_hours, hold_time_distribution) = hold_time_distribution(&positions);
+
+    let (labels, label_details) = compute_labels(
+        &positions,
+        &market_info,
+        &category_breakdown,
+        total_volume,
+        total_trade_count,
+        positions.len() as u64,
+        active_span_days,
+    );
+
+    let alias = match user {
+        Some(AuthUser(owner)) => {
+            let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+            db::get_trader_aliases(&conn, &owner, std::slice::from_ref(&address))
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .remove(&address)
+        }
+        None => None,
+    };
+
+    Ok(Json(TraderProfile {
+        avg_position_size: agg.avg_position_size,
+        avg_hold_time_hours: agg.avg_hold_time_hours,
+        median_hold_time_hours,
+        hold_time_distribution,
+        biggest_win,
+        biggest_loss,
+        category_breakdown,
+        total_positions: agg.total_positions,
+        resolved_positions: agg.resolved_positions,
+        labels,
+        label_details,
+        alias,
+    }))
+}
+
+fn position_highlight(
+    row: &ProfilePositionRow,
+    market_info: &std::collections::HashMap<String, markets::MarketInfo>,
+) -> PositionHighlight {
+    let info = market_info.get(&row.asset_id);
+    PositionHighlight {
+        asset_id: info
+            .map(|i| i.gamma_token_id.clone())
+            .unwrap_or_else(|| markets::to_integer_id(&row.asset_id)),
+        question: info
+            .map(|i| i.question.clone())
+            .unwrap_or_else(|| shorten_id(&row.asset_id)),
+        outcome: info.map(|i| i.outcome.clone()).unwrap_or_default(),
+        pnl: row.pnl.clone(),
+    }
+}
+
+/// Effective settlement price of a position: on-chain resolution, else 1/0 when the
+/// latest price is within 0.05 of either end (de facto decided). None while live.
+fn settled_price(p: &ProfilePositionRow) -> Option<f64> {
+    if p.on_chain_resolved == 1 {
+        return Some(p.resolved_price.parse().unwrap_or(0.5));
+    }
+    let lp: f64 = p.latest_price.parse().unwrap_or(0.5);
+    if lp >= 0.95 {
+        Some(1.0)
+    } else if lp <= 0.05 {
+        Some(0.0)
+    } else {
+        None
+    }
+}
+
+/// Whether a settled position that was still held at settlement ended on the right side.
+/// None for live positions and for positions fully closed before settlement.
+fn settled_outcome(p: &ProfilePositionRow) -> Option<bool> {
+    let price = settled_price(p)?;
+    let net: f64 = p.net_tokens.parse().unwrap_or(0.0);
+    if net.abs() < 1e-9 {
+        return None;
+    }
+    Some((net > 0.0 && price > 0.5) || (net < 0.0 && price < 0.5))
+}
+
+const CATEGORY_TOP_POSITIONS: usize = 3;
+
+pub async fn trader_categories(
+    State(state): State<AppState>,
+    ValidatedAddress(address): ValidatedAddress,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let positions = fetch_profile_positions(&state, &address).await?;
+    if positions.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "Trader not found".into()));
+    }
+
+    let token_ids: Vec<String> = positions.iter().map(|p| p.asset_id.clone()).collect();
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &token_ids,
+    )
+    .await;
+
+    #[derive(Default)]
+    struct Acc<'a> {
+        settled_pnl: f64,
+        unrealized_pnl: f64,
+        volume: f64,
+        position_count: u64,
+        settled_count: u64,
+        win_count: u64,
+        positions: Vec<(f64, &'a ProfilePositionRow)>,
+    }
+
+    let mut cat_map: std::collections::HashMap<String, Acc> = std::collections::HashMap::new();
+    for p in &positions {
+        let category = market_info
+            .get(&p.asset_id)
+            .map(|i| i.category.clone())
+            .unwrap_or_else(|| "Unknown".to_string());
+        let pnl: f64 = p.pnl.parse().unwrap_or(0.0);
+        let vol: f64 = p.total_volume.parse().unwrap_or(0.0);
+        let net: f64 = p.net_tokens.parse().unwrap_or(0.0);
+
+        let acc = cat_map.entry(category).or_default();
+        // Fully exited positions are realized even if the market is still live
+        if settled_price(p).is_some() || net.abs() < 1e-9 {
+            acc.settled_pnl += pnl;
+        } else {
+            acc.unrealized_pnl += pnl;
+        }
+        match settled_outcome(p) {
+            Some(true) => {
+                acc.settled_count += 1;
+                acc.win_count += 1;
+            }
+            Some(false) => acc.settled_count += 1,
+            None => {}
+        }
+        acc.volume += vol;
+        acc.position_count += 1;
+        acc.positions.push((vol, p));
+    }
+
+    let mut categories: Vec<TraderCategoryStats> = cat_map
+        .into_iter()
+        .map(|(category, mut acc)| {
+            acc.positions
+                .sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+            let top_positions = acc
+                .positions
+                .iter()
+                .take(CATEGORY_TOP_POSITIONS)
+                .map(|(_, row)| position_highlight(row, &market_info))
+                .collect();
+            TraderCategoryStats {
+                category,
+                settled_pnl: format!("{:.6}", acc.settled_pnl),
+                unrealized_pnl: format!("{:.6}", acc.unrealized_pnl),
+                volume: format!("{:.6}", acc.volume),
+                position_count: acc.position_count,
+                settled_count: acc.settled_count,
+                win_rate: if acc.settled_count > 0 {
+                    acc.win_count as f64 * 100.0 / acc.settled_count as f64
+                } else {
+                    0.0
+                },
+                top_positions,
+            }
+        })
+        .collect();
+    categories.sort_by(|a, b| {
+        let va: f64 = a.volume.parse().unwrap_or(0.0);
+        let vb: f64 = b.volume.parse().unwrap_or(0.0);
+        vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(Json(TraderCategoriesResponse { categories }))
+}
+
+/// Upper bound (exclusive, in hours) of each hold-time bucket; the last is open-ended.
+const HOLD_TIME_BUCKETS: &[(&str, f64)] = &[
+    ("<1h", 1.0),
+    ("1-6h", 6.0),
+    ("6-24h", 24.0),
+    ("1-7d", 168.0),
+    (">7d", f64::INFINITY),
+];
+
+fn parse_position_ts(ts: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+}
+
+/// Median hold time (hours) and bucketed hold times across all positions.
+/// Positions still holding tokens on an unresolved market count as open and are
+/// measured up to now instead of `last_ts`.
+fn hold_time_distribution(positions: &[ProfilePositionRow]) -> (f64, Vec<HoldTimeBucket>) {
+    let now = chrono::Utc::now().naive_utc();
+    let mut buckets: Vec<HoldTimeBucket> = HOLD_TIME_BUCKETS
+        .iter()
+        .map(|&(bucket, _)| HoldTimeBucket {
+            bucket,
+            count: 0,
+            open_count: 0,
+        })
+        .collect();
+    let mut hours: Vec<f64> = Vec::with_capacity(positions.len());
+
+    for p in positions {
+        let Some(first) = parse_position_ts(&p.first_ts) else {
+            continue;
+        };
+        let net: f64 = p.net_tokens.parse().unwrap_or(0.0);
+        let is_open = p.on_chain_resolved == 0 && net.abs() >= 0.000001;
+        let end = if is_open {
+            now
+        } else {
+            match parse_position_ts(&p.last_ts) {
+                Some(last) => last,
+                None => continue,
+            }
+        };
+        let held = ((end - first).num_seconds() as f64 / 3600.0).max(0.0);
+        hours.push(held);
+
+        let idx = HOLD_TIME_BUCKETS
+            .iter()
+            .position(|(_, upper)| held < *upper)
+            .unwrap_or(HOLD_TIME_BUCKETS.len() - 1);
+        buckets[idx].count += 1;
+        if is_open {
+            buckets[idx].open_count += 1;
+        }
+    }
+
+    hours.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = hours.len() / 2;
+    let median = match hours.len() {
+        0 => 0.0,
+        n if n % 2 == 0 => (hours[mid - 1] + hours[mid]) / 2.0,
+        _ => hours[mid],
+    };
+
+    (median, buckets)
+}
+
+const PNL_DISTRIBUTION_BUCKETS: usize = 20;
+const PNL_DISTRIBUTION_MAX_BUCKETS: usize = 100;
+
+pub async fn pnl_distribution(
+    State(state): State<AppState>,
+    ValidatedAddress(address): ValidatedAddress,
+    Query(params): Query<PnlDistributionParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let bucket_count = params
+        .buckets
+        .unwrap_or(PNL_DISTRIBUTION_BUCKETS)
+        .clamp(1, PNL_DISTRIBUTION_MAX_BUCKETS);
+
+    let positions = fetch_profile_positions(&state, &address).await?;
+    if positions.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "Trader not found".into()));
+    }
+
+    let mut pnls: Vec<f64> = positions
+        .iter()
+        .map(|p| p.pnl.parse().unwrap_or(0.0))
+        .collect();
+    pnls.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let n = pnls.len() as f64;
+    let mean = pnls.iter().sum::<f64>() / n;
+    let variance = pnls.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    let mid = pnls.len() / 2;
+    let median = if pnls.len().is_multiple_of(2) {
+        (pnls[mid - 1] + pnls[mid]) / 2.0
+    } else {
+        pnls[mid]
+    };
+    let profitable = pnls.iter().filter(|v| **v > 0.0).count();
+
+    // Bucket edges span the observed non-zero range
+    let non_zero: Vec<f64> = pnls.iter().copied().filter(|v| *v != 0.0).collect();
+    let zero_count = (pnls.len() - non_zero.len()) as u64;
+    let buckets = match (non_zero.first(), non_zero.last()) {
+        (Some(&min), Some(&max)) => {
+            let bucket_count = if max > min { bucket_count } else { 1 };
+            let width = (max - min) / bucket_count as f64;
+            let mut counts = vec![0u64; bucket_count];
+            for v in &non_zero {
+                let idx = if width > 0.0 {
+                    (((v - min) / width) as usize).min(bucket_count - 1)
+                } else {
+                    0
+                };
+                counts[idx] += 1;
+            }
+            counts
+                .into_iter()
+                .enumerate()
+                .map(|(i, count)| PnlBucket {
+                    lower: format!("{:.6}", min + width * i as f64),
+                    upper: format!(
+                        "{:.6}",
+                        if i + 1 == bucket_count {
+                            max
+                        } else {
+                            min + width * (i + 1) as f64
+                        }
+                    ),
+                    count,
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(Json(PnlDistributionResponse {
+        buckets,
+        zero_count,
+        total_positions: pnls.len() as u64,
+        median_pnl: format!("{:.6}", median),
+        mean_pnl: format!("{:.6}", mean),
+        stddev_pnl: format!("{:.6}", variance.sqrt()),
+        profitable_share: profitable as f64 / n,
+    }))
+}
+
+/// Batch-compute labels for a list of traders (used by leaderboard).
+/// Returns empty map on error — leaderboard still works without labels.
+async fn batch_compute_labels(
+    state: &AppState,
+    addresses: &[String],
+) -> (
+    std::collections::HashMap<String, Vec<BehavioralLabel>>,
+    std::collections::HashMap<String, LabelDetails>,
+) {
+    let mut result = std::collections::HashMap::new();
+    let mut details_map = std::collections::HashMap::new();
+    if addresses.is_empty() {
+        return (result, details_map);
+    }
+
+    let in_list = addresses
+        .iter()
+        .map(|a| format!("'{}'", a.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let t0 = std::time::Instant::now();
+    let positions: Vec<BatchPositionRow> = match state
+        .db
+        .query(&format!(
+            "WITH resolved AS (
+                SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                FROM poly_dearboard.resolved_prices FINAL
+            ),
+            filtered AS (
+                SELECT trader, asset_id,
+                       sum(buy_usdc) AS buy_usdc, sum(sell_usdc) AS sell_usdc,
+                       sum(buy_amount) AS buy_amount, sum(sell_amount) AS sell_amount,
+                       sum(total_volume) AS total_volume, sum(trade_count) AS trade_count,
+                       sum(total_fee) AS total_fee,
+                       min(first_ts) AS first_ts, max(last_ts) AS last_ts
+                FROM poly_dearboard.trader_positions
+                WHERE lower(trader) IN ({in_list})
+                GROUP BY trader, asset_id
+            )
+            SELECT
+                toString(tp.trader) AS trader,
+                tp.asset_id,
+                toString(ROUND((tp.sell_usdc - tp.buy_usdc)
+                    + (tp.buy_amount - tp.sell_amount)
+                    * coalesce(rp.resolved_price, toFloat64(lp.latest_price)), 6)) AS pnl,
+                toString(tp.total_volume) AS total_volume,
+                tp.trade_count,
+                toString(tp.buy_amount - tp.sell_amount) AS net_tokens,
+                ifNull(toString(tp.first_ts), '') AS first_ts,
+                ifNull(toString(tp.last_ts), '') AS last_ts,
+                ifNull(toString(rp.resolved_price), '') AS resolved_price,
+                if(rp.resolved_price IS NOT NULL, 1, 0) AS on_chain_resolved,
+                toString(coalesce(toFloat64(lp.latest_price), 0)) AS latest_price,
+                toString(tp.buy_usdc) AS buy_usdc,
+                toString(tp.sell_usdc) AS sell_usdc,
+                toString(tp.buy_amount) AS buy_amount,
+                toString(tp.total_fee) AS total_fee
+            FROM filtered tp
+            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) lp
+                ON tp.asset_id = lp.asset_id
+            LEFT JOIN resolved rp ON tp.asset_id = rp.asset_id"
+        ))
+        .fetch_all()
+        .await
+    {
+        Ok(rows) => {
+            tracing::debug!("batch labels: CH query returned {} rows in {:?}", rows.len(), t0.elapsed());
+            rows
+        }
+        Err(e) => {
+            tracing::warn!("Failed to batch-query positions for labels: {e}");
+            return (result, details_map);
+        }
+    };
+
+    // Cache-only market info lookup (no Gamma API calls — leaderboard must stay fast)
+    let market_info: std::collections::HashMap<String, markets::MarketInfo> = {
+        let cache = state.market_cache.read().await;
+        let mut info = std::collections::HashMap::new();
+        for p in &positions {
+            if let Some(m) = cache.get(&p.asset_id) {
+                info.insert(p.asset_id.clone(), m.clone());
+            }
+        }
+        info
+    };
 
     // Group positions by trader
     let mut by_trader: std::collections::HashMap<String, Vec<ProfilePositionRow>> =
         std::collections::HashMap::new();
-    for p in positions {
-        by_trader
-            .entry(p.trader.to_lowercase())
-            .or_default()
-            .push(ProfilePositionRow {
-                asset_id: p.asset_id,
-                pnl: p.pnl,
-                total_volume: p.total_volume,
-                trade_count: p.trade_count,
-                net_tokens: p.net_tokens,
-                first_ts: p.first_ts,
-                last_ts: p.last_ts,
-                resolved_price: p.resolved_price,
-                on_chain_resolved: p.on_chain_resolved,
-                latest_price: p.latest_price,
-                buy_usdc: p.buy_usdc,
-                sell_usdc: p.sell_usdc,
-                buy_amount: p.buy_amount,
-            });
+    for p in positions {
+        by_trader
+            .entry(p.trader.to_lowercase())
+            .or_default()
+            .push(ProfilePositionRow {
+                asset_id: p.asset_id,
+                pnl: p.pnl,
+                total_volume: p.total_volume,
+                trade_count: p.trade_count,
+                net_tokens: p.net_tokens,
+                first_ts: p.first_ts,
+                last_ts: p.last_ts,
+                resolved_price: p.resolved_price,
+                on_chain_resolved: p.on_chain_resolved,
+                latest_price: p.latest_price,
+                buy_usdc: p.buy_usdc,
+                sell_usdc: p.sell_usdc,
+                buy_amount: p.buy_amount,
+                total_fee: p.total_fee,
+            });
+    }
+
+    // Compute labels per trader
+    for (addr, positions) in &by_trader {
+        let mut cat_map: std::collections::HashMap<String, (f64, u64, f64)> =
+            std::collections::HashMap::new();
+        let mut total_volume: f64 = 0.0;
+        let mut total_trade_count: u64 = 0;
+        let mut earliest_ts: Option<&str> = None;
+        let mut latest_ts: Option<&str> = None;
+
+        for p in positions {
+            let category = market_info
+                .get(&p.asset_id)
+                .map(|i| i.category.clone())
+                .unwrap_or_else(|| "Unknown".to_string());
+            let vol: f64 = p.total_volume.parse().unwrap_or(0.0);
+            let pnl: f64 = p.pnl.parse().unwrap_or(0.0);
+            let entry = cat_map.entry(category).or_insert((0.0, 0, 0.0));
+            entry.0 += vol;
+            entry.1 += p.trade_count;
+            entry.2 += pnl;
+            total_volume += vol;
+            total_trade_count += p.trade_count;
+
+            if !p.first_ts.is_empty() {
+                if earliest_ts.map(|e| p.first_ts.as_str() < e).unwrap_or(true) {
+                    earliest_ts = Some(&p.first_ts);
+                }
+            }
+            if !p.last_ts.is_empty() {
+                if latest_ts.map(|l| p.last_ts.as_str() > l).unwrap_or(true) {
+                    latest_ts = Some(&p.last_ts);
+                }
+            }
+        }
+
+        let mut category_breakdown: Vec<CategoryStats> = cat_map
+            .into_iter()
+            .map(|(cat, (vol, tc, pnl))| CategoryStats {
+                category: cat,
+                volume: format!("{:.6}", vol),
+                trade_count: tc,
+                pnl: format!("{:.6}", pnl),
+            })
+            .collect();
+        category_breakdown.sort_by(|a, b| {
+            let va: f64 = a.volume.parse().unwrap_or(0.0);
+            let vb: f64 = b.volume.parse().unwrap_or(0.0);
+            vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let active_span_days = match (earliest_ts, latest_ts) {
+            (Some(e), Some(l)) => {
+                let early = chrono::NaiveDateTime::parse_from_str(e, "%Y-%m-%d %H:%M:%S")
+                    .or_else(|_| chrono::NaiveDateTime::parse_from_str(e, "%Y-%m-%dT%H:%M:%S"));
+                let late = chrono::NaiveDateTime::parse_from_str(l, "%Y-%m-%d %H:%M:%S")
+                    .or_else(|_| chrono::NaiveDateTime::parse_from_str(l, "%Y-%m-%dT%H:%M:%S"));
+                match (early, late) {
+                    (Ok(e), Ok(l)) => (l - e).num_hours() as f64 / 24.0,
+                    _ => 0.0,
+                }
+            }
+            _ => 0.0,
+        };
+
+        let (labels, details) = compute_labels(
+            positions,
+            &market_info,
+            &category_breakdown,
+            total_volume,
+            total_trade_count,
+            positions.len() as u64,
+            active_span_days,
+        );
+
+        if !labels.is_empty() {
+            result.insert(addr.clone(), labels);
+            details_map.insert(addr.clone(), details);
+        }
+    }
+
+    (result, details_map)
+}
+
+fn compute_labels(
+    positions: &[ProfilePositionRow],
+    market_info: &std::collections::HashMap<String, markets::MarketInfo>,
+    category_breakdown: &[CategoryStats],
+    total_volume: f64,
+    total_trade_count: u64,
+    unique_markets: u64,
+    active_span_days: f64,
+) -> (Vec<BehavioralLabel>, LabelDetails) {
+    let mut labels = Vec::new();
+
+    let total_fees_paid: f64 = positions
+        .iter()
+        .map(|p| p.total_fee.parse::<f64>().unwrap_or(0.0))
+        .sum();
+
+    // Win rate + z-score from settled positions
+    // "Settled" = on-chain resolved OR price near 0/1 (de facto decided)
+    let mut settled_count: u64 = 0;
+    let mut correct_count: u64 = 0;
+
+    for p in positions {
+        match settled_outcome(p) {
+            Some(true) => {
+                settled_count += 1;
+                correct_count += 1;
+            }
+            Some(false) => settled_count += 1,
+            None => {}
+        }
+    }
+
+    let win_rate = if settled_count > 0 {
+        (correct_count as f64 / settled_count as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    let z_score = if settled_count >= 2 {
+        let n = settled_count as f64;
+        (correct_count as f64 - n * 0.5) / (n * 0.25_f64).sqrt()
+    } else {
+        0.0
+    };
+
+    // Category dominance
+    let (dominant_category, dominant_pct, cat_win_rate) =
+        if !category_breakdown.is_empty() && total_volume > 0.0 {
+            let top = &category_breakdown[0]; // already sorted by volume desc
+            let top_vol: f64 = top.volume.parse().unwrap_or(0.0);
+            let pct = (top_vol / total_volume) * 100.0;
+
+            // Category win rate from settled positions in this category
+            let mut cat_settled = 0u64;
+            let mut cat_correct = 0u64;
+            for p in positions {
+                let lp: f64 = p.latest_price.parse().unwrap_or(0.5);
+                let is_settled = p.on_chain_resolved == 1 || lp >= 0.95 || lp <= 0.05;
+                if !is_settled {
+                    continue;
+                }
+                let cat = market_info
+                    .get(&p.asset_id)
+                    .map(|i| i.category.as_str())
+                    .unwrap_or("Unknown");
+                if cat != top.category {
+                    continue;
+                }
+                let effective_price: f64 = if p.on_chain_resolved == 1 {
+                    p.resolved_price.parse().unwrap_or(0.5)
+                } else if lp >= 0.95 {
+                    1.0
+                } else {
+                    0.0
+                };
+                let net: f64 = p.net_tokens.parse().unwrap_or(0.0);
+                if net.abs() < 1e-9 {
+                    continue;
+                }
+                cat_settled += 1;
+                if (net > 0.0 && effective_price > 0.5) || (net < 0.0 && effective_price < 0.5) {
+                    cat_correct += 1;
+                }
+            }
+            let cwr = if cat_settled > 0 {
+                (cat_correct as f64 / cat_settled as f64) * 100.0
+            } else {
+                0.0
+            };
+            (top.category.clone(), pct, cwr)
+        } else {
+            (String::new(), 0.0, 0.0)
+        };
+
+    let avg_position = if unique_markets > 0 {
+        total_volume / unique_markets as f64
+    } else {
+        0.0
+    };
+
+    // Buy/sell balance for Market Maker detection
+    let total_buy: f64 = positions
+        .iter()
+        .map(|p| p.buy_usdc.parse::<f64>().unwrap_or(0.0))
+        .sum();
+    let total_sell: f64 = positions
+        .iter()
+        .map(|p| p.sell_usdc.parse::<f64>().unwrap_or(0.0))
+        .sum();
+    let buy_sell_ratio = if total_buy.max(total_sell) > 0.0 {
+        total_buy.min(total_sell) / total_buy.max(total_sell)
+    } else {
+        0.0
+    };
+
+    let trades_per_market = if unique_markets > 0 {
+        total_trade_count as f64 / unique_markets as f64
+    } else {
+        0.0
+    };
+
+    // --- Labels (not mutually exclusive) ---
+
+    // Sharp: skilled trader, statistically significant edge
+    if win_rate >= 60.0 && settled_count >= 10 && z_score > 1.5 {
+        labels.push(BehavioralLabel::Sharp);
+    }
+
+    // Specialist: concentrated in one category
+    let cat_settled_count: u64 = positions
+        .iter()
+        .filter(|p| {
+            let lp: f64 = p.latest_price.parse().unwrap_or(0.5);
+            let is_settled = p.on_chain_resolved == 1 || lp >= 0.95 || lp <= 0.05;
+            is_settled
+                && market_info
+                    .get(&p.asset_id)
+                    .map(|i| i.category == dominant_category)
+                    .unwrap_or(false)
+        })
+        .count() as u64;
+    let is_specialist = if cat_settled_count >= 5 {
+        dominant_pct > 70.0 && cat_win_rate > 55.0
+    } else {
+        dominant_pct >= 80.0 && total_volume > 10_000.0 && total_trade_count >= 10
+    };
+    if is_specialist && !dominant_category.is_empty() && dominant_category != "Unknown" {
+        labels.push(BehavioralLabel::Specialist);
+    }
+
+    // Whale: large concentrated bets
+    if total_volume > 100_000.0 && avg_position > 5_000.0 && unique_markets < 30 {
+        labels.push(BehavioralLabel::Whale);
+    }
+
+    // Degen: high volume, poor win rate — no edge
+    if win_rate < 40.0 && settled_count >= 10 && total_volume > 5_000.0 {
+        labels.push(BehavioralLabel::Degen);
+    }
+
+    // Market Maker: balanced buy/sell, high activity across many markets
+    if buy_sell_ratio > 0.6 && total_trade_count >= 50 && unique_markets >= 10 {
+        labels.push(BehavioralLabel::MarketMaker);
+    }
+
+    // Bot: high trade frequency per market (constant rebalancing)
+    if total_trade_count >= 200 && trades_per_market >= 15.0 {
+        labels.push(BehavioralLabel::Bot);
+    }
+
+    // Contrarian: buys cheap (unpopular) outcomes that settle correctly
+    let mut contrarian_trades: u64 = 0;
+    let mut contrarian_correct: u64 = 0;
+    for p in positions {
+        let net: f64 = p.net_tokens.parse().unwrap_or(0.0);
+        if net <= 0.0 {
+            continue;
+        }
+        let lp: f64 = p.latest_price.parse().unwrap_or(0.5);
+        let is_settled = p.on_chain_resolved == 1 || lp >= 0.95 || lp <= 0.05;
+        if !is_settled {
+            continue;
+        }
+        let buy_amt: f64 = p.buy_amount.parse().unwrap_or(0.0);
+        if buy_amt < 1e-9 {
+            continue;
+        }
+        let buy_usd: f64 = p.buy_usdc.parse().unwrap_or(0.0);
+        let avg_cost = buy_usd / buy_amt;
+        if avg_cost < 0.30 {
+            contrarian_trades += 1;
+            let effective_price: f64 = if p.on_chain_resolved == 1 {
+                p.resolved_price.parse().unwrap_or(0.5)
+            } else if lp >= 0.95 {
+                1.0
+            } else {
+                0.0
+            };
+            if effective_price > 0.5 {
+                contrarian_correct += 1;
+            }
+        }
+    }
+    let contrarian_rate = if contrarian_trades > 0 {
+        (contrarian_correct as f64 / contrarian_trades as f64) * 100.0
+    } else {
+        0.0
+    };
+    if contrarian_trades >= 5 && contrarian_rate >= 60.0 {
+        labels.push(BehavioralLabel::Contrarian);
+    }
+
+    // Casual: small/infrequent
+    if total_trade_count < 10 || total_volume < 500.0 {
+        labels.push(BehavioralLabel::Casual);
+    }
+
+    let details = LabelDetails {
+        win_rate,
+        z_score,
+        settled_count,
+        dominant_category,
+        dominant_category_pct: dominant_pct,
+        category_win_rate: cat_win_rate,
+        total_volume: format!("{:.6}", total_volume),
+        avg_position_size_usd: format!("{:.6}", avg_position),
+        unique_markets,
+        total_trade_count,
+        active_span_days,
+        buy_sell_ratio,
+        trades_per_market,
+        contrarian_trades,
+        contrarian_correct,
+        contrarian_rate,
+        total_fees_paid: format!("{:.6}", total_fees_paid),
+    };
+
+    (labels, details)
+}
+
+// ---------------------------------------------------------------------------
+// PolyLab Backtest
+// ---------------------------------------------------------------------------
+
+#[derive(clickhouse::Row, serde::Deserialize)]
+struct TopTraderRow {
+    address: String,
+}
+
+/// Current top `n` traders by all-time PnL (exchange contracts excluded).
+async fn top_pnl_traders(
+    state: &AppState,
+    n: u32,
+) -> Result<Vec<TopTraderRow>, (StatusCode, String)> {
+    let exclude = exclude_clause();
+    let top_query = format!(
+        "WITH resolved AS (
+            SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+            FROM poly_dearboard.resolved_prices FINAL
+        )
+        SELECT toString(p.trader) AS address
+        FROM poly_dearboard.trader_positions p
+        LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+        LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+        WHERE p.trader NOT IN ({exclude})
+        GROUP BY p.trader
+        ORDER BY sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) DESC
+        LIMIT {n}"
+    );
+    state
+        .db
+        .query(&top_query)
+        .fetch_all::<TopTraderRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Top-N traders by PnL accumulated before `as_of`, marked at each asset's
+/// last traded price by then, so the ranking can't see the backtest window.
+async fn top_pnl_traders_before(
+    state: &AppState,
+    n: u32,
+    as_of: &BacktestBound,
+) -> Result<Vec<TopTraderRow>, (StatusCode, String)> {
+    let exclude = exclude_clause();
+    let top_query = format!(
+        "SELECT toString(trader) AS address
+        FROM (
+            SELECT
+                trader,
+                toFloat64(sum(sell_usdc) - sum(buy_usdc))
+                    + toFloat64(sum(buy_amount) - sum(sell_amount)) * toFloat64(argMaxMerge(last_price_state)) AS pnl
+            FROM poly_dearboard.pnl_daily
+            WHERE day < {as_of} AND trader NOT IN ({exclude})
+            GROUP BY trader, asset_id
+        )
+        GROUP BY trader
+        ORDER BY sum(pnl) DESC
+        LIMIT {n}"
+    );
+    state
+        .db
+        .query(&top_query)
+        .fetch_all::<TopTraderRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// A backtest window bound, rendered as a ClickHouse date expression.
+enum BacktestBound {
+    DaysAgo(u32),
+    Date(chrono::NaiveDate),
+}
+
+impl std::fmt::Display for BacktestBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DaysAgo(days) => write!(f, "today() - {days}"),
+            Self::Date(date) => write!(f, "toDate('{}')", date.format("%Y-%m-%d")),
+        }
+    }
+}
+
+fn parse_backtest_date(name: &str, value: &str) -> Result<chrono::NaiveDate, (StatusCode, String)> {
+    chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid `{name}`: expected YYYY-MM-DD"),
+        )
+    })
+}
+
+pub async fn backtest(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Json(req): Json<BacktestRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    Ok(Json(run_backtest(&state, &owner, req).await?))
+}
+
+/// Exactly one of `top_n` or `list_id` picks the traders.
+fn check_backtest_source(req: &BacktestRequest) -> Result<(), (StatusCode, String)> {
+    if req.top_n.is_some() && req.list_id.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Specify list_id or top_n, not both".into(),
+        ));
+    }
+    if req.top_n.is_none() && req.list_id.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Specify either list_id or top_n".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Runs a backtest, reusing a cached result for identical top-N requests
+/// unless `force` is set. List runs read the owner's lists and are never cached.
+async fn run_backtest(
+    state: &AppState,
+    owner: &str,
+    req: BacktestRequest,
+) -> Result<BacktestResponse, (StatusCode, String)> {
+    check_backtest_source(&req)?;
+    let key = req.list_id.is_none().then(|| backtest_cache_key(&req));
+    let cached = match key {
+        Some(key) if !req.force.unwrap_or(false) => state.backtest_cache.get(&key).await,
+        _ => None,
+    };
+    if let Some(mut response) = cached {
+        response.cached = true;
+        return Ok(response);
+    }
+
+    let response = compute_backtest(state, owner, req).await?;
+    if let Some(key) = key {
+        state.backtest_cache.insert(key, response.clone()).await;
+    }
+    Ok(response)
+}
+
+/// Hash of the request as JSON with unset fields dropped, so omitted and
+/// explicit-null fields match. `force` is never serialized.
+fn backtest_cache_key(req: &BacktestRequest) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut value = serde_json::to_value(req).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        fields.retain(|_, v| !v.is_null());
+    }
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    value.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
+async fn compute_backtest(
+    state: &AppState,
+    owner: &str,
+    req: BacktestRequest,
+) -> Result<BacktestResponse, (StatusCode, String)> {
+    // Window bounds as SQL expressions over pnl_daily.day: [from, to)
+    let (timeframe, from, to) = match (
+        req.timeframe.as_deref(),
+        req.from.as_deref(),
+        req.to.as_deref(),
+    ) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Specify timeframe or from/to, not both".into(),
+            ));
+        }
+        (Some("7d"), ..) => ("7d", Some(BacktestBound::DaysAgo(7)), None),
+        (Some("30d"), ..) => ("30d", Some(BacktestBound::DaysAgo(30)), None),
+        (Some("all"), ..) => ("all", None, None),
+        (Some(_), ..) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "timeframe must be 7d, 30d, or all".into(),
+            ));
+        }
+        (None, Some(from), to) => {
+            let from = parse_backtest_date("from", from)?;
+            let to = to.map(|t| parse_backtest_date("to", t)).transpose()?;
+            if to.is_some_and(|to| to <= from) {
+                return Err((StatusCode::BAD_REQUEST, "to must be after from".into()));
+            }
+            (
+                "custom",
+                Some(BacktestBound::Date(from)),
+                to.map(BacktestBound::Date),
+            )
+        }
+        (None, None, Some(_)) => {
+            return Err((StatusCode::BAD_REQUEST, "to requires from".into()));
+        }
+        (None, None, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Specify either timeframe or from".into(),
+            ));
+        }
+    };
+    // Ranking by PnL earned inside the window would be look-ahead; only lists
+    // and `current` skip the point-in-time cut
+    let selection = match (req.selection.as_deref(), &from) {
+        (_, _) if req.list_id.is_some() => None,
+        (None, Some(_)) | (Some("point_in_time"), Some(_)) => Some("point_in_time"),
+        (None, None) | (Some("current"), _) => Some("current"),
+        (Some("point_in_time"), None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "selection=point_in_time needs a window start (timeframe 7d/30d or from)".into(),
+            ));
+        }
+        (Some(_), _) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "selection must be point_in_time or current".into(),
+            ));
+        }
+    };
+    let initial_capital = req
+        .initial_capital
+        .unwrap_or(10_000.0)
+        .clamp(100.0, 1_000_000.0);
+    let copy_pct = req.copy_pct.unwrap_or(1.0).clamp(0.01, 1.0);
+    let costs = ExecutionCosts {
+        slippage_bps: req.slippage_bps.unwrap_or(0).min(BACKTEST_MAX_SLIPPAGE_BPS),
+        fee_bps: req.fee_bps.unwrap_or(0).min(BACKTEST_MAX_FEE_BPS),
+    };
+    let requested = req.weighting.as_deref().unwrap_or("equal");
+    let Some(weighting) = BACKTEST_WEIGHTINGS
+        .iter()
+        .copied()
+        .find(|w| *w == requested)
+    else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid weighting. Allowed: {BACKTEST_WEIGHTINGS:?}"),
+        ));
+    };
+
+    // 1) Resolve trader addresses — from list or top-N
+    let trader_rows: Vec<TopTraderRow>;
+
+    if let Some(ref list_id) = req.list_id {
+        let addresses = {
+            let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+            db::get_list_member_addresses(&conn, list_id, owner).map_err(|e| match e {
+                db::ListError::NotFound => (StatusCode::NOT_FOUND, "List not found".into()),
+                _ => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to load list".into(),
+                ),
+            })?
+        };
+        if addresses.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "List has no members".into()));
+        }
+        trader_rows = addresses
+            .into_iter()
+            .map(|a| TopTraderRow { address: a })
+            .collect();
+    } else {
+        let top_n = req.top_n.unwrap().clamp(1, 50);
+        let as_of = from.as_ref().filter(|_| selection == Some("point_in_time"));
+        trader_rows = match as_of {
+            Some(as_of) => top_pnl_traders_before(state, top_n, as_of).await?,
+            None => top_pnl_traders(state, top_n).await?,
+        };
+    }
+
+    let top_n = trader_rows.len() as u32;
+    let user_allocation = initial_capital * copy_pct;
+    let per_trader_budget = if top_n > 0 {
+        user_allocation / top_n as f64
+    } else {
+        0.0
+    };
+    let min_weight = req.min_weight.unwrap_or(0.0);
+    if !min_weight.is_finite() || !(0.0..=1.0).contains(&min_weight) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "min_weight must be between 0 and 1".into(),
+        ));
+    }
+    let min_weight = min_weight.min(1.0 / top_n.max(1) as f64);
+    let max_position_pct = req.max_position_pct;
+    if max_position_pct.is_some_and(|p| !p.is_finite() || p <= 0.0 || p > 1.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "max_position_pct must be in (0, 1]".into(),
+        ));
+    }
+    let mut exclude_categories: Vec<String> = Vec::new();
+    for c in req.exclude_categories.iter().flatten() {
+        let c = c.trim();
+        if !c.is_empty() && !exclude_categories.iter().any(|e| e.eq_ignore_ascii_case(c)) {
+            exclude_categories.push(c.to_string());
+        }
+    }
+
+    let config = BacktestConfig {
+        initial_capital,
+        copy_pct,
+        top_n,
+        timeframe: timeframe.to_string(),
+        from: req.from.clone(),
+        to: req.to.clone(),
+        selection,
+        per_trader_budget,
+        weighting,
+        min_weight,
+        max_position_pct,
+        exclude_categories,
+        slippage_bps: costs.slippage_bps,
+        fee_bps: costs.fee_bps,
+    };
+
+    if trader_rows.is_empty() {
+        return Ok(BacktestResponse {
+            portfolio_curve: vec![],
+            pnl_curve: vec![],
+            summary: BacktestSummary {
+                total_pnl: "0.00".into(),
+                gross_pnl: "0.00".into(),
+                slippage_cost: "0.00".into(),
+                fee_cost: "0.00".into(),
+                total_costs: "0.00".into(),
+                clipped_buys: "0.00".into(),
+                position_cap_clipped: "0.00".into(),
+                total_return_pct: 0.0,
+                win_rate: 0.0,
+                max_drawdown: "0.00".into(),
+                max_drawdown_pct: 0.0,
+                sharpe_ratio: 0.0,
+                sortino_ratio: 0.0,
+                volatility_annualized: 0.0,
+                best_day: 0.0,
+                worst_day: 0.0,
+                calmar_ratio: 0.0,
+                positions_count: 0,
+                traders_count: 0,
+                initial_capital,
+                final_value: initial_capital,
+            },
+            traders: vec![],
+            config,
+            trades: req.include_trades.unwrap_or(false).then(Vec::new),
+            trades_truncated: false,
+            cached: false,
+            computed_at: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    let addresses: Vec<String> = trader_rows
+        .iter()
+        .map(|r| r.address.to_lowercase())
+        .collect();
+    let in_list = addresses
+        .iter()
+        .map(|a| format!("'{a}'"))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    // 2) Fetch per-trader scaling data
+    let scale_rows = state
+        .db
+        .query(&format!(
+            "WITH resolved AS (
+            SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+            FROM poly_dearboard.resolved_prices FINAL
+        )
+        SELECT
+            toString(p.trader) AS address,
+            toString(ROUND(sum(p.buy_usdc) / count(), 6)) AS avg_position_size,
+            count() AS market_count,
+            toString(ROUND(sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))), 6)) AS pnl,
+            toString(ROUND(sum(p.buy_usdc + p.sell_usdc), 6)) AS volume
+        FROM poly_dearboard.trader_positions p
+        LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+        LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+        WHERE lower(p.trader) IN ({in_list})
+        GROUP BY p.trader"
+        ))
+        .fetch_all::<TraderScaleRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let weight_inputs: Vec<(String, f64)> = scale_rows
+        .iter()
+        .map(|row| {
+            let value = match weighting {
+                "pnl_weighted" => row.pnl.parse::<f64>().unwrap_or(0.0),
+                "volume_weighted" => row.volume.parse::<f64>().unwrap_or(0.0),
+                _ => 1.0,
+            };
+            (row.address.to_lowercase(), value)
+        })
+        .collect();
+    let weights = trader_weights(&weight_inputs, min_weight);
+
+    let mut trader_scales: std::collections::HashMap<String, f64> =
+        std::collections::HashMap::new();
+    for row in &scale_rows {
+        let address = row.address.to_lowercase();
+        let avg_pos = row.avg_position_size.parse::<f64>().unwrap_or(1.0).max(1.0);
+        // Traders without positions have no row and keep scale 1.0
+        let budget = user_allocation * weights.get(&address).copied().unwrap_or(0.0);
+        let scale = budget / avg_pos;
+        trader_scales.insert(address, scale);
+    }
+
+    // 3) Build portfolio simulation from per-trader pnl_daily
+    // Pre-window initial state (per-trader, for scaling)
+    let mut asset_state: std::collections::HashMap<String, (f64, f64, f64)> =
+        std::collections::HashMap::new();
+
+    if let Some(from) = &from {
+        let initial = state
+            .db
+            .query(&format!(
+                "SELECT
+                toString(trader) AS trader,
+                asset_id,
+                toString(sum(buy_amount) - sum(sell_amount)) AS net_tokens,
+                toString(sum(sell_usdc) - sum(buy_usdc)) AS cash_flow,
+                toString(argMaxMerge(last_price_state)) AS last_price,
+                toString(sum(buy_usdc)) AS buy_usdc
+            FROM poly_dearboard.pnl_daily
+            WHERE lower(trader) IN ({in_list})
+              AND day < {from}
+            GROUP BY trader, asset_id"
+            ))
+            .fetch_all::<PnlInitialStateTraderRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        for row in initial {
+            let scale = trader_scales
+                .get(&row.trader.to_lowercase())
+                .copied()
+                .unwrap_or(1.0);
+            let tokens = row.net_tokens.parse::<f64>().unwrap_or(0.0) * scale;
+            let cash = row.cash_flow.parse::<f64>().unwrap_or(0.0) * scale;
+            let price = row.last_price.parse::<f64>().unwrap_or(0.0);
+            let entry = asset_state
+                .entry(row.asset_id.clone())
+                .or_insert((0.0, 0.0, 0.0));
+            entry.0 += tokens;
+            entry.1 += cash;
+            entry.2 = price;
+        }
+    }
+
+    // Window deltas (per-trader for scaling)
+    let day_where = [
+        from.as_ref().map(|f| format!("AND day >= {f}")),
+        to.as_ref().map(|t| format!("AND day < {t}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    let mut rows = state
+        .db
+        .query(&format!(
+            "SELECT
+            toString(trader) AS trader,
+            toString(day) AS date,
+            asset_id,
+            toString(sum(buy_amount) - sum(sell_amount)) AS net_token_delta,
+            toString(sum(sell_usdc) - sum(buy_usdc)) AS cash_flow_delta,
+            toString(argMaxMerge(last_price_state)) AS last_price,
+            toString(sum(buy_usdc)) AS buy_usdc_delta
+        FROM poly_dearboard.pnl_daily
+        WHERE lower(trader) IN ({in_list})
+          {day_where}
+        GROUP BY trader, day, asset_id
+        ORDER BY day, trader, asset_id"
+        ))
+        .fetch_all::<PnlDailyTraderRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // Drop excluded categories, pre-window holdings included
+    if !config.exclude_categories.is_empty() {
+        let excluded: std::collections::HashSet<String> = {
+            let cache = state.market_cache.read().await;
+            rows.iter()
+                .map(|r| &r.asset_id)
+                .chain(asset_state.keys())
+                .filter(|a| {
+                    cache.get(a).is_some_and(|m| {
+                        config
+                            .exclude_categories
+                            .iter()
+                            .any(|c| m.category.eq_ignore_ascii_case(c))
+                    })
+                })
+                .cloned()
+                .collect()
+        };
+        rows.retain(|r| !excluded.contains(&r.asset_id));
+        asset_state.retain(|a, _| !excluded.contains(a));
+    }
+
+    // Resolutions after a custom `to` are look-ahead: mark to the last price instead
+    let resolved = if to.is_some() {
+        std::collections::HashMap::new()
+    } else {
+        fetch_resolved_prices(state).await
+    };
+
+    // Cost-free run first (on a copy of the pre-window state) for gross PnL
+    let gross_pnl = if costs.is_zero() {
+        None
+    } else {
+        let (curve, _) = simulate_portfolio(
+            &rows,
+            &mut asset_state.clone(),
+            &resolved,
+            &trader_scales,
+            initial_capital,
+            ExecutionCosts::default(),
+            max_position_pct,
+        );
+        Some(
+            curve
+                .last()
+                .and_then(|p| p.value.parse::<f64>().ok())
+                .unwrap_or(initial_capital)
+                - initial_capital,
+        )
+    };
+
+    // Simulate portfolio with scaling
+    let (portfolio_curve, mut report) = simulate_portfolio(
+        &rows,
+        &mut asset_state,
+        &resolved,
+        &trader_scales,
+        initial_capital,
+        costs,
+        max_position_pct,
+    );
+
+    let include_trades = req.include_trades.unwrap_or(false);
+    let trades_truncated = include_trades && report.trades_total > report.trades.len();
+    let trades = if include_trades {
+        let mut asset_ids: Vec<String> = report.trades.iter().map(|t| t.asset_id.clone()).collect();
+        asset_ids.sort_unstable();
+        asset_ids.dedup();
+        let market_info = markets::resolve_markets(
+            &state.http,
+            &state.gamma,
+            &state.db,
+            &state.market_cache,
+            &asset_ids,
+        )
+        .await;
+        for trade in &mut report.trades {
+            trade.question = market_info.get(&trade.asset_id).map(|m| m.question.clone());
+        }
+        Some(std::mem::take(&mut report.trades))
+    } else {
+        None
+    };
+
+    // Also build raw PnL curve for backward compat
+    let pnl_curve: Vec<PnlChartPoint> = portfolio_curve
+        .iter()
+        .map(|p| PnlChartPoint {
+            date: p.date.clone(),
+            pnl: p.pnl.clone(),
+            peak: None,
+            drawdown: None,
+        })
+        .collect();
+
+    // 4) Summary stats
+    let final_value = portfolio_curve
+        .last()
+        .and_then(|p| p.value.parse::<f64>().ok())
+        .unwrap_or(initial_capital);
+    let total_pnl = final_value - initial_capital;
+    let total_return_pct = if initial_capital > 0.0 {
+        (total_pnl / initial_capital) * 100.0
+    } else {
+        0.0
+    };
+
+    // Max drawdown on portfolio value
+    let mut drawdown = Drawdown::new(initial_capital);
+    let mut max_dd_pct: f64 = 0.0;
+    for pt in &portfolio_curve {
+        let v = pt.value.parse::<f64>().unwrap_or(initial_capital);
+        let (peak_value, dd) = drawdown.step(v);
+        let dd_pct = if peak_value > 0.0 {
+            dd / peak_value * 100.0
+        } else {
+            0.0
+        };
+        if dd_pct > max_dd_pct {
+            max_dd_pct = dd_pct;
+        }
+    }
+
+    let risk = risk_metrics(&portfolio_curve, initial_capital, max_dd_pct);
+
+    // Win rate + position count
+    #[derive(clickhouse::Row, serde::Deserialize)]
+    struct WinRateRow {
+        total: u64,
+        wins: u64,
+    }
+    let wr = state.db.query(&format!(
+        "WITH resolved AS (
+            SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+            FROM poly_dearboard.resolved_prices FINAL
+        )
+        SELECT
+            count() AS total,
+            countIf((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price)) > 0) AS wins
+        FROM poly_dearboard.trader_positions p
+        LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+        LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+        WHERE lower(p.trader) IN ({in_list})"
+    ))
+    .fetch_one::<WinRateRow>()
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let win_rate = if wr.total > 0 {
+        (wr.wins as f64 / wr.total as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    // 5) Per-trader breakdown with scaling
+    #[derive(clickhouse::Row, serde::Deserialize)]
+    struct TraderPnlRow {
+        address: String,
+        pnl: String,
+        markets_traded: u64,
+    }
+    let trader_pnls = state.db.query(&format!(
+        "WITH resolved AS (
+            SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+            FROM poly_dearboard.resolved_prices FINAL
+        )
+        SELECT
+            toString(p.trader) AS address,
+            toString(ROUND(sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))), 6)) AS pnl,
+            count() AS markets_traded
+        FROM poly_dearboard.trader_positions p
+        LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+        LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+        WHERE lower(p.trader) IN ({in_list})
+        GROUP BY p.trader
+        ORDER BY sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) DESC"
+    ))
+    .fetch_all::<TraderPnlRow>()
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let total_scaled_abs: f64 = trader_pnls
+        .iter()
+        .map(|t| {
+            let raw = t.pnl.parse::<f64>().unwrap_or(0.0);
+            let scale = trader_scales
+                .get(&t.address.to_lowercase())
+                .copied()
+                .unwrap_or(1.0);
+            (raw * scale).abs()
+        })
+        .sum();
+
+    let traders: Vec<BacktestTrader> = trader_pnls
+        .into_iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let raw_pnl = t.pnl.parse::<f64>().unwrap_or(0.0);
+            let scale = trader_scales
+                .get(&t.address.to_lowercase())
+                .copied()
+                .unwrap_or(1.0);
+            let scaled = raw_pnl * scale;
+            BacktestTrader {
+                address: t.address,
+                rank: (i + 1) as u32,
+                pnl: t.pnl,
+                scaled_pnl: format!("{:.2}", scaled),
+                markets_traded: t.markets_traded,
+                contribution_pct: if total_scaled_abs > 0.0 {
+                    (scaled.abs() / total_scaled_abs) * 100.0
+                } else {
+                    0.0
+                },
+                scale_factor: (scale * 1000.0).round() / 1000.0,
+            }
+        })
+        .collect();
+
+    Ok(BacktestResponse {
+        portfolio_curve,
+        pnl_curve,
+        summary: BacktestSummary {
+            total_pnl: format!("{:.2}", total_pnl),
+            gross_pnl: format!("{:.2}", gross_pnl.unwrap_or(total_pnl)),
+            slippage_cost: format!("{:.2}", report.slippage),
+            fee_cost: format!("{:.2}", report.fees),
+            total_costs: format!("{:.2}", report.slippage + report.fees),
+            clipped_buys: format!("{:.2}", report.cash_clipped + report.cap_clipped),
+            position_cap_clipped: format!("{:.2}", report.cap_clipped),
+            total_return_pct: (total_return_pct * 10.0).round() / 10.0,
+            win_rate: (win_rate * 10.0).round() / 10.0,
+            max_drawdown: format!("{:.2}", drawdown.max),
+            max_drawdown_pct: (max_dd_pct * 10.0).round() / 10.0,
+            sharpe_ratio: round1(risk.sharpe_ratio),
+            sortino_ratio: round1(risk.sortino_ratio),
+            volatility_annualized: round1(risk.volatility_annualized),
+            best_day: round1(risk.best_day),
+            worst_day: round1(risk.worst_day),
+            calmar_ratio: round1(risk.calmar_ratio),
+            positions_count: wr.total,
+            traders_count: top_n,
+            initial_capital,
+            final_value: (final_value * 100.0).round() / 100.0,
+        },
+        traders,
+        config,
+        trades,
+        trades_truncated,
+        cached: false,
+        computed_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+fn map_strategy_error(e: db::ListError) -> (StatusCode, String) {
+    match e {
+        db::ListError::DuplicateName => (
+            StatusCode::CONFLICT,
+            "A strategy with this name already exists".into(),
+        ),
+        db::ListError::NotFound => (StatusCode::NOT_FOUND, "Strategy not found".into()),
+        other => map_list_error(other),
     }
+}
 
-    // Compute labels per trader
-    for (addr, positions) in &by_trader {
-        let mut cat_map: std::collections::HashMap<String, (f64, u64, f64)> =
-            std::collections::HashMap::new();
-        let mut total_volume: f64 = 0.0;
-        let mut total_trade_count: u64 = 0;
-        let mut earliest_ts: Option<&str> = None;
-        let mut latest_ts: Option<&str> = None;
+pub async fn create_backtest_strategy(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Json(req): Json<CreateStrategyRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let name = req.name.trim().to_string();
+    if name.is_empty() || name.len() > 50 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Name must be 1-50 characters".into(),
+        ));
+    }
+    check_backtest_source(&req.config)?;
+    let config = serde_json::to_value(&req.config)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+    let strategy =
+        db::create_backtest_strategy(&conn, &owner, &name, &config).map_err(map_strategy_error)?;
+    Ok((StatusCode::CREATED, Json(strategy)))
+}
 
-        for p in positions {
-            let category = market_info
-                .get(&p.asset_id)
-                .map(|i| i.category.clone())
-                .unwrap_or_else(|| "Unknown".to_string());
-            let vol: f64 = p.total_volume.parse().unwrap_or(0.0);
-            let pnl: f64 = p.pnl.parse().unwrap_or(0.0);
-            let entry = cat_map.entry(category).or_insert((0.0, 0, 0.0));
-            entry.0 += vol;
-            entry.1 += p.trade_count;
-            entry.2 += pnl;
-            total_volume += vol;
-            total_trade_count += p.trade_count;
+pub async fn list_backtest_strategies(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+    let strategies = db::list_backtest_strategies(&conn, &owner)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(strategies))
+}
 
-            if !p.first_ts.is_empty() {
-                if earliest_ts.map(|e| p.first_ts.as_str() < e).unwrap_or(true) {
-                    earliest_ts = Some(&p.first_ts);
-                }
-            }
-            if !p.last_ts.is_empty() {
-                if latest_ts.map(|l| p.last_ts.as_str() > l).unwrap_or(true) {
-                    latest_ts = Some(&p.last_ts);
-                }
-            }
-        }
+pub async fn delete_backtest_strategy(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+    db::delete_backtest_strategy(&conn, &id, &owner).map_err(map_strategy_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-        let mut category_breakdown: Vec<CategoryStats> = cat_map
-            .into_iter()
-            .map(|(cat, (vol, tc, pnl))| CategoryStats {
-                category: cat,
-                volume: format!("{:.6}", vol),
-                trade_count: tc,
-                pnl: format!("{:.6}", pnl),
-            })
-            .collect();
-        category_breakdown.sort_by(|a, b| {
-            let va: f64 = a.volume.parse().unwrap_or(0.0);
-            let vb: f64 = b.volume.parse().unwrap_or(0.0);
-            vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
-        });
+/// Runs a saved strategy's config through the regular backtest.
+pub async fn run_backtest_strategy(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let strategy = {
+        let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+        db::get_backtest_strategy(&conn, &id, &owner).map_err(map_strategy_error)?
+    };
+    let req: BacktestRequest = serde_json::from_value(strategy.config).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Stored strategy is invalid: {e}"),
+        )
+    })?;
+    Ok(Json(run_backtest(&state, &owner, req).await?))
+}
 
-        let active_span_days = match (earliest_ts, latest_ts) {
-            (Some(e), Some(l)) => {
-                let early = chrono::NaiveDateTime::parse_from_str(e, "%Y-%m-%d %H:%M:%S")
-                    .or_else(|_| chrono::NaiveDateTime::parse_from_str(e, "%Y-%m-%dT%H:%M:%S"));
-                let late = chrono::NaiveDateTime::parse_from_str(l, "%Y-%m-%d %H:%M:%S")
-                    .or_else(|_| chrono::NaiveDateTime::parse_from_str(l, "%Y-%m-%dT%H:%M:%S"));
-                match (early, late) {
-                    (Ok(e), Ok(l)) => (l - e).num_hours() as f64 / 24.0,
-                    _ => 0.0,
-                }
-            }
-            _ => 0.0,
-        };
+const BACKTEST_WEIGHTINGS: &[&str] = &["equal", "pnl_weighted", "volume_weighted"];
+
+/// Capital weights per trader, normalized to sum to 1 and never negative.
+/// `values` (PnL, volume, or 1.0 for equal) below zero count as zero; traders
+/// left with less than `min_weight` are raised to it before normalizing. All
+/// non-positive values fall back to equal weights.
+fn trader_weights(
+    values: &[(String, f64)],
+    min_weight: f64,
+) -> std::collections::HashMap<String, f64> {
+    let clean = |v: f64| if v.is_finite() { v.max(0.0) } else { 0.0 };
+    let total: f64 = values.iter().map(|(_, v)| clean(*v)).sum();
+    if total <= 0.0 {
+        let equal = 1.0 / values.len().max(1) as f64;
+        return values.iter().map(|(a, _)| (a.clone(), equal)).collect();
+    }
+    let raw: Vec<f64> = values
+        .iter()
+        .map(|(_, v)| (clean(*v) / total).max(min_weight.max(0.0)))
+        .collect();
+    let sum: f64 = raw.iter().sum();
+    values
+        .iter()
+        .zip(raw)
+        .map(|((a, _), w)| (a.clone(), w / sum))
+        .collect()
+}
 
-        let (labels, details) = compute_labels(
-            positions,
-            &market_info,
-            &category_breakdown,
-            total_volume,
-            total_trade_count,
-            positions.len() as u64,
-            active_span_days,
-        );
+const BACKTEST_MAX_SLIPPAGE_BPS: u32 = 500;
+const BACKTEST_MAX_FEE_BPS: u32 = 1000;
 
-        if !labels.is_empty() {
-            result.insert(addr.clone(), labels);
-            details_map.insert(addr.clone(), details);
-        }
+/// Copier execution costs, in basis points of traded notional.
+#[derive(Clone, Copy, Default)]
+struct ExecutionCosts {
+    slippage_bps: u32,
+    fee_bps: u32,
+}
+
+impl ExecutionCosts {
+    fn is_zero(&self) -> bool {
+        self.slippage_bps == 0 && self.fee_bps == 0
     }
+}
 
-    (result, details_map)
+/// Max simulated trades a backtest returns.
+const BACKTEST_MAX_TRADES: usize = 1000;
+
+/// Costs `simulate_portfolio` charged and buy notional it clipped (in USDC),
+/// plus the first `BACKTEST_MAX_TRADES` trades it made.
+#[derive(Default)]
+struct SimulationReport {
+    slippage: f64,
+    fees: f64,
+    /// Buys not copied for lack of cash
+    cash_clipped: f64,
+    /// Buys not copied because of `max_position_pct`
+    cap_clipped: f64,
+    trades: Vec<SimulatedTrade>,
+    /// All trades, including those past the cap
+    trades_total: usize,
 }
 
-fn compute_labels(
-    positions: &[ProfilePositionRow],
-    market_info: &std::collections::HashMap<String, markets::MarketInfo>,
-    category_breakdown: &[CategoryStats],
-    total_volume: f64,
-    total_trade_count: u64,
-    unique_markets: u64,
-    active_span_days: f64,
-) -> (Vec<BehavioralLabel>, LabelDetails) {
-    let mut labels = Vec::new();
+impl SimulationReport {
+    fn record(
+        &mut self,
+        row: &PnlDailyTraderRow,
+        side: &'static str,
+        tokens: f64,
+        cash: f64,
+        clipped: bool,
+    ) {
+        self.trades_total += 1;
+        if self.trades.len() < BACKTEST_MAX_TRADES {
+            self.trades.push(SimulatedTrade {
+                date: row.date.clone(),
+                trader: row.trader.clone(),
+                asset_id: row.asset_id.clone(),
+                question: None,
+                side,
+                tokens: format!("{tokens:.6}"),
+                cash: format!("{cash:.6}"),
+                clipped,
+            });
+        }
+    }
+}
 
-    // Win rate + z-score from settled positions
-    // "Settled" = on-chain resolved OR price near 0/1 (de facto decided)
-    let mut settled_count: u64 = 0;
-    let mut correct_count: u64 = 0;
+/// Portfolio simulation with per-trader scaling and capital constraints.
+/// Buys pay `costs.slippage_bps` more and sells receive that much less, and
+/// `costs.fee_bps` is charged, each on the day's gross buy and sell notional.
+/// With `max_position_pct`, buys are clipped so no asset is worth more than
+/// that fraction of the portfolio.
+fn simulate_portfolio(
+    rows: &[PnlDailyTraderRow],
+    asset_state: &mut std::collections::HashMap<String, (f64, f64, f64)>,
+    resolved: &std::collections::HashMap<String, f64>,
+    trader_scales: &std::collections::HashMap<String, f64>,
+    initial_capital: f64,
+    costs: ExecutionCosts,
+    max_position_pct: Option<f64>,
+) -> (Vec<PortfolioPoint>, SimulationReport) {
+    // Compute initial cash: initial_capital minus cost of pre-window positions
+    let pre_window_cost: f64 = asset_state
+        .values()
+        .map(|(_, cash, _)| -cash)
+        .sum::<f64>()
+        .max(0.0);
+    let mut cash_balance = (initial_capital - pre_window_cost).max(0.0);
+
+    let mut points: Vec<PortfolioPoint> = Vec::new();
+    let mut report = SimulationReport::default();
+    let mut current_date = String::new();
+
+    for row in rows {
+        if !current_date.is_empty() && row.date != current_date {
+            // Emit point for previous date
+            let positions_value: f64 = asset_state
+                .values()
+                .map(|(tokens, _, price)| tokens * price)
+                .sum();
+            let total_value = cash_balance + positions_value;
+            let pnl = total_value - initial_capital;
+            points.push(PortfolioPoint {
+                date: current_date.clone(),
+                value: format!("{:.2}", total_value),
+                pnl: format!("{:.2}", pnl),
+                pnl_pct: format!(
+                    "{:.2}",
+                    if initial_capital > 0.0 {
+                        pnl / initial_capital * 100.0
+                    } else {
+                        0.0
+                    }
+                ),
+            });
+        }
+        current_date.clone_from(&row.date);
+
+        let scale = trader_scales
+            .get(&row.trader.to_lowercase())
+            .copied()
+            .unwrap_or(1.0);
+        let mut delta_tokens = row.net_token_delta.parse::<f64>().unwrap_or(0.0) * scale;
+        let mut delta_cash = row.cash_flow_delta.parse::<f64>().unwrap_or(0.0) * scale;
+        let price = row.last_price.parse::<f64>().unwrap_or(0.0);
+        let side = if delta_tokens < 0.0 { "sell" } else { "buy" };
+        let mut clipped = false;
+
+        // sell_usdc = cash_flow + buy_usdc
+        let mut buy_notional = row.buy_usdc_delta.parse::<f64>().unwrap_or(0.0) * scale;
+        let sell_notional = (delta_cash + buy_notional).max(0.0);
+        let notional = buy_notional + sell_notional;
+        let mut slippage = notional * costs.slippage_bps as f64 / 10_000.0;
+        let mut fees = notional * costs.fee_bps as f64 / 10_000.0;
+        delta_cash -= slippage + fees;
+
+        // Position cap: clip buys to the room left under max_position_pct of
+        // the portfolio (valued at current marks)
+        if let Some(max_pct) = max_position_pct.filter(|_| delta_tokens > 0.0 && price > 0.0) {
+            let held = asset_state.get(&row.asset_id).map_or(0.0, |s| s.0);
+            let portfolio_value = cash_balance
+                + asset_state
+                    .values()
+                    .map(|(tokens, _, price)| tokens * price)
+                    .sum::<f64>();
+            let room = (max_pct * portfolio_value - held * price).max(0.0) / price;
+            if delta_tokens > room {
+                let clip = room / delta_tokens;
+                delta_tokens *= clip;
+                delta_cash *= clip;
+                slippage *= clip;
+                fees *= clip;
+                report.cap_clipped += buy_notional * (1.0 - clip);
+                buy_notional *= clip;
+                clipped = true;
+            }
+        }
+
+        // Capital constraint: if buying (delta_cash < 0), clip to available cash
+        if delta_cash < 0.0 {
+            let cost = -delta_cash;
+            if cost > cash_balance && cash_balance > 0.0 {
+                let clip = cash_balance / cost;
+                delta_tokens *= clip;
+                delta_cash *= clip;
+                slippage *= clip;
+                fees *= clip;
+                report.cash_clipped += buy_notional * (1.0 - clip);
+                clipped = true;
+            } else if cash_balance <= 0.0 {
+                // No cash left — skip this buy
+                report.cash_clipped += buy_notional;
+                report.record(row, side, 0.0, 0.0, true);
+                let entry = asset_state
+                    .entry(row.asset_id.clone())
+                    .or_insert((0.0, 0.0, 0.0));
+                entry.2 = price; // Still update price
+                continue;
+            }
+        }
+
+        cash_balance += delta_cash;
+        report.slippage += slippage;
+        report.fees += fees;
+        report.record(row, side, delta_tokens, delta_cash, clipped);
+        let entry = asset_state
+            .entry(row.asset_id.clone())
+            .or_insert((0.0, 0.0, 0.0));
+        entry.0 += delta_tokens;
+        entry.1 += delta_cash;
+        entry.2 = price;
+    }
+
+    // Final point with resolved prices
+    if !current_date.is_empty() {
+        let positions_value: f64 = asset_state
+            .iter()
+            .map(|(asset_id, (tokens, _, price))| {
+                let final_price = resolved.get(asset_id).copied().unwrap_or(*price);
+                tokens * final_price
+            })
+            .sum();
+        let total_value = cash_balance + positions_value;
+        let pnl = total_value - initial_capital;
+        points.push(PortfolioPoint {
+            date: current_date,
+            value: format!("{:.2}", total_value),
+            pnl: format!("{:.2}", pnl),
+            pnl_pct: format!(
+                "{:.2}",
+                if initial_capital > 0.0 {
+                    pnl / initial_capital * 100.0
+                } else {
+                    0.0
+                }
+            ),
+        });
+    }
 
-    for p in positions {
-        let lp: f64 = p.latest_price.parse().unwrap_or(0.5);
-        let is_settled = p.on_chain_resolved == 1 || lp >= 0.95 || lp <= 0.05;
-        if !is_settled {
-            continue;
-        }
+    (points, report)
+}
 
-        let effective_price: f64 = if p.on_chain_resolved == 1 {
-            p.resolved_price.parse().unwrap_or(0.5)
-        } else if lp >= 0.95 {
-            1.0
-        } else {
-            0.0
-        };
+/// Curve points per year; the curve has one point per active day.
+const TRADING_DAYS_PER_YEAR: f64 = 365.0;
+
+/// Risk-adjusted statistics of a backtest curve; ratios are 0 when undefined
+/// (fewer than two returns, or no variance / drawdown).
+#[derive(Debug, Default, PartialEq)]
+struct RiskMetrics {
+    sharpe_ratio: f64,
+    sortino_ratio: f64,
+    /// In %
+    volatility_annualized: f64,
+    /// In %
+    best_day: f64,
+    /// In %
+    worst_day: f64,
+    calmar_ratio: f64,
+}
 
-        let net: f64 = p.net_tokens.parse().unwrap_or(0.0);
-        if net.abs() < 1e-9 {
-            continue; // fully closed before settlement, skip
-        }
-        settled_count += 1;
-        let is_correct =
-            (net > 0.0 && effective_price > 0.5) || (net < 0.0 && effective_price < 0.5);
-        if is_correct {
-            correct_count += 1;
+/// Per-point returns of `curve` (starting from `initial_capital`), annualized
+/// with rf = 0. `max_drawdown_pct` is the curve's max drawdown, in %.
+fn risk_metrics(
+    curve: &[PortfolioPoint],
+    initial_capital: f64,
+    max_drawdown_pct: f64,
+) -> RiskMetrics {
+    let mut prev = initial_capital;
+    let mut returns = Vec::with_capacity(curve.len());
+    for point in curve {
+        let value = point.value.parse::<f64>().unwrap_or(prev);
+        if prev > 0.0 {
+            returns.push(value / prev - 1.0);
         }
+        prev = value;
+    }
+    if returns.is_empty() {
+        return RiskMetrics::default();
     }
 
-    let win_rate = if settled_count > 0 {
-        (correct_count as f64 / settled_count as f64) * 100.0
-    } else {
-        0.0
-    };
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let best_day = returns.iter().copied().fold(f64::MIN, f64::max) * 100.0;
+    let worst_day = returns.iter().copied().fold(f64::MAX, f64::min) * 100.0;
+    let annual = TRADING_DAYS_PER_YEAR.sqrt();
 
-    let z_score = if settled_count >= 2 {
-        let n = settled_count as f64;
-        (correct_count as f64 - n * 0.5) / (n * 0.25_f64).sqrt()
+    let std_dev = if returns.len() > 1 {
+        (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
     } else {
         0.0
     };
+    let downside_dev = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+    let ratio = |num: f64, den: f64| {
+        if returns.len() > 1 && den > 0.0 {
+            num / den
+        } else {
+            0.0
+        }
+    };
 
-    // Category dominance
-    let (dominant_category, dominant_pct, cat_win_rate) =
-        if !category_breakdown.is_empty() && total_volume > 0.0 {
-            let top = &category_breakdown[0]; // already sorted by volume desc
-            let top_vol: f64 = top.volume.parse().unwrap_or(0.0);
-            let pct = (top_vol / total_volume) * 100.0;
+    RiskMetrics {
+        sharpe_ratio: ratio(mean, std_dev) * annual,
+        sortino_ratio: ratio(mean, downside_dev) * annual,
+        volatility_annualized: std_dev * annual * 100.0,
+        best_day,
+        worst_day,
+        calmar_ratio: ratio(mean * TRADING_DAYS_PER_YEAR * 100.0, max_drawdown_pct),
+    }
+}
 
-            // Category win rate from settled positions in this category
-            let mut cat_settled = 0u64;
-            let mut cat_correct = 0u64;
-            for p in positions {
-                let lp: f64 = p.latest_price.parse().unwrap_or(0.5);
-                let is_settled = p.on_chain_resolved == 1 || lp >= 0.95 || lp <= 0.05;
-                if !is_settled {
-                    continue;
-                }
-                let cat = market_info
-                    .get(&p.asset_id)
-                    .map(|i| i.category.as_str())
-                    .unwrap_or("Unknown");
-                if cat != top.category {
-                    continue;
-                }
-                let effective_price: f64 = if p.on_chain_resolved == 1 {
-                    p.resolved_price.parse().unwrap_or(0.5)
-                } else if lp >= 0.95 {
-                    1.0
-                } else {
-                    0.0
-                };
-                let net: f64 = p.net_tokens.parse().unwrap_or(0.0);
-                if net.abs() < 1e-9 {
-                    continue;
-                }
-                cat_settled += 1;
-                if (net > 0.0 && effective_price > 0.5) || (net < 0.0 && effective_price < 0.5) {
-                    cat_correct += 1;
-                }
-            }
-            let cwr = if cat_settled > 0 {
-                (cat_correct as f64 / cat_settled as f64) * 100.0
-            } else {
-                0.0
-            };
-            (top.category.clone(), pct, cwr)
-        } else {
-            (String::new(), 0.0, 0.0)
-        };
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
 
-    let avg_position = if unique_markets > 0 {
-        total_volume / unique_markets as f64
-    } else {
-        0.0
-    };
+// ---------------------------------------------------------------------------
+// Copy Portfolio
+// ---------------------------------------------------------------------------
 
-    // Buy/sell balance for Market Maker detection
-    let total_buy: f64 = positions
-        .iter()
-        .map(|p| p.buy_usdc.parse::<f64>().unwrap_or(0.0))
-        .sum();
-    let total_sell: f64 = positions
-        .iter()
-        .map(|p| p.sell_usdc.parse::<f64>().unwrap_or(0.0))
-        .sum();
-    let buy_sell_ratio = if total_buy.max(total_sell) > 0.0 {
-        total_buy.min(total_sell) / total_buy.max(total_sell)
+pub async fn copy_portfolio(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Query(params): Query<CopyPortfolioParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    Ok(Json(build_copy_portfolio(&state, &owner, &params).await?))
+}
+
+async fn build_copy_portfolio(
+    state: &AppState,
+    owner: &str,
+    params: &CopyPortfolioParams,
+) -> Result<CopyPortfolioResponse, (StatusCode, String)> {
+    // Mutual exclusion: list_id and top cannot both be present
+    if params.list_id.is_some() && params.top.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Specify list_id or top, not both".into(),
+        ));
+    }
+    if params.capital.is_some_and(|c| !c.is_finite() || c <= 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "capital must be a positive number".into(),
+        ));
+    }
+
+    let mut list_name = None;
+    let (trader_filter, trader_count) = if let Some(ref list_id) = params.list_id {
+        // List mode: load addresses from SQLite (404 unless the caller owns the list)
+        let detail = {
+            let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+            db::get_trader_list(&conn, list_id, owner).map_err(|e| match e {
+                db::ListError::NotFound => (StatusCode::NOT_FOUND, "List not found".into()),
+                _ => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to load list".into(),
+                ),
+            })?
+        };
+        if detail.members.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "List has no members".into()));
+        }
+        let count = detail.members.len() as u32;
+        let in_list = detail
+            .members
+            .iter()
+            .map(|m| format!("'{}'", m.address.to_lowercase()))
+            .collect::<Vec<_>>()
+            .join(",");
+        list_name = Some(detail.name);
+        (in_list, count)
     } else {
-        0.0
+        // Top-N mode (default)
+        let top = params.top.unwrap_or(10).clamp(5, 50);
+        (String::new(), top)
     };
 
-    let trades_per_market = if unique_markets > 0 {
-        total_trade_count as f64 / unique_markets as f64
+    let query = if params.list_id.is_some() {
+        // List mode: filter by explicit trader addresses (no ranking)
+        format!(
+            "WITH
+                resolved AS (
+                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                    FROM poly_dearboard.resolved_prices FINAL
+                )
+            SELECT
+                toString(p.trader) AS trader,
+                p.asset_id AS asset_id,
+                toString(p.buy_amount - p.sell_amount) AS net_tokens,
+                toString(if(p.buy_amount > toDecimal128(0, 6),
+                    ROUND(p.buy_usdc / p.buy_amount, 6),
+                    toDecimal128(0, 6))) AS avg_entry,
+                toString(toFloat64(lp.latest_price)) AS latest_price,
+                toString(abs(toFloat64(p.buy_amount - p.sell_amount)) * toFloat64(lp.latest_price)) AS exposure,
+                toString(ROUND((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * toFloat64(lp.latest_price), 6)) AS pnl
+            FROM poly_dearboard.trader_positions p
+            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+            LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+            WHERE lower(p.trader) IN ({trader_filter})
+              AND rp.resolved_price IS NULL
+              AND toFloat64(lp.latest_price) > 0.01
+              AND toFloat64(lp.latest_price) < 0.99
+              AND abs(p.buy_amount - p.sell_amount) > 0.01
+            ORDER BY abs(toFloat64(p.buy_amount - p.sell_amount)) * toFloat64(lp.latest_price) DESC"
+        )
     } else {
-        0.0
+        // Top-N mode: use CTE to rank traders by PnL
+        let top = trader_count;
+        let exclude = exclude_clause();
+        format!(
+            "WITH
+                resolved AS (
+                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                    FROM poly_dearboard.resolved_prices FINAL
+                ),
+                trader_pnl AS (
+                    SELECT p.trader,
+                           sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) AS total_pnl
+                    FROM poly_dearboard.trader_positions p
+                    LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+                    LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+                    WHERE p.trader NOT IN ({exclude})
+                    GROUP BY p.trader
+                    ORDER BY total_pnl DESC
+                    LIMIT {top}
+                )
+            SELECT
+                toString(p.trader) AS trader,
+                p.asset_id AS asset_id,
+                toString(p.buy_amount - p.sell_amount) AS net_tokens,
+                toString(if(p.buy_amount > toDecimal128(0, 6),
+                    ROUND(p.buy_usdc / p.buy_amount, 6),
+                    toDecimal128(0, 6))) AS avg_entry,
+                toString(toFloat64(lp.latest_price)) AS latest_price,
+                toString(abs(toFloat64(p.buy_amount - p.sell_amount)) * toFloat64(lp.latest_price)) AS exposure,
+                toString(ROUND((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * toFloat64(lp.latest_price), 6)) AS pnl
+            FROM poly_dearboard.trader_positions p
+            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+            LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+            WHERE p.trader IN (SELECT trader FROM trader_pnl)
+              AND rp.resolved_price IS NULL
+              AND toFloat64(lp.latest_price) > 0.01
+              AND toFloat64(lp.latest_price) < 0.99
+              AND abs(p.buy_amount - p.sell_amount) > 0.01
+            ORDER BY abs(toFloat64(p.buy_amount - p.sell_amount)) * toFloat64(lp.latest_price) DESC"
+        )
     };
 
-    // --- Labels (not mutually exclusive) ---
+    let rows = state
+        .db
+        .query(&query)
+        .fetch_all::<CopyPortfolioRow>()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    // Sharp: skilled trader, statistically significant edge
-    if win_rate >= 60.0 && settled_count >= 10 && z_score > 1.5 {
-        labels.push(BehavioralLabel::Sharp);
-    }
+    // Market enrichment
+    let asset_ids: Vec<String> = rows.iter().map(|r| r.asset_id.clone()).collect();
+    let market_info = markets::resolve_markets(
+        &state.http,
+        &state.gamma,
+        &state.db,
+        &state.market_cache,
+        &asset_ids,
+    )
+    .await;
 
-    // Specialist: concentrated in one category
-    let cat_settled_count: u64 = positions
-        .iter()
-        .filter(|p| {
-            let lp: f64 = p.latest_price.parse().unwrap_or(0.5);
-            let is_settled = p.on_chain_resolved == 1 || lp >= 0.95 || lp <= 0.05;
-            is_settled
-                && market_info
-                    .get(&p.asset_id)
-                    .map(|i| i.category == dominant_category)
-                    .unwrap_or(false)
-        })
-        .count() as u64;
-    let is_specialist = if cat_settled_count >= 5 {
-        dominant_pct > 70.0 && cat_win_rate > 55.0
-    } else {
-        dominant_pct >= 80.0 && total_volume > 10_000.0 && total_trade_count >= 10
-    };
-    if is_specialist && !dominant_category.is_empty() && dominant_category != "Unknown" {
-        labels.push(BehavioralLabel::Specialist);
-    }
+    // Merge Yes/No tokens of the same market, aggregate per question
+    let mut merged: std::collections::HashMap<String, CopyPortfolioPosition> =
+        std::collections::HashMap::new();
+    // Track unique traders per market for convergence count
+    let mut traders_per_market: std::collections::HashMap<
+        String,
+        std::collections::HashSet<String>,
+    > = std::collections::HashMap::new();
 
-    // Whale: large concentrated bets
-    if total_volume > 100_000.0 && avg_position > 5_000.0 && unique_markets < 30 {
-        labels.push(BehavioralLabel::Whale);
-    }
+    for r in &rows {
+        let info = match market_info.get(&r.asset_id) {
+            Some(i) if i.active => i,
+            _ => continue,
+        };
 
-    // Degen: high volume, poor win rate — no edge
-    if win_rate < 40.0 && settled_count >= 10 && total_volume > 5_000.0 {
-        labels.push(BehavioralLabel::Degen);
-    }
+        let question = info.question.clone();
+        let net: f64 = r.net_tokens.parse().unwrap_or(0.0);
+        let exposure: f64 = r.exposure.parse().unwrap_or(0.0);
+        let pnl: f64 = r.pnl.parse().unwrap_or(0.0);
+        let entry: f64 = r.avg_entry.parse().unwrap_or(0.0);
+        let is_long = net > 0.0;
 
-    // Market Maker: balanced buy/sell, high activity across many markets
-    if buy_sell_ratio > 0.6 && total_trade_count >= 50 && unique_markets >= 10 {
-        labels.push(BehavioralLabel::MarketMaker);
-    }
+        let traders = traders_per_market.entry(question.clone()).or_default();
+        let is_new_trader = traders.insert(r.trader.clone());
 
-    // Bot: high trade frequency per market (constant rebalancing)
-    if total_trade_count >= 200 && trades_per_market >= 15.0 {
-        labels.push(BehavioralLabel::Bot);
-    }
+        if let Some(existing) = merged.get_mut(&question) {
+            let ex_exp: f64 = existing.total_exposure.parse().unwrap_or(0.0);
+            let ex_pnl: f64 = existing.total_pnl.parse().unwrap_or(0.0);
+            let ex_entry: f64 = existing.avg_entry.parse().unwrap_or(0.0);
 
-    // Contrarian: buys cheap (unpopular) outcomes that settle correctly
-    let mut contrarian_trades: u64 = 0;
-    let mut contrarian_correct: u64 = 0;
-    for p in positions {
-        let net: f64 = p.net_tokens.parse().unwrap_or(0.0);
-        if net <= 0.0 {
-            continue;
-        }
-        let lp: f64 = p.latest_price.parse().unwrap_or(0.5);
-        let is_settled = p.on_chain_resolved == 1 || lp >= 0.95 || lp <= 0.05;
-        if !is_settled {
-            continue;
-        }
-        let buy_amt: f64 = p.buy_amount.parse().unwrap_or(0.0);
-        if buy_amt < 1e-9 {
-            continue;
-        }
-        let buy_usd: f64 = p.buy_usdc.parse().unwrap_or(0.0);
-        let avg_cost = buy_usd / buy_amt;
-        if avg_cost < 0.30 {
-            contrarian_trades += 1;
-            let effective_price: f64 = if p.on_chain_resolved == 1 {
-                p.resolved_price.parse().unwrap_or(0.5)
-            } else if lp >= 0.95 {
-                1.0
+            let new_total_exp = ex_exp + exposure;
+            let weighted_entry = if new_total_exp > 0.0 {
+                (ex_exp * ex_entry + exposure * entry) / new_total_exp
             } else {
                 0.0
             };
-            if effective_price > 0.5 {
-                contrarian_correct += 1;
+
+            existing.total_exposure = format!("{new_total_exp:.6}");
+            existing.total_pnl = format!("{:.6}", ex_pnl + pnl);
+            existing.avg_entry = format!("{weighted_entry:.6}");
+            // Only count unique traders for convergence
+            if is_new_trader {
+                if is_long {
+                    existing.long_count += 1;
+                } else {
+                    existing.short_count += 1;
+                }
             }
+        } else {
+            merged.insert(
+                question.clone(),
+                CopyPortfolioPosition {
+                    token_id: info.gamma_token_id.clone(),
+                    question,
+                    outcome: info.outcome.clone(),
+                    convergence: 0, // set from HashSet len after loop
+                    long_count: if is_long { 1 } else { 0 },
+                    short_count: if !is_long { 1 } else { 0 },
+                    total_exposure: format!("{exposure:.6}"),
+                    avg_entry: format!("{entry:.6}"),
+                    latest_price: r.latest_price.clone(),
+                    total_pnl: format!("{pnl:.6}"),
+                    suggested_allocation: None,
+                    suggested_shares: None,
+                },
+            );
         }
     }
-    let contrarian_rate = if contrarian_trades > 0 {
-        (contrarian_correct as f64 / contrarian_trades as f64) * 100.0
-    } else {
-        0.0
-    };
-    if contrarian_trades >= 5 && contrarian_rate >= 60.0 {
-        labels.push(BehavioralLabel::Contrarian);
+
+    // Set convergence from unique trader counts
+    for (question, pos) in merged.iter_mut() {
+        if let Some(traders) = traders_per_market.get(question) {
+            pos.convergence = traders.len() as u32;
+        }
     }
 
-    // Casual: small/infrequent
-    if total_trade_count < 10 || total_volume < 500.0 {
-        labels.push(BehavioralLabel::Casual);
+    // Sort by convergence DESC, then exposure DESC
+    let mut positions: Vec<CopyPortfolioPosition> = merged.into_values().collect();
+    positions.sort_by(|a, b| {
+        b.convergence.cmp(&a.convergence).then_with(|| {
+            let a_exp: f64 = a.total_exposure.parse().unwrap_or(0.0);
+            let b_exp: f64 = b.total_exposure.parse().unwrap_or(0.0);
+            b_exp
+                .partial_cmp(&a_exp)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+
+    if let Some(min) = params.min_convergence {
+        positions.retain(|p| p.convergence >= min);
+    }
+    if let Some(max) = params.max_positions {
+        positions.truncate(max.max(1) as usize);
+    }
+    if let Some(capital) = params.capital {
+        suggest_allocations(&mut positions, capital);
     }
 
-    let details = LabelDetails {
-        win_rate,
-        z_score,
-        settled_count,
-        dominant_category,
-        dominant_category_pct: dominant_pct,
-        category_win_rate: cat_win_rate,
-        total_volume: format!("{:.6}", total_volume),
-        avg_position_size_usd: format!("{:.6}", avg_position),
-        unique_markets,
-        total_trade_count,
-        active_span_days,
-        buy_sell_ratio,
-        trades_per_market,
-        contrarian_trades,
-        contrarian_correct,
-        contrarian_rate,
+    let total_exposure: f64 = positions
+        .iter()
+        .map(|p| p.total_exposure.parse::<f64>().unwrap_or(0.0))
+        .sum();
+    let total_pnl: f64 = positions
+        .iter()
+        .map(|p| p.total_pnl.parse::<f64>().unwrap_or(0.0))
+        .sum();
+
+    let summary = CopyPortfolioSummary {
+        total_positions: positions.len() as u32,
+        unique_markets: positions.len() as u32,
+        total_exposure: format!("{total_exposure:.6}"),
+        total_pnl: format!("{total_pnl:.6}"),
+        top_n: trader_count,
+        member_count: list_name.is_some().then_some(trader_count),
+        list_name,
     };
 
-    (labels, details)
+    Ok(CopyPortfolioResponse { positions, summary })
+}
+
+/// Splits `capital` across `positions` in proportion to their exposure.
+/// Shares are left unset when the price is unknown.
+fn suggest_allocations(positions: &mut [CopyPortfolioPosition], capital: f64) {
+    let total: f64 = positions
+        .iter()
+        .map(|p| p.total_exposure.parse::<f64>().unwrap_or(0.0))
+        .sum();
+    for p in positions.iter_mut() {
+        let exposure = p.total_exposure.parse::<f64>().unwrap_or(0.0);
+        let allocation = if total > 0.0 {
+            capital * exposure / total
+        } else {
+            0.0
+        };
+        let price = p.latest_price.parse::<f64>().unwrap_or(0.0);
+        p.suggested_allocation = Some(format!("{allocation:.2}"));
+        p.suggested_shares = (price > 0.0).then(|| format!("{:.2}", allocation / price));
+    }
 }
 
 // ---------------------------------------------------------------------------
-// PolyLab Backtest
+// Paper Portfolios
 // ---------------------------------------------------------------------------
 
-#[derive(clickhouse::Row, serde::Deserialize)]
-struct TopTraderRow {
-    address: String,
+/// Positions snapshotted when `max_positions` is not given.
+const PAPER_DEFAULT_POSITIONS: u32 = 20;
+
+fn map_paper_error(e: db::ListError) -> (StatusCode, String) {
+    match e {
+        db::ListError::NotFound => (StatusCode::NOT_FOUND, "Paper portfolio not found".into()),
+        other => map_list_error(other),
+    }
 }
 
-pub async fn backtest(
+/// Snapshots the current copy portfolio, sized to `capital`, to be tracked
+/// forward from today.
+pub async fn create_paper_portfolio(
     State(state): State<AppState>,
-    user: AuthUser,
-    Json(req): Json<BacktestRequest>,
+    AuthUser(owner): AuthUser,
+    Json(req): Json<CreatePaperPortfolioRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    // Mutual-exclusion validation: exactly one of top_n or list_id
-    if req.top_n.is_some() && req.list_id.is_some() {
+    if !req.capital.is_finite() || !(100.0..=1_000_000.0).contains(&req.capital) {
         return Err((
             StatusCode::BAD_REQUEST,
-            "Specify list_id or top_n, not both".into(),
+            "capital must be between 100 and 1000000".into(),
         ));
     }
-    if req.top_n.is_none() && req.list_id.is_none() {
+    let params = CopyPortfolioParams {
+        top: req.top,
+        list_id: req.list_id.clone(),
+        capital: Some(req.capital),
+        max_positions: Some(
+            req.max_positions
+                .unwrap_or(PAPER_DEFAULT_POSITIONS)
+                .clamp(1, 100),
+        ),
+        min_convergence: req.min_convergence,
+    };
+    let snapshot = build_copy_portfolio(&state, &owner, &params).await?;
+
+    let positions: Vec<PaperPosition> = snapshot
+        .positions
+        .iter()
+        .filter_map(|p| {
+            let shares = p
+                .suggested_shares
+                .as_deref()?
+                .parse::<f64>()
+                .ok()
+                .filter(|s| *s > 0.0)?;
+            Some(PaperPosition {
+                token_id: p.token_id.clone(),
+                question: p.question.clone(),
+                outcome: p.outcome.clone(),
+                entry_price: p.latest_price.parse().unwrap_or(0.0),
+                shares,
+            })
+        })
+        .collect();
+    if positions.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            "Specify either list_id or top_n".into(),
+            "Copy portfolio has no open positions to snapshot".into(),
         ));
     }
-
-    let timeframe = match req.timeframe.as_str() {
-        "7d" | "30d" | "all" => req.timeframe.as_str(),
-        _ => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                "timeframe must be 7d, 30d, or all".into(),
-            ));
-        }
+    let invested: f64 = positions.iter().map(|p| p.entry_price * p.shares).sum();
+    let portfolio = PaperPortfolio {
+        id: String::new(),
+        capital: req.capital,
+        cash: (req.capital - invested).max(0.0),
+        top_n: req.list_id.is_none().then_some(snapshot.summary.top_n),
+        list_id: req.list_id,
+        list_name: snapshot.summary.list_name,
+        created_at: String::new(),
     };
-    let initial_capital = req
-        .initial_capital
-        .unwrap_or(10_000.0)
-        .clamp(100.0, 1_000_000.0);
-    let copy_pct = req.copy_pct.unwrap_or(1.0).clamp(0.01, 1.0);
 
-    // 1) Resolve trader addresses — from list or top-N
-    let trader_rows: Vec<TopTraderRow>;
-
-    if let Some(ref list_id) = req.list_id {
-        let owner = user.0.clone();
-        let addresses = {
-            let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-            db::get_list_member_addresses(&conn, list_id, &owner).map_err(|e| match e {
-                db::ListError::NotFound => (StatusCode::NOT_FOUND, "List not found".into()),
-                _ => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Failed to load list".into(),
-                ),
-            })?
-        };
-        if addresses.is_empty() {
-            return Err((StatusCode::BAD_REQUEST, "List has no members".into()));
-        }
-        trader_rows = addresses
-            .into_iter()
-            .map(|a| TopTraderRow { address: a })
-            .collect();
-    } else {
-        let top_n = req.top_n.unwrap().clamp(1, 50);
-        let exclude = exclude_clause();
-        let top_query = format!(
-            "WITH resolved AS (
-                SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-                FROM poly_dearboard.resolved_prices FINAL
-            )
-            SELECT toString(p.trader) AS address
-            FROM poly_dearboard.trader_positions p
-            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-            LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-            WHERE p.trader NOT IN ({exclude})
-            GROUP BY p.trader
-            ORDER BY sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) DESC
-            LIMIT {top_n}"
-        );
-        trader_rows = state
-            .db
-            .query(&top_query)
-            .fetch_all::<TopTraderRow>()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    }
+    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+    let portfolio = db::create_paper_portfolio(&conn, &owner, portfolio, &positions)
+        .map_err(map_paper_error)?;
+    Ok((StatusCode::CREATED, Json(portfolio)))
+}
 
-    let top_n = trader_rows.len() as u32;
-    let user_allocation = initial_capital * copy_pct;
-    let per_trader_budget = if top_n > 0 {
-        user_allocation / top_n as f64
-    } else {
-        0.0
+/// Marks a paper portfolio to market, with its daily equity curve.
+pub async fn get_paper_portfolio(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (portfolio, positions, equity_curve) = {
+        let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+        db::get_paper_portfolio(&conn, &id, &owner).map_err(map_paper_error)?
     };
+    let token_ids: Vec<String> = positions.iter().map(|p| p.token_id.clone()).collect();
+    let marks = fetch_paper_marks(&state, &token_ids)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let config = BacktestConfig {
-        initial_capital,
-        copy_pct,
-        top_n,
-        timeframe: timeframe.to_string(),
-        per_trader_budget,
+    let mut current_value = portfolio.cash;
+    let mut resolved_count = 0;
+    let positions: Vec<PaperPositionStatus> = positions
+        .into_iter()
+        .map(|position| {
+            let (price, resolved) = paper_mark(&position, &marks);
+            let value = position.shares * price;
+            let pnl = value - position.shares * position.entry_price;
+            current_value += value;
+            if resolved.is_some() {
+                resolved_count += 1;
+            }
+            PaperPositionStatus {
+                position,
+                current_price: format!("{price:.4}"),
+                value: format!("{value:.2}"),
+                pnl: format!("{pnl:.2}"),
+                status: if resolved.is_some() {
+                    "resolved"
+                } else {
+                    "open"
+                },
+                resolved_price: resolved.map(|r| format!("{r}")),
+            }
+        })
+        .collect();
+
+    let pnl = current_value - portfolio.capital;
+    let pnl_pct = if portfolio.capital > 0.0 {
+        pnl / portfolio.capital * 100.0
+    } else {
+        0.0
     };
+    Ok(Json(PaperPortfolioDetail {
+        portfolio,
+        current_value: format!("{current_value:.2}"),
+        pnl: format!("{pnl:.2}"),
+        pnl_pct: (pnl_pct * 10.0).round() / 10.0,
+        resolved_count,
+        positions,
+        equity_curve,
+    }))
+}
 
-    if trader_rows.is_empty() {
-        return Ok(Json(BacktestResponse {
-            portfolio_curve: vec![],
-            pnl_curve: vec![],
-            summary: BacktestSummary {
-                total_pnl: "0.00".into(),
-                total_return_pct: 0.0,
-                win_rate: 0.0,
-                max_drawdown: "0.00".into(),
-                max_drawdown_pct: 0.0,
-                positions_count: 0,
-                traders_count: 0,
-                initial_capital,
-                final_value: initial_capital,
-            },
-            traders: vec![],
-            config,
-        }));
+/// Latest and resolved price per asset, in one query.
+async fn fetch_paper_marks(
+    state: &AppState,
+    asset_ids: &[String],
+) -> Result<std::collections::HashMap<String, (f64, Option<f64>)>, clickhouse::error::Error> {
+    if asset_ids.is_empty() {
+        return Ok(std::collections::HashMap::new());
     }
-
-    let addresses: Vec<String> = trader_rows
+    let id_list = asset_ids
         .iter()
-        .map(|r| r.address.to_lowercase())
-        .collect();
-    let in_list = addresses
-        .iter()
-        .map(|a| format!("'{a}'"))
+        .map(|id| format!("'{}'", id.replace('\'', "''")))
         .collect::<Vec<_>>()
         .join(",");
-
-    // 2) Fetch per-trader scaling data
-    let scale_rows = state
+    let rows = state
         .db
         .query(&format!(
             "SELECT
-            toString(p.trader) AS address,
-            toString(ROUND(sum(p.buy_usdc) / count(), 6)) AS avg_position_size,
-            count() AS market_count
-        FROM poly_dearboard.trader_positions p
-        WHERE lower(p.trader) IN ({in_list})
-        GROUP BY p.trader"
+                lp.asset_id AS asset_id,
+                toString(lp.latest_price) AS latest_price,
+                rp.resolved_price AS resolved_price
+            FROM (
+                SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL
+                WHERE asset_id IN ({id_list})
+            ) AS lp
+            LEFT JOIN (
+                SELECT asset_id, resolved_price FROM poly_dearboard.resolved_prices FINAL
+                WHERE asset_id IN ({id_list})
+            ) AS rp ON lp.asset_id = rp.asset_id"
         ))
-        .fetch_all::<TraderScaleRow>()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .fetch_all::<PaperMarkRow>()
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let latest = r.latest_price.parse::<f64>().unwrap_or(0.0);
+            (r.asset_id, (latest, r.resolved_price.parse::<f64>().ok()))
+        })
+        .collect())
+}
 
-    let mut trader_scales: std::collections::HashMap<String, f64> =
-        std::collections::HashMap::new();
-    for row in &scale_rows {
-        let avg_pos = row.avg_position_size.parse::<f64>().unwrap_or(1.0).max(1.0);
-        let scale = per_trader_budget / avg_pos;
-        trader_scales.insert(row.address.to_lowercase(), scale);
+/// Price a paper position is worth now: the resolved price once its market
+/// resolved, else the latest trade, else the entry price. Also returns the
+/// resolved price, if any.
+fn paper_mark(
+    position: &PaperPosition,
+    marks: &std::collections::HashMap<String, (f64, Option<f64>)>,
+) -> (f64, Option<f64>) {
+    match marks.get(&position.token_id) {
+        Some((_, Some(resolved))) => (*resolved, Some(*resolved)),
+        Some((latest, None)) if *latest > 0.0 => (*latest, None),
+        _ => (position.entry_price, None),
     }
+}
 
-    // 3) Build portfolio simulation from per-trader pnl_daily
-    let day_filter = match timeframe {
-        "7d" => Some(7),
-        "30d" => Some(30),
-        _ => None,
+/// Appends today's value point to every paper portfolio (upserting if the
+/// day already has one).
+pub async fn record_paper_portfolio_values(state: &AppState) {
+    let portfolios = {
+        let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+        match db::all_paper_portfolios(&conn) {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::warn!("Failed to load paper portfolios: {e}");
+                return;
+            }
+        }
     };
+    if portfolios.is_empty() {
+        return;
+    }
 
-    // Pre-window initial state (per-trader, for scaling)
-    let mut asset_state: std::collections::HashMap<String, (f64, f64, f64)> =
-        std::collections::HashMap::new();
-
-    if let Some(days) = day_filter {
-        let initial = state
-            .db
-            .query(&format!(
-                "SELECT
-                toString(trader) AS trader,
-                asset_id,
-                toString(sum(buy_amount) - sum(sell_amount)) AS net_tokens,
-                toString(sum(sell_usdc) - sum(buy_usdc)) AS cash_flow,
-                toString(argMaxMerge(last_price_state)) AS last_price
-            FROM poly_dearboard.pnl_daily
-            WHERE lower(trader) IN ({in_list})
-              AND day < today() - {days}
-            GROUP BY trader, asset_id"
-            ))
-            .fetch_all::<PnlInitialStateTraderRow>()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut token_ids: Vec<String> = portfolios
+        .iter()
+        .flat_map(|(_, positions)| positions.iter().map(|p| p.token_id.clone()))
+        .collect();
+    token_ids.sort_unstable();
+    token_ids.dedup();
+    let marks = match fetch_paper_marks(state, &token_ids).await {
+        Ok(m) => m,
+        Err(e) => {
+            tracing::warn!("Failed to mark paper portfolios: {e}");
+            return;
+        }
+    };
 
-        for row in initial {
-            let scale = trader_scales
-                .get(&row.trader.to_lowercase())
-                .copied()
-                .unwrap_or(1.0);
-            let tokens = row.net_tokens.parse::<f64>().unwrap_or(0.0) * scale;
-            let cash = row.cash_flow.parse::<f64>().unwrap_or(0.0) * scale;
-            let price = row.last_price.parse::<f64>().unwrap_or(0.0);
-            let entry = asset_state
-                .entry(row.asset_id.clone())
-                .or_insert((0.0, 0.0, 0.0));
-            entry.0 += tokens;
-            entry.1 += cash;
-            entry.2 = price;
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
+    for (portfolio, positions) in &portfolios {
+        let value = portfolio.cash
+            + positions
+                .iter()
+                .map(|p| p.shares * paper_mark(p, &marks).0)
+                .sum::<f64>();
+        if let Err(e) = db::record_paper_value(&conn, &portfolio.id, &today, value) {
+            tracing::warn!("Failed to record paper portfolio {}: {e}", portfolio.id);
         }
     }
+    tracing::info!(
+        "Recorded value points for {} paper portfolios",
+        portfolios.len()
+    );
+}
 
-    // Window deltas (per-trader for scaling)
-    let day_where = day_filter
-        .map(|d| format!("AND day >= today() - {d}"))
-        .unwrap_or_default();
+/// Positions smaller than this (tokens) are treated as closed for overlap purposes.
+const SIMILAR_MIN_NET_TOKENS: f64 = 0.01;
+/// Candidates must share at least one of the target's largest open positions.
+const SIMILAR_SEED_POSITIONS: u32 = 20;
+const SIMILAR_MIN_OVERLAP: u32 = 3;
+const SIMILAR_LIMIT: u32 = 10;
+
+/// Traders whose open positions overlap most with `address` (copy-traders, sybils).
+pub async fn similar_traders(
+    State(state): State<AppState>,
+    ValidatedAddress(address): ValidatedAddress,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let exclude = exclude_clause();
 
     let rows = state
         .db
         .query(&format!(
-            "SELECT
-            toString(trader) AS trader,
-            toString(day) AS date,
-            asset_id,
-            toString(sum(buy_amount) - sum(sell_amount)) AS net_token_delta,
-            toString(sum(sell_usdc) - sum(buy_usdc)) AS cash_flow_delta,
-            toString(argMaxMerge(last_price_state)) AS last_price
-        FROM poly_dearboard.pnl_daily
-        WHERE lower(trader) IN ({in_list})
-          {day_where}
-        GROUP BY trader, day, asset_id
-        ORDER BY day, trader, asset_id"
+            "WITH target AS (
+                SELECT asset_id, toFloat64(sum(buy_amount) - sum(sell_amount)) AS net
+                FROM poly_dearboard.trader_positions
+                WHERE lower(trader) = ?
+                GROUP BY asset_id
+                HAVING abs(net) > {SIMILAR_MIN_NET_TOKENS}
+            ),
+            seed AS (
+                SELECT asset_id FROM target
+                ORDER BY abs(net) DESC
+                LIMIT {SIMILAR_SEED_POSITIONS}
+            ),
+            candidates AS (
+                SELECT DISTINCT trader
+                FROM poly_dearboard.trader_positions
+                WHERE asset_id IN (SELECT asset_id FROM seed)
+                  AND lower(trader) != ?
+                  AND trader NOT IN ({exclude})
+            ),
+            candidate_positions AS (
+                SELECT trader, asset_id, toFloat64(sum(buy_amount) - sum(sell_amount)) AS net
+                FROM poly_dearboard.trader_positions
+                WHERE trader IN (SELECT trader FROM candidates)
+                GROUP BY trader, asset_id
+                HAVING abs(net) > {SIMILAR_MIN_NET_TOKENS}
+            )
+            SELECT
+                toString(c.trader) AS address,
+                toUInt64(countIf(t.asset_id != '')) AS overlap_count,
+                round(countIf(t.asset_id != '')
+                    / toFloat64((SELECT count() FROM target) + count() - countIf(t.asset_id != '')), 6) AS overlap_ratio,
+                toUInt64(countIf(t.asset_id != '' AND sign(c.net) = sign(t.net))) AS same_side_count
+            FROM candidate_positions c
+            LEFT JOIN target t ON c.asset_id = t.asset_id
+            GROUP BY c.trader
+            HAVING overlap_count >= {SIMILAR_MIN_OVERLAP}
+            ORDER BY overlap_ratio DESC, overlap_count DESC, address
+            LIMIT {SIMILAR_LIMIT}"
         ))
-        .fetch_all::<PnlDailyTraderRow>()
+        .bind(&address)
+        .bind(&address)
+        .fetch_all::<SimilarTraderRow>()
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let resolved = fetch_resolved_prices(&state).await;
-
-    // Simulate portfolio with scaling
-    let portfolio_curve = simulate_portfolio(
-        &rows,
-        &mut asset_state,
-        &resolved,
-        &trader_scales,
-        initial_capital,
-    );
-
-    // Also build raw PnL curve for backward compat
-    let pnl_curve: Vec<PnlChartPoint> = portfolio_curve
-        .iter()
-        .map(|p| PnlChartPoint {
-            date: p.date.clone(),
-            pnl: p.pnl.clone(),
+    let traders = rows
+        .into_iter()
+        .map(|r| {
+            let opposite = r.overlap_count - r.same_side_count;
+            SimilarTrader {
+                side: match r.same_side_count.cmp(&opposite) {
+                    std::cmp::Ordering::Greater => "same",
+                    std::cmp::Ordering::Less => "opposite",
+                    std::cmp::Ordering::Equal => "mixed",
+                },
+                address: r.address,
+                overlap_count: r.overlap_count,
+                overlap_ratio: r.overlap_ratio,
+                same_side_count: r.same_side_count,
+            }
         })
         .collect();
 
-    // 4) Summary stats
-    let final_value = portfolio_curve
-        .last()
-        .and_then(|p| p.value.parse::<f64>().ok())
-        .unwrap_or(initial_capital);
-    let total_pnl = final_value - initial_capital;
-    let total_return_pct = if initial_capital > 0.0 {
-        (total_pnl / initial_capital) * 100.0
-    } else {
-        0.0
-    };
+    Ok(Json(SimilarTradersResponse { address, traders }))
+}
 
-    // Max drawdown on portfolio value
-    let mut peak_value = initial_capital;
-    let mut max_dd: f64 = 0.0;
-    let mut max_dd_pct: f64 = 0.0;
-    for pt in &portfolio_curve {
-        let v = pt.value.parse::<f64>().unwrap_or(initial_capital);
-        if v > peak_value {
-            peak_value = v;
-        }
-        let dd = peak_value - v;
-        if dd > max_dd {
-            max_dd = dd;
-        }
-        let dd_pct = if peak_value > 0.0 {
-            dd / peak_value * 100.0
-        } else {
-            0.0
-        };
-        if dd_pct > max_dd_pct {
-            max_dd_pct = dd_pct;
-        }
+fn shorten_id(id: &str) -> String {
+    if id.len() <= 12 {
+        id.to_string()
+    } else {
+        format!("{}...{}", &id[..6], &id[id.len() - 4..])
     }
+}
 
-    // Win rate + position count
-    #[derive(clickhouse::Row, serde::Deserialize)]
-    struct WinRateRow {
-        total: u64,
-        wins: u64,
+// ---------------------------------------------------------------------------
+// Trader Lists CRUD
+// ---------------------------------------------------------------------------
+
+fn map_list_error(e: db::ListError) -> (StatusCode, String) {
+    match e {
+        db::ListError::LimitExceeded(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.into()),
+        db::ListError::DuplicateName => (
+            StatusCode::CONFLICT,
+            "A list with this name already exists".into(),
+        ),
+        db::ListError::NotFound => (StatusCode::NOT_FOUND, "List not found".into()),
+        db::ListError::Db(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
     }
-    let wr = state.db.query(&format!(
-        "WITH resolved AS (
-            SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-            FROM poly_dearboard.resolved_prices FINAL
-        )
-        SELECT
-            count() AS total,
-            countIf((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price)) > 0) AS wins
-        FROM poly_dearboard.trader_positions p
-        LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-        LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-        WHERE lower(p.trader) IN ({in_list})"
-    ))
-    .fetch_one::<WinRateRow>()
+}
+
+/// Runs a list operation against the SQLite user DB on the blocking pool.
+async fn with_list_db<T: Send + 'static>(
+    state: &AppState,
+    f: impl FnOnce(&rusqlite::Connection) -> Result<T, db::ListError> + Send + 'static,
+) -> Result<T, (StatusCode, String)> {
+    let user_db = state.user_db.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = user_db.lock().unwrap_or_else(|p| p.into_inner());
+        f(&conn)
+    })
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(map_list_error)
+}
+
+fn validate_list_name(name: &str) -> Result<String, (StatusCode, String)> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 50 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Name must be 1-50 characters".into(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+pub async fn list_trader_lists(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let lists = with_list_db(&state, move |conn| Ok(db::list_trader_lists(conn, &owner)?)).await?;
+    Ok(Json(lists))
+}
+
+pub async fn create_trader_list(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Json(req): Json<CreateListRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let name = validate_list_name(&req.name)?;
+    let list = with_list_db(&state, move |conn| {
+        db::create_trader_list(conn, &owner, &name)
+    })
+    .await?;
+    Ok((StatusCode::CREATED, Json(list)))
+}
+
+/// Copies one of the caller's lists, members and labels included.
+pub async fn duplicate_trader_list(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+    req: Option<Json<DuplicateListRequest>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let Json(req) = req.unwrap_or_default();
+    let name = req.name.as_deref().map(validate_list_name).transpose()?;
+    let detail = with_list_db(&state, move |conn| {
+        db::duplicate_trader_list(conn, &id, &owner, name.as_deref())
+    })
+    .await?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+pub async fn get_trader_list(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let detail = with_list_db(&state, move |conn| db::get_trader_list(conn, &id, &owner)).await?;
+    Ok(Json(detail))
+}
 
-    let win_rate = if wr.total > 0 {
-        (wr.wins as f64 / wr.total as f64) * 100.0
-    } else {
-        0.0
-    };
+pub async fn rename_trader_list(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+    Json(req): Json<RenameListRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let name = validate_list_name(&req.name)?;
+    with_list_db(&state, move |conn| {
+        db::rename_trader_list(conn, &id, &owner, &name)
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    // 5) Per-trader breakdown with scaling
-    #[derive(clickhouse::Row, serde::Deserialize)]
-    struct TraderPnlRow {
-        address: String,
-        pnl: String,
-        markets_traded: u64,
+pub async fn delete_trader_list(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    with_list_db(&state, move |conn| {
+        db::delete_trader_list(conn, &id, &owner)
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn add_list_members(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+    Json(req): Json<AddMembersRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if req.addresses.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "At least one address required".into(),
+        ));
     }
-    let trader_pnls = state.db.query(&format!(
-        "WITH resolved AS (
-            SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-            FROM poly_dearboard.resolved_prices FINAL
-        )
-        SELECT
-            toString(p.trader) AS address,
-            toString(ROUND(sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))), 6)) AS pnl,
-            count() AS markets_traded
-        FROM poly_dearboard.trader_positions p
-        LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-        LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-        WHERE lower(p.trader) IN ({in_list})
-        GROUP BY p.trader
-        ORDER BY sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) DESC"
-    ))
-    .fetch_all::<TraderPnlRow>()
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let total_scaled_abs: f64 = trader_pnls
-        .iter()
-        .map(|t| {
-            let raw = t.pnl.parse::<f64>().unwrap_or(0.0);
-            let scale = trader_scales
-                .get(&t.address.to_lowercase())
-                .copied()
-                .unwrap_or(1.0);
-            (raw * scale).abs()
-        })
-        .sum();
+    let labels = req.labels.unwrap_or_default();
 
-    let traders: Vec<BacktestTrader> = trader_pnls
-        .into_iter()
+    let members: Vec<(String, Option<String>)> = req
+        .addresses
+        .iter()
         .enumerate()
-        .map(|(i, t)| {
-            let raw_pnl = t.pnl.parse::<f64>().unwrap_or(0.0);
-            let scale = trader_scales
-                .get(&t.address.to_lowercase())
-                .copied()
-                .unwrap_or(1.0);
-            let scaled = raw_pnl * scale;
-            BacktestTrader {
-                address: t.address,
-                rank: (i + 1) as u32,
-                pnl: t.pnl,
-                scaled_pnl: format!("{:.2}", scaled),
-                markets_traded: t.markets_traded,
-                contribution_pct: if total_scaled_abs > 0.0 {
-                    (scaled.abs() / total_scaled_abs) * 100.0
-                } else {
-                    0.0
-                },
-                scale_factor: (scale * 1000.0).round() / 1000.0,
-            }
+        .map(|(i, addr)| {
+            let validated = middleware::validate_eth_address(addr)
+                .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid address: {addr}")))?;
+            let label = labels.get(i).and_then(|l| l.clone());
+            Ok((validated, label))
         })
-        .collect();
+        .collect::<Result<Vec<_>, (StatusCode, String)>>()?;
 
-    Ok(Json(BacktestResponse {
-        portfolio_curve,
-        pnl_curve,
-        summary: BacktestSummary {
-            total_pnl: format!("{:.2}", total_pnl),
-            total_return_pct: (total_return_pct * 10.0).round() / 10.0,
-            win_rate: (win_rate * 10.0).round() / 10.0,
-            max_drawdown: format!("{:.2}", max_dd),
-            max_drawdown_pct: (max_dd_pct * 10.0).round() / 10.0,
-            positions_count: wr.total,
-            traders_count: top_n,
-            initial_capital,
-            final_value: (final_value * 100.0).round() / 100.0,
-        },
-        traders,
-        config,
-    }))
+    with_list_db(&state, move |conn| {
+        db::add_list_members(conn, &id, &owner, &members)
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
-/// Portfolio simulation with per-trader scaling and capital constraints.
-fn simulate_portfolio(
-    rows: &[PnlDailyTraderRow],
-    asset_state: &mut std::collections::HashMap<String, (f64, f64, f64)>,
-    resolved: &std::collections::HashMap<String, f64>,
-    trader_scales: &std::collections::HashMap<String, f64>,
-    initial_capital: f64,
-) -> Vec<PortfolioPoint> {
-    // Compute initial cash: initial_capital minus cost of pre-window positions
-    let pre_window_cost: f64 = asset_state
-        .values()
-        .map(|(_, cash, _)| -cash)
-        .sum::<f64>()
-        .max(0.0);
-    let mut cash_balance = (initial_capital - pre_window_cost).max(0.0);
-
-    let mut points: Vec<PortfolioPoint> = Vec::new();
-    let mut current_date = String::new();
+const MAX_MEMBER_LABEL_CHARS: usize = 64;
 
-    for row in rows {
-        if !current_date.is_empty() && row.date != current_date {
-            // Emit point for previous date
-            let positions_value: f64 = asset_state
-                .values()
-                .map(|(tokens, _, price)| tokens * price)
-                .sum();
-            let total_value = cash_balance + positions_value;
-            let pnl = total_value - initial_capital;
-            points.push(PortfolioPoint {
-                date: current_date.clone(),
-                value: format!("{:.2}", total_value),
-                pnl: format!("{:.2}", pnl),
-                pnl_pct: format!(
-                    "{:.2}",
-                    if initial_capital > 0.0 {
-                        pnl / initial_capital * 100.0
-                    } else {
-                        0.0
-                    }
-                ),
-            });
-        }
-        current_date.clone_from(&row.date);
+pub async fn update_member_label(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path((id, address)): Path<(String, String)>,
+    Json(req): Json<UpdateMemberLabelRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let address = middleware::validate_eth_address(&address)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid address".to_string()))?;
+    let label = req
+        .label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty());
+    if label
+        .as_ref()
+        .is_some_and(|l| l.chars().count() > MAX_MEMBER_LABEL_CHARS)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Label must be at most {MAX_MEMBER_LABEL_CHARS} characters"),
+        ));
+    }
 
-        let scale = trader_scales
-            .get(&row.trader.to_lowercase())
-            .copied()
-            .unwrap_or(1.0);
-        let mut delta_tokens = row.net_token_delta.parse::<f64>().unwrap_or(0.0) * scale;
-        let mut delta_cash = row.cash_flow_delta.parse::<f64>().unwrap_or(0.0) * scale;
-        let price = row.last_price.parse::<f64>().unwrap_or(0.0);
+    with_list_db(&state, move |conn| {
+        db::update_member_label(conn, &id, &owner, &address, label.as_deref())
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-        // Capital constraint: if buying (delta_cash < 0), clip to available cash
-        if delta_cash < 0.0 {
-            let cost = -delta_cash;
-            if cost > cash_balance && cash_balance > 0.0 {
-                let clip = cash_balance / cost;
-                delta_tokens *= clip;
-                delta_cash *= clip;
-            } else if cash_balance <= 0.0 {
-                // No cash left — skip this buy
-                let entry = asset_state
-                    .entry(row.asset_id.clone())
-                    .or_insert((0.0, 0.0, 0.0));
-                entry.2 = price; // Still update price
-                continue;
-            }
-        }
+pub async fn remove_list_members(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+    Json(req): Json<RemoveMembersRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let addresses: Vec<String> = req.addresses.iter().map(|a| a.to_lowercase()).collect();
 
-        cash_balance += delta_cash;
-        let entry = asset_state
-            .entry(row.asset_id.clone())
-            .or_insert((0.0, 0.0, 0.0));
-        entry.0 += delta_tokens;
-        entry.1 += delta_cash;
-        entry.2 = price;
-    }
+    with_list_db(&state, move |conn| {
+        db::remove_list_members(conn, &id, &owner, &addresses)
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    // Final point with resolved prices
-    if !current_date.is_empty() {
-        let positions_value: f64 = asset_state
-            .iter()
-            .map(|(asset_id, (tokens, _, price))| {
-                let final_price = resolved.get(asset_id).copied().unwrap_or(*price);
-                tokens * final_price
-            })
-            .sum();
-        let total_value = cash_balance + positions_value;
-        let pnl = total_value - initial_capital;
-        points.push(PortfolioPoint {
-            date: current_date,
-            value: format!("{:.2}", total_value),
-            pnl: format!("{:.2}", pnl),
-            pnl_pct: format!(
-                "{:.2}",
-                if initial_capital > 0.0 {
-                    pnl / initial_capital * 100.0
-                } else {
-                    0.0
-                }
-            ),
-        });
+/// Creates a list from the current top of the leaderboard, labelling members
+/// with their rank ("#1", "#2", ...).
+pub async fn create_list_from_leaderboard(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Json(req): Json<ListFromLeaderboardRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let name = validate_list_name(&req.name)?;
+    let top_n = req.top_n.unwrap_or(10).clamp(1, db::MAX_MEMBERS_PER_LIST);
+    let params = LeaderboardParams {
+        sort: req.sort,
+        timeframe: req.timeframe,
+        limit: Some(top_n),
+        ..Default::default()
+    };
+    let query = LeaderboardQuery::parse(&params, String::new(), None)?;
+    let ranked = compute_leaderboard(&state, &query).await?;
+    if ranked.traders.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Leaderboard is empty".into()));
     }
+    let members: Vec<(String, Option<String>)> = ranked
+        .traders
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.address.to_lowercase(), Some(format!("#{}", i + 1))))
+        .collect();
 
-    points
+    let detail = with_list_db(&state, move |conn| {
+        db::create_trader_list_with_members(conn, &owner, &name, &members)
+    })
+    .await?;
+    Ok((StatusCode::CREATED, Json(detail)))
 }
 
-// ---------------------------------------------------------------------------
-// Copy Portfolio
-// ---------------------------------------------------------------------------
-
-pub async fn copy_portfolio(
+/// Combined all-time metrics, member summaries and open exposure of a list.
+pub async fn trader_list_stats(
     State(state): State<AppState>,
-    user: AuthUser,
-    Query(params): Query<CopyPortfolioParams>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    // Mutual exclusion: list_id and top cannot both be present
-    if params.list_id.is_some() && params.top.is_some() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Specify list_id or top, not both".into(),
-        ));
-    }
+    let detail = {
+        let owner = owner.clone();
+        with_list_db(&state, move |conn| db::get_trader_list(conn, &id, &owner)).await?
+    };
+    Ok(Json(list_stats(&state, &detail, Some(&owner)).await?))
+}
 
-    let (trader_filter, trader_count) = if let Some(ref list_id) = params.list_id {
-        // List mode: load addresses from SQLite
-        let owner = user.0.clone();
-        let addresses = {
-            let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-            db::get_list_member_addresses(&conn, list_id, &owner).map_err(|e| match e {
-                db::ListError::NotFound => (StatusCode::NOT_FOUND, "List not found".into()),
-                _ => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Failed to load list".into(),
-                ),
-            })?
-        };
-        let count = addresses.len() as u32;
-        let in_list = addresses
-            .into_iter()
-            .take(100)
-            .map(|a| format!("'{a}'"))
-            .collect::<Vec<_>>()
-            .join(",");
-        (in_list, count)
-    } else {
-        // Top-N mode (default)
-        let top = params.top.unwrap_or(10).clamp(5, 50);
-        (String::new(), top)
+/// Stats for a loaded list. `alias_owner` gets their private aliases applied;
+/// shared (public) views pass `None`.
+async fn list_stats(
+    state: &AppState,
+    detail: &TraderListDetail,
+    alias_owner: Option<&str>,
+) -> Result<TraderListStats, (StatusCode, String)> {
+    let addresses: Vec<String> = detail
+        .members
+        .iter()
+        .map(|m| m.address.to_lowercase())
+        .collect();
+    let mut stats = TraderListStats {
+        id: detail.id.clone(),
+        name: detail.name.clone(),
+        member_count: addresses.len() as u32,
+        total_pnl: "0.000000".into(),
+        total_volume: "0.000000".into(),
+        members: vec![],
+        labels: std::collections::HashMap::new(),
+        exposure: vec![],
     };
+    if addresses.is_empty() {
+        return Ok(stats);
+    }
 
-    let query = if params.list_id.is_some() {
-        // List mode: filter by explicit trader addresses (no ranking)
-        format!(
-            "WITH
-                resolved AS (
-                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
-                    FROM poly_dearboard.resolved_prices FINAL
-                )
-            SELECT
-                toString(p.trader) AS trader,
-                p.asset_id AS asset_id,
-                toString(p.buy_amount - p.sell_amount) AS net_tokens,
-                toString(if(p.buy_amount > toDecimal128(0, 6),
-                    ROUND(p.buy_usdc / p.buy_amount, 6),
-                    toDecimal128(0, 6))) AS avg_entry,
-                toString(toFloat64(lp.latest_price)) AS latest_price,
-                toString(abs(toFloat64(p.buy_amount - p.sell_amount)) * toFloat64(lp.latest_price)) AS exposure,
-                toString(ROUND((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * toFloat64(lp.latest_price), 6)) AS pnl
-            FROM poly_dearboard.trader_positions p
-            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-            LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-            WHERE p.trader IN ({trader_filter})
-              AND rp.resolved_price IS NULL
-              AND toFloat64(lp.latest_price) > 0.01
-              AND toFloat64(lp.latest_price) < 0.99
-              AND abs(p.buy_amount - p.sell_amount) > 0.01
-            ORDER BY abs(toFloat64(p.buy_amount - p.sell_amount)) * toFloat64(lp.latest_price) DESC"
+    // Member summaries: the all-time leaderboard restricted to the list
+    let params = LeaderboardParams {
+        limit: Some(db::MAX_MEMBERS_PER_LIST),
+        list_id: Some(detail.id.clone()),
+        ..Default::default()
+    };
+    let query = LeaderboardQuery::parse(&params, String::new(), Some(addresses.clone()))?;
+    let ranked = compute_leaderboard(state, &query).await?;
+    let (total_pnl, total_volume) = ranked.traders.iter().fold((0.0, 0.0), |(pnl, vol), t| {
+        (
+            pnl + t.realized_pnl.parse::<f64>().unwrap_or(0.0),
+            vol + t.total_volume.parse::<f64>().unwrap_or(0.0),
         )
-    } else {
-        // Top-N mode: use CTE to rank traders by PnL
-        let top = trader_count;
-        let exclude = exclude_clause();
-        format!(
+    });
+    stats.total_pnl = format!("{total_pnl:.6}");
+    stats.total_volume = format!("{total_volume:.6}");
+    stats.members = ranked.traders;
+    stats.labels = ranked.labels;
+    apply_aliases(state, alias_owner, &mut stats.members);
+
+    // Open exposure, as in smart_money's smart_positions scoped to the list
+    let in_list = addresses
+        .iter()
+        .map(|a| format!("'{a}'"))
+        .collect::<Vec<_>>()
+        .join(",");
+    let rows = state
+        .db
+        .query(&format!(
             "WITH
                 resolved AS (
                     SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
                     FROM poly_dearboard.resolved_prices FINAL
                 ),
-                trader_pnl AS (
-                    SELECT p.trader,
-                           sum((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * coalesce(rp.resolved_price, toFloat64(lp.latest_price))) AS total_pnl
+                smart_positions AS (
+                    SELECT p.asset_id AS asset_id,
+                           (p.buy_amount - p.sell_amount) AS net_tokens,
+                           toFloat64(lp.latest_price) AS price,
+                           toFloat64(p.buy_amount - p.sell_amount) * toFloat64(lp.latest_price) AS exposure
                     FROM poly_dearboard.trader_positions p
                     LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
                     LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-                    WHERE p.trader NOT IN ({exclude})
-                    GROUP BY p.trader
-                    ORDER BY total_pnl DESC
-                    LIMIT {top}
+                    WHERE lower(p.trader) IN ({in_list})
+                      AND rp.resolved_price IS NULL
+                      AND toFloat64(lp.latest_price) > 0.01
+                      AND toFloat64(lp.latest_price) < 0.99
+                      AND abs(p.buy_amount - p.sell_amount) > 0.01
                 )
             SELECT
-                toString(p.trader) AS trader,
-                p.asset_id AS asset_id,
-                toString(p.buy_amount - p.sell_amount) AS net_tokens,
-                toString(if(p.buy_amount > toDecimal128(0, 6),
-                    ROUND(p.buy_usdc / p.buy_amount, 6),
-                    toDecimal128(0, 6))) AS avg_entry,
-                toString(toFloat64(lp.latest_price)) AS latest_price,
-                toString(abs(toFloat64(p.buy_amount - p.sell_amount)) * toFloat64(lp.latest_price)) AS exposure,
-                toString(ROUND((p.sell_usdc - p.buy_usdc) + (p.buy_amount - p.sell_amount) * toFloat64(lp.latest_price), 6)) AS pnl
-            FROM poly_dearboard.trader_positions p
-            LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
-            LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
-            WHERE p.trader IN (SELECT trader FROM trader_pnl)
-              AND rp.resolved_price IS NULL
-              AND toFloat64(lp.latest_price) > 0.01
-              AND toFloat64(lp.latest_price) < 0.99
-              AND abs(p.buy_amount - p.sell_amount) > 0.01
-            ORDER BY abs(toFloat64(p.buy_amount - p.sell_amount)) * toFloat64(lp.latest_price) DESC"
-        )
-    };
-
-    let rows = state
-        .db
-        .query(&query)
-        .fetch_all::<CopyPortfolioRow>()
+                asset_id,
+                count() AS smart_trader_count,
+                countIf(net_tokens > 0) AS long_count,
+                countIf(net_tokens < 0) AS short_count,
+                toString(sum(if(net_tokens > 0, exposure, toFloat64(0)))) AS long_exposure,
+                toString(sum(if(net_tokens < 0, abs(exposure), toFloat64(0)))) AS short_exposure,
+                toString(avg(price)) AS avg_price
+            FROM smart_positions
+            GROUP BY asset_id
+            ORDER BY count() DESC, sum(abs(exposure)) DESC
+            LIMIT 20"
+        ))
+        .fetch_all::<SmartMoneyRow>()
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    stats.exposure = merge_smart_money_rows(state, rows).await;
 
-    // Market enrichment
-    let asset_ids: Vec<String> = rows.iter().map(|r| r.asset_id.clone()).collect();
-    let market_info =
-        markets::resolve_markets(&state.http, &state.db, &state.market_cache, &asset_ids).await;
+    Ok(stats)
+}
 
-    // Merge Yes/No tokens of the same market, aggregate per question
-    let mut merged: std::collections::HashMap<String, CopyPortfolioPosition> =
+/// Exports a list's members with all-time stats as JSON or CSV (`?format=`).
+pub async fn export_trader_list(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+    Query(params): Query<ListExportParams>,
+) -> Result<axum::response::Response, (StatusCode, String)> {
+    let format = params.format.as_deref().unwrap_or("json");
+    if format != "json" && format != "csv" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid format. Allowed: json, csv".into(),
+        ));
+    }
+    let detail = with_list_db(&state, move |conn| db::get_trader_list(conn, &id, &owner)).await?;
+
+    let mut stats: std::collections::HashMap<String, ListExportStatsRow> =
         std::collections::HashMap::new();
-    // Track unique traders per market for convergence count
-    let mut traders_per_market: std::collections::HashMap<
-        String,
-        std::collections::HashSet<String>,
-    > = std::collections::HashMap::new();
+    if !detail.members.is_empty() {
+        let in_list = detail
+            .members
+            .iter()
+            .map(|m| format!("'{}'", m.address.to_lowercase()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let pos_pnl = all_time_position_pnl(false);
+        let rows = state
+            .db
+            .query(&format!(
+                "WITH resolved AS (
+                    SELECT asset_id, toNullable(toFloat64(resolved_price)) AS resolved_price
+                    FROM poly_dearboard.resolved_prices FINAL
+                )
+                SELECT
+                    lower(toString(p.trader)) AS address,
+                    toString(sum(p.total_volume)) AS total_volume,
+                    toString(ROUND(sum({pos_pnl}), 6)) AS realized_pnl,
+                    sum(p.trade_count) AS trade_count,
+                    ifNull(toString(max(p.last_ts)), '') AS last_trade
+                FROM poly_dearboard.trader_positions p
+                LEFT JOIN (SELECT asset_id, latest_price FROM poly_dearboard.asset_latest_price FINAL) AS lp ON p.asset_id = lp.asset_id
+                LEFT JOIN resolved rp ON p.asset_id = rp.asset_id
+                WHERE lower(p.trader) IN ({in_list})
+                GROUP BY p.trader"
+            ))
+            .fetch_all::<ListExportStatsRow>()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        stats.extend(rows.into_iter().map(|r| (r.address.clone(), r)));
+    }
 
-    for r in &rows {
-        let info = match market_info.get(&r.asset_id) {
-            Some(i) if i.active => i,
-            _ => continue,
-        };
+    let members = detail
+        .members
+        .into_iter()
+        .map(|m| {
+            let row = stats.remove(&m.address.to_lowercase());
+            ListExportMember {
+                address: m.address,
+                label: m.label,
+                added_at: m.added_at,
+                total_volume: row.as_ref().map(|r| r.total_volume.clone()),
+                realized_pnl: row.as_ref().map(|r| r.realized_pnl.clone()),
+                trade_count: row.as_ref().map(|r| r.trade_count),
+                last_trade: row.map(|r| r.last_trade),
+            }
+        })
+        .collect();
+    let export = TraderListExport {
+        id: detail.id,
+        name: detail.name,
+        members,
+        created_at: detail.created_at,
+        updated_at: detail.updated_at,
+    };
 
-        let question = info.question.clone();
-        let net: f64 = r.net_tokens.parse().unwrap_or(0.0);
-        let exposure: f64 = r.exposure.parse().unwrap_or(0.0);
-        let pnl: f64 = r.pnl.parse().unwrap_or(0.0);
-        let entry: f64 = r.avg_entry.parse().unwrap_or(0.0);
-        let is_long = net > 0.0;
+    if format == "csv" {
+        let filename = format!("trader-list-{}.csv", export.id);
+        return Ok((
+            [
+                (
+                    axum::http::header::CONTENT_TYPE,
+                    "text/csv; charset=utf-8".to_string(),
+                ),
+                (
+                    axum::http::header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{filename}\""),
+                ),
+            ],
+            list_export_csv(&export.members),
+        )
+            .into_response());
+    }
+    Ok(Json(export).into_response())
+}
 
-        let traders = traders_per_market.entry(question.clone()).or_default();
-        let is_new_trader = traders.insert(r.trader.clone());
+/// Renders exported list members as CSV; members without activity get empty stats.
+fn list_export_csv(members: &[ListExportMember]) -> String {
+    let mut out =
+        String::from("address,label,added_at,total_volume,realized_pnl,trade_count,last_trade\n");
+    for m in members {
+        let fields = [
+            csv_field(&m.address),
+            csv_field(m.label.as_deref().unwrap_or("")),
+            csv_field(&m.added_at),
+            csv_field(m.total_volume.as_deref().unwrap_or("")),
+            csv_field(m.realized_pnl.as_deref().unwrap_or("")),
+            m.trade_count.map(|c| c.to_string()).unwrap_or_default(),
+            csv_field(m.last_trade.as_deref().unwrap_or("")),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
 
-        if let Some(existing) = merged.get_mut(&question) {
-            let ex_exp: f64 = existing.total_exposure.parse().unwrap_or(0.0);
-            let ex_pnl: f64 = existing.total_pnl.parse().unwrap_or(0.0);
-            let ex_entry: f64 = existing.avg_entry.parse().unwrap_or(0.0);
+/// Creates or rotates the list's public share token; old links stop working.
+pub async fn share_trader_list(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let share_token = with_list_db(&state, move |conn| {
+        db::set_list_share_token(conn, &id, &owner)
+    })
+    .await?;
+    Ok(Json(ListShareResponse { share_token }))
+}
 
-            let new_total_exp = ex_exp + exposure;
-            let weighted_entry = if new_total_exp > 0.0 {
-                (ex_exp * ex_entry + exposure * entry) / new_total_exp
-            } else {
-                0.0
-            };
+pub async fn unshare_trader_list(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    with_list_db(&state, move |conn| {
+        db::clear_list_share_token(conn, &id, &owner)
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-            existing.total_exposure = format!("{new_total_exp:.6}");
-            existing.total_pnl = format!("{:.6}", ex_pnl + pnl);
-            existing.avg_entry = format!("{weighted_entry:.6}");
-            // Only count unique traders for convergence
-            if is_new_trader {
-                if is_long {
-                    existing.long_count += 1;
-                } else {
-                    existing.short_count += 1;
-                }
-            }
-        } else {
-            merged.insert(
-                question.clone(),
-                CopyPortfolioPosition {
-                    token_id: info.gamma_token_id.clone(),
-                    question,
-                    outcome: info.outcome.clone(),
-                    convergence: 0, // set from HashSet len after loop
-                    long_count: if is_long { 1 } else { 0 },
-                    short_count: if !is_long { 1 } else { 0 },
-                    total_exposure: format!("{exposure:.6}"),
-                    avg_entry: format!("{entry:.6}"),
-                    latest_price: r.latest_price.clone(),
-                    total_pnl: format!("{pnl:.6}"),
-                },
-            );
-        }
+/// Read-only view of a shared list (no auth). `?stats=true` adds aggregate stats.
+pub async fn shared_trader_list(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(params): Query<SharedListParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if token.len() != 64 || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err((StatusCode::NOT_FOUND, "List not found".into()));
+    }
+    let list = with_list_db(&state, move |conn| db::get_shared_list(conn, &token)).await?;
+    let stats = if params.stats.unwrap_or(false) {
+        Some(list_stats(&state, &list, None).await?)
+    } else {
+        None
+    };
+    Ok(Json(SharedTraderList { list, stats }))
+}
+
+const MAX_IMPORT_ROWS: usize = 1000;
+
+/// Bulk-adds members from a JSON array of addresses or a `text/csv` body
+/// (`address[,label]` per record, optional `address` header). Rows past the
+/// member cap are reported as `over_limit` instead of failing the import.
+pub async fn import_list_members(
+    State(state): State<AppState>,
+    AuthUser(owner): AuthUser,
+    Path(id): Path<String>,
+    headers: axum::http::HeaderMap,
+    body: String,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let is_csv = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("text/csv"));
+    let rows = if is_csv {
+        parse_import_csv(&body)
+    } else {
+        serde_json::from_str::<Vec<String>>(&body)
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Expected a JSON array of addresses or a text/csv body: {e}"),
+                )
+            })?
+            .into_iter()
+            .map(|a| (a, None))
+            .collect()
+    };
+    if rows.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "At least one address required".into(),
+        ));
+    }
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {MAX_IMPORT_ROWS} rows per import"),
+        ));
     }
 
-    // Set convergence from unique trader counts
-    for (question, pos) in merged.iter_mut() {
-        if let Some(traders) = traders_per_market.get(question) {
-            pos.convergence = traders.len() as u32;
+    let response = with_list_db(&state, move |conn| {
+        let existing: std::collections::HashSet<String> =
+            db::get_list_member_addresses(conn, &id, &owner)?
+                .into_iter()
+                .collect();
+        let capacity = (db::MAX_MEMBERS_PER_LIST as usize).saturating_sub(existing.len());
+        let (response, members) = plan_list_import(rows, &existing, capacity);
+        if !members.is_empty() {
+            db::add_list_members(conn, &id, &owner, &members)?;
         }
-    }
+        Ok(response)
+    })
+    .await?;
+    Ok(Json(response))
+}
 
-    // Sort by convergence DESC, then exposure DESC
-    let mut positions: Vec<CopyPortfolioPosition> = merged.into_values().collect();
-    positions.sort_by(|a, b| {
-        b.convergence.cmp(&a.convergence).then_with(|| {
-            let a_exp: f64 = a.total_exposure.parse().unwrap_or(0.0);
-            let b_exp: f64 = b.total_exposure.parse().unwrap_or(0.0);
-            b_exp
-                .partial_cmp(&a_exp)
-                .unwrap_or(std::cmp::Ordering::Equal)
+/// `address[,label]` per non-empty record; a leading `address` header is skipped
+/// and further columns (as in a list export) are ignored.
+fn parse_import_csv(body: &str) -> Vec<(String, Option<String>)> {
+    csv_records(body)
+        .into_iter()
+        .filter(|fields| fields.iter().any(|f| !f.trim().is_empty()))
+        .map(|fields| {
+            let mut cols = fields.into_iter();
+            let address = cols.next().unwrap_or_default().trim().to_string();
+            let label = cols
+                .next()
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty());
+            (address, label)
         })
-    });
-
-    let total_exposure: f64 = positions
-        .iter()
-        .map(|p| p.total_exposure.parse::<f64>().unwrap_or(0.0))
-        .sum();
-    let total_pnl: f64 = positions
-        .iter()
-        .map(|p| p.total_pnl.parse::<f64>().unwrap_or(0.0))
-        .sum();
-
-    let summary = CopyPortfolioSummary {
-        total_positions: positions.len() as u32,
-        unique_markets: positions.len() as u32,
-        total_exposure: format!("{total_exposure:.6}"),
-        total_pnl: format!("{total_pnl:.6}"),
-        top_n: trader_count,
-    };
+        .enumerate()
+        .filter(|(i, (address, _))| !(*i == 0 && address.eq_ignore_ascii_case("address")))
+        .map(|(_, row)| row)
+        .collect()
+}
 
-    Ok(Json(CopyPortfolioResponse { positions, summary }))
+/// Splits a CSV body into records of unquoted fields (RFC 4180): quoted fields
+/// may hold commas, line breaks and `""` escapes. Accepts LF or CRLF endings.
+fn csv_records(body: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
 }
 
-fn shorten_id(id: &str) -> String {
-    if id.len() <= 12 {
-        id.to_string()
-    } else {
-        format!("{}...{}", &id[..6], &id[id.len() - 4..])
+/// Classifies import rows against the list's `existing` members and the
+/// remaining `capacity`; returns the report and the members to insert.
+fn plan_list_import(
+    rows: Vec<(String, Option<String>)>,
+    existing: &std::collections::HashSet<String>,
+    capacity: usize,
+) -> (ListImportResponse, Vec<(String, Option<String>)>) {
+    let mut seen = existing.clone();
+    let mut members = Vec::new();
+    let mut response = ListImportResponse {
+        added: 0,
+        duplicates: 0,
+        invalid: 0,
+        overflow: 0,
+        rows: Vec::with_capacity(rows.len()),
+    };
+    for (i, (raw, label)) in rows.into_iter().enumerate() {
+        let (address, status) = match middleware::validate_eth_address(raw.trim()) {
+            Err(_) => {
+                response.invalid += 1;
+                (raw, "invalid")
+            }
+            Ok(address) if seen.contains(&address) => {
+                response.duplicates += 1;
+                (address, "duplicate")
+            }
+            Ok(address) if members.len() >= capacity => {
+                response.overflow += 1;
+                (address, "over_limit")
+            }
+            Ok(address) => {
+                seen.insert(address.clone());
+                members.push((address.clone(), label));
+                response.added += 1;
+                (address, "added")
+            }
+        };
+        response.rows.push(ListImportRow {
+            row: i as u32 + 1,
+            address,
+            status,
+        });
     }
+    (response, members)
 }
 
 // ---------------------------------------------------------------------------
-// Trader Lists CRUD
+// Trader Notes (private alias + note)
 // ---------------------------------------------------------------------------
 
-fn map_list_error(e: db::ListError) -> (StatusCode, String) {
-    match e {
-        db::ListError::LimitExceeded(msg) => (StatusCode::BAD_REQUEST, msg.into()),
-        db::ListError::DuplicateName => (
-            StatusCode::CONFLICT,
-            "A list with this name already exists".into(),
-        ),
-        db::ListError::NotFound => (StatusCode::NOT_FOUND, "List not found".into()),
-        db::ListError::Db(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
-    }
-}
+const MAX_ALIAS_CHARS: usize = 64;
+const MAX_NOTE_CHARS: usize = 2000;
 
-pub async fn list_trader_lists(
+pub async fn get_trader_note(
     State(state): State<AppState>,
     AuthUser(owner): AuthUser,
+    ValidatedAddress(address): ValidatedAddress,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-    let lists = db::list_trader_lists(&conn, &owner)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    Ok(Json(lists))
+    let note = with_list_db(&state, move |conn| {
+        Ok(db::get_trader_note(conn, &owner, &address)?)
+    })
+    .await?
+    .ok_or((StatusCode::NOT_FOUND, "Note not found".to_string()))?;
+    Ok(Json(note))
 }
 
-pub async fn create_trader_list(
+pub async fn upsert_trader_note(
     State(state): State<AppState>,
     AuthUser(owner): AuthUser,
-    Json(req): Json<CreateListRequest>,
+    ValidatedAddress(address): ValidatedAddress,
+    Json(req): Json<UpsertNoteRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let name = req.name.trim().to_string();
-    if name.is_empty() || name.len() > 50 {
+    let alias = req
+        .alias
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty());
+    let note = req.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    if alias.is_none() && note.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "Alias or note required".into()));
+    }
+    if alias.is_some_and(|a| a.chars().count() > MAX_ALIAS_CHARS) {
         return Err((
             StatusCode::BAD_REQUEST,
-            "Name must be 1-50 characters".into(),
+            format!("Alias must be at most {MAX_ALIAS_CHARS} characters"),
         ));
     }
-    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-    let list = db::create_trader_list(&conn, &owner, &name).map_err(map_list_error)?;
-    Ok((StatusCode::CREATED, Json(list)))
+    if note.is_some_and(|n| n.chars().count() > MAX_NOTE_CHARS) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Note must be at most {MAX_NOTE_CHARS} characters"),
+        ));
+    }
+
+    let (alias, note) = (alias.map(str::to_string), note.map(str::to_string));
+    let saved = with_list_db(&state, move |conn| {
+        Ok(db::upsert_trader_note(
+            conn,
+            &owner,
+            &address,
+            alias.as_deref(),
+            note.as_deref(),
+        )?)
+    })
+    .await?;
+    Ok(Json(saved))
 }
 
-pub async fn get_trader_list(
+pub async fn delete_trader_note(
     State(state): State<AppState>,
     AuthUser(owner): AuthUser,
-    Path(id): Path<String>,
+    ValidatedAddress(address): ValidatedAddress,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-    let detail = db::get_trader_list(&conn, &id, &owner).map_err(map_list_error)?;
-    Ok(Json(detail))
+    let deleted = with_list_db(&state, move |conn| {
+        Ok(db::delete_trader_note(conn, &owner, &address)?)
+    })
+    .await?;
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Note not found".into()));
+    }
+    Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn rename_trader_list(
+// ---------------------------------------------------------------------------
+// Trader Follows
+// ---------------------------------------------------------------------------
+
+pub async fn list_follows(
     State(state): State<AppState>,
     AuthUser(owner): AuthUser,
-    Path(id): Path<String>,
-    Json(req): Json<RenameListRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let name = req.name.trim().to_string();
-    if name.is_empty() || name.len() > 50 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Name must be 1-50 characters".into(),
-        ));
-    }
-    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-    db::rename_trader_list(&conn, &id, &owner, &name).map_err(map_list_error)?;
-    Ok(StatusCode::NO_CONTENT)
+    let follows = with_list_db(&state, move |conn| Ok(db::list_follows(conn, &owner)?)).await?;
+    Ok(Json(follows))
 }
 
-pub async fn delete_trader_list(
+/// Follow changes patch the map the alert task reads instead of reloading every
+/// user's follows. The patch runs under the DB lock so it lands in commit order.
+pub async fn follow_trader(
     State(state): State<AppState>,
     AuthUser(owner): AuthUser,
-    Path(id): Path<String>,
+    ValidatedAddress(address): ValidatedAddress,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-    db::delete_trader_list(&conn, &id, &owner).map_err(map_list_error)?;
+    let follows_state = state.clone();
+    with_list_db(&state, move |conn| {
+        db::follow_trader(conn, &owner, &address)?;
+        follows_state.follows_tx.send_modify(|follows| {
+            follows.entry(address).or_default().insert(owner);
+        });
+        Ok(())
+    })
+    .await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn add_list_members(
+pub async fn unfollow_trader(
     State(state): State<AppState>,
     AuthUser(owner): AuthUser,
-    Path(id): Path<String>,
-    Json(req): Json<AddMembersRequest>,
+    ValidatedAddress(address): ValidatedAddress,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    if req.addresses.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "At least one address required".into(),
-        ));
+    let follows_state = state.clone();
+    let removed = with_list_db(&state, move |conn| {
+        let removed = db::unfollow_trader(conn, &owner, &address)?;
+        follows_state.follows_tx.send_if_modified(|follows| {
+            let Some(owners) = follows.get_mut(&address) else {
+                return false;
+            };
+            let changed = owners.remove(&owner);
+            if owners.is_empty() {
+                follows.remove(&address);
+            }
+            changed
+        });
+        Ok(removed)
+    })
+    .await?;
+    if !removed {
+        return Err((StatusCode::NOT_FOUND, "Not following this trader".into()));
     }
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    let labels = req.labels.unwrap_or_default();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn row(date: &str, net_tokens: f64, cash: f64, price: f64, buy_usdc: f64) -> PnlDailyTraderRow {
+        PnlDailyTraderRow {
+            trader: "0xabc".into(),
+            date: date.into(),
+            asset_id: "1".into(),
+            net_token_delta: net_tokens.to_string(),
+            cash_flow_delta: cash.to_string(),
+            last_price: price.to_string(),
+            buy_usdc_delta: buy_usdc.to_string(),
+        }
+    }
 
-    let members: Vec<(String, Option<String>)> = req
-        .addresses
-        .iter()
-        .enumerate()
-        .map(|(i, addr)| {
-            let validated = middleware::validate_eth_address(addr)
-                .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid address: {addr}")))?;
-            let label = labels.get(i).and_then(|l| l.clone());
-            Ok((validated, label))
-        })
-        .collect::<Result<Vec<_>, (StatusCode, String)>>()?;
+    fn simulate(
+        rows: &[PnlDailyTraderRow],
+        initial_capital: f64,
+        costs: ExecutionCosts,
+    ) -> (Vec<String>, SimulationReport) {
+        let (points, modeled) = simulate_portfolio(
+            rows,
+            &mut HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            initial_capital,
+            costs,
+            None,
+        );
+        (points.into_iter().map(|p| p.value).collect(), modeled)
+    }
 
-    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-    db::add_list_members(&conn, &id, &owner, &members).map_err(map_list_error)?;
-    Ok(StatusCode::NO_CONTENT)
-}
+    // Buy 100 @ 0.50, then sell them all @ 0.60
+    fn round_trip() -> Vec<PnlDailyTraderRow> {
+        vec![
+            row("2024-01-01", 100.0, -50.0, 0.5, 50.0),
+            row("2024-01-02", -100.0, 60.0, 0.6, 0.0),
+        ]
+    }
 
-pub async fn remove_list_members(
-    State(state): State<AppState>,
-    AuthUser(owner): AuthUser,
-    Path(id): Path<String>,
-    Json(req): Json<RemoveMembersRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let addresses: Vec<String> = req.addresses.iter().map(|a| a.to_lowercase()).collect();
+    #[test]
+    fn zero_costs_copy_exact_prices() {
+        let (values, modeled) = simulate(&round_trip(), 1000.0, ExecutionCosts::default());
+        assert_eq!(values, ["1000.00", "1010.00"]);
+        assert_eq!((modeled.slippage, modeled.fees), (0.0, 0.0));
+    }
 
-    let conn = state.user_db.lock().unwrap_or_else(|p| p.into_inner());
-    db::remove_list_members(&conn, &id, &owner, &addresses).map_err(map_list_error)?;
-    Ok(StatusCode::NO_CONTENT)
+    #[test]
+    fn slippage_and_fees_worsen_both_legs() {
+        let costs = ExecutionCosts {
+            slippage_bps: 100,
+            fee_bps: 50,
+        };
+        let (values, modeled) = simulate(&round_trip(), 1000.0, costs);
+        // Day 1: 50 notional → 0.50 slippage + 0.25 fee
+        // Day 2: 60 notional → 0.60 slippage + 0.30 fee
+        assert_eq!(values, ["999.25", "1008.35"]);
+        assert!((modeled.slippage - 1.10).abs() < 1e-9);
+        assert!((modeled.fees - 0.55).abs() < 1e-9);
+    }
+
+    #[test]
+    fn capital_clip_scales_costs_with_the_fill() {
+        let costs = ExecutionCosts {
+            slippage_bps: 100,
+            fee_bps: 0,
+        };
+        // 101 USDC outflow with slippage against 100 cash: fill 100/101 of it
+        let rows = [row("2024-01-01", 200.0, -100.0, 0.5, 100.0)];
+        let (values, modeled) = simulate(&rows, 100.0, costs);
+        assert_eq!(values, ["99.01"]);
+        assert!((modeled.slippage - 100.0 / 101.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn position_cap_clips_buys_to_the_remaining_room() {
+        // Buy 100 @ 0.50 with a 20% cap on a 100 portfolio, then 100 more
+        let rows = [
+            row("2024-01-01", 100.0, -50.0, 0.5, 50.0),
+            row("2024-01-02", 100.0, -50.0, 0.5, 50.0),
+        ];
+        let (points, modeled) = simulate_portfolio(
+            &rows,
+            &mut HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            100.0,
+            ExecutionCosts::default(),
+            Some(0.2),
+        );
+        // Day 1 fills 40 tokens (20 USDC); day 2 has no room left
+        assert_eq!(points.last().unwrap().value, "100.00");
+        assert!((modeled.cap_clipped - 80.0).abs() < 1e-9);
+        assert_eq!(modeled.cash_clipped, 0.0);
+    }
+
+    #[test]
+    fn cash_clip_is_reported() {
+        let rows = [row("2024-01-01", 200.0, -100.0, 0.5, 100.0)];
+        let (_, modeled) = simulate(&rows, 40.0, ExecutionCosts::default());
+        assert!((modeled.cash_clipped - 60.0).abs() < 1e-9);
+        assert_eq!(modeled.cap_clipped, 0.0);
+    }
+
+    #[test]
+    fn trade_log_records_scaled_and_clipped_deltas() {
+        let mut rows = round_trip();
+        rows.insert(1, row("2024-01-01", 10_000.0, -5_000.0, 0.5, 5_000.0));
+        let (_, modeled) = simulate(&rows, 1000.0, ExecutionCosts::default());
+        let log: Vec<_> = modeled
+            .trades
+            .iter()
+            .map(|t| (t.side, t.tokens.as_str(), t.clipped))
+            .collect();
+        assert_eq!(
+            log,
+            [
+                ("buy", "100.000000", false),
+                ("buy", "1900.000000", true),
+                ("sell", "-100.000000", false),
+            ]
+        );
+        assert_eq!(modeled.trades_total, 3);
+    }
+
+    fn backtest_key(json: serde_json::Value) -> u64 {
+        backtest_cache_key(&serde_json::from_value(json).unwrap())
+    }
+
+    #[test]
+    fn backtest_cache_key_ignores_force_and_nulls() {
+        let base = backtest_key(serde_json::json!({"top_n": 10, "timeframe": "30d"}));
+        assert_eq!(
+            base,
+            backtest_key(
+                serde_json::json!({"timeframe": "30d", "top_n": 10, "force": true, "from": null})
+            )
+        );
+        assert_ne!(
+            base,
+            backtest_key(serde_json::json!({"top_n": 10, "timeframe": "7d"}))
+        );
+    }
+
+    fn copy_position(exposure: &str, price: &str) -> CopyPortfolioPosition {
+        CopyPortfolioPosition {
+            token_id: String::new(),
+            question: String::new(),
+            outcome: String::new(),
+            convergence: 1,
+            long_count: 1,
+            short_count: 0,
+            total_exposure: exposure.into(),
+            avg_entry: "0".into(),
+            latest_price: price.into(),
+            total_pnl: "0".into(),
+            suggested_allocation: None,
+            suggested_shares: None,
+        }
+    }
+
+    #[test]
+    fn allocations_follow_exposure_share() {
+        let mut positions = [
+            copy_position("300", "0.5"),
+            copy_position("100", "0.25"),
+            copy_position("0", "0"),
+        ];
+        suggest_allocations(&mut positions, 1000.0);
+        let got: Vec<_> = positions
+            .iter()
+            .map(|p| {
+                (
+                    p.suggested_allocation.as_deref(),
+                    p.suggested_shares.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            got,
+            [
+                (Some("750.00"), Some("1500.00")),
+                (Some("250.00"), Some("1000.00")),
+                (Some("0.00"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn paper_mark_prefers_resolution_then_latest_then_entry() {
+        let position = |token_id: &str| PaperPosition {
+            token_id: token_id.into(),
+            question: String::new(),
+            outcome: String::new(),
+            entry_price: 0.4,
+            shares: 10.0,
+        };
+        let marks = HashMap::from([
+            ("won".to_string(), (0.97, Some(1.0))),
+            ("open".to_string(), (0.55, None)),
+            ("untraded".to_string(), (0.0, None)),
+        ]);
+        assert_eq!(paper_mark(&position("won"), &marks), (1.0, Some(1.0)));
+        assert_eq!(paper_mark(&position("open"), &marks), (0.55, None));
+        assert_eq!(paper_mark(&position("untraded"), &marks), (0.4, None));
+        assert_eq!(paper_mark(&position("missing"), &marks), (0.4, None));
+    }
+
+    #[test]
+    fn list_export_csv_escapes_labels_and_blanks_inactive_members() {
+        let members = vec![
+            ListExportMember {
+                address: "0xabc".into(),
+                label: Some("Whale, \"big\"".into()),
+                added_at: "2026-01-01T00:00:00+00:00".into(),
+                total_volume: Some("100.5".into()),
+                realized_pnl: Some("-3.25".into()),
+                trade_count: Some(7),
+                last_trade: Some("2026-01-02 00:00:00".into()),
+            },
+            ListExportMember {
+                address: "0xdef".into(),
+                label: None,
+                added_at: "2026-01-03T00:00:00+00:00".into(),
+                total_volume: None,
+                realized_pnl: None,
+                trade_count: None,
+                last_trade: None,
+            },
+        ];
+        let csv = list_export_csv(&members);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "address,label,added_at,total_volume,realized_pnl,trade_count,last_trade"
+        );
+        assert_eq!(
+            lines[1],
+            "0xabc,\"Whale, \"\"big\"\"\",2026-01-01T00:00:00+00:00,100.5,-3.25,7,2026-01-02 00:00:00"
+        );
+        assert_eq!(lines[2], "0xdef,,2026-01-03T00:00:00+00:00,,,,");
+    }
+
+    #[test]
+    fn csv_import_skips_header_and_reads_labels() {
+        let body = "Address,Label\n0xAbC,\"Whale one\"\n\n 0xdef ,\n";
+        assert_eq!(
+            parse_import_csv(body),
+            [
+                ("0xAbC".to_string(), Some("Whale one".to_string())),
+                ("0xdef".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_import_reads_quoted_fields() {
+        let body = "address,label\r\n0xabc,\"Whale, \"\"big\"\"\"\r\n0xdef,\"two\nlines\"\r\n";
+        assert_eq!(
+            parse_import_csv(body),
+            [
+                ("0xabc".to_string(), Some("Whale, \"big\"".to_string())),
+                ("0xdef".to_string(), Some("two\nlines".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn list_export_round_trips_through_import() {
+        let member = |address: &str, label: Option<&str>| ListExportMember {
+            address: address.into(),
+            label: label.map(str::to_string),
+            added_at: "2026-01-01T00:00:00+00:00".into(),
+            total_volume: Some("1,000.5".into()),
+            realized_pnl: None,
+            trade_count: Some(3),
+            last_trade: None,
+        };
+        let members = vec![
+            member("0xabc", Some("Whale, \"big\"")),
+            member("0xdef", None),
+        ];
+        assert_eq!(
+            parse_import_csv(&list_export_csv(&members)),
+            [
+                ("0xabc".to_string(), Some("Whale, \"big\"".to_string())),
+                ("0xdef".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_plan_reports_each_row() {
+        let addr = |n: u8| format!("0x{:040x}", n);
+        let existing = std::collections::HashSet::from([addr(1)]);
+        let rows = vec![
+            (addr(1), None),
+            (
+                addr(2).to_uppercase().replace("0X", "0x"),
+                Some("two".to_string()),
+            ),
+            ("not-an-address".to_string(), None),
+            (addr(2), None),
+            (addr(3), None),
+        ];
+        let (response, members) = plan_list_import(rows, &existing, 1);
+        let statuses: Vec<_> = response.rows.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            ["duplicate", "added", "invalid", "duplicate", "over_limit"]
+        );
+        assert_eq!(members, [(addr(2), Some("two".to_string()))]);
+        assert_eq!(
+            (
+                response.added,
+                response.duplicates,
+                response.invalid,
+                response.overflow
+            ),
+            (1, 2, 1, 1)
+        );
+    }
+
+    fn curve(values: &[f64]) -> Vec<PortfolioPoint> {
+        values
+            .iter()
+            .map(|v| PortfolioPoint {
+                date: String::new(),
+                value: format!("{v:.2}"),
+                pnl: String::new(),
+                pnl_pct: String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flat_curve_has_no_risk() {
+        let m = risk_metrics(&curve(&[1000.0, 1000.0, 1000.0]), 1000.0, 0.0);
+        assert_eq!(m, RiskMetrics::default());
+    }
+
+    #[test]
+    fn empty_curve_has_no_risk() {
+        assert_eq!(risk_metrics(&[], 1000.0, 0.0), RiskMetrics::default());
+    }
+
+    #[test]
+    fn single_point_reports_its_return_but_no_ratios() {
+        let m = risk_metrics(&curve(&[1100.0]), 1000.0, 0.0);
+        assert_eq!((round1(m.best_day), round1(m.worst_day)), (10.0, 10.0));
+        assert_eq!(
+            (
+                m.sharpe_ratio,
+                m.sortino_ratio,
+                m.volatility_annualized,
+                m.calmar_ratio
+            ),
+            (0.0, 0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn alternating_returns_match_hand_computed_ratios() {
+        // Returns: +10%, -10%, +10%
+        let m = risk_metrics(&curve(&[110.0, 99.0, 108.9]), 100.0, 10.0);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+        assert!(close(m.sharpe_ratio, 5.515130702591431), "{m:?}");
+        assert!(close(m.sortino_ratio, 11.030261405182863), "{m:?}");
+        assert!(close(m.volatility_annualized, 220.60522810365728), "{m:?}");
+        assert!(close(m.calmar_ratio, 121.66666666666666), "{m:?}");
+        assert_eq!((round1(m.best_day), round1(m.worst_day)), (10.0, -10.0));
+    }
+
+    #[test]
+    fn no_losing_day_leaves_sortino_undefined() {
+        // Returns: +10%, +20%
+        let m = risk_metrics(&curve(&[110.0, 132.0]), 100.0, 0.0);
+        assert!(m.sharpe_ratio > 0.0);
+        assert_eq!((m.sortino_ratio, m.calmar_ratio), (0.0, 0.0));
+    }
+
+    fn weights(values: &[(&str, f64)], min_weight: f64) -> Vec<f64> {
+        let values: Vec<(String, f64)> = values.iter().map(|(a, v)| (a.to_string(), *v)).collect();
+        let w = trader_weights(&values, min_weight);
+        let sum: f64 = w.values().sum();
+        assert!((sum - 1.0).abs() < 1e-9, "weights sum to {sum}");
+        assert!(w.values().all(|w| *w >= 0.0));
+        values.iter().map(|(a, _)| w[a]).collect()
+    }
+
+    #[test]
+    fn pnl_weights_are_proportional_and_skip_losers() {
+        let w = weights(&[("a", 300.0), ("b", 100.0), ("c", -50.0)], 0.0);
+        assert_eq!(w, [0.75, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn min_weight_lifts_losers_and_renormalizes() {
+        let w = weights(&[("a", 300.0), ("b", 100.0), ("c", -50.0)], 0.1);
+        // 0.75 / 0.25 / 0.1, scaled by 1/1.1
+        assert!((w[0] - 0.75 / 1.1).abs() < 1e-9);
+        assert!((w[2] - 0.1 / 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn all_losers_fall_back_to_equal_weights() {
+        assert_eq!(weights(&[("a", -1.0), ("b", 0.0)], 0.0), [0.5, 0.5]);
+        assert_eq!(weights(&[("a", 1.0), ("b", 1.0)], 0.0), [0.5, 0.5]);
+    }
+
+    #[test]
+    fn rank_changes_only_apply_to_the_snapshot_ranking() {
+        let query = |params: LeaderboardParams, members: Option<Vec<String>>| {
+            LeaderboardQuery::parse(&params, String::new(), members)
+                .unwrap()
+                .matches_rank_snapshot()
+        };
+        let with = |f: fn(&mut LeaderboardParams)| {
+            let mut params = LeaderboardParams::default();
+            f(&mut params);
+            params
+        };
+        assert!(query(LeaderboardParams::default(), None));
+        assert!(!query(with(|p| p.sort = Some("total_volume".into())), None));
+        assert!(!query(with(|p| p.order = Some("asc".into())), None));
+        assert!(!query(with(|p| p.timeframe = Some("7d".into())), None));
+        assert!(!query(with(|p| p.include_fees = Some(true)), None));
+        assert!(!query(with(|p| p.category = Some("Politics".into())), None));
+        assert!(!query(
+            LeaderboardParams::default(),
+            Some(vec!["0xabc".into()])
+        ));
+    }
 }
@@ -1,12 +1,15 @@
 use axum::Router;
-use axum::routing::{delete, get, post};
-use std::collections::{HashMap, HashSet};
+use axum::routing::{delete, get, patch, post};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::sync::{RwLock, broadcast};
 use tower_http::cors::{Any, CorsLayer};
 
 use super::{
-    alerts, contracts, copytrade, db, engine, markets, routes, scanner, types::LeaderboardResponse,
+    alerts, auth, contracts, copytrade, db, engine, markets, notifier, rate_limit, routes, scanner,
+    types::{BacktestResponse, CacheStats, LeaderboardResponse, PnlChartResponse},
     wallet, ws_subscriber,
 };
 
@@ -16,7 +19,237 @@ pub struct CachedResponse {
     pub expires: std::time::Instant,
 }
 
-pub type LeaderboardCache = Arc<RwLock<HashMap<String, CachedResponse>>>;
+/// How long past expiry a leaderboard entry may still be served while it is
+/// being recomputed in the background (stale-while-revalidate).
+pub const LEADERBOARD_STALE_GRACE: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Result of a leaderboard cache lookup.
+pub enum CacheLookup {
+    Fresh(LeaderboardResponse),
+    /// Expired but within `LEADERBOARD_STALE_GRACE` — serve it and refresh.
+    Stale(LeaderboardResponse),
+    Miss,
+}
+
+/// Bounded leaderboard response cache. Entries past the stale grace window are
+/// dropped by a periodic sweep; once `max_entries` is reached, inserts evict the
+/// entry closest to expiry.
+pub struct LeaderboardCacheStore {
+    entries: RwLock<HashMap<String, CachedResponse>>,
+    /// Keys with a background refresh in flight (stampede guard)
+    refreshing: Mutex<HashSet<String>>,
+    max_entries: usize,
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+pub type LeaderboardCache = Arc<LeaderboardCacheStore>;
+
+impl LeaderboardCacheStore {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            refreshing: Mutex::new(HashSet::new()),
+            max_entries: max_entries.max(1),
+            hits: AtomicU64::new(0),
+            stale_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Looks up an entry, counting the lookup as a hit, stale hit, or miss.
+    pub async fn lookup(&self, key: &str) -> CacheLookup {
+        let now = std::time::Instant::now();
+        let entries = self.entries.read().await;
+        match entries.get(key) {
+            Some(entry) if entry.expires > now => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                CacheLookup::Fresh(entry.data.clone())
+            }
+            Some(entry) if entry.expires + LEADERBOARD_STALE_GRACE > now => {
+                self.stale_hits.fetch_add(1, Ordering::Relaxed);
+                CacheLookup::Stale(entry.data.clone())
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                CacheLookup::Miss
+            }
+        }
+    }
+
+    /// Claims the background refresh for `key`; `None` if one is already running.
+    /// The claim is released when the guard drops, even if the refresh panics.
+    pub fn begin_refresh(self: &Arc<Self>, key: &str) -> Option<RefreshGuard> {
+        self.refreshing
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(key.to_string())
+            .then(|| RefreshGuard {
+                cache: Arc::clone(self),
+                key: key.to_string(),
+            })
+    }
+
+    pub async fn insert(&self, key: String, data: LeaderboardResponse, ttl: std::time::Duration) {
+        let now = std::time::Instant::now();
+        let mut entries = self.entries.write().await;
+        if !entries.contains_key(&key) && entries.len() >= self.max_entries {
+            entries.retain(|_, e| e.expires + LEADERBOARD_STALE_GRACE > now);
+            if entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, e)| e.expires)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(
+            key,
+            CachedResponse {
+                data,
+                expires: now + ttl,
+            },
+        );
+    }
+
+    /// Drops entries past the stale grace window; returns how many were removed.
+    pub async fn sweep(&self) -> usize {
+        let now = std::time::Instant::now();
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+        entries.retain(|_, e| e.expires + LEADERBOARD_STALE_GRACE > now);
+        before - entries.len()
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.read().await.len(),
+            max_entries: self.max_entries,
+            hits: self.hits.load(Ordering::Relaxed),
+            stale_hits: self.stale_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Claim on a leaderboard key's background refresh; see `begin_refresh`.
+pub struct RefreshGuard {
+    cache: LeaderboardCache,
+    key: String,
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.cache
+            .refreshing
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&self.key);
+    }
+}
+
+/// Bounded TTL cache. Nothing is invalidated proactively; expired entries are
+/// dropped by a periodic sweep. Once `max_entries` is reached, inserts evict the
+/// entry closest to expiry.
+pub struct TtlCache<K, V> {
+    /// Labels the hit/miss debug logs
+    name: &'static str,
+    entries: RwLock<HashMap<K, (V, std::time::Instant)>>,
+    ttl: std::time::Duration,
+    max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K: Eq + Hash + Clone + std::fmt::Debug, V: Clone> TtlCache<K, V> {
+    pub fn new(name: &'static str, ttl: std::time::Duration, max_entries: usize) -> Self {
+        Self {
+            name,
+            entries: RwLock::new(HashMap::new()),
+            ttl,
+            max_entries: max_entries.max(1),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns a fresh entry, counting the lookup as a hit or miss.
+    pub async fn get(&self, key: &K) -> Option<V> {
+        let now = std::time::Instant::now();
+        let entries = self.entries.read().await;
+        match entries.get(key) {
+            Some((data, expires)) if *expires > now => {
+                let hits = self.hits.fetch_add(1, Ordering::Relaxed) + 1;
+                tracing::debug!(
+                    "{} cache hit: {key:?} ({hits} hits, {} misses)",
+                    self.name,
+                    self.misses.load(Ordering::Relaxed)
+                );
+                Some(data.clone())
+            }
+            _ => {
+                let misses = self.misses.fetch_add(1, Ordering::Relaxed) + 1;
+                tracing::debug!(
+                    "{} cache miss: {key:?} ({} hits, {misses} misses)",
+                    self.name,
+                    self.hits.load(Ordering::Relaxed)
+                );
+                None
+            }
+        }
+    }
+
+    pub async fn insert(&self, key: K, data: V) {
+        let now = std::time::Instant::now();
+        let mut entries = self.entries.write().await;
+        if !entries.contains_key(&key) && entries.len() >= self.max_entries {
+            entries.retain(|_, (_, expires)| *expires > now);
+            if entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (_, expires))| *expires)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (data, now + self.ttl));
+    }
+
+    /// Drops expired entries; returns how many were removed.
+    pub async fn sweep(&self) -> usize {
+        let now = std::time::Instant::now();
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+        entries.retain(|_, (_, expires)| *expires > now);
+        before - entries.len()
+    }
+
+    pub fn counters(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Short-lived per-trader `pnl_chart` responses, keyed on address + query params.
+pub type PnlChartCache = Arc<TtlCache<String, PnlChartResponse>>;
+
+/// How long a top-N backtest result is reused.
+pub const BACKTEST_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Top-N backtest responses, keyed by a hash of the request. List-based runs
+/// are never stored.
+pub type BacktestCache = Arc<TtlCache<u64, BacktestResponse>>;
+
+/// Top-trader benchmark curves for `pnl_chart` (bucket label → average % return)
+/// with their expiry, keyed by window.
+pub type BenchmarkCache = Arc<RwLock<HashMap<String, (HashMap<String, f64>, std::time::Instant)>>>;
 
 /// Per-wallet balance + approval state (ephemeral, not persisted).
 #[derive(Clone)]
@@ -30,21 +263,51 @@ pub struct WalletBalanceState {
     pub last_checked: std::time::Instant,
 }
 
+/// Latest shared convergence alerts, newest first (see `alerts::convergence_loop`)
+pub type ConvergenceHistory = Arc<RwLock<VecDeque<alerts::ConvergenceAlert>>>;
+
 pub type WalletBalances = Arc<RwLock<HashMap<String, WalletBalanceState>>>;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: clickhouse::Client,
     pub http: reqwest::Client,
+    /// Shared Gamma rate-limit cooldown
+    pub gamma: markets::GammaBackoff,
     pub market_cache: markets::MarketCache,
+    /// Incremental `resolved_prices` progress, shared with the admin rebuild
+    pub resolved_prices: Arc<tokio::sync::Mutex<markets::ResolvedPricesState>>,
     pub alert_tx: broadcast::Sender<alerts::Alert>,
     pub trade_tx: broadcast::Sender<alerts::LiveTrade>,
+    pub convergence_tx: broadcast::Sender<alerts::ConvergenceAlert>,
+    pub convergence_history: ConvergenceHistory,
     pub metadata_tx: tokio::sync::mpsc::Sender<(String, markets::MarketInfo)>,
     pub leaderboard_cache: LeaderboardCache,
+    pub pnl_chart_cache: PnlChartCache,
+    pub benchmark_cache: BenchmarkCache,
+    pub backtest_cache: BacktestCache,
+    pub whale_alerts: alerts::WhaleAlertConfig,
+    /// Taker order hashes already turned into trades (webhook)
+    pub taker_orders: alerts::RecentKeys,
+    /// Condition ids already broadcast as resolution alerts (webhook + WS subscriber)
+    pub resolutions: alerts::RecentKeys,
+    pub webhook_tx: tokio::sync::mpsc::Sender<alerts::WebhookPayload>,
+    pub webhook_counters: Arc<alerts::WebhookCounters>,
+    pub live_feed: Arc<ws_subscriber::SubscriberStatus>,
     pub user_db: Arc<Mutex<rusqlite::Connection>>,
     pub jwt_secret: Arc<Vec<u8>>,
+    /// Absolute session lifetime from wallet sign-in; refresh stops after it
+    pub jwt_max_session_secs: u64,
+    /// App URI embedded in SIWE sign-in messages (its host is the SIWE domain)
+    pub siwe_uri: Arc<String>,
+    /// Per-IP / per-address request budgets for `/api/*`
+    pub rate_limiter: Arc<rate_limit::RateLimiter>,
+    /// Revoked tokens and logout-all cutoffs (mirrors SQLite)
+    pub revocations: auth::Revocations,
     pub copytrade_live_tx: broadcast::Sender<alerts::LiveTrade>,
     pub trader_watch_tx: tokio::sync::watch::Sender<HashSet<String>>,
+    /// Followed trader address → owners following it (mirrors `trader_follows`)
+    pub follows_tx: tokio::sync::watch::Sender<HashMap<String, HashSet<String>>>,
     pub encryption_key: Arc<[u8; 32]>,
     pub erpc_url: Arc<String>,
     pub wallet_balances: WalletBalances,
@@ -77,6 +340,7 @@ async fn metadata_writer(
                     active: if info.active { 1 } else { 0 },
                     all_token_ids: info.all_token_ids,
                     outcomes: info.outcomes,
+                    end_date: info.end_date.unwrap_or_default(),
                     updated_at: now,
                 });
                 if batch.len() >= 100 {
@@ -125,6 +389,13 @@ pub async fn run(client: clickhouse::Client, port: u16) {
     let jwt_secret = std::env::var("JWT_SECRET")
         .expect("JWT_SECRET env var is required for wallet authentication");
 
+    let jwt_max_session_days = std::env::var("JWT_MAX_SESSION_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+
+    let siwe_uri = std::env::var("SIWE_URI").unwrap_or_else(|_| "http://localhost:5173".into());
+
     let encryption_key_hex = std::env::var("WALLET_ENCRYPTION_KEY")
         .expect("WALLET_ENCRYPTION_KEY env var is required (64 hex chars = 32 bytes)");
     let encryption_key_bytes =
@@ -136,10 +407,34 @@ pub async fn run(client: clickhouse::Client, port: u16) {
     let erpc_url = std::env::var("POLYGON_RPC_URL")
         .unwrap_or_else(|_| "http://localhost:4000/main/evm/137".into());
 
+    let leaderboard_cache_max = std::env::var("LEADERBOARD_CACHE_MAX_ENTRIES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(256);
+
+    let pnl_chart_cache_ttl = std::env::var("PNL_CHART_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(60);
+
+    let pnl_chart_cache_max = std::env::var("PNL_CHART_CACHE_MAX_ENTRIES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1024);
+
     let user_conn = db::init_user_db("data/users.db");
+    let (revoked_tokens, session_cutoffs) =
+        db::load_revocations(&user_conn).expect("failed to load token revocations");
+    let follows = db::get_all_follows(&user_conn).unwrap_or_else(|e| {
+        tracing::warn!("failed to load trader follows: {e}");
+        HashMap::new()
+    });
 
     let (alert_tx, _) = broadcast::channel::<alerts::Alert>(256);
     let (trade_tx, _) = broadcast::channel::<alerts::LiveTrade>(512);
+    let (convergence_tx, _) = broadcast::channel::<alerts::ConvergenceAlert>(64);
+    let (webhook_tx, webhook_rx) =
+        tokio::sync::mpsc::channel::<alerts::WebhookPayload>(alerts::WEBHOOK_QUEUE_CAPACITY);
     let (metadata_tx, metadata_rx) =
         tokio::sync::mpsc::channel::<(String, markets::MarketInfo)>(1024);
     let (copytrade_cmd_tx, copytrade_cmd_rx) =
@@ -148,19 +443,49 @@ pub async fn run(client: clickhouse::Client, port: u16) {
     let (copytrade_live_tx, _) = broadcast::channel::<alerts::LiveTrade>(128);
     let (trader_watch_tx, trader_watch_rx) =
         tokio::sync::watch::channel::<HashSet<String>>(HashSet::new());
+    let (follows_tx, _) = tokio::sync::watch::channel(follows);
 
     let state = AppState {
         db: client,
         http: reqwest::Client::new(),
+        gamma: markets::GammaBackoff::default(),
         market_cache: markets::new_cache(),
+        resolved_prices: Arc::default(),
         alert_tx,
         trade_tx,
+        convergence_tx,
+        convergence_history: Arc::new(RwLock::new(VecDeque::with_capacity(
+            alerts::CONVERGENCE_HISTORY_LEN,
+        ))),
         metadata_tx,
-        leaderboard_cache: Arc::new(RwLock::new(HashMap::new())),
+        leaderboard_cache: Arc::new(LeaderboardCacheStore::new(leaderboard_cache_max)),
+        pnl_chart_cache: Arc::new(TtlCache::new(
+            "pnl chart",
+            std::time::Duration::from_secs(pnl_chart_cache_ttl),
+            pnl_chart_cache_max,
+        )),
+        benchmark_cache: Arc::new(RwLock::new(HashMap::new())),
+        backtest_cache: Arc::new(TtlCache::new("backtest", BACKTEST_CACHE_TTL, 64)),
+        whale_alerts: alerts::WhaleAlertConfig::from_env(),
+        taker_orders: alerts::RecentKeys::new(alerts::TAKER_ORDER_DEDUP_TTL),
+        resolutions: alerts::RecentKeys::new(alerts::RESOLUTION_DEDUP_TTL),
+        webhook_tx,
+        webhook_counters: Arc::new(alerts::WebhookCounters::default()),
+        live_feed: Arc::new(ws_subscriber::SubscriberStatus::default()),
         user_db: Arc::new(Mutex::new(user_conn)),
         jwt_secret: Arc::new(jwt_secret.into_bytes()),
+        jwt_max_session_secs: jwt_max_session_days * 24 * 3600,
+        siwe_uri: Arc::new(siwe_uri),
+        rate_limiter: Arc::new(rate_limit::RateLimiter::new(
+            rate_limit::RateLimitConfig::from_env(),
+        )),
+        revocations: Arc::new(std::sync::RwLock::new(auth::RevocationList::new(
+            revoked_tokens,
+            session_cutoffs,
+        ))),
         copytrade_live_tx,
         trader_watch_tx,
+        follows_tx,
         encryption_key: Arc::new(encryption_key),
         erpc_url: Arc::new(erpc_url),
         wallet_balances: Arc::new(RwLock::new(HashMap::new())),
@@ -172,44 +497,216 @@ pub async fn run(client: clickhouse::Client, port: u16) {
     // Pre-warm the market name cache in the background, then refresh periodically
     {
         let http = state.http.clone();
+        let gamma = state.gamma.clone();
         let db = state.db.clone();
         let cache = state.market_cache.clone();
+        let resolved_prices = state.resolved_prices.clone();
         tokio::spawn(async move {
-            markets::warm_cache(&http, &db, &cache).await;
-            markets::persist_cache_to_clickhouse(&db, &cache).await;
-            markets::populate_resolved_prices(&db, &cache).await;
+            let mut warmer = markets::CacheWarmer::default();
+            markets::warm_cache(&http, &gamma, &db, &cache, &mut warmer).await;
+            let mut persisted = markets::persist_cache_to_clickhouse(&db, &cache, None).await;
+            markets::populate_resolved_prices(&db, &cache, &mut *resolved_prices.lock().await)
+                .await;
             // Re-warm every 10 minutes to catch new markets + resolutions
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
             interval.tick().await; // skip immediate tick
             loop {
                 interval.tick().await;
                 tracing::info!("Refreshing market cache...");
-                markets::warm_cache(&http, &db, &cache).await;
-                markets::persist_cache_to_clickhouse(&db, &cache).await;
-                markets::populate_resolved_prices(&db, &cache).await;
+                markets::warm_cache(&http, &gamma, &db, &cache, &mut warmer).await;
+                // Only entries added or re-fetched since the last persist
+                persisted = markets::persist_cache_to_clickhouse(&db, &cache, persisted).await;
+                markets::populate_resolved_prices(&db, &cache, &mut *resolved_prices.lock().await)
+                    .await;
             }
         });
     }
 
+    // Re-fetch market cache entries older than MARKET_CACHE_TTL_SECS (active/closed status)
+    tokio::spawn(markets::refresh_stale_loop(
+        state.http.clone(),
+        state.gamma.clone(),
+        state.market_cache.clone(),
+        markets::cache_ttl_from_env(),
+    ));
+
     // Batched metadata writer: drains webhook-time metadata inserts into ClickHouse
     {
         let db = state.db.clone();
         tokio::spawn(metadata_writer(db, metadata_rx));
     }
 
-    // Background leaderboard cache warmer — keeps the default view always warm
+    // Background leaderboard cache warmer — keeps the common views always warm
     {
         let state = state.clone();
         tokio::spawn(async move {
+            let variants = routes::warm_variants();
             // Wait for market cache to warm first
             tokio::time::sleep(std::time::Duration::from_secs(30)).await;
             loop {
-                let _ = routes::warm_leaderboard(&state).await;
+                let _ = routes::warm_leaderboard(&state, &variants).await;
                 tokio::time::sleep(std::time::Duration::from_secs(25)).await;
             }
         });
     }
 
+    // Leaderboard cache sweeper — drops expired entries so the map stays small
+    {
+        let cache = state.leaderboard_cache.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let removed = cache.sweep().await;
+                if removed > 0 {
+                    tracing::debug!("leaderboard cache: swept {removed} expired entries");
+                }
+            }
+        });
+    }
+
+    // PnL chart cache sweeper — same cadence as the TTL, logs hit/miss counters
+    {
+        let cache = state.pnl_chart_cache.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(pnl_chart_cache_ttl.max(1)));
+            loop {
+                interval.tick().await;
+                let removed = cache.sweep().await;
+                let (hits, misses) = cache.counters();
+                if removed > 0 {
+                    tracing::info!(
+                        "pnl chart cache: swept {removed} expired entries ({hits} hits, {misses} misses)"
+                    );
+                }
+            }
+        });
+    }
+
+    // Backtest cache sweeper
+    {
+        let cache = state.backtest_cache.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let removed = cache.sweep().await;
+                if removed > 0 {
+                    tracing::debug!("backtest cache: swept {removed} expired entries");
+                }
+            }
+        });
+    }
+
+    // Daily paper portfolio valuation — one equity point per portfolio per day
+    {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(24 * 3600));
+            loop {
+                interval.tick().await;
+                routes::record_paper_portfolio_values(&state).await;
+            }
+        });
+    }
+
+    // Hourly GC of revocations that can no longer match an unexpired token
+    {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let now = chrono::Utc::now().timestamp() as u64;
+                state
+                    .revocations
+                    .write()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .purge_expired(now);
+                let user_db = state.user_db.clone();
+                let purged = tokio::task::spawn_blocking(move || {
+                    let conn = user_db.lock().unwrap_or_else(|p| p.into_inner());
+                    db::purge_expired_revocations(&conn, now, auth::TOKEN_TTL_SECS)
+                })
+                .await;
+                match purged {
+                    Ok(Ok(n)) if n > 0 => tracing::info!("purged {n} expired token revocations"),
+                    Ok(Err(e)) => tracing::warn!("token revocation GC failed: {e}"),
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    // Hourly leaderboard rank snapshot — baseline for rank_change_24h
+    {
+        let state = state.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                if let Err(e) = routes::snapshot_leaderboard_ranks(&state).await {
+                    tracing::warn!("leaderboard rank snapshot failed: {e}");
+                }
+            }
+        });
+    }
+
+    // Webhook worker: drains payloads queued by /webhooks/rindexer
+    tokio::spawn(alerts::webhook_worker(state.clone(), webhook_rx));
+
+    // Followed-trader alerts: live trades from followed addresses → alert_tx
+    {
+        let trade_rx = state.trade_tx.subscribe();
+        let follows_rx = state.follows_tx.subscribe();
+        let alert_tx = state.alert_tx.clone();
+        tokio::spawn(alerts::follow_alerts_loop(trade_rx, follows_rx, alert_tx));
+    }
+
+    // Price swing alerts: large short-window price moves → alert_tx
+    {
+        let trade_rx = state.trade_tx.subscribe();
+        let alert_tx = state.alert_tx.clone();
+        tokio::spawn(alerts::price_swing_loop(trade_rx, alert_tx));
+    }
+
+    // Outbound notifications: mirror selected alerts to Discord/Telegram/JSON webhooks
+    if let Some(config) = notifier::NotifierConfig::from_env() {
+        let alert_rx = state.alert_tx.subscribe();
+        tokio::spawn(notifier::run(config, state.http.clone(), alert_rx));
+    }
+
+    // Smart money entry alerts: top traders opening new positions → alert_tx
+    {
+        let trade_rx = state.trade_tx.subscribe();
+        let alert_tx = state.alert_tx.clone();
+        tokio::spawn(alerts::smart_money_loop(
+            state.db.clone(),
+            trade_rx,
+            alert_tx,
+        ));
+    }
+
+    // Shared convergence: one detector over the top-20 traders → convergence_tx
+    {
+        let trade_rx = state.trade_tx.subscribe();
+        tokio::spawn(alerts::convergence_loop(
+            state.db.clone(),
+            trade_rx,
+            state.convergence_tx.clone(),
+            state.convergence_history.clone(),
+        ));
+    }
+
+    // Alert history: persist broadcast alerts for /api/alerts/history
+    {
+        let alert_rx = state.alert_tx.subscribe();
+        tokio::spawn(alerts::alert_history_loop(state.db.clone(), alert_rx));
+    }
+
     // Phantom fill scanner: polls Polygon blocks for reverted exchange TXs
     {
         let rpc_url = std::env::var("POLYGON_RPC_URL")
@@ -246,15 +743,23 @@ pub async fn run(client: clickhouse::Client, port: u16) {
         ));
     }
 
-    // Targeted eth_subscribe for copy-trade sessions only (zero CU when no sessions active)
+    // eth_subscribe: OrderFilled for copy-trade sessions (only while sessions are
+    // active) + ConditionResolution for low-latency resolution alerts
     {
-        let copytrade_tx = state.copytrade_live_tx.clone();
+        let sinks = ws_subscriber::Sinks {
+            copytrade_tx: state.copytrade_live_tx.clone(),
+            alert_tx: state.alert_tx.clone(),
+            resolutions: state.resolutions.clone(),
+            db: state.db.clone(),
+            status: state.live_feed.clone(),
+            gamma: state.gamma.clone(),
+        };
         let cache = state.market_cache.clone();
         let http = state.http.clone();
         let rpc_url = std::env::var("POLYGON_RPC_URL")
             .unwrap_or_else(|_| "http://erpc:4000/main/evm/137".into());
         tokio::spawn(ws_subscriber::run(
-            copytrade_tx,
+            sinks,
             trader_watch_rx,
             cache,
             http,
@@ -265,28 +770,90 @@ pub async fn run(client: clickhouse::Client, port: u16) {
     // Public API routes (no auth required)
     let public_api = Router::new()
         .route("/auth/nonce", get(routes::auth_nonce))
+        .route("/auth/siwe-message", get(routes::auth_siwe_message))
         .route("/auth/verify", post(routes::auth_verify))
-        .route("/health", get(routes::health));
+        .route("/health", get(routes::health))
+        // Read-only shared lists; the unguessable token is the credential
+        .route("/shared/lists/{token}", get(routes::shared_trader_list))
+        // Admin token checked in the handler
+        .route(
+            "/admin/resolved-prices/rebuild",
+            post(routes::rebuild_resolved_prices),
+        );
 
     // Protected API routes (JWT required — AuthUser extractor on each handler)
     let protected_api = Router::new()
+        .route("/auth/refresh", post(routes::auth_refresh))
+        .route("/auth/logout", post(routes::auth_logout))
+        .route("/auth/logout-all", post(routes::auth_logout_all))
         .route("/leaderboard", get(routes::leaderboard))
         .route("/trader/{address}", get(routes::trader_stats))
         .route("/trader/{address}/trades", get(routes::trader_trades))
         .route("/trader/{address}/positions", get(routes::trader_positions))
         .route("/trader/{address}/pnl-chart", get(routes::pnl_chart))
+        .route("/trader/{address}/volume-chart", get(routes::volume_chart))
+        .route("/trader/{address}/rank", get(routes::trader_rank))
         .route("/markets/hot", get(routes::hot_markets))
+        .route("/markets/trending", get(routes::trending_markets))
+        .route("/markets/search", get(routes::search_markets))
         .route("/trades/recent", get(routes::recent_trades))
+        .route("/trades/stream", get(alerts::trades_stream_handler))
+        .route("/whales/recent", get(routes::recent_whales))
+        .route("/alerts/history", get(routes::alert_history))
+        .route(
+            "/signals/convergence/recent",
+            get(routes::recent_convergence),
+        )
         .route("/market/resolve", get(routes::resolve_market))
+        .route("/market/{token_id}", get(routes::market_detail))
+        .route("/market/{token_id}/holders", get(routes::market_holders))
+        .route("/market/{token_id}/flow", get(routes::market_flow))
+        .route(
+            "/market/{token_id}/concentration",
+            get(routes::market_concentration),
+        )
         .route("/smart-money", get(routes::smart_money))
         .route("/trader/{address}/profile", get(routes::trader_profile))
+        .route(
+            "/trader/{address}/categories",
+            get(routes::trader_categories),
+        )
+        .route(
+            "/trader/{address}/pnl-distribution",
+            get(routes::pnl_distribution),
+        )
+        .route("/trader/{address}/similar", get(routes::similar_traders))
         .route("/lab/backtest", post(routes::backtest))
         .route("/lab/copy-portfolio", get(routes::copy_portfolio))
+        .route(
+            "/lab/strategies",
+            get(routes::list_backtest_strategies).post(routes::create_backtest_strategy),
+        )
+        .route(
+            "/lab/strategies/{id}",
+            delete(routes::delete_backtest_strategy),
+        )
+        .route(
+            "/lab/strategies/{id}/run",
+            post(routes::run_backtest_strategy),
+        )
+        .route(
+            "/lab/paper-portfolios",
+            post(routes::create_paper_portfolio),
+        )
+        .route(
+            "/lab/paper-portfolios/{id}",
+            get(routes::get_paper_portfolio),
+        )
         // Trader Lists CRUD
         .route(
             "/lists",
             get(routes::list_trader_lists).post(routes::create_trader_list),
         )
+        .route(
+            "/lists/from-leaderboard",
+            post(routes::create_list_from_leaderboard),
+        )
         .route(
             "/lists/{id}",
             get(routes::get_trader_list)
@@ -297,6 +864,31 @@ pub async fn run(client: clickhouse::Client, port: u16) {
             "/lists/{id}/members",
             post(routes::add_list_members).delete(routes::remove_list_members),
         )
+        .route(
+            "/lists/{id}/members/{address}",
+            patch(routes::update_member_label),
+        )
+        .route("/lists/{id}/import", post(routes::import_list_members))
+        .route("/lists/{id}/duplicate", post(routes::duplicate_trader_list))
+        .route("/lists/{id}/stats", get(routes::trader_list_stats))
+        .route("/lists/{id}/export", get(routes::export_trader_list))
+        .route(
+            "/lists/{id}/share",
+            post(routes::share_trader_list).delete(routes::unshare_trader_list),
+        )
+        // Followed traders (alerts on /ws/alerts)
+        .route("/me/follows", get(routes::list_follows))
+        .route(
+            "/me/follows/{address}",
+            post(routes::follow_trader).delete(routes::unfollow_trader),
+        )
+        // Private trader notes / aliases
+        .route(
+            "/me/notes/{address}",
+            get(routes::get_trader_note)
+                .put(routes::upsert_trader_note)
+                .delete(routes::delete_trader_note),
+        )
         // Trading Wallets (multi-wallet, up to 3 per user)
         .route("/wallets", get(wallet::get_wallets))
         .route("/wallets/generate", post(wallet::generate_wallet))
@@ -346,8 +938,15 @@ pub async fn run(client: clickhouse::Client, port: u16) {
         )
         .route("/copytrade/close-position", post(copytrade::close_position));
 
+    let api = public_api
+        .merge(protected_api)
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::rate_limit,
+        ));
+
     let app = Router::new()
-        .nest("/api", public_api.merge(protected_api))
+        .nest("/api", api)
         .route("/webhooks/rindexer", post(alerts::webhook_handler))
         .route("/ws/alerts", get(alerts::ws_handler))
         .route("/ws/trades", get(alerts::trades_ws_handler))
@@ -363,7 +962,13 @@ pub async fn run(client: clickhouse::Client, port: u16) {
         .expect("Failed to bind");
 
     tracing::info!("API server listening on port {port}");
-    axum::serve(listener, app).await.expect("Server failed");
+    // Peer addresses feed the rate limiter's per-IP keys
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .expect("Server failed");
 }
 
 /// Background task: polls USDC.e balance + allowances for all trading wallets every 30s.
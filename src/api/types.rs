@@ -9,6 +9,21 @@ pub struct LeaderboardResponse {
     pub offset: u32,
     pub labels: std::collections::HashMap<String, Vec<BehavioralLabel>>,
     pub label_details: std::collections::HashMap<String, LabelDetails>,
+    /// Ranked rows read from ClickHouse to build this page. With a `label`
+    /// filter this can exceed `traders.len()`; the next page starts at `offset + scanned`.
+    pub scanned: u32,
+    /// Pass as `cursor` to fetch the next page; `None` when there are no more rows
+    pub next_cursor: Option<String>,
+    /// Category this leaderboard is restricted to (`category` param), if any
+    pub category: Option<String>,
+    /// Markets in `category` known to the market cache
+    pub markets_covered: Option<u64>,
+    /// 7d/30d only: `pnl_daily` metrics (`trade_count`, `fee`) added after this
+    /// window began on this deployment, so their older days could not be
+    /// backfilled and undercount. With `fee` listed, `include_fees` PnL is
+    /// overstated and won't match the all-time figures.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub incomplete_metrics: Vec<String>,
 }
 
 #[derive(Row, Deserialize, Serialize, Clone)]
@@ -17,10 +32,32 @@ pub struct TraderSummary {
     pub total_volume: String,
     pub trade_count: u64,
     pub markets_traded: u64,
+    /// Total PnL (closed + unrealized), kept for compatibility
     pub realized_pnl: String,
+    /// PnL on resolved markets or fully exited positions
+    pub realized_pnl_closed: String,
+    /// Mark-to-market PnL on open positions (unresolved, tokens still held)
+    pub unrealized_pnl: String,
+    /// realized_pnl / total_volume (0 when the trader has no volume)
+    pub roi: f64,
+    /// % of settled positions on the winning side (0 when none settled)
+    pub win_rate: f64,
+    /// Positions held into settlement (resolved or priced within 5¢ of 0/1)
+    pub settled_count: u64,
     pub total_fees: String,
+    /// Timestamp of the first/last trade in the timeframe. The 7d/30d leaderboards
+    /// read daily aggregates, so there these are dates (`YYYY-MM-DD`) only.
     pub first_trade: String,
     pub last_trade: String,
+    /// Places gained (positive) or lost vs. the all-time PnL rank 24h ago;
+    /// `None` when the trader wasn't in both rank snapshots, or on any other
+    /// ranking (sort, timeframe, fees, category, list). Not a ClickHouse column.
+    #[serde(skip_deserializing)]
+    pub rank_change_24h: Option<i64>,
+    /// The caller's private alias for this trader (authenticated requests only).
+    /// Not a ClickHouse column.
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -58,15 +95,118 @@ pub struct HealthResponse {
     pub trade_count: u64,
     pub trader_count: u64,
     pub latest_block: u64,
+    pub leaderboard_cache: CacheStats,
+    pub webhook: WebhookStats,
+    pub live_feed: LiveFeedStats,
+    /// Gamma is rate-limiting us; market metadata lookups are paused
+    pub gamma_degraded: bool,
+    pub rate_limit: RateLimitStats,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize)]
+pub struct RateLimitStats {
+    /// Live (class, caller) buckets
+    pub tracked_keys: usize,
+    pub max_keys: usize,
+    pub allowed: u64,
+    /// Rejected with 429
+    pub limited: u64,
+    /// Buckets dropped to stay within `max_keys`
+    pub evicted: u64,
+}
+
+#[derive(Serialize)]
+pub struct LiveFeedStats {
+    /// `"websocket"` while eth_subscribe is up, otherwise `"webhook_fallback"`
+    pub mode: &'static str,
+    pub connected: bool,
+    pub subscription_id: Option<String>,
+    /// Unix seconds
+    pub connected_since: Option<i64>,
+    pub events_processed: u64,
+    /// Unix seconds
+    pub last_event_at: Option<i64>,
+    pub reconnects: u64,
+}
+
+#[derive(Serialize)]
+pub struct WebhookStats {
+    /// Payloads accepted or rejected by `/webhooks/rindexer`
+    pub received: u64,
+    pub processed: u64,
+    /// Rejected with 429 because the queue was full
+    pub dropped: u64,
+    /// Resolutions neither the market cache nor Gamma could identify
+    pub enrichment_misses: u64,
+    pub queued: usize,
+    pub queue_capacity: usize,
+}
+
+#[derive(Serialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub max_entries: usize,
+    pub hits: u64,
+    pub stale_hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Deserialize, Default)]
 pub struct LeaderboardParams {
     pub sort: Option<String>,
     pub order: Option<String>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
     pub timeframe: Option<String>,
+    /// Minimum total volume (USDC) to be ranked; defaults to 1000 for sort=roi
+    pub min_volume: Option<f64>,
+    /// Only return traders carrying this behavioral label (snake_case)
+    pub label: Option<String>,
+    /// Opaque keyset cursor from a previous `next_cursor` (mutually exclusive with `offset`)
+    pub cursor: Option<String>,
+    /// `json` (default) or `csv` for a spreadsheet export
+    pub format: Option<String>,
+    /// Rank only members of this trader list (requires auth; must be owned by the caller)
+    pub list_id: Option<String>,
+    /// Rank only positions in markets of this category (e.g. `Politics`)
+    pub category: Option<String>,
+    /// Subtract trading fees from every PnL figure (default false)
+    pub include_fees: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct TraderStatsParams {
+    /// Subtract trading fees from every PnL figure (default false)
+    pub include_fees: Option<bool>,
+}
+
+/// The ranking subset of `LeaderboardParams`, with the same meaning.
+#[derive(Deserialize)]
+pub struct TraderRankParams {
+    pub sort: Option<String>,
+    pub timeframe: Option<String>,
+    /// Minimum total volume (USDC) to be ranked; defaults to 1000 for sort=roi,
+    /// matching the leaderboard
+    pub min_volume: Option<f64>,
+    /// Rank only positions in markets of this category
+    pub category: Option<String>,
+    /// Subtract trading fees from every PnL figure (default false)
+    pub include_fees: Option<bool>,
+    /// Rank only among members of this trader list (requires auth)
+    pub list_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TraderRankResponse {
+    pub address: String,
+    pub sort: String,
+    pub timeframe: String,
+    /// 1-based position when sorted descending by `sort`
+    pub rank: u64,
+    pub total: u64,
+    /// Percentage of ranked traders with a worse value
+    pub percentile: f64,
+    pub value: f64,
 }
 
 #[derive(Deserialize)]
@@ -74,6 +214,16 @@ pub struct TradesParams {
     pub limit: Option<u32>,
     pub offset: Option<u32>,
     pub side: Option<String>,
+    /// Comma-separated token ids (full-precision or legacy scientific notation)
+    pub asset_id: Option<String>,
+    /// Only fills of at least this many USDC
+    pub min_usdc: Option<f64>,
+    /// `usdc_amount` for largest fills first (default: newest first)
+    pub sort: Option<String>,
+    /// Inclusive lower bound: unix seconds, RFC 3339 timestamp, or YYYY-MM-DD
+    pub from: Option<String>,
+    /// Exclusive upper bound, same formats as `from`
+    pub to: Option<String>,
 }
 
 // -- Hot Markets --
@@ -100,17 +250,92 @@ pub struct HotMarket {
     pub unique_traders: u64,
     pub last_price: String,
     pub last_trade: String,
+    /// `last_price` minus the price 24h ago for the `token_id` token; null
+    /// when it has no trade older than 24h
+    pub price_change_24h: Option<String>,
+    /// Volume per bucket, oldest first (24 hourly or 7 daily points); only
+    /// with `include_sparkline=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sparkline: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+pub struct TrendingMarketsParams {
+    /// Max market age in days (default 3, max 30)
+    pub days: Option<u32>,
+    pub limit: Option<u32>,
+    /// Minimum event volume in USDC (default 1000)
+    pub min_volume: Option<f64>,
+}
+
+/// `MarketStatsRow` plus first-seen day and half-window volumes
+#[derive(Row, Deserialize)]
+pub struct TrendingMarketRow {
+    pub asset_id: String,
+    pub volume: String,
+    pub trade_count: u64,
+    pub unique_traders: u64,
+    pub last_price: String,
+    pub last_trade: String,
+    pub first_seen: String,
+    pub first_half_volume: String,
+    pub second_half_volume: String,
+}
+
+#[derive(Serialize)]
+pub struct TrendingMarket {
+    #[serde(flatten)]
+    pub market: HotMarket,
+    /// First day the market traded (`YYYY-MM-DD`)
+    pub first_seen: String,
+    /// Second-half vs first-half window volume, in %; null when the market
+    /// only started trading in the second half
+    pub volume_growth_pct: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct TrendingMarketsResponse {
+    pub days: u32,
+    pub markets: Vec<TrendingMarket>,
+}
+
+/// Per-asset volume in one sparkline bucket
+#[derive(Row, Deserialize)]
+pub struct SparklineRow {
+    pub asset_id: String,
+    pub bucket: String,
+    pub volume: String,
+}
+
+/// Per-asset price lookup (price as of a point in time)
+#[derive(Row, Deserialize)]
+pub struct AssetPriceRow {
+    pub asset_id: String,
+    pub price: String,
 }
 
 #[derive(Serialize)]
 pub struct HotMarketsResponse {
+    /// Period actually used (`1h`, `24h`, or `Nd`)
+    pub period: String,
     pub markets: Vec<HotMarket>,
 }
 
 #[derive(Deserialize)]
 pub struct HotMarketsParams {
+    /// `1h`, `24h` (default), `7d` or `30d`
     pub period: Option<String>,
+    /// Custom window in days (1-90); overrides `period`
+    pub days: Option<u32>,
     pub limit: Option<u32>,
+    /// `volume` (default), `trade_count` or `unique_traders`
+    pub sort: Option<String>,
+    /// Case-insensitive match on the resolved market category
+    pub category: Option<String>,
+    /// Minimum merged (event-level) volume in USDC
+    pub min_volume: Option<f64>,
+    /// Attach a per-market volume sparkline
+    pub include_sparkline: Option<bool>,
 }
 
 // -- Live Feed --
@@ -125,6 +350,8 @@ pub struct RecentTradeRow {
     pub amount: String,
     pub price: String,
     pub usdc_amount: String,
+    pub block_number: u64,
+    pub log_index: u64,
 }
 
 #[derive(Serialize)]
@@ -145,12 +372,61 @@ pub struct FeedTrade {
 #[derive(Serialize)]
 pub struct LiveFeedResponse {
     pub trades: Vec<FeedTrade>,
+    pub filters: LiveFeedFilters,
+    /// Pass back as `cursor` to fetch only newer trades
+    pub next_cursor: Option<String>,
+    /// Trades after the request `cursor` (before the category filter and page
+    /// cap); null without a cursor
+    pub total_new: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct WhaleTradesParams {
+    /// `1h` or `24h` (default)
+    pub period: Option<String>,
+    /// Minimum fill size in USDC (default `WHALE_ALERT_DEFAULT_USDC`)
+    pub min_usdc: Option<f64>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct WhaleTrade {
+    #[serde(flatten)]
+    pub trade: FeedTrade,
+    pub labels: Vec<BehavioralLabel>,
+}
+
+#[derive(Serialize)]
+pub struct WhaleTradesResponse {
+    pub period: String,
+    pub min_usdc: f64,
+    pub trades: Vec<WhaleTrade>,
+}
+
+/// Filters applied to the feed (echoed back; null when unset)
+#[derive(Serialize)]
+pub struct LiveFeedFilters {
+    pub token_id: Option<String>,
+    pub trader: Option<String>,
+    pub min_usdc: Option<f64>,
+    pub side: Option<String>,
+    pub category: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct LiveFeedParams {
     pub limit: Option<u32>,
     pub token_id: Option<String>,
+    /// Trader address (case-insensitive)
+    pub trader: Option<String>,
+    /// Minimum trade size in USDC
+    pub min_usdc: Option<f64>,
+    /// `buy` or `sell`
+    pub side: Option<String>,
+    /// Case-insensitive match on the resolved market category
+    pub category: Option<String>,
+    /// `next_cursor` from a previous response: only newer trades, up to 500
+    pub cursor: Option<String>,
 }
 
 // -- Trader Positions --
@@ -180,6 +456,11 @@ pub struct OpenPosition {
     pub pnl: String,
     pub volume: String,
     pub trade_count: u64,
+    /// Market end date from Gamma (falls back to its close time), if known
+    pub end_date: Option<String>,
+    /// Why the position is open/closed: `on_chain`, `gamma_inactive`,
+    /// `price_settled`, `user_exited`, or `open`
+    pub resolution_source: &'static str,
 }
 
 #[derive(Serialize)]
@@ -193,6 +474,23 @@ pub struct PositionsResponse {
 #[derive(Deserialize)]
 pub struct PnlChartParams {
     pub timeframe: Option<String>,
+    /// `hour`, `day` or `week` (default: hour for 24h, day otherwise)
+    pub granularity: Option<String>,
+    /// `asset` adds per-asset `series` alongside the aggregate points
+    pub breakdown: Option<String>,
+    pub top_assets: Option<usize>,
+    /// Subtract trading fees from the PnL line (default false)
+    pub include_fees: Option<bool>,
+    /// Custom window start (inclusive); overrides `timeframe`
+    pub from: Option<String>,
+    /// Custom window end (exclusive); overrides `timeframe`
+    pub to: Option<String>,
+    /// Adds `peak`/`drawdown` per point and `max_drawdown` (default false)
+    pub include_drawdown: Option<bool>,
+    /// `absolute` (default) or `percent` of cumulative capital deployed
+    pub mode: Option<String>,
+    /// `top10` adds the top-10 traders' average % return as `benchmark`
+    pub benchmark: Option<String>,
 }
 
 /// Per-(bucket, asset) trade summary for mark-to-market PnL computation
@@ -203,6 +501,7 @@ pub struct PnlDailyRow {
     pub net_token_delta: String,
     pub cash_flow_delta: String,
     pub last_price: String,
+    pub buy_usdc_delta: String,
 }
 
 /// Pre-window portfolio state per asset (for windowed timeframes)
@@ -212,6 +511,7 @@ pub struct PnlInitialStateRow {
     pub net_tokens: String,
     pub cash_flow: String,
     pub last_price: String,
+    pub buy_usdc: String,
 }
 
 /// Lightweight read type for resolved_prices lookups
@@ -221,15 +521,72 @@ pub struct ResolvedPriceLookup {
     pub resolved_price: String,
 }
 
-#[derive(Serialize)]
+/// Lightweight read type for asset_latest_price lookups
+#[derive(Row, Deserialize)]
+pub struct LatestPriceLookup {
+    pub asset_id: String,
+    pub latest_price: String,
+}
+
+#[derive(Serialize, Clone)]
 pub struct PnlChartPoint {
     pub date: String,
     pub pnl: String,
+    /// Running max PnL; only with `include_drawdown`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak: Option<String>,
+    /// `peak - pnl`; only with `include_drawdown`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drawdown: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct PnlChartResponse {
+    /// `absolute` (USDC) or `percent` (of capital deployed so far)
+    pub mode: &'static str,
+    /// Largest peak-to-trough PnL drop; only with `include_drawdown`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_drawdown: Option<String>,
     pub points: Vec<PnlChartPoint>,
+    /// Only with `breakdown=asset`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<Vec<PnlSeries>>,
+    /// Average % return of the current top-10 traders on the `points` date axis;
+    /// only with `benchmark=top10`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<Vec<Option<String>>>,
+}
+
+/// One asset's cumulative PnL on the `points` date axis (null before it was held).
+/// The trailing "Other" series has no `asset_id`.
+#[derive(Serialize, Clone)]
+pub struct PnlSeries {
+    pub asset_id: Option<String>,
+    pub question: String,
+    pub outcome: String,
+    pub values: Vec<Option<String>>,
+}
+
+#[derive(Row, Deserialize)]
+pub struct VolumeChartRow {
+    pub date: String,
+    pub volume: String,
+    pub trade_count: u64,
+    pub markets_traded: u64,
+}
+
+#[derive(Serialize)]
+pub struct VolumeChartPoint {
+    pub date: String,
+    pub volume: String,
+    pub trade_count: u64,
+    pub markets_traded: u64,
+}
+
+#[derive(Serialize)]
+pub struct VolumeChartResponse {
+    pub timeframe: String,
+    pub points: Vec<VolumeChartPoint>,
 }
 
 // -- Condition Resolution (on-chain) --
@@ -247,6 +604,8 @@ pub struct ResolvedPriceRow {
     pub resolved_price: String,
     pub condition_id: String,
     pub block_number: u64,
+    /// `normal`, or `invalid` for an even split across all outcomes (UMA 50/50)
+    pub resolution_kind: String,
 }
 
 // -- On-demand market resolve --
@@ -270,6 +629,230 @@ pub struct ResolvedMarket {
     pub outcomes: Vec<String>,
 }
 
+// -- Market search --
+
+#[derive(Deserialize)]
+pub struct MarketSearchParams {
+    /// At least 3 characters; every word must appear in the question or an outcome
+    pub q: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Row, Deserialize)]
+pub struct AssetVolumeRow {
+    pub asset_id: String,
+    pub volume: String,
+}
+
+#[derive(Serialize)]
+pub struct MarketSearchResult {
+    #[serde(flatten)]
+    pub market: ResolvedMarket,
+    /// Both sides combined, excluding exchange contracts
+    pub volume_24h: String,
+}
+
+#[derive(Serialize)]
+pub struct MarketSearchResponse {
+    pub query: String,
+    pub markets: Vec<MarketSearchResult>,
+}
+
+// -- Market detail --
+
+#[derive(Row, Deserialize)]
+pub struct MarketDetailStatsRow {
+    pub asset_id: String,
+    pub volume: String,
+    pub volume_7d: String,
+    pub trade_count: u64,
+    pub last_price: String,
+    pub last_trade: String,
+}
+
+#[derive(Row, Deserialize)]
+pub struct MarketDetailTotalsRow {
+    pub unique_traders: u64,
+    pub volume_24h: String,
+}
+
+#[derive(Row, Deserialize)]
+pub struct MarketDetailResolvedRow {
+    pub asset_id: String,
+    pub resolved_price: String,
+    pub condition_id: String,
+    pub block_number: u64,
+}
+
+#[derive(Serialize)]
+pub struct MarketResolution {
+    pub condition_id: String,
+    pub block_number: u64,
+    /// Payout per token (parallel to all_token_ids)
+    pub resolved_prices: Vec<Option<String>>,
+    pub winning_outcome: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MarketDetailResponse {
+    /// Full-precision Gamma token ID of the requested side
+    pub token_id: String,
+    pub question: String,
+    pub category: String,
+    pub active: bool,
+    pub all_token_ids: Vec<String>,
+    pub outcomes: Vec<String>,
+    pub end_date: Option<String>,
+    /// Stats below are summed across both sides
+    pub volume: String,
+    pub volume_24h: String,
+    pub volume_7d: String,
+    pub trade_count: u64,
+    pub unique_traders: u64,
+    /// Price of the most recently traded side
+    pub last_price: String,
+    pub last_trade: String,
+    /// Last traded price per token (parallel to all_token_ids)
+    pub last_prices: Vec<Option<String>>,
+    /// On-chain resolution, once the condition has resolved
+    pub resolution: Option<MarketResolution>,
+}
+
+// -- Market flow --
+
+#[derive(Deserialize)]
+pub struct MarketFlowParams {
+    /// `1h` or `24h` (default)
+    pub period: Option<String>,
+    /// Minimum trade size in USDC for the whale figures (default 1000)
+    pub whale_min_usdc: Option<f64>,
+}
+
+#[derive(Row, Deserialize)]
+pub struct MarketFlowRow {
+    pub bucket: String,
+    pub buy_volume: String,
+    pub sell_volume: String,
+    pub buy_count: u64,
+    pub sell_count: u64,
+    pub whale_buy_volume: String,
+    pub whale_sell_volume: String,
+    pub whale_buy_count: u64,
+    pub whale_sell_count: u64,
+}
+
+#[derive(Serialize)]
+pub struct MarketFlowStats {
+    pub buy_volume: String,
+    pub sell_volume: String,
+    /// `buy_volume - sell_volume`
+    pub net_flow: String,
+    pub buy_count: u64,
+    pub sell_count: u64,
+}
+
+#[derive(Serialize)]
+pub struct MarketFlowBucket {
+    /// Hour start (`YYYY-MM-DD hh:mm:ss`, UTC)
+    pub bucket: String,
+    pub flow: MarketFlowStats,
+    pub whale: MarketFlowStats,
+}
+
+#[derive(Serialize)]
+pub struct MarketFlowResponse {
+    pub token_id: String,
+    pub question: String,
+    pub outcome: String,
+    pub period: String,
+    pub whale_min_usdc: f64,
+    /// Totals over the whole period
+    pub flow: MarketFlowStats,
+    pub whale: MarketFlowStats,
+    pub buckets: Vec<MarketFlowBucket>,
+}
+
+// -- Market concentration --
+
+#[derive(Deserialize)]
+pub struct MarketConcentrationParams {
+    /// `1h`, `24h` (default), `7d`, `30d` or `all`
+    pub period: Option<String>,
+}
+
+#[derive(Row, Deserialize)]
+pub struct ConcentrationTotalsRow {
+    pub traders: u64,
+    pub volume: String,
+    pub herfindahl: f64,
+}
+
+#[derive(Row, Deserialize)]
+pub struct ConcentrationTraderRow {
+    pub address: String,
+    pub volume: f64,
+}
+
+#[derive(Serialize)]
+pub struct ConcentrationTrader {
+    pub address: String,
+    pub volume: String,
+    /// Share of the market's period volume (0-1)
+    pub share: f64,
+}
+
+#[derive(Serialize)]
+pub struct MarketConcentrationResponse {
+    pub question: String,
+    pub all_token_ids: Vec<String>,
+    pub period: String,
+    /// Traders with volume in the period
+    pub traders: u64,
+    pub volume: String,
+    /// Sum of squared volume shares (0-1); near 1 means one wallet dominates
+    pub herfindahl: f64,
+    /// Combined share of `top_traders` (0-1)
+    pub top_share: f64,
+    /// Top 5 traders by volume
+    pub top_traders: Vec<ConcentrationTrader>,
+}
+
+// -- Market holders --
+
+#[derive(Row, Deserialize)]
+pub struct MarketHolderRow {
+    pub trader: String,
+    pub asset_id: String,
+    pub net_tokens: String,
+    pub exposure: String,
+    pub cost_basis: String,
+    pub pnl: String,
+}
+
+#[derive(Serialize)]
+pub struct MarketHolder {
+    pub address: String,
+    pub token_id: String,
+    pub outcome: String,
+    /// `buy_amount - sell_amount`; negative for shorts
+    pub net_tokens: String,
+    /// Net tokens × latest price (USDC)
+    pub exposure: String,
+    /// Net USDC paid in (`buy_usdc - sell_usdc`)
+    pub cost_basis: String,
+    pub pnl: String,
+    pub labels: Vec<BehavioralLabel>,
+}
+
+#[derive(Serialize)]
+pub struct MarketHoldersResponse {
+    pub question: String,
+    pub all_token_ids: Vec<String>,
+    pub outcomes: Vec<String>,
+    pub longs: Vec<MarketHolder>,
+    pub shorts: Vec<MarketHolder>,
+}
+
 // -- Trader Profile --
 
 #[derive(Row, Deserialize)]
@@ -295,6 +878,7 @@ pub struct ProfilePositionRow {
     pub buy_usdc: String,
     pub sell_usdc: String,
     pub buy_amount: String,
+    pub total_fee: String,
 }
 
 #[derive(Row, Deserialize)]
@@ -313,6 +897,7 @@ pub struct BatchPositionRow {
     pub buy_usdc: String,
     pub sell_usdc: String,
     pub buy_amount: String,
+    pub total_fee: String,
 }
 
 #[derive(Serialize)]
@@ -331,10 +916,31 @@ pub struct CategoryStats {
     pub pnl: String,
 }
 
+/// Per-category split of a trader's positions. `settled_pnl` covers settled markets
+/// and fully exited positions; everything else is `unrealized_pnl`.
+#[derive(Serialize)]
+pub struct TraderCategoryStats {
+    pub category: String,
+    pub settled_pnl: String,
+    pub unrealized_pnl: String,
+    pub volume: String,
+    pub position_count: u64,
+    pub settled_count: u64,
+    pub win_rate: f64,
+    pub top_positions: Vec<PositionHighlight>,
+}
+
+#[derive(Serialize)]
+pub struct TraderCategoriesResponse {
+    pub categories: Vec<TraderCategoryStats>,
+}
+
 #[derive(Serialize)]
 pub struct TraderProfile {
     pub avg_position_size: String,
     pub avg_hold_time_hours: f64,
+    pub median_hold_time_hours: f64,
+    pub hold_time_distribution: Vec<HoldTimeBucket>,
     pub biggest_win: Option<PositionHighlight>,
     pub biggest_loss: Option<PositionHighlight>,
     pub category_breakdown: Vec<CategoryStats>,
@@ -342,9 +948,47 @@ pub struct TraderProfile {
     pub resolved_positions: u64,
     pub labels: Vec<BehavioralLabel>,
     pub label_details: LabelDetails,
+    /// The caller's private alias for this trader (authenticated requests only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
 }
 
-#[derive(Clone, Serialize)]
+/// Positions whose hold time falls in this bucket. Open positions are measured
+/// from their first trade to now and also counted in `open_count`.
+#[derive(Serialize)]
+pub struct HoldTimeBucket {
+    pub bucket: &'static str,
+    pub count: u64,
+    pub open_count: u64,
+}
+
+#[derive(Deserialize)]
+pub struct PnlDistributionParams {
+    pub buckets: Option<usize>,
+}
+
+/// Histogram of per-position PnL. Flat (exactly zero) positions are counted in
+/// `zero_count` and kept out of `buckets`; the summary stats cover every position.
+#[derive(Serialize)]
+pub struct PnlDistributionResponse {
+    pub buckets: Vec<PnlBucket>,
+    pub zero_count: u64,
+    pub total_positions: u64,
+    pub median_pnl: String,
+    pub mean_pnl: String,
+    pub stddev_pnl: String,
+    pub profitable_share: f64,
+}
+
+/// `[lower, upper)` — the last bucket also includes `upper`
+#[derive(Serialize)]
+pub struct PnlBucket {
+    pub lower: String,
+    pub upper: String,
+    pub count: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum BehavioralLabel {
     Sharp,
@@ -357,6 +1001,36 @@ pub enum BehavioralLabel {
     Contrarian,
 }
 
+impl BehavioralLabel {
+    pub const ALL: [Self; 8] = [
+        Self::Sharp,
+        Self::Specialist,
+        Self::Whale,
+        Self::Degen,
+        Self::MarketMaker,
+        Self::Bot,
+        Self::Casual,
+        Self::Contrarian,
+    ];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.as_str() == s)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sharp => "sharp",
+            Self::Specialist => "specialist",
+            Self::Whale => "whale",
+            Self::Degen => "degen",
+            Self::MarketMaker => "market_maker",
+            Self::Bot => "bot",
+            Self::Casual => "casual",
+            Self::Contrarian => "contrarian",
+        }
+    }
+}
+
 #[derive(Serialize, Clone)]
 pub struct LabelDetails {
     pub win_rate: f64,
@@ -375,6 +1049,7 @@ pub struct LabelDetails {
     pub contrarian_trades: u64,
     pub contrarian_correct: u64,
     pub contrarian_rate: f64,
+    pub total_fees_paid: String,
 }
 
 // -- Smart Money Signal --
@@ -383,6 +1058,8 @@ pub struct LabelDetails {
 pub struct SmartMoneyParams {
     pub top: Option<u32>,
     pub timeframe: Option<String>,
+    /// Use the caller's trader list as the smart set (exclusive with `top`)
+    pub list_id: Option<String>,
 }
 
 #[derive(Row, Deserialize)]
@@ -412,7 +1089,38 @@ pub struct SmartMoneyMarket {
 #[derive(Serialize)]
 pub struct SmartMoneyResponse {
     pub markets: Vec<SmartMoneyMarket>,
+    /// Size of the smart set (list member count in list mode)
     pub top: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_name: Option<String>,
+}
+
+// -- Similar Traders --
+
+#[derive(Row, Deserialize)]
+pub struct SimilarTraderRow {
+    pub address: String,
+    pub overlap_count: u64,
+    pub overlap_ratio: f64,
+    pub same_side_count: u64,
+}
+
+#[derive(Serialize)]
+pub struct SimilarTrader {
+    pub address: String,
+    /// Open positions shared with the target trader
+    pub overlap_count: u64,
+    /// Shared / union of both traders' open positions (Jaccard)
+    pub overlap_ratio: f64,
+    pub same_side_count: u64,
+    /// "same", "opposite" or "mixed" — which side they hold on most shared positions
+    pub side: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct SimilarTradersResponse {
+    pub address: String,
+    pub traders: Vec<SimilarTrader>,
 }
 
 // -- Trader Lists --
@@ -433,6 +1141,79 @@ pub struct TraderListDetail {
     pub members: Vec<TraderListMember>,
     pub created_at: String,
     pub updated_at: String,
+    /// Public read-only token (`/api/shared/lists/{token}`), if shared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_token: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ListShareResponse {
+    pub share_token: String,
+}
+
+#[derive(Deserialize)]
+pub struct SharedListParams {
+    pub stats: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct SharedTraderList {
+    #[serde(flatten)]
+    pub list: TraderListDetail,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TraderListStats>,
+}
+
+#[derive(Deserialize)]
+pub struct ListExportParams {
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+}
+
+#[derive(Row, Deserialize)]
+pub struct ListExportStatsRow {
+    pub address: String,
+    pub total_volume: String,
+    pub realized_pnl: String,
+    pub trade_count: u64,
+    pub last_trade: String,
+}
+
+/// A list member plus all-time stats; stats are `None` without on-chain activity.
+#[derive(Serialize)]
+pub struct ListExportMember {
+    pub address: String,
+    pub label: Option<String>,
+    pub added_at: String,
+    pub total_volume: Option<String>,
+    pub realized_pnl: Option<String>,
+    pub trade_count: Option<u64>,
+    pub last_trade: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TraderListExport {
+    pub id: String,
+    pub name: String,
+    pub members: Vec<ListExportMember>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Serialize)]
+pub struct TraderListStats {
+    pub id: String,
+    pub name: String,
+    pub member_count: u32,
+    /// Sum of members' all-time PnL
+    pub total_pnl: String,
+    pub total_volume: String,
+    /// All-time leaderboard rows for members with trades, by PnL
+    pub members: Vec<TraderSummary>,
+    /// Behavioral labels per member address
+    pub labels: std::collections::HashMap<String, Vec<BehavioralLabel>>,
+    /// Members' combined open exposure, top 10 markets
+    pub exposure: Vec<SmartMoneyMarket>,
 }
 
 #[derive(Serialize)]
@@ -458,20 +1239,104 @@ pub struct AddMembersRequest {
     pub labels: Option<Vec<Option<String>>>,
 }
 
+#[derive(Deserialize)]
+pub struct ListFromLeaderboardRequest {
+    pub name: String,
+    /// Traders to take from the top (default 10, max 100)
+    pub top_n: Option<u32>,
+    /// Same values as the leaderboard's `timeframe` (default `all`)
+    pub timeframe: Option<String>,
+    /// Same values as the leaderboard's `sort` (default `realized_pnl`)
+    pub sort: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct DuplicateListRequest {
+    /// Defaults to "Copy of <original>"
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMemberLabelRequest {
+    /// `null` or blank clears the label
+    pub label: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct RemoveMembersRequest {
     pub addresses: Vec<String>,
 }
 
-// -- PolyLab Backtest --
+#[derive(Serialize)]
+pub struct ListImportRow {
+    /// 1-based position in the submitted array / CSV (header excluded)
+    pub row: u32,
+    pub address: String,
+    /// `added`, `duplicate`, `invalid` or `over_limit`
+    pub status: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct ListImportResponse {
+    pub added: u32,
+    pub duplicates: u32,
+    pub invalid: u32,
+    /// Valid new addresses left out because the list is full
+    pub overflow: u32,
+    pub rows: Vec<ListImportRow>,
+}
+
+// -- Trader Notes --
+
+#[derive(Serialize)]
+pub struct TraderNote {
+    pub address: String,
+    pub alias: Option<String>,
+    pub note: Option<String>,
+    pub updated_at: String,
+}
 
 #[derive(Deserialize)]
+pub struct UpsertNoteRequest {
+    pub alias: Option<String>,
+    pub note: Option<String>,
+}
+
+// -- PolyLab Backtest --
+
+#[derive(Deserialize, Serialize)]
 pub struct BacktestRequest {
     pub top_n: Option<u32>,
     pub list_id: Option<String>,
-    pub timeframe: String,
+    /// `7d`, `30d` or `all`; mutually exclusive with `from`/`to`
+    pub timeframe: Option<String>,
+    /// Custom window start (`YYYY-MM-DD`, inclusive)
+    pub from: Option<String>,
+    /// Custom window end (`YYYY-MM-DD`, exclusive; default today)
+    pub to: Option<String>,
+    /// How top-N traders are picked: `point_in_time` (PnL before the window
+    /// start; default when there is one) or `current` (all-time PnL)
+    pub selection: Option<String>,
     pub initial_capital: Option<f64>,
     pub copy_pct: Option<f64>,
+    /// Price impact per copied fill, in basis points of notional (default 0, max 500)
+    pub slippage_bps: Option<u32>,
+    /// Trading fee, in basis points of notional (default 0, max 1000)
+    pub fee_bps: Option<u32>,
+    /// Capital split across traders: `equal` (default), `pnl_weighted` or `volume_weighted`
+    pub weighting: Option<String>,
+    /// Return the simulated trade log (default false)
+    pub include_trades: Option<bool>,
+    /// Recompute even if a cached result exists; never stored with strategies
+    #[serde(skip_serializing)]
+    pub force: Option<bool>,
+    /// Floor weight (0-1) for traders the weighting would give nothing, e.g.
+    /// negative PnL; capped at an equal share (default 0)
+    pub min_weight: Option<f64>,
+    /// Cap on any one asset's value, as a fraction (0-1] of portfolio value
+    pub max_position_pct: Option<f64>,
+    /// Market categories (case-insensitive) whose trades are not copied
+    pub exclude_categories: Option<Vec<String>>,
 }
 
 #[derive(Row, Deserialize)]
@@ -482,6 +1347,7 @@ pub struct PnlDailyTraderRow {
     pub net_token_delta: String,
     pub cash_flow_delta: String,
     pub last_price: String,
+    pub buy_usdc_delta: String,
 }
 
 #[derive(Row, Deserialize)]
@@ -491,6 +1357,7 @@ pub struct PnlInitialStateTraderRow {
     pub net_tokens: String,
     pub cash_flow: String,
     pub last_price: String,
+    pub buy_usdc: String,
 }
 
 #[derive(Row, Deserialize)]
@@ -499,9 +1366,11 @@ pub struct TraderScaleRow {
     pub address: String,
     pub avg_position_size: String,
     pub market_count: u64,
+    pub pnl: String,
+    pub volume: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct PortfolioPoint {
     pub date: String,
     pub value: String,
@@ -509,38 +1378,97 @@ pub struct PortfolioPoint {
     pub pnl_pct: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct BacktestConfig {
     pub initial_capital: f64,
     pub copy_pct: f64,
     pub top_n: u32,
+    /// `7d`, `30d`, `all`, or `custom` for a `from`/`to` window
     pub timeframe: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// `point_in_time` or `current`; null for list backtests
+    pub selection: Option<&'static str>,
+    /// Average budget per trader; each trader's share follows `weighting`
     pub per_trader_budget: f64,
+    pub weighting: &'static str,
+    pub min_weight: f64,
+    pub max_position_pct: Option<f64>,
+    pub exclude_categories: Vec<String>,
+    pub slippage_bps: u32,
+    pub fee_bps: u32,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct BacktestResponse {
     pub portfolio_curve: Vec<PortfolioPoint>,
     pub pnl_curve: Vec<PnlChartPoint>,
     pub summary: BacktestSummary,
     pub traders: Vec<BacktestTrader>,
     pub config: BacktestConfig,
+    /// Simulated trades in order, capped at 1000; only with `include_trades`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trades: Option<Vec<SimulatedTrade>>,
+    /// More trades were simulated than `trades` holds
+    pub trades_truncated: bool,
+    /// Served from the backtest cache (top-N runs only)
+    pub cached: bool,
+    /// When the result was computed (RFC 3339)
+    pub computed_at: String,
+}
+
+/// One pnl_daily delta as the backtest copied it, after scaling and clipping.
+#[derive(Serialize, Clone)]
+pub struct SimulatedTrade {
+    pub date: String,
+    pub trader: String,
+    pub asset_id: String,
+    pub question: Option<String>,
+    /// `buy` or `sell`, from the source trader's net token delta
+    pub side: &'static str,
+    pub tokens: String,
+    /// Cash flow including costs; negative when buying
+    pub cash: String,
+    /// Cut by the cash or position-size limit
+    pub clipped: bool,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct BacktestSummary {
+    /// Net of modeled slippage and fees
     pub total_pnl: String,
+    /// PnL copying at the source traders' exact prices, without costs
+    pub gross_pnl: String,
+    pub slippage_cost: String,
+    pub fee_cost: String,
+    /// `slippage_cost + fee_cost`
+    pub total_costs: String,
+    /// Buy notional not copied for lack of cash or room under the position cap
+    pub clipped_buys: String,
+    /// Part of `clipped_buys` due to `max_position_pct`
+    pub position_cap_clipped: String,
     pub total_return_pct: f64,
     pub win_rate: f64,
     pub max_drawdown: String,
     pub max_drawdown_pct: f64,
+    /// Annualized mean / stdev of per-point returns (risk-free rate 0)
+    pub sharpe_ratio: f64,
+    /// Like `sharpe_ratio`, but only penalizing downside deviation
+    pub sortino_ratio: f64,
+    /// Annualized stdev of per-point returns, in %
+    pub volatility_annualized: f64,
+    /// Best and worst per-point return, in %
+    pub best_day: f64,
+    pub worst_day: f64,
+    /// Annualized return / max drawdown
+    pub calmar_ratio: f64,
     pub positions_count: u64,
     pub traders_count: u32,
     pub initial_capital: f64,
     pub final_value: f64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct BacktestTrader {
     pub address: String,
     pub rank: u32,
@@ -551,12 +1479,35 @@ pub struct BacktestTrader {
     pub scale_factor: f64,
 }
 
+#[derive(Deserialize)]
+pub struct CreateStrategyRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub config: BacktestRequest,
+}
+
+#[derive(Serialize)]
+pub struct BacktestStrategy {
+    pub id: String,
+    pub name: String,
+    /// The stored `BacktestRequest`
+    pub config: serde_json::Value,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
 // -- Copy Portfolio --
 
 #[derive(Deserialize)]
 pub struct CopyPortfolioParams {
     pub top: Option<u32>,
     pub list_id: Option<String>,
+    /// Bankroll (USDC) to split across positions as `suggested_allocation`
+    pub capital: Option<f64>,
+    /// Keep only the first N positions (by convergence, then exposure)
+    pub max_positions: Option<u32>,
+    /// Minimum number of copied traders holding a market
+    pub min_convergence: Option<u32>,
 }
 
 #[derive(Row, Deserialize)]
@@ -582,6 +1533,12 @@ pub struct CopyPortfolioPosition {
     pub avg_entry: String,
     pub latest_price: String,
     pub total_pnl: String,
+    /// Share of `capital` proportional to `total_exposure`; only with `capital`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_allocation: Option<String>,
+    /// `suggested_allocation / latest_price`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_shares: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -590,7 +1547,11 @@ pub struct CopyPortfolioSummary {
     pub unique_markets: u32,
     pub total_exposure: String,
     pub total_pnl: String,
+    /// Traders copied (list members in list mode)
     pub top_n: u32,
+    /// Set in list mode
+    pub list_name: Option<String>,
+    pub member_count: Option<u32>,
 }
 
 #[derive(Serialize)]
@@ -599,6 +1560,81 @@ pub struct CopyPortfolioResponse {
     pub summary: CopyPortfolioSummary,
 }
 
+// -- Paper Portfolios --
+
+#[derive(Deserialize)]
+pub struct CreatePaperPortfolioRequest {
+    /// Virtual USDC to allocate across the snapshot
+    pub capital: f64,
+    pub top: Option<u32>,
+    pub list_id: Option<String>,
+    /// Positions to snapshot (default 20)
+    pub max_positions: Option<u32>,
+    pub min_convergence: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct PaperPortfolio {
+    pub id: String,
+    pub capital: f64,
+    /// Capital left unallocated at inception
+    pub cash: f64,
+    pub top_n: Option<u32>,
+    pub list_id: Option<String>,
+    pub list_name: Option<String>,
+    pub created_at: String,
+}
+
+/// A snapshotted position: `shares` bought at `entry_price` at inception.
+#[derive(Serialize, Clone)]
+pub struct PaperPosition {
+    pub token_id: String,
+    pub question: String,
+    pub outcome: String,
+    pub entry_price: f64,
+    pub shares: f64,
+}
+
+#[derive(Serialize)]
+pub struct PaperPositionStatus {
+    #[serde(flatten)]
+    pub position: PaperPosition,
+    pub current_price: String,
+    pub value: String,
+    pub pnl: String,
+    /// `open` or `resolved`
+    pub status: &'static str,
+    pub resolved_price: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PaperValuePoint {
+    pub date: String,
+    pub value: String,
+}
+
+#[derive(Serialize)]
+pub struct PaperPortfolioDetail {
+    #[serde(flatten)]
+    pub portfolio: PaperPortfolio,
+    pub current_value: String,
+    pub pnl: String,
+    pub pnl_pct: f64,
+    /// Positions whose market resolved since inception
+    pub resolved_count: u32,
+    pub positions: Vec<PaperPositionStatus>,
+    /// One point per day, starting at inception
+    pub equity_curve: Vec<PaperValuePoint>,
+}
+
+/// Mark inputs for a paper position; `resolved_price` is empty if unresolved.
+#[derive(Row, Deserialize)]
+pub struct PaperMarkRow {
+    pub asset_id: String,
+    pub latest_price: String,
+    pub resolved_price: String,
+}
+
 // -- Trading Wallet --
 
 #[derive(Serialize)]
@@ -682,6 +1718,39 @@ pub struct PendingDeposit {
 
 // -- Market Metadata (persisted from Gamma API cache to ClickHouse) --
 
+/// Persisted alert (`alerts_history`); `payload` is the serialized `Alert`
+#[derive(Row, Serialize)]
+pub struct AlertHistoryRow {
+    pub kind: String,
+    /// Unix seconds
+    pub timestamp: u32,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub log_index: u64,
+    pub payload: String,
+}
+
+#[derive(Serialize)]
+pub struct RecentConvergenceResponse {
+    pub alerts: Vec<super::alerts::ConvergenceAlert>,
+}
+
+#[derive(Deserialize)]
+pub struct AlertHistoryParams {
+    /// `WhaleTrade`, `MarketResolution`, `FailedSettlement`, `PriceSwing` or
+    /// `SmartMoneyEntry`
+    pub kind: Option<String>,
+    pub limit: Option<u32>,
+    /// Unix seconds, RFC 3339 timestamp, or `YYYY-MM-DD`
+    pub since: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AlertHistoryResponse {
+    /// Newest first, in the same shape as /ws/alerts frames
+    pub alerts: Vec<serde_json::Value>,
+}
+
 #[derive(clickhouse::Row, Serialize, Deserialize)]
 pub struct MarketMetadataRow {
     pub asset_id: String,
@@ -694,6 +1763,8 @@ pub struct MarketMetadataRow {
     pub active: u8,
     pub all_token_ids: Vec<String>,
     pub outcomes: Vec<String>,
+    /// Empty when Gamma has no end date
+    pub end_date: String,
     pub updated_at: u32,
 }
 
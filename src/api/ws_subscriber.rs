@@ -1,4 +1,6 @@
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use alloy_primitives::B256;
@@ -8,8 +10,9 @@ use serde::Deserialize;
 use tokio::sync::{broadcast, watch};
 use tokio_tungstenite::tungstenite::Message;
 
-use super::alerts::LiveTrade;
+use super::alerts::{self, Alert, LiveTrade, RecentKeys};
 use super::markets;
+use super::types::LiveFeedStats;
 
 // ---------------------------------------------------------------------------
 // Constants
@@ -17,10 +20,16 @@ use super::markets;
 
 const CTF_EXCHANGE: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
 const NEGRISK_EXCHANGE: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a";
+const CONDITIONAL_TOKENS: &str = "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045";
 const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(2);
 const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
 const HEALTH_LOG_INTERVAL: Duration = Duration::from_secs(60);
 const MAX_TRACKED_ADDRESSES_WARN: usize = 200;
+const GAP_RECOVERY_CHUNK_BLOCKS: u64 = 2000;
+const GAP_RECOVERY_MAX_BLOCKS_DEFAULT: u64 = 5000;
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+/// Blocks behind head a checkpoint assumes are already delivered over WS
+const CHECKPOINT_LAG_BLOCKS: u64 = 5;
 
 // ---------------------------------------------------------------------------
 // ABI
@@ -37,6 +46,91 @@ sol! {
         uint256 takerAmountFilled,
         uint256 fee
     );
+
+    event ConditionResolution(
+        bytes32 indexed conditionId,
+        address indexed oracle,
+        bytes32 indexed questionId,
+        uint256 outcomeSlotCount,
+        uint256[] payoutNumerators
+    );
+}
+
+/// Where decoded events go.
+pub struct Sinks {
+    /// OrderFilled trades by tracked addresses (copy-trade engine)
+    pub copytrade_tx: broadcast::Sender<LiveTrade>,
+    /// ConditionResolution → `Alert::MarketResolution`
+    pub alert_tx: broadcast::Sender<Alert>,
+    /// Shared with the webhook so each resolution is broadcast once
+    pub resolutions: RecentKeys,
+    /// Incremental `resolved_prices` inserts on resolution
+    pub db: clickhouse::Client,
+    pub status: Arc<SubscriberStatus>,
+    /// Shared Gamma cooldown for resolution enrichment
+    pub gamma: markets::GammaBackoff,
+}
+
+/// Connection state surfaced as `live_feed` in `/api/health`.
+#[derive(Default)]
+pub struct SubscriberStatus {
+    connected: AtomicBool,
+    subscription_id: Mutex<Option<String>>,
+    /// Unix seconds, 0 while disconnected
+    connected_since: AtomicI64,
+    events: AtomicU64,
+    /// Unix seconds, 0 before the first event
+    last_event_at: AtomicI64,
+    reconnects: AtomicU64,
+}
+
+impl SubscriberStatus {
+    fn set_connected(&self, sub_id: &str) {
+        *self
+            .subscription_id
+            .lock()
+            .unwrap_or_else(|p| p.into_inner()) = Some(sub_id.into());
+        self.connected_since
+            .store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+        self.connected.store(true, Ordering::Relaxed);
+    }
+
+    fn set_disconnected(&self) {
+        self.connected.store(false, Ordering::Relaxed);
+        self.connected_since.store(0, Ordering::Relaxed);
+        *self
+            .subscription_id
+            .lock()
+            .unwrap_or_else(|p| p.into_inner()) = None;
+    }
+
+    fn record_event(&self) {
+        self.events.fetch_add(1, Ordering::Relaxed);
+        self.last_event_at
+            .store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> LiveFeedStats {
+        let connected = self.connected.load(Ordering::Relaxed);
+        let nonzero = |v: i64| (v > 0).then_some(v);
+        LiveFeedStats {
+            mode: if connected {
+                "websocket"
+            } else {
+                "webhook_fallback"
+            },
+            connected,
+            subscription_id: self
+                .subscription_id
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .clone(),
+            connected_since: nonzero(self.connected_since.load(Ordering::Relaxed)),
+            events_processed: self.events.load(Ordering::Relaxed),
+            last_event_at: nonzero(self.last_event_at.load(Ordering::Relaxed)),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -69,6 +163,8 @@ struct LogEntry {
     transaction_hash: String,
     block_number: String,
     #[serde(default)]
+    log_index: String,
+    #[serde(default)]
     removed: bool,
 }
 
@@ -109,12 +205,73 @@ async fn get_block_timestamp(
     u64::from_str_radix(ts_hex.trim_start_matches("0x"), 16).ok()
 }
 
+async fn get_block_number(http: &reqwest::Client, rpc_url: &str) -> Option<u64> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "eth_blockNumber",
+        "params": [],
+        "id": 1
+    });
+    let resp = http
+        .post(rpc_url)
+        .json(&body)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .ok()?;
+    let rpc: RpcResponse<String> = resp.json().await.ok()?;
+    u64::from_str_radix(rpc.result?.trim_start_matches("0x"), 16).ok()
+}
+
+#[derive(Deserialize)]
+struct GetLogsResponse {
+    result: Option<Vec<LogEntry>>,
+    error: Option<serde_json::Value>,
+}
+
+async fn get_logs(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    filter: &serde_json::Value,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<LogEntry>, String> {
+    let mut filter = filter.clone();
+    if let Some(obj) = filter.as_object_mut() {
+        obj.insert("fromBlock".into(), format!("0x{from_block:x}").into());
+        obj.insert("toBlock".into(), format!("0x{to_block:x}").into());
+    }
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "eth_getLogs",
+        "params": [filter],
+        "id": 1
+    });
+    let resp = http
+        .post(rpc_url)
+        .json(&body)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let rpc: GetLogsResponse = resp.json().await.map_err(|e| e.to_string())?;
+    match (rpc.result, rpc.error) {
+        (Some(logs), _) => Ok(logs),
+        (None, Some(err)) => Err(err.to_string()),
+        (None, None) => Err("empty eth_getLogs response".into()),
+    }
+}
+
+fn parse_hex_u64(s: &str) -> u64 {
+    u64::from_str_radix(s.trim_start_matches("0x"), 16).unwrap_or(0)
+}
+
 // ---------------------------------------------------------------------------
 // Public entry point
 // ---------------------------------------------------------------------------
 
 pub async fn run(
-    copytrade_tx: broadcast::Sender<LiveTrade>,
+    sinks: Sinks,
     mut trader_watch_rx: watch::Receiver<HashSet<String>>,
     market_cache: markets::MarketCache,
     http: reqwest::Client,
@@ -123,14 +280,24 @@ pub async fn run(
     let ws_url = std::env::var("POLYGON_WS_URL").unwrap_or_else(|_| {
         "".into()
     });
+    let mut gap = GapRecovery {
+        last_block: None,
+        unrecovered: Vec::new(),
+        max_gap_blocks: std::env::var("WS_GAP_RECOVERY_MAX_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(GAP_RECOVERY_MAX_BLOCKS_DEFAULT),
+    };
 
     // Wait for market cache to warm before subscribing
     tokio::time::sleep(Duration::from_secs(10)).await;
 
     loop {
-        // Wait for non-empty address set
+        // Without a WS endpoint, only connect once copy-trade sessions need it.
+        // Otherwise ConditionResolution is always subscribed; OrderFilled only
+        // while addresses are tracked.
         let addrs = trader_watch_rx.borrow_and_update().clone();
-        if addrs.is_empty() {
+        if addrs.is_empty() && ws_url.is_empty() {
             tracing::info!("WS subscriber: no tracked addresses, waiting for sessions...");
             if trader_watch_rx.changed().await.is_err() {
                 tracing::info!("WS subscriber: watch channel closed, shutting down");
@@ -154,12 +321,13 @@ pub async fn run(
 
         subscribe_and_process(
             &addrs,
-            &copytrade_tx,
+            &sinks,
             &mut trader_watch_rx,
             &market_cache,
             &http,
             &rpc_url,
             &ws_url,
+            &mut gap,
         )
         .await;
     }
@@ -169,22 +337,26 @@ pub async fn run(
 // Subscribe and process loop
 // ---------------------------------------------------------------------------
 
+#[allow(clippy::too_many_arguments)]
 async fn subscribe_and_process(
     addrs: &HashSet<String>,
-    copytrade_tx: &broadcast::Sender<LiveTrade>,
+    sinks: &Sinks,
     trader_watch_rx: &mut watch::Receiver<HashSet<String>>,
     market_cache: &markets::MarketCache,
     http: &reqwest::Client,
     rpc_url: &str,
     ws_url: &str,
+    gap: &mut GapRecovery,
 ) {
     let mut backoff = RECONNECT_BASE_DELAY;
+    let filters = log_filters(addrs);
+    let mut attempts: u64 = 0;
 
     loop {
         // Check if address set changed while reconnecting
         if trader_watch_rx.has_changed().unwrap_or(false) {
             let new_addrs = trader_watch_rx.borrow_and_update().clone();
-            if new_addrs.is_empty() || new_addrs != *addrs {
+            if new_addrs != *addrs {
                 tracing::info!(
                     "WS subscriber: addresses changed during reconnect, returning to resubscribe"
                 );
@@ -192,6 +364,11 @@ async fn subscribe_and_process(
             }
         }
 
+        if attempts > 0 {
+            sinks.status.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+        attempts += 1;
+
         tracing::info!(
             "WS subscriber: connecting to {}",
             &ws_url[..ws_url.len().min(60)]
@@ -202,74 +379,91 @@ async fn subscribe_and_process(
                 backoff = RECONNECT_BASE_DELAY;
                 let (mut write, mut read) = ws_stream.split();
 
-                // Build topic filter with maker addresses (topic[2])
-                let topic0 = format!("0x{}", hex::encode(OrderFilled::SIGNATURE_HASH));
-                let maker_topics = build_maker_topic_filter(addrs);
-
-                let subscribe_msg = serde_json::json!({
-                    "jsonrpc": "2.0",
-                    "id": 1,
-                    "method": "eth_subscribe",
-                    "params": ["logs", {
-                        "address": [CTF_EXCHANGE, NEGRISK_EXCHANGE],
-                        "topics": [topic0, serde_json::Value::Null, maker_topics]
-                    }]
-                });
+                let subscribe_msgs: Vec<serde_json::Value> = filters
+                    .iter()
+                    .enumerate()
+                    .map(|(i, filter)| {
+                        serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": 1 + i,
+                            "method": "eth_subscribe",
+                            "params": ["logs", filter]
+                        })
+                    })
+                    .collect();
 
                 tracing::debug!(
                     "WS subscriber: sending eth_subscribe with {} maker filter(s)",
                     addrs.len()
                 );
 
-                if let Err(e) = write.send(Message::Text(subscribe_msg.to_string())).await {
-                    tracing::warn!("WS subscriber: failed to send subscribe: {e}");
+                let mut send_failed = false;
+                for msg in &subscribe_msgs {
+                    if let Err(e) = write.send(Message::Text(msg.to_string())).await {
+                        tracing::warn!("WS subscriber: failed to send subscribe: {e}");
+                        send_failed = true;
+                        break;
+                    }
+                }
+                if send_failed {
                     tokio::time::sleep(backoff).await;
                     backoff = (backoff * 2).min(RECONNECT_MAX_DELAY);
                     continue;
                 }
 
-                // Wait for subscription confirmation
-                let sub_id = match read.next().await {
-                    Some(Ok(Message::Text(text))) => {
-                        match serde_json::from_str::<SubscriptionResponse>(&text) {
-                            Ok(resp) if resp.result.is_some() => {
-                                let id = resp.result.unwrap();
-                                tracing::info!(
-                                    "WS subscriber: active (sub_id={id}, tracking {} address(es))",
-                                    addrs.len()
-                                );
-                                id
-                            }
-                            Ok(resp) => {
-                                tracing::warn!(
-                                    "WS subscriber: subscription rejected: {:?}",
-                                    resp.error
-                                );
-                                tokio::time::sleep(backoff).await;
-                                backoff = (backoff * 2).min(RECONNECT_MAX_DELAY);
-                                continue;
-                            }
-                            Err(e) => {
-                                tracing::warn!("WS subscriber: unexpected response: {e} — {text}");
-                                tokio::time::sleep(backoff).await;
-                                backoff = (backoff * 2).min(RECONNECT_MAX_DELAY);
-                                continue;
+                // Wait for subscription confirmations
+                let mut sub_ids: Vec<String> = Vec::with_capacity(subscribe_msgs.len());
+                while sub_ids.len() < subscribe_msgs.len() {
+                    match read.next().await {
+                        Some(Ok(Message::Text(text))) => {
+                            match serde_json::from_str::<SubscriptionResponse>(&text) {
+                                Ok(SubscriptionResponse { result: Some(id), .. }) => {
+                                    sub_ids.push(id);
+                                }
+                                // Log notification for an already-confirmed subscription
+                                Ok(SubscriptionResponse { error: None, .. }) => {}
+                                Ok(resp) => {
+                                    tracing::warn!(
+                                        "WS subscriber: subscription rejected: {:?}",
+                                        resp.error
+                                    );
+                                    break;
+                                }
+                                Err(e) => {
+                                    tracing::warn!("WS subscriber: unexpected response: {e} — {text}");
+                                    break;
+                                }
                             }
                         }
+                        other => {
+                            tracing::warn!("WS subscriber: no subscription response: {other:?}");
+                            break;
+                        }
                     }
-                    other => {
-                        tracing::warn!("WS subscriber: no subscription response: {other:?}");
-                        tokio::time::sleep(backoff).await;
-                        backoff = (backoff * 2).min(RECONNECT_MAX_DELAY);
-                        continue;
-                    }
-                };
+                }
+                if sub_ids.len() < subscribe_msgs.len() {
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(RECONNECT_MAX_DELAY);
+                    continue;
+                }
+                let sub_id = sub_ids.join(",");
+                tracing::info!(
+                    "WS subscriber: active (sub_id={sub_id}, tracking {} address(es))",
+                    addrs.len()
+                );
+                sinks.status.set_connected(&sub_id);
+
+                // Replay what was missed while disconnected. Live logs up to the
+                // recovered block were already covered and are skipped below.
+                let recovered_to =
+                    recover_gap(&filters, gap, sinks, market_cache, http, rpc_url).await;
 
                 // Inner message loop
                 let connected_at = Instant::now();
                 let mut event_count: u64 = 0;
                 let mut last_health_log = Instant::now();
                 let mut cached_block: Option<(u64, u64)> = None;
+                let mut checkpoint = tokio::time::interval(CHECKPOINT_INTERVAL);
 
                 loop {
                     tokio::select! {
@@ -278,13 +472,13 @@ async fn subscribe_and_process(
                                 Some(Ok(Message::Text(text))) => {
                                     // Health log
                                     if last_health_log.elapsed() >= HEALTH_LOG_INTERVAL {
-                                        let receivers = copytrade_tx.receiver_count();
+                                        let receivers = sinks.copytrade_tx.receiver_count();
                                         tracing::info!(
                                             "WS subscriber health: {event_count} events, uptime={}s, sub={sub_id}, addrs={}, receivers={receivers}",
                                             connected_at.elapsed().as_secs(),
                                             addrs.len(),
                                         );
-                                        if receivers == 0 {
+                                        if receivers == 0 && !addrs.is_empty() {
                                             tracing::warn!("WS subscriber: copytrade_tx has zero receivers while addresses are tracked");
                                         }
                                         last_health_log = Instant::now();
@@ -306,7 +500,26 @@ async fn subscribe_and_process(
                                         continue;
                                     }
 
+                                    let block = parse_hex_u64(&log_entry.block_number);
+                                    if recovered_to.is_some_and(|r| block <= r) {
+                                        continue;
+                                    }
+                                    gap.record_live(block);
+
                                     event_count += 1;
+                                    sinks.status.record_event();
+
+                                    if is_condition_resolution(&log_entry) {
+                                        handle_condition_resolution(
+                                            &log_entry,
+                                            sinks,
+                                            market_cache,
+                                            http,
+                                            rpc_url,
+                                            &mut cached_block,
+                                        ).await;
+                                        continue;
+                                    }
 
                                     if let Some((trade, _usdc_raw)) = decode_order_filled(
                                         &log_entry,
@@ -315,7 +528,7 @@ async fn subscribe_and_process(
                                         rpc_url,
                                         &mut cached_block,
                                     ).await {
-                                        let _ = copytrade_tx.send(trade);
+                                        let _ = sinks.copytrade_tx.send(trade);
                                     }
                                 }
                                 Some(Ok(Message::Ping(data))) => {
@@ -338,7 +551,14 @@ async fn subscribe_and_process(
                                 _ => {}
                             }
                         }
+                        _ = checkpoint.tick() => {
+                            // Sparse logs would otherwise leave last_block far behind
+                            if let Some(head) = get_block_number(http, rpc_url).await {
+                                gap.advance(head.saturating_sub(CHECKPOINT_LAG_BLOCKS));
+                            }
+                        }
                         result = trader_watch_rx.changed() => {
+                            sinks.status.set_disconnected();
                             if result.is_err() {
                                 tracing::info!("WS subscriber: watch channel closed");
                                 return;
@@ -351,19 +571,22 @@ async fn subscribe_and_process(
                                 new_addrs.len()
                             );
                             // Send eth_unsubscribe (best-effort)
-                            let unsub_msg = serde_json::json!({
-                                "jsonrpc": "2.0",
-                                "id": 2,
-                                "method": "eth_unsubscribe",
-                                "params": [sub_id]
-                            });
-                            let _ = write.send(Message::Text(unsub_msg.to_string())).await;
+                            for (i, id) in sub_ids.iter().enumerate() {
+                                let unsub_msg = serde_json::json!({
+                                    "jsonrpc": "2.0",
+                                    "id": 3 + i,
+                                    "method": "eth_unsubscribe",
+                                    "params": [id]
+                                });
+                                let _ = write.send(Message::Text(unsub_msg.to_string())).await;
+                            }
                             return;
                         }
                     }
                 }
 
                 // WS disconnected — outer loop will reconnect
+                sinks.status.set_disconnected();
             }
             Err(e) => {
                 tracing::warn!("WS subscriber: connection failed: {e}");
@@ -376,6 +599,294 @@ async fn subscribe_and_process(
     }
 }
 
+// ---------------------------------------------------------------------------
+// Gap recovery after reconnect
+// ---------------------------------------------------------------------------
+
+/// Highest block whose logs have been processed, carried across reconnects.
+struct GapRecovery {
+    last_block: Option<u64>,
+    /// Block ranges a failed `eth_getLogs` left unreplayed, retried on the next
+    /// reconnect. Trimmed to end before the first block seen live.
+    unrecovered: Vec<(u64, u64)>,
+    max_gap_blocks: u64,
+}
+
+impl GapRecovery {
+    fn advance(&mut self, block: u64) {
+        self.last_block = Some(self.last_block.map_or(block, |b| b.max(block)));
+    }
+
+    /// A live log for `block` arrived: every later block is covered by the
+    /// subscription, so an unreplayed range only needs the blocks before it.
+    fn record_live(&mut self, block: u64) {
+        for range in &mut self.unrecovered {
+            if range.1 >= block {
+                range.1 = block.saturating_sub(1);
+            }
+        }
+        self.unrecovered.retain(|(from, to)| from <= to);
+        self.advance(block);
+    }
+}
+
+/// Replay logs between the last processed block and the current head through
+/// the live decode path, after retrying ranges a previous recovery failed on.
+/// Returns the highest block of the new gap replayed, if any; a failed range
+/// is kept in `gap.unrecovered` from its first unreplayed block.
+async fn recover_gap(
+    filters: &[serde_json::Value],
+    gap: &mut GapRecovery,
+    sinks: &Sinks,
+    market_cache: &markets::MarketCache,
+    http: &reqwest::Client,
+    rpc_url: &str,
+) -> Option<u64> {
+    let Some(head) = get_block_number(http, rpc_url).await else {
+        tracing::warn!("WS subscriber: eth_blockNumber failed, skipping gap recovery");
+        return None;
+    };
+    let Some(last) = gap.last_block else {
+        // First connection — nothing was missed
+        gap.advance(head);
+        return None;
+    };
+
+    for (from, to) in std::mem::take(&mut gap.unrecovered) {
+        tracing::info!("WS subscriber: retrying unrecovered blocks {from}..={to}");
+        if let Err(failed_from) =
+            replay_blocks(filters, from, to, sinks, market_cache, http, rpc_url).await
+        {
+            gap.unrecovered.push((failed_from, to));
+        }
+    }
+    if head <= last {
+        return None;
+    }
+
+    let missed = head - last;
+    if missed > gap.max_gap_blocks {
+        tracing::warn!(
+            "WS subscriber: gap of {missed} blocks exceeds max {}, skipping recovery",
+            gap.max_gap_blocks
+        );
+        gap.advance(head);
+        return None;
+    }
+
+    tracing::info!(
+        "WS subscriber: recovering {missed} block(s) ({}..={head})",
+        last + 1
+    );
+    gap.advance(head);
+    match replay_blocks(filters, last + 1, head, sinks, market_cache, http, rpc_url).await {
+        Ok(()) => Some(head),
+        Err(failed_from) => {
+            // Retried on the next reconnect; live logs past here still apply
+            gap.unrecovered.push((failed_from, head));
+            Some(failed_from - 1)
+        }
+    }
+}
+
+/// Replays `from..=to` in chunks of `GAP_RECOVERY_CHUNK_BLOCKS`. `Err` is the
+/// first block not replayed after an `eth_getLogs` failure.
+async fn replay_blocks(
+    filters: &[serde_json::Value],
+    from: u64,
+    to: u64,
+    sinks: &Sinks,
+    market_cache: &markets::MarketCache,
+    http: &reqwest::Client,
+    rpc_url: &str,
+) -> Result<(), u64> {
+    let mut cached_block: Option<(u64, u64)> = None;
+    let mut recovered: u64 = 0;
+    let mut start = from;
+    while start <= to {
+        let end = (start + GAP_RECOVERY_CHUNK_BLOCKS - 1).min(to);
+        let mut logs = Vec::new();
+        for filter in filters {
+            match get_logs(http, rpc_url, filter, start, end).await {
+                Ok(mut chunk) => logs.append(&mut chunk),
+                Err(e) => {
+                    tracing::warn!(
+                        "WS subscriber: eth_getLogs {start}..={end} failed: {e}, \
+                         {} block(s) not recovered",
+                        to - start + 1
+                    );
+                    return Err(start);
+                }
+            }
+        }
+        logs.sort_by_key(|l| (parse_hex_u64(&l.block_number), parse_hex_u64(&l.log_index)));
+
+        for log_entry in &logs {
+            if log_entry.removed {
+                continue;
+            }
+            if is_condition_resolution(log_entry) {
+                handle_condition_resolution(
+                    log_entry,
+                    sinks,
+                    market_cache,
+                    http,
+                    rpc_url,
+                    &mut cached_block,
+                )
+                .await;
+                continue;
+            }
+            if let Some((mut trade, _usdc_raw)) =
+                decode_order_filled(log_entry, market_cache, http, rpc_url, &mut cached_block).await
+            {
+                trade.recovered = true;
+                recovered += 1;
+                let _ = sinks.copytrade_tx.send(trade);
+            }
+        }
+        start = end + 1;
+    }
+
+    tracing::info!("WS subscriber: recovered {recovered} trade(s) up to block {to}");
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Block number + timestamp (cached per block)
+// ---------------------------------------------------------------------------
+
+async fn block_number_and_timestamp(
+    log_entry: &LogEntry,
+    http: &reqwest::Client,
+    rpc_url: &str,
+    cached_block: &mut Option<(u64, u64)>,
+) -> (u64, u64) {
+    let block_number =
+        u64::from_str_radix(log_entry.block_number.trim_start_matches("0x"), 16).unwrap_or(0);
+
+    let block_timestamp = match cached_block {
+        Some((cached_num, cached_ts)) if *cached_num == block_number => *cached_ts,
+        _ => {
+            let ts = get_block_timestamp(http, rpc_url, &log_entry.block_number)
+                .await
+                .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
+            *cached_block = Some((block_number, ts));
+            ts
+        }
+    };
+    (block_number, block_timestamp)
+}
+
+// ---------------------------------------------------------------------------
+// ConditionResolution → Alert::MarketResolution
+// ---------------------------------------------------------------------------
+
+fn is_condition_resolution(log_entry: &LogEntry) -> bool {
+    log_entry
+        .topics
+        .first()
+        .and_then(|t| t.parse::<B256>().ok())
+        .is_some_and(|t| t == ConditionResolution::SIGNATURE_HASH)
+}
+
+/// Decode a resolution, enrich it like the webhook does, broadcast it once
+/// across both ingestion paths, and insert its resolved prices.
+async fn handle_condition_resolution(
+    log_entry: &LogEntry,
+    sinks: &Sinks,
+    market_cache: &markets::MarketCache,
+    http: &reqwest::Client,
+    rpc_url: &str,
+    cached_block: &mut Option<(u64, u64)>,
+) {
+    let topics: Vec<B256> = log_entry
+        .topics
+        .iter()
+        .filter_map(|t| t.parse::<B256>().ok())
+        .collect();
+    let Some(data_bytes) = hex::decode(log_entry.data.trim_start_matches("0x")).ok() else {
+        return;
+    };
+    let decoded = match ConditionResolution::decode_raw_log(topics.iter().copied(), &data_bytes) {
+        Ok(d) => d,
+        Err(e) => {
+            tracing::debug!("WS subscriber: undecodable ConditionResolution: {e}");
+            return;
+        }
+    };
+
+    let condition_id = decoded.conditionId.to_string();
+    if !sinks
+        .resolutions
+        .first_seen(condition_id.trim_start_matches("0x"))
+    {
+        tracing::debug!("WS subscriber: resolution {condition_id} already broadcast");
+        return;
+    }
+
+    let numerators: Vec<String> = decoded
+        .payoutNumerators
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+    let log_index =
+        u64::from_str_radix(log_entry.log_index.trim_start_matches("0x"), 16).unwrap_or(0);
+    let (block_number, block_timestamp) =
+        block_number_and_timestamp(log_entry, http, rpc_url, cached_block).await;
+    let tx_info = alerts::TxInfo {
+        transaction_hash: log_entry.transaction_hash.clone(),
+        block_number,
+        block_timestamp: block_timestamp.to_string(),
+        log_index,
+    };
+
+    let mut alert = {
+        let cache = market_cache.read().await;
+        alerts::resolution_alert(
+            &condition_id,
+            &format!("{:?}", decoded.oracle),
+            &decoded.questionId.to_string(),
+            numerators.clone(),
+            tx_info,
+            &cache,
+        )
+    };
+    alerts::enrich_resolution(http, &sinks.gamma, &mut alert).await;
+    tracing::info!("WS subscriber: ConditionResolution {condition_id} at block {block_number}");
+    let _ = sinks.alert_tx.send(alert);
+
+    tokio::spawn(markets::populate_resolved_prices_for_condition(
+        sinks.db.clone(),
+        market_cache.clone(),
+        condition_id,
+        numerators,
+        block_number,
+    ));
+}
+
+// ---------------------------------------------------------------------------
+// Log filters shared by eth_subscribe and eth_getLogs
+// ---------------------------------------------------------------------------
+
+/// ConditionResolution always; OrderFilled filtered by maker addresses
+/// (topic[2]) while copy-trade sessions are active.
+fn log_filters(addrs: &HashSet<String>) -> Vec<serde_json::Value> {
+    let resolution_topic0 = format!("0x{}", hex::encode(ConditionResolution::SIGNATURE_HASH));
+    let mut filters = vec![serde_json::json!({
+        "address": [CONDITIONAL_TOKENS],
+        "topics": [resolution_topic0]
+    })];
+    if !addrs.is_empty() {
+        let topic0 = format!("0x{}", hex::encode(OrderFilled::SIGNATURE_HASH));
+        filters.push(serde_json::json!({
+            "address": [CTF_EXCHANGE, NEGRISK_EXCHANGE],
+            "topics": [topic0, serde_json::Value::Null, build_maker_topic_filter(addrs)]
+        }));
+    }
+    filters
+}
+
 // ---------------------------------------------------------------------------
 // Build topic filter for maker addresses (topic[2])
 // ---------------------------------------------------------------------------
@@ -434,19 +945,11 @@ async fn decode_order_filled(
     let usdc_raw_u128: u128 = usdc_raw.try_into().ok()?;
     let token_raw_u128: u128 = token_raw.try_into().ok()?;
 
-    let block_number =
-        u64::from_str_radix(log_entry.block_number.trim_start_matches("0x"), 16).unwrap_or(0);
+    let log_index =
+        u64::from_str_radix(log_entry.log_index.trim_start_matches("0x"), 16).unwrap_or(0);
 
-    let block_timestamp = match cached_block {
-        Some((cached_num, cached_ts)) if *cached_num == block_number => *cached_ts,
-        _ => {
-            let ts = get_block_timestamp(http, rpc_url, &log_entry.block_number)
-                .await
-                .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
-            *cached_block = Some((block_number, ts));
-            ts
-        }
-    };
+    let (block_number, block_timestamp) =
+        block_number_and_timestamp(log_entry, http, rpc_url, cached_block).await;
 
     let usdc_whole = usdc_raw_u128 / 1_000_000;
     let usdc_frac = usdc_raw_u128 % 1_000_000;
@@ -465,7 +968,7 @@ async fn decode_order_filled(
     let asset_id_str = asset_id.to_string();
     let cache_key = markets::cache_key(&asset_id_str);
     let cache = market_cache.read().await;
-    let info = cache.get(&cache_key);
+    let info = cache.get(&asset_id_str);
 
     let trade = LiveTrade {
         tx_hash: log_entry.transaction_hash.clone(),
@@ -482,6 +985,8 @@ async fn decode_order_filled(
         outcome: info.map(|i| i.outcome.clone()).unwrap_or_default(),
         category: info.map(|i| i.category.clone()).unwrap_or_default(),
         block_number,
+        log_index,
+        recovered: false,
         cache_key,
     };
 
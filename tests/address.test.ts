@@ -0,0 +1,87 @@
+import { describe, test, expect, beforeAll } from "bun:test";
+import { api, waitForServer } from "./helpers";
+
+// ---------------------------------------------------------------------------
+// Types (mirrored from frontend/src/types.ts — kept minimal for tests)
+// ---------------------------------------------------------------------------
+
+interface LeaderboardResponse {
+  traders: { address: string }[];
+}
+
+interface TraderRankResponse {
+  address: string;
+  rank: number;
+}
+
+/** Mixed-case form of a lowercase address, as a checksummed wallet would send it. */
+function mixedCase(address: string): string {
+  return (
+    "0x" +
+    [...address.slice(2)]
+      .map((c, i) => (i % 2 === 0 ? c.toUpperCase() : c))
+      .join("")
+  );
+}
+
+// ---------------------------------------------------------------------------
+// Setup
+// ---------------------------------------------------------------------------
+
+let trader = "";
+
+beforeAll(async () => {
+  await waitForServer();
+
+  const lb = await api<LeaderboardResponse>("GET", "/api/leaderboard?limit=1");
+  trader = lb.data.traders[0].address.toLowerCase();
+});
+
+// ---------------------------------------------------------------------------
+// /api/trader/{address}/* — path validation
+// ---------------------------------------------------------------------------
+
+const TRADER_ROUTES = [
+  "",
+  "/trades",
+  "/positions",
+  "/pnl-chart",
+  "/volume-chart",
+  "/rank",
+  "/profile",
+  "/categories",
+  "/pnl-distribution",
+];
+
+describe("trader address path parameter", () => {
+  test("accepts checksummed input and lowercases it", async () => {
+    const res = await api<TraderRankResponse>(
+      "GET",
+      `/api/trader/${mixedCase(trader)}/rank`,
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.address).toBe(trader);
+  });
+
+  for (const suffix of TRADER_ROUTES) {
+    test(`rejects a short address on /api/trader/{address}${suffix}`, async () => {
+      const res = await api("GET", `/api/trader/${trader.slice(0, 20)}${suffix}`);
+      expect(res.status).toBe(400);
+    });
+
+    test(`rejects an address without 0x on /api/trader/{address}${suffix}`, async () => {
+      const res = await api("GET", `/api/trader/${trader.slice(2)}${suffix}`);
+      expect(res.status).toBe(400);
+    });
+  }
+
+  test("rejects non-hex characters", async () => {
+    const res = await api("GET", `/api/trader/0x${"z".repeat(40)}`);
+    expect(res.status).toBe(400);
+  });
+
+  test("rejects garbage", async () => {
+    const res = await api("GET", "/api/trader/notanaddress/positions");
+    expect(res.status).toBe(400);
+  });
+});
@@ -0,0 +1,80 @@
+import { describe, test, expect, beforeAll } from "bun:test";
+import { api, waitForServer, mintJwt } from "./helpers";
+
+// Dedicated addresses so logout-all never touches other suites' users
+const ADDR_REFRESH = `0x${"a1".repeat(20)}`;
+const ADDR_LOGOUT = `0x${"a2".repeat(20)}`;
+const ADDR_LOGOUT_ALL = `0x${"a3".repeat(20)}`;
+
+async function refresh(token: string): Promise<string> {
+  const res = await api<{ token: string; address: string }>("POST", "/api/auth/refresh", { token });
+  expect(res.status).toBe(200);
+  return res.data.token;
+}
+
+async function authorized(token: string): Promise<boolean> {
+  const res = await api("GET", "/api/lists", { token });
+  return res.status === 200;
+}
+
+beforeAll(async () => {
+  await waitForServer();
+});
+
+describe("POST /api/auth/refresh", () => {
+  test("issues a new working token for the same address", async () => {
+    const token = mintJwt(ADDR_REFRESH);
+    const res = await api<{ token: string; address: string }>("POST", "/api/auth/refresh", {
+      token,
+    });
+    expect(res.status).toBe(200);
+    expect(res.data.address).toBe(ADDR_REFRESH);
+    expect(res.data.token).not.toBe(token);
+    expect(await authorized(res.data.token)).toBe(true);
+  });
+
+  test("requires a valid token", async () => {
+    expect((await api("POST", "/api/auth/refresh")).status).toBe(401);
+    expect((await api("POST", "/api/auth/refresh", { token: "not-a-jwt" })).status).toBe(401);
+  });
+});
+
+describe("POST /api/auth/logout", () => {
+  test("revokes only the presented token", async () => {
+    const base = mintJwt(ADDR_LOGOUT);
+    const first = await refresh(base);
+    const second = await refresh(base);
+
+    const res = await api("POST", "/api/auth/logout", { token: first });
+    expect(res.status).toBe(204);
+
+    expect(await authorized(first)).toBe(false);
+    expect((await api("POST", "/api/auth/refresh", { token: first })).status).toBe(401);
+    expect(await authorized(second)).toBe(true);
+  });
+});
+
+describe("POST /api/auth/logout-all", () => {
+  test("revokes every earlier token for the address", async () => {
+    const base = mintJwt(ADDR_LOGOUT_ALL, 86400);
+    // Move past base's issue second so the cutoff covers it
+    await new Promise((r) => setTimeout(r, 1100));
+    const refreshed = await refresh(base);
+
+    const res = await api("POST", "/api/auth/logout-all", { token: refreshed });
+    expect(res.status).toBe(204);
+
+    expect(await authorized(base)).toBe(false);
+    expect(await authorized(refreshed)).toBe(false);
+  });
+
+  test("accepts a sign-in immediately after logout-all", async () => {
+    // A server-issued token carries a jti, unlike the legacy-shaped mintJwt
+    const token = await refresh(mintJwt(ADDR_LOGOUT_ALL));
+    const res = await api("POST", "/api/auth/logout-all", { token });
+    expect(res.status).toBe(204);
+    expect(await authorized(token)).toBe(false);
+
+    expect(await authorized(mintJwt(ADDR_LOGOUT_ALL))).toBe(true);
+  });
+});
@@ -0,0 +1,97 @@
+import { describe, test, expect, beforeAll } from "bun:test";
+import { api, waitForServer } from "./helpers";
+
+// ---------------------------------------------------------------------------
+// Types (mirrored from frontend/src/types.ts — kept minimal for tests)
+// ---------------------------------------------------------------------------
+
+interface TraderSummary {
+  address: string;
+  realized_pnl: string;
+  total_fees: string;
+}
+
+interface LeaderboardResponse {
+  traders: TraderSummary[];
+}
+
+const num = (s: string) => parseFloat(s);
+
+// ---------------------------------------------------------------------------
+// Setup
+// ---------------------------------------------------------------------------
+
+let feePayers: TraderSummary[] = [];
+
+beforeAll(async () => {
+  await waitForServer();
+
+  // High-volume traders are the ones most likely to have paid fees
+  const res = await api<LeaderboardResponse>(
+    "GET",
+    "/api/leaderboard?sort=total_volume&limit=500",
+  );
+  feePayers = res.data.traders.filter((t) => num(t.total_fees) > 0);
+});
+
+// ---------------------------------------------------------------------------
+// include_fees=true
+// ---------------------------------------------------------------------------
+
+describe("include_fees", () => {
+  test("has fee-paying traders to compare against", () => {
+    expect(feePayers.length).toBeGreaterThan(0);
+  });
+
+  test("trader stats PnL drops by exactly the fees paid", async () => {
+    const t = feePayers[0];
+    const gross = await api<TraderSummary>("GET", `/api/trader/${t.address}`);
+    const net = await api<TraderSummary>(
+      "GET",
+      `/api/trader/${t.address}?include_fees=true`,
+    );
+    expect(gross.status).toBe(200);
+    expect(net.status).toBe(200);
+    expect(num(net.data.realized_pnl)).toBeCloseTo(
+      num(gross.data.realized_pnl) - num(gross.data.total_fees),
+      2,
+    );
+  });
+
+  test("a break-even trader with large fees shows negative PnL", async () => {
+    // Gross PnL smaller than fees paid: roughly break-even before fees
+    const t = feePayers.find(
+      (t) => Math.abs(num(t.realized_pnl)) < num(t.total_fees),
+    );
+    expect(t).toBeDefined();
+
+    const net = await api<TraderSummary>(
+      "GET",
+      `/api/trader/${t!.address}?include_fees=true`,
+    );
+    expect(net.status).toBe(200);
+    expect(num(net.data.realized_pnl)).toBeLessThan(0);
+  });
+
+  test("leaderboard rows net out fees when requested", async () => {
+    const gross = await api<LeaderboardResponse>(
+      "GET",
+      "/api/leaderboard?sort=total_volume&limit=50",
+    );
+    const net = await api<LeaderboardResponse>(
+      "GET",
+      "/api/leaderboard?sort=total_volume&limit=50&include_fees=true",
+    );
+    expect(net.status).toBe(200);
+
+    const netByAddr = new Map(net.data.traders.map((t) => [t.address, t]));
+    for (const g of gross.data.traders) {
+      const n = netByAddr.get(g.address);
+      if (!n) continue;
+      expect(num(n.realized_pnl)).toBeCloseTo(
+        num(g.realized_pnl) - num(g.total_fees),
+        2,
+      );
+    }
+  });
+});
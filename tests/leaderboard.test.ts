@@ -0,0 +1,124 @@
+import { describe, test, expect, beforeAll } from "bun:test";
+import { api, waitForServer } from "./helpers";
+
+// ---------------------------------------------------------------------------
+// Types (mirrored from frontend/src/types.ts — kept minimal for tests)
+// ---------------------------------------------------------------------------
+
+interface TraderSummary {
+  address: string;
+  trade_count: number;
+  realized_pnl: string;
+}
+
+interface LeaderboardResponse {
+  traders: TraderSummary[];
+  total: number;
+  scanned: number;
+  next_cursor: string | null;
+}
+
+// ---------------------------------------------------------------------------
+// Setup
+// ---------------------------------------------------------------------------
+
+beforeAll(async () => {
+  await waitForServer();
+});
+
+// ---------------------------------------------------------------------------
+// GET /api/leaderboard — cursor pagination
+// ---------------------------------------------------------------------------
+
+describe("GET /api/leaderboard cursor pagination", () => {
+  // trade_count has many ties (lots of traders with identical counts), so
+  // page boundaries regularly fall inside a run of equal sort values.
+  test("walks pages across equal-value boundaries without gaps or duplicates", async () => {
+    const pageSize = 7;
+    const pages = 6;
+
+    const full = await api<LeaderboardResponse>(
+      "GET",
+      `/api/leaderboard?sort=trade_count&order=asc&limit=${pageSize * pages}`,
+    );
+    expect(full.status).toBe(200);
+
+    const seen: string[] = [];
+    let cursor: string | null = null;
+    for (let i = 0; i < pages; i++) {
+      const qs = cursor ? `&cursor=${encodeURIComponent(cursor)}` : "";
+      const res = await api<LeaderboardResponse>(
+        "GET",
+        `/api/leaderboard?sort=trade_count&order=asc&limit=${pageSize}${qs}`,
+      );
+      expect(res.status).toBe(200);
+      seen.push(...res.data.traders.map((t) => t.address));
+      cursor = res.data.next_cursor;
+      if (!cursor) break;
+    }
+
+    expect(new Set(seen).size).toBe(seen.length);
+    expect(seen).toEqual(full.data.traders.map((t) => t.address));
+
+    // The walk must actually have crossed a boundary inside a tie run
+    const counts = full.data.traders.map((t) => t.trade_count);
+    const tieAtBoundary = counts.some(
+      (c, i) => i > 0 && i % pageSize === 0 && c === counts[i - 1],
+    );
+    expect(tieAtBoundary).toBe(true);
+  });
+
+  test("returns no next_cursor on the last page", async () => {
+    const res = await api<LeaderboardResponse>(
+      "GET",
+      "/api/leaderboard?timeframe=1h&limit=500",
+    );
+    expect(res.status).toBe(200);
+    if (res.data.traders.length < 500) {
+      expect(res.data.next_cursor).toBeNull();
+    }
+  });
+
+  test("rejects cursor combined with offset", async () => {
+    const first = await api<LeaderboardResponse>(
+      "GET",
+      "/api/leaderboard?limit=5",
+    );
+    const cursor = first.data.next_cursor!;
+    const res = await api(
+      "GET",
+      `/api/leaderboard?limit=5&offset=5&cursor=${encodeURIComponent(cursor)}`,
+    );
+    expect(res.status).toBe(400);
+  });
+
+  test("rejects a malformed cursor", async () => {
+    const res = await api("GET", "/api/leaderboard?cursor=not-a-cursor");
+    expect(res.status).toBe(400);
+  });
+});
+
+// ---------------------------------------------------------------------------
+// GET /api/trader/:address/rank — agrees with the leaderboard
+// ---------------------------------------------------------------------------
+
+describe("GET /api/trader/:address/rank", () => {
+  test("ranks the leaderboard leader first under include_fees", async () => {
+    const board = await api<LeaderboardResponse>(
+      "GET",
+      "/api/leaderboard?sort=realized_pnl&timeframe=7d&include_fees=true&limit=1",
+    );
+    expect(board.status).toBe(200);
+    const leader = board.data.traders[0];
+    if (!leader) return;
+
+    const res = await api<{ rank: number; total: number; value: number }>(
+      "GET",
+      `/api/trader/${leader.address}/rank?sort=realized_pnl&timeframe=7d&include_fees=true`,
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.rank).toBe(1);
+    expect(res.data.total).toBe(board.data.total);
+    expect(res.data.value).toBeCloseTo(parseFloat(leader.realized_pnl), 4);
+  });
+});
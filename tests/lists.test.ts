@@ -0,0 +1,504 @@
+import { describe, test, expect, beforeAll } from "bun:test";
+import { api, waitForServer, testUser } from "./helpers";
+
+// ---------------------------------------------------------------------------
+// Types (mirrored from frontend/src/types.ts — kept minimal for tests)
+// ---------------------------------------------------------------------------
+
+interface TraderList {
+  id: string;
+  name: string;
+  member_count: number;
+}
+
+interface TraderListDetail {
+  id: string;
+  name: string;
+  members: { address: string; label: string | null }[];
+}
+
+const MEMBER = "0x1111111111111111111111111111111111111111";
+
+/** Helper: delete all lists for a user (test addresses repeat across runs) */
+async function cleanupLists(token: string) {
+  const list = await api<TraderList[]>("GET", "/api/lists", { token });
+  if (list.ok && Array.isArray(list.data)) {
+    for (const l of list.data) {
+      await api("DELETE", `/api/lists/${l.id}`, { token });
+    }
+  }
+}
+
+async function createList(token: string, name: string): Promise<TraderList> {
+  const res = await api<TraderList>("POST", "/api/lists", {
+    token,
+    body: { name },
+  });
+  expect(res.status).toBe(201);
+  return res.data;
+}
+
+// ---------------------------------------------------------------------------
+// Setup
+// ---------------------------------------------------------------------------
+
+const alice = testUser();
+const bob = testUser();
+
+beforeAll(async () => {
+  await waitForServer();
+  await cleanupLists(alice.token);
+  await cleanupLists(bob.token);
+});
+
+// ---------------------------------------------------------------------------
+// CRUD
+// ---------------------------------------------------------------------------
+
+describe("/api/lists", () => {
+  test("returns 401 without auth", async () => {
+    const res = await api("GET", "/api/lists");
+    expect(res.status).toBe(401);
+  });
+
+  test("create, rename, add and remove members, delete", async () => {
+    const list = await createList(alice.token, "Whales");
+    expect(list.name).toBe("Whales");
+    expect(list.member_count).toBe(0);
+
+    const renamed = await api("PATCH", `/api/lists/${list.id}`, {
+      token: alice.token,
+      body: { name: "Big whales" },
+    });
+    expect(renamed.status).toBe(204);
+
+    const added = await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER], labels: ["one"] },
+    });
+    expect(added.status).toBe(204);
+
+    const detail = await api<TraderListDetail>("GET", `/api/lists/${list.id}`, {
+      token: alice.token,
+    });
+    expect(detail.status).toBe(200);
+    expect(detail.data.name).toBe("Big whales");
+    expect(detail.data.members.map((m) => m.address)).toEqual([MEMBER]);
+
+    const removed = await api("DELETE", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER] },
+    });
+    expect(removed.status).toBe(204);
+
+    const deleted = await api("DELETE", `/api/lists/${list.id}`, {
+      token: alice.token,
+    });
+    expect(deleted.status).toBe(204);
+    const gone = await api("GET", `/api/lists/${list.id}`, { token: alice.token });
+    expect(gone.status).toBe(404);
+  });
+
+  test("duplicate names return 409", async () => {
+    await createList(alice.token, "Dupes");
+    const res = await api("POST", "/api/lists", {
+      token: alice.token,
+      body: { name: "Dupes" },
+    });
+    expect(res.status).toBe(409);
+  });
+
+  test("too many members return 422", async () => {
+    const list = await createList(alice.token, "Crowded");
+    const addresses = Array.from(
+      { length: 101 },
+      (_, i) => `0x${(i + 1).toString(16).padStart(40, "0")}`,
+    );
+    const res = await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses },
+    });
+    expect(res.status).toBe(422);
+  });
+});
+
+// ---------------------------------------------------------------------------
+// POST /api/lists/{id}/import
+// ---------------------------------------------------------------------------
+
+describe("PATCH /api/lists/{id}/members/{address}", () => {
+  test("updates the label in place and keeps added_at", async () => {
+    const list = await createList(alice.token, "Relabel");
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER], labels: ["Old"] },
+    });
+    type Detail = { members: { address: string; label: string | null; added_at: string }[] };
+    const before = await api<Detail>("GET", `/api/lists/${list.id}`, { token: alice.token });
+
+    const res = await api("PATCH", `/api/lists/${list.id}/members/${MEMBER}`, {
+      token: alice.token,
+      body: { label: "New" },
+    });
+    expect(res.status).toBe(204);
+
+    const after = await api<Detail>("GET", `/api/lists/${list.id}`, { token: alice.token });
+    expect(after.data.members[0].label).toBe("New");
+    expect(after.data.members[0].added_at).toBe(before.data.members[0].added_at);
+  });
+
+  test("validates the address and label length", async () => {
+    const list = await createList(alice.token, "Relabel invalid");
+    const badAddr = await api("PATCH", `/api/lists/${list.id}/members/0xnope`, {
+      token: alice.token,
+      body: { label: "x" },
+    });
+    expect(badAddr.status).toBe(400);
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER] },
+    });
+    const long = await api("PATCH", `/api/lists/${list.id}/members/${MEMBER}`, {
+      token: alice.token,
+      body: { label: "x".repeat(65) },
+    });
+    expect(long.status).toBe(400);
+  });
+
+  test("returns 404 for a non-member or another user's list", async () => {
+    const list = await createList(alice.token, "Relabel 404");
+    const missing = await api("PATCH", `/api/lists/${list.id}/members/${MEMBER}`, {
+      token: alice.token,
+      body: { label: "x" },
+    });
+    expect(missing.status).toBe(404);
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER] },
+    });
+    const other = await api("PATCH", `/api/lists/${list.id}/members/${MEMBER}`, {
+      token: bob.token,
+      body: { label: "x" },
+    });
+    expect(other.status).toBe(404);
+  });
+});
+
+describe("POST /api/lists/{id}/duplicate", () => {
+  test("copies members and labels under a default name", async () => {
+    const list = await createList(alice.token, "Original");
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER], labels: ["Kept"] },
+    });
+    const res = await api<TraderListDetail>("POST", `/api/lists/${list.id}/duplicate`, {
+      token: alice.token,
+    });
+    expect(res.status).toBe(201);
+    expect(res.data.id).not.toBe(list.id);
+    expect(res.data.name).toBe("Copy of Original");
+    expect(res.data.members).toHaveLength(1);
+    expect(res.data.members[0].label).toBe("Kept");
+
+    // Duplicating again with the same default name conflicts
+    const again = await api("POST", `/api/lists/${list.id}/duplicate`, { token: alice.token });
+    expect(again.status).toBe(409);
+  });
+
+  test("accepts a custom name and rejects other users", async () => {
+    const list = await createList(alice.token, "Source");
+    const named = await api<TraderListDetail>("POST", `/api/lists/${list.id}/duplicate`, {
+      token: alice.token,
+      body: { name: "Fork" },
+    });
+    expect(named.status).toBe(201);
+    expect(named.data.name).toBe("Fork");
+
+    const other = await api("POST", `/api/lists/${list.id}/duplicate`, { token: bob.token });
+    expect(other.status).toBe(404);
+  });
+});
+
+interface ListImportResponse {
+  added: number;
+  duplicates: number;
+  invalid: number;
+  overflow: number;
+  rows: { row: number; address: string; status: string }[];
+}
+
+describe("POST /api/lists/{id}/import", () => {
+  test("reports added, duplicate and invalid rows", async () => {
+    const list = await createList(alice.token, "Imported");
+    const res = await api<ListImportResponse>(
+      "POST",
+      `/api/lists/${list.id}/import`,
+      { token: alice.token, body: [MEMBER, MEMBER.toUpperCase().replace("0X", "0x"), "nope"] },
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.rows.map((r) => r.status)).toEqual([
+      "added",
+      "duplicate",
+      "invalid",
+    ]);
+  });
+
+  test("imports up to the member cap and reports the overflow", async () => {
+    const list = await createList(alice.token, "Import cap");
+    const addresses = Array.from(
+      { length: 105 },
+      (_, i) => `0x${(i + 1).toString(16).padStart(40, "0")}`,
+    );
+    const res = await api<ListImportResponse>(
+      "POST",
+      `/api/lists/${list.id}/import`,
+      { token: alice.token, body: addresses },
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.added).toBe(100);
+    expect(res.data.overflow).toBe(5);
+  });
+
+  test("returns 404 for another user's list", async () => {
+    const list = await createList(alice.token, "Not yours");
+    const res = await api("POST", `/api/lists/${list.id}/import`, {
+      token: bob.token,
+      body: [MEMBER],
+    });
+    expect(res.status).toBe(404);
+  });
+});
+
+// ---------------------------------------------------------------------------
+// POST /api/lists/from-leaderboard
+// ---------------------------------------------------------------------------
+
+describe("POST /api/lists/from-leaderboard", () => {
+  test("creates a list of the top traders labelled by rank", async () => {
+    const res = await api<TraderListDetail>("POST", "/api/lists/from-leaderboard", {
+      token: bob.token,
+      body: { name: "Top 5", top_n: 5, timeframe: "all", sort: "realized_pnl" },
+    });
+    expect(res.status).toBe(201);
+    expect(res.data.name).toBe("Top 5");
+    expect(res.data.members.length).toBeGreaterThan(0);
+    expect(res.data.members.length).toBeLessThanOrEqual(5);
+    expect(res.data.members.map((m) => m.label)).toContain("#1");
+  });
+
+  test("rejects an unknown sort column", async () => {
+    const res = await api("POST", "/api/lists/from-leaderboard", {
+      token: bob.token,
+      body: { name: "Bad sort", sort: "nope" },
+    });
+    expect(res.status).toBe(400);
+  });
+});
+
+// ---------------------------------------------------------------------------
+// GET /api/lists/{id}/stats
+// ---------------------------------------------------------------------------
+
+describe("GET /api/lists/{id}/stats", () => {
+  test("returns zeros for an empty list", async () => {
+    const list = await createList(alice.token, "Empty stats");
+    const res = await api<{ member_count: number; members: unknown[]; exposure: unknown[] }>(
+      "GET",
+      `/api/lists/${list.id}/stats`,
+      { token: alice.token },
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.member_count).toBe(0);
+    expect(res.data.members).toEqual([]);
+    expect(res.data.exposure).toEqual([]);
+  });
+
+  test("returns 404 for another user's list", async () => {
+    const list = await createList(alice.token, "Stats private");
+    const res = await api("GET", `/api/lists/${list.id}/stats`, { token: bob.token });
+    expect(res.status).toBe(404);
+  });
+});
+
+describe("GET /api/lists/{id}/export", () => {
+  test("JSON export keeps inactive members with empty stats", async () => {
+    const list = await createList(alice.token, "Export");
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER], labels: ["Quiet"] },
+    });
+    const res = await api<{
+      name: string;
+      members: { address: string; total_volume: string | null; trade_count: number | null }[];
+    }>("GET", `/api/lists/${list.id}/export`, { token: alice.token });
+    expect(res.status).toBe(200);
+    expect(res.data.name).toBe("Export");
+    expect(res.data.members).toHaveLength(1);
+    expect(res.data.members[0].total_volume).toBeNull();
+    expect(res.data.members[0].trade_count).toBeNull();
+  });
+
+  test("CSV export has a header and one row per member", async () => {
+    const list = await createList(alice.token, "Export CSV");
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER] },
+    });
+    const res = await api("GET", `/api/lists/${list.id}/export?format=csv`, {
+      token: alice.token,
+    });
+    expect(res.status).toBe(200);
+    const lines = res.text.trim().split("\n");
+    expect(lines[0]).toBe("address,label,added_at,total_volume,realized_pnl,trade_count,last_trade");
+    expect(lines).toHaveLength(2);
+    expect(lines[1].startsWith(MEMBER)).toBe(true);
+  });
+
+  test("rejects unknown formats and other users", async () => {
+    const list = await createList(alice.token, "Export private");
+    const bad = await api("GET", `/api/lists/${list.id}/export?format=xml`, { token: alice.token });
+    expect(bad.status).toBe(400);
+    const other = await api("GET", `/api/lists/${list.id}/export`, { token: bob.token });
+    expect(other.status).toBe(404);
+  });
+});
+
+describe("GET /api/smart-money?list_id=", () => {
+  test("scopes the smart set to the list and names it", async () => {
+    const list = await createList(alice.token, "Smart set");
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER] },
+    });
+    const res = await api<{ top: number; list_name: string; markets: unknown[] }>(
+      "GET",
+      `/api/smart-money?list_id=${list.id}`,
+      { token: alice.token },
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.top).toBe(1);
+    expect(res.data.list_name).toBe("Smart set");
+    expect(Array.isArray(res.data.markets)).toBe(true);
+  });
+
+  test("rejects list_id together with top", async () => {
+    const list = await createList(alice.token, "Smart exclusive");
+    const res = await api("GET", `/api/smart-money?list_id=${list.id}&top=10`, {
+      token: alice.token,
+    });
+    expect(res.status).toBe(400);
+  });
+
+  test("returns 400 for an empty list and 404 for another user's list", async () => {
+    const list = await createList(alice.token, "Smart empty");
+    const empty = await api("GET", `/api/smart-money?list_id=${list.id}`, { token: alice.token });
+    expect(empty.status).toBe(400);
+    const other = await api("GET", `/api/smart-money?list_id=${list.id}`, { token: bob.token });
+    expect(other.status).toBe(404);
+  });
+});
+
+// ---------------------------------------------------------------------------
+// Sharing
+// ---------------------------------------------------------------------------
+
+describe("/api/lists/{id}/share", () => {
+  test("shared link works without auth until revoked", async () => {
+    const list = await createList(alice.token, "Shared");
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER] },
+    });
+
+    const share = await api<{ share_token: string }>("POST", `/api/lists/${list.id}/share`, {
+      token: alice.token,
+    });
+    expect(share.status).toBe(200);
+    expect(share.data.share_token).toMatch(/^[0-9a-f]{64}$/);
+
+    const pub = await api<TraderListDetail>("GET", `/api/shared/lists/${share.data.share_token}`);
+    expect(pub.status).toBe(200);
+    expect(pub.data.name).toBe("Shared");
+    expect(pub.data.members).toHaveLength(1);
+
+    const withStats = await api<{ stats: { member_count: number } }>(
+      "GET",
+      `/api/shared/lists/${share.data.share_token}?stats=true`,
+    );
+    expect(withStats.status).toBe(200);
+    expect(withStats.data.stats.member_count).toBe(1);
+
+    const revoke = await api("DELETE", `/api/lists/${list.id}/share`, { token: alice.token });
+    expect(revoke.status).toBe(204);
+    const gone = await api("GET", `/api/shared/lists/${share.data.share_token}`);
+    expect(gone.status).toBe(404);
+  });
+
+  test("rotating the token invalidates the old link", async () => {
+    const list = await createList(alice.token, "Rotated");
+    const first = await api<{ share_token: string }>("POST", `/api/lists/${list.id}/share`, {
+      token: alice.token,
+    });
+    const second = await api<{ share_token: string }>("POST", `/api/lists/${list.id}/share`, {
+      token: alice.token,
+    });
+    expect(second.data.share_token).not.toBe(first.data.share_token);
+    expect((await api("GET", `/api/shared/lists/${first.data.share_token}`)).status).toBe(404);
+    expect((await api("GET", `/api/shared/lists/${second.data.share_token}`)).status).toBe(200);
+  });
+
+  test("another user cannot share a list", async () => {
+    const list = await createList(alice.token, "Not yours");
+    const res = await api("POST", `/api/lists/${list.id}/share`, { token: bob.token });
+    expect(res.status).toBe(404);
+  });
+
+  test("unknown token returns 404", async () => {
+    const res = await api("GET", `/api/shared/lists/${"0".repeat(64)}`);
+    expect(res.status).toBe(404);
+  });
+});
+
+// ---------------------------------------------------------------------------
+// Ownership isolation
+// ---------------------------------------------------------------------------
+
+describe("/api/lists ownership", () => {
+  test("another user cannot see or change a list", async () => {
+    const list = await createList(alice.token, "Private");
+    await api("POST", `/api/lists/${list.id}/members`, {
+      token: alice.token,
+      body: { addresses: [MEMBER] },
+    });
+
+    const bobLists = await api<TraderList[]>("GET", "/api/lists", {
+      token: bob.token,
+    });
+    expect(bobLists.status).toBe(200);
+    expect(bobLists.data.some((l) => l.id === list.id)).toBe(false);
+
+    const paths: [("GET" | "PATCH" | "DELETE" | "POST"), string, unknown][] = [
+      ["GET", `/api/lists/${list.id}`, undefined],
+      ["PATCH", `/api/lists/${list.id}`, { name: "Stolen" }],
+      ["POST", `/api/lists/${list.id}/members`, { addresses: [MEMBER] }],
+      ["DELETE", `/api/lists/${list.id}/members`, { addresses: [MEMBER] }],
+      ["DELETE", `/api/lists/${list.id}`, undefined],
+    ];
+    for (const [method, path, body] of paths) {
+      const res = await api(method, path, { token: bob.token, body });
+      expect(res.status).toBe(404);
+    }
+
+    // Untouched for the owner
+    const detail = await api<TraderListDetail>("GET", `/api/lists/${list.id}`, {
+      token: alice.token,
+    });
+    expect(detail.data.name).toBe("Private");
+    expect(detail.data.members).toHaveLength(1);
+  });
+
+  test("the same name is allowed for different users", async () => {
+    await createList(alice.token, "Shared name");
+    await createList(bob.token, "Shared name");
+  });
+});
@@ -0,0 +1,137 @@
+import { describe, test, expect, beforeAll } from "bun:test";
+import { api, waitForServer } from "./helpers";
+
+// ---------------------------------------------------------------------------
+// Types (mirrored from frontend/src/types.ts — kept minimal for tests)
+// ---------------------------------------------------------------------------
+
+interface TraderSummary {
+  address: string;
+  trade_count: number;
+}
+
+interface LeaderboardResponse {
+  traders: TraderSummary[];
+}
+
+interface PnlChartResponse {
+  points: { date: string; pnl: string }[];
+}
+
+interface PositionsResponse {
+  open: { pnl: string }[];
+  closed: { pnl: string }[];
+}
+
+const DAY_MS = 24 * 60 * 60 * 1000;
+const HOUR_MS = 60 * 60 * 1000;
+
+/** `YYYY-MM-DD` or `YYYY-MM-DD hh:mm:ss` (UTC) → epoch ms */
+function bucketMs(date: string): number {
+  return Date.parse(date.includes(" ") ? `${date.replace(" ", "T")}Z` : `${date}T00:00:00Z`);
+}
+
+// ---------------------------------------------------------------------------
+// Setup
+// ---------------------------------------------------------------------------
+
+let singleTrader = "";
+let activeTrader = "";
+
+beforeAll(async () => {
+  await waitForServer();
+
+  // A trader with exactly one trade: flat after it, except for later price moves
+  const quiet = await api<LeaderboardResponse>(
+    "GET",
+    "/api/leaderboard?sort=trade_count&order=asc&limit=100",
+  );
+  singleTrader = quiet.data.traders.find((t) => t.trade_count === 1)?.address ?? "";
+
+  const busy = await api<LeaderboardResponse>(
+    "GET",
+    "/api/leaderboard?sort=trade_count&timeframe=24h&limit=1",
+  );
+  activeTrader = busy.data.traders[0].address;
+});
+
+// ---------------------------------------------------------------------------
+// GET /api/trader/{address}/pnl-chart — gap filling
+// ---------------------------------------------------------------------------
+
+describe("GET /api/trader/{address}/pnl-chart gap filling", () => {
+  test("emits one daily point from the first trade through today", async () => {
+    expect(singleTrader).not.toBe("");
+    const res = await api<PnlChartResponse>(
+      "GET",
+      `/api/trader/${singleTrader}/pnl-chart?timeframe=all`,
+    );
+    expect(res.status).toBe(200);
+
+    const points = res.data.points;
+    expect(points.length).toBeGreaterThan(0);
+    for (let i = 1; i < points.length; i++) {
+      expect(bucketMs(points[i].date) - bucketMs(points[i - 1].date)).toBe(DAY_MS);
+    }
+    expect(points[points.length - 1].date).toBe(new Date().toISOString().slice(0, 10));
+  });
+
+  test("carries a single trade forward flat until the final mark-to-market point", async () => {
+    const res = await api<PnlChartResponse>(
+      "GET",
+      `/api/trader/${singleTrader}/pnl-chart?timeframe=all`,
+    );
+    const pnls = res.data.points.map((p) => p.pnl);
+    // Every bucket before today is priced at the trade, so the line is flat
+    for (const pnl of pnls.slice(0, -1)) {
+      expect(pnl).toBe(pnls[0]);
+    }
+  });
+
+  test("final point reflects the latest market price", async () => {
+    const [chart, positions] = await Promise.all([
+      api<PnlChartResponse>("GET", `/api/trader/${singleTrader}/pnl-chart?timeframe=all`),
+      api<PositionsResponse>("GET", `/api/trader/${singleTrader}/positions`),
+    ]);
+    expect(positions.status).toBe(200);
+
+    // Positions are marked at asset_latest_price (or the resolved price)
+    const expected = [...positions.data.open, ...positions.data.closed].reduce(
+      (sum, p) => sum + parseFloat(p.pnl),
+      0,
+    );
+    const last = chart.data.points[chart.data.points.length - 1];
+    expect(parseFloat(last.pnl)).toBeCloseTo(expected, 1);
+  });
+
+  test("covers every hour of the 24h window", async () => {
+    const res = await api<PnlChartResponse>(
+      "GET",
+      `/api/trader/${activeTrader}/pnl-chart?timeframe=24h`,
+    );
+    expect(res.status).toBe(200);
+
+    const points = res.data.points;
+    expect(points.length).toBeGreaterThanOrEqual(24);
+    for (let i = 1; i < points.length; i++) {
+      expect(bucketMs(points[i].date) - bucketMs(points[i - 1].date)).toBe(HOUR_MS);
+    }
+  });
+
+  test("windowed views start at the window even without early trades", async () => {
+    const res = await api<PnlChartResponse>(
+      "GET",
+      `/api/trader/${singleTrader}/pnl-chart?timeframe=30d&granularity=week`,
+    );
+    expect(res.status).toBe(200);
+
+    const points = res.data.points;
+    for (let i = 1; i < points.length; i++) {
+      expect(bucketMs(points[i].date) - bucketMs(points[i - 1].date)).toBe(7 * DAY_MS);
+    }
+    for (const p of points) {
+      // Weekly buckets are keyed by their Monday
+      expect(new Date(bucketMs(p.date)).getUTCDay()).toBe(1);
+    }
+  });
+});
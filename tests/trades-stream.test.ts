@@ -0,0 +1,118 @@
+import { describe, test, expect, beforeAll } from "bun:test";
+import { API_BASE, api, waitForServer } from "./helpers";
+
+// ---------------------------------------------------------------------------
+// Types (mirrored from frontend/src/types.ts — kept minimal for tests)
+// ---------------------------------------------------------------------------
+
+interface LiveTrade {
+  asset_id: string;
+  usdc_amount: string;
+}
+
+interface LiveFeedResponse {
+  trades: { asset_id: string }[];
+}
+
+interface SseFrame {
+  event?: string;
+  data?: string;
+  comment?: string;
+}
+
+/** Read SSE frames from `/api/trades/stream` for `ms`, then disconnect. */
+async function readStream(query: string, ms: number): Promise<{ status: number; contentType: string; frames: SseFrame[] }> {
+  const abort = new AbortController();
+  const res = await fetch(`${API_BASE}/api/trades/stream${query}`, { signal: abort.signal });
+  const contentType = res.headers.get("content-type") ?? "";
+  const frames: SseFrame[] = [];
+  if (!res.ok || !res.body) {
+    return { status: res.status, contentType, frames };
+  }
+
+  const timer = setTimeout(() => abort.abort(), ms);
+  const decoder = new TextDecoder();
+  let buffer = "";
+  try {
+    for await (const chunk of res.body) {
+      buffer += decoder.decode(chunk, { stream: true });
+      let end: number;
+      while ((end = buffer.indexOf("\n\n")) !== -1) {
+        const frame: SseFrame = {};
+        for (const line of buffer.slice(0, end).split("\n")) {
+          if (line.startsWith(":")) frame.comment = line.slice(1).trim();
+          else if (line.startsWith("event:")) frame.event = line.slice(6).trim();
+          else if (line.startsWith("data:")) frame.data = (frame.data ?? "") + line.slice(5).trim();
+        }
+        frames.push(frame);
+        buffer = buffer.slice(end + 2);
+      }
+    }
+  } catch {
+    // aborted: client disconnect
+  } finally {
+    clearTimeout(timer);
+  }
+  return { status: res.status, contentType, frames };
+}
+
+/** First 15 significant digits, like the server's `cache_key` for integer IDs */
+const cacheKey = (id: string) => id.slice(0, 15);
+
+// ---------------------------------------------------------------------------
+// Setup
+// ---------------------------------------------------------------------------
+
+let activeToken = "";
+
+beforeAll(async () => {
+  await waitForServer();
+
+  const feed = await api<LiveFeedResponse>("GET", "/api/trades/recent?limit=1");
+  activeToken = feed.data.trades[0]?.asset_id ?? "";
+});
+
+// ---------------------------------------------------------------------------
+// GET /api/trades/stream
+// ---------------------------------------------------------------------------
+
+describe("GET /api/trades/stream", () => {
+  test("rejects a negative min_usdc", async () => {
+    const res = await api("GET", "/api/trades/stream?min_usdc=-5");
+    expect(res.status).toBe(400);
+  });
+
+  test("sends a keep-alive comment within 15 seconds", async () => {
+    // A threshold no trade reaches: only keep-alives come through
+    const { status, contentType, frames } = await readStream("?min_usdc=1000000000", 16_500);
+    expect(status).toBe(200);
+    expect(contentType).toContain("text/event-stream");
+    expect(frames.some((f) => f.comment === "keep-alive")).toBe(true);
+    expect(frames.some((f) => f.event === "trade")).toBe(false);
+  }, 20_000);
+
+  test("only forwards trades at or above min_usdc", async () => {
+    const { frames } = await readStream("?min_usdc=100", 10_000);
+    for (const f of frames.filter((f) => f.event === "trade")) {
+      const trade = JSON.parse(f.data!) as LiveTrade;
+      expect(parseFloat(trade.usdc_amount)).toBeGreaterThanOrEqual(100);
+    }
+  }, 15_000);
+
+  test("only forwards trades for the requested token", async () => {
+    expect(activeToken).not.toBe("");
+    const { frames } = await readStream(`?token_ids=${activeToken}`, 10_000);
+    for (const f of frames.filter((f) => f.event === "trade")) {
+      const trade = JSON.parse(f.data!) as LiveTrade;
+      expect(cacheKey(trade.asset_id)).toBe(cacheKey(activeToken));
+    }
+  }, 15_000);
+
+  test("lag frames carry the dropped count", async () => {
+    const { frames } = await readStream("", 10_000);
+    for (const f of frames.filter((f) => f.event === "lag")) {
+      const lag = JSON.parse(f.data!) as { dropped: number };
+      expect(lag.dropped).toBeGreaterThan(0);
+    }
+  }, 15_000);
+});
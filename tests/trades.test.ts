@@ -0,0 +1,105 @@
+import { describe, test, expect, beforeAll } from "bun:test";
+import { api, waitForServer } from "./helpers";
+
+// ---------------------------------------------------------------------------
+// Types (mirrored from frontend/src/types.ts — kept minimal for tests)
+// ---------------------------------------------------------------------------
+
+interface TradeRecord {
+  tx_hash: string;
+  block_number: number;
+  block_timestamp: string;
+}
+
+interface TradesResponse {
+  trades: TradeRecord[];
+  total: number;
+}
+
+interface LeaderboardResponse {
+  traders: { address: string }[];
+}
+
+/** ClickHouse `YYYY-MM-DD hh:mm:ss` (UTC) → unix seconds */
+function unixSeconds(ts: string): number {
+  return Math.floor(Date.parse(`${ts.replace(" ", "T")}Z`) / 1000);
+}
+
+// ---------------------------------------------------------------------------
+// Setup
+// ---------------------------------------------------------------------------
+
+let trader = "";
+let trade: TradeRecord;
+
+beforeAll(async () => {
+  await waitForServer();
+
+  // Most recent trade of the most active trader in the last 24h (within raw-trade TTL)
+  const lb = await api<LeaderboardResponse>(
+    "GET",
+    "/api/leaderboard?sort=trade_count&timeframe=24h&limit=1",
+  );
+  trader = lb.data.traders[0].address;
+  const res = await api<TradesResponse>(
+    "GET",
+    `/api/trader/${trader}/trades?limit=1`,
+  );
+  trade = res.data.trades[0];
+});
+
+// ---------------------------------------------------------------------------
+// GET /api/trader/{address}/trades — from/to range
+// ---------------------------------------------------------------------------
+
+describe("GET /api/trader/{address}/trades date range", () => {
+  test("excludes a trade that falls exactly on `to`", async () => {
+    const t = unixSeconds(trade.block_timestamp);
+    const res = await api<TradesResponse>(
+      "GET",
+      `/api/trader/${trader}/trades?to=${t}&limit=200`,
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.trades.map((r) => r.tx_hash)).not.toContain(trade.tx_hash);
+    for (const r of res.data.trades) {
+      expect(unixSeconds(r.block_timestamp)).toBeLessThan(t);
+    }
+  });
+
+  test("includes a trade that falls exactly on `from`", async () => {
+    const t = unixSeconds(trade.block_timestamp);
+    const res = await api<TradesResponse>(
+      "GET",
+      `/api/trader/${trader}/trades?from=${t}&to=${t + 1}&limit=200`,
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.trades.map((r) => r.tx_hash)).toContain(trade.tx_hash);
+    expect(res.data.total).toBe(res.data.trades.length);
+  });
+
+  test("accepts an open-ended range and RFC 3339 input", async () => {
+    const iso = `${trade.block_timestamp.replace(" ", "T")}Z`;
+    const res = await api<TradesResponse>(
+      "GET",
+      `/api/trader/${trader}/trades?from=${encodeURIComponent(iso)}`,
+    );
+    expect(res.status).toBe(200);
+    expect(res.data.total).toBeGreaterThanOrEqual(1);
+  });
+
+  test("rejects a reversed range", async () => {
+    const res = await api(
+      "GET",
+      `/api/trader/${trader}/trades?from=2025-02-01&to=2025-01-01`,
+    );
+    expect(res.status).toBe(400);
+  });
+
+  test("rejects an unparseable bound", async () => {
+    const res = await api(
+      "GET",
+      `/api/trader/${trader}/trades?from=yesterday`,
+    );
+    expect(res.status).toBe(400);
+  });
+});